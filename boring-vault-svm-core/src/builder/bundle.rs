@@ -0,0 +1,177 @@
+use std::{thread::sleep, time::Instant};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::Result;
+use solana_instruction::Instruction;
+use solana_message::AddressLookupTableAccount;
+use solana_pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_signer::Signer;
+use solana_system_interface::instruction as system_instruction;
+
+use crate::utils::{
+    decode_transaction_error, instruction_program_ids, BlockEngineClient, BundleStatus,
+    DecodedError,
+};
+
+use crate::{config::BundleConfig, utils::AccountSource};
+
+use super::{plan_instruction_groups, Builder};
+
+// Jito rejects bundles with more transactions than this.
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+impl<C: AccountSource> Builder<C> {
+    pub fn tip_instruction(&self, payer: &Pubkey) -> Instruction {
+        tip_instruction(&self.bundle_config, payer)
+    }
+
+    // Plans the instructions into at most `MAX_BUNDLE_TRANSACTIONS` signed transactions, with the
+    // tip transfer packed into the last one.
+    pub fn compile_bundle_b64(&self, payer_pubkey: Pubkey) -> Result<Vec<String>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let plan = plan_bundle(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            self.tip_instruction(&payer_pubkey),
+            &payer_pubkey,
+            &lookup_table_accounts,
+        )?;
+        self.ensure_single_nonce_transaction(plan.len())?;
+        let blockhash = self.get_blockhash()?;
+
+        plan.iter()
+            .map(|instructions| {
+                let message = self.compile_transaction_message(
+                    instructions,
+                    &payer_pubkey,
+                    blockhash,
+                    &lookup_table_accounts,
+                )?;
+                let tx = self.sign_message(message)?;
+
+                Ok(STANDARD.encode(bincode::serialize(&tx)?))
+            })
+            .collect()
+    }
+
+    // Submits the plan as a Jito bundle and waits for it to confirm, returning the bundle id.
+    pub fn try_bundle_all<S: Signer + Send + Sync + 'static>(
+        &mut self,
+        payer: S,
+    ) -> Result<String> {
+        let payer_pubkey = payer.pubkey();
+        self.signers
+            .entry(payer_pubkey)
+            .or_insert_with(|| Box::new(payer));
+
+        let block_engine = BlockEngineClient::new(self.bundle_config.block_engine_url()?);
+        let transactions = self.compile_bundle_b64(payer_pubkey)?;
+        let bundle_id = block_engine.send_bundle(&transactions)?;
+
+        if let Err(e) = self.confirm_bundle(&block_engine, &bundle_id, &transactions) {
+            self.invalidate_account_cache();
+            return Err(e);
+        }
+
+        self.clear()?;
+
+        Ok(bundle_id)
+    }
+
+    // `transactions` are the base64 transactions that were sent, used to decode a failure.
+    pub fn confirm_bundle(
+        &self,
+        block_engine: &BlockEngineClient,
+        bundle_id: &str,
+        transactions: &[String],
+    ) -> Result<BundleStatus> {
+        let start = Instant::now();
+
+        loop {
+            let status = block_engine.get_bundle_status(bundle_id)?;
+            if let Some(status) = confirmed_bundle_status(bundle_id, status, transactions)? {
+                return Ok(status);
+            }
+
+            if start.elapsed() >= self.bundle_config.confirm_timeout {
+                return Err(eyre::eyre!(
+                    "Bundle {} not confirmed after {:?}",
+                    bundle_id,
+                    self.bundle_config.confirm_timeout
+                ));
+            }
+            sleep(self.bundle_config.poll_interval);
+        }
+    }
+}
+
+pub fn tip_instruction(config: &BundleConfig, payer: &Pubkey) -> Instruction {
+    system_instruction::transfer(payer, &config.tip_account, config.tip_lamports)
+}
+
+// Plans the groups with the tip as the last one, in at most `MAX_BUNDLE_TRANSACTIONS`
+// transactions.
+pub fn plan_bundle(
+    groups: &[&[Instruction]],
+    prefix_instructions: &[Instruction],
+    tip_instruction: Instruction,
+    payer: &Pubkey,
+    lookup_table_accounts: &[AddressLookupTableAccount],
+) -> Result<Vec<Vec<Instruction>>> {
+    let tip_ix = [tip_instruction];
+    let mut groups = groups.to_vec();
+    groups.push(&tip_ix);
+
+    let plan = plan_instruction_groups(&groups, prefix_instructions, payer, lookup_table_accounts)?;
+    if plan.len() > MAX_BUNDLE_TRANSACTIONS {
+        return Err(eyre::eyre!(
+            "Bundle needs {} transactions, max is {}",
+            plan.len(),
+            MAX_BUNDLE_TRANSACTIONS
+        ));
+    }
+
+    Ok(plan)
+}
+
+// The status once the bundle confirmed, `None` while it's still pending. Errors when it failed.
+pub fn confirmed_bundle_status(
+    bundle_id: &str,
+    status: Option<BundleStatus>,
+    transactions: &[String],
+) -> Result<Option<BundleStatus>> {
+    match status {
+        Some(status) if !status.is_ok() => Err(bundle_error(bundle_id, &status, transactions)),
+        Some(status) if status.is_confirmed() => Ok(Some(status)),
+        _ => Ok(None),
+    }
+}
+
+// The status doesn't say which transaction failed, so the error is decoded against each
+// transaction's instruction at the failing index, preferring one that names the error.
+fn bundle_error(bundle_id: &str, status: &BundleStatus, transactions: &[String]) -> eyre::Report {
+    let context = format!("Bundle {} failed: {}", bundle_id, status.err);
+    let Some(err) = status.transaction_error() else {
+        return eyre::eyre!(context);
+    };
+
+    let decoded: Vec<DecodedError> = transactions
+        .iter()
+        .filter_map(|transaction| {
+            let bytes = STANDARD.decode(transaction).ok()?;
+            let transaction: VersionedTransaction = bincode::deserialize(&bytes).ok()?;
+            let program_ids = instruction_program_ids(&transaction.message);
+            decode_transaction_error(&err, &[], &program_ids)
+        })
+        .collect();
+
+    match decoded
+        .iter()
+        .find(|decoded| decoded.name.is_some())
+        .or(decoded.first())
+    {
+        Some(decoded) => eyre::Report::new(decoded.clone()).wrap_err(context),
+        None => eyre::eyre!(context),
+    }
+}
@@ -0,0 +1,221 @@
+use eyre::Result;
+use solana_client::{
+    rpc_config::RpcSimulateTransactionConfig, rpc_response::RpcSimulateTransactionResult,
+};
+use solana_instruction::Instruction;
+use solana_message::{AddressLookupTableAccount, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, compute_budget::ComputeBudgetInstruction, hash::Hash,
+    instruction::InstructionError, transaction::TransactionError,
+};
+
+use crate::config::{ComputeBudgetConfig, PriorityFeePolicy};
+
+use crate::utils::{decode_transaction_error, instruction_program_ids, AccountSource};
+
+use super::{compile_message, unsigned_transaction, Builder};
+
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+// getRecentPrioritizationFees accepts at most this many accounts.
+const MAX_PRIORITIZATION_FEE_ACCOUNTS: usize = 128;
+
+// Anchor's `AccountNotInitialized`.
+const ANCHOR_ACCOUNT_NOT_INITIALIZED: u32 = 3012;
+
+// Cost of the system program's advance nonce instruction, which isn't part of the simulation.
+pub const ADVANCE_NONCE_COMPUTE_UNITS: u64 = 150;
+
+impl<C: AccountSource> Builder<C> {
+    // Same shape as the final compute budget instructions so the planner can reserve their size.
+    pub fn compute_budget_placeholder_instructions(&self) -> Vec<Instruction> {
+        placeholder_compute_budget_instructions(self.compute_budget.as_ref())
+    }
+
+    pub fn apply_compute_budget(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        blockhash: Hash,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<Vec<Instruction>> {
+        let Some(config) = &self.compute_budget else {
+            return Ok(instructions.to_vec());
+        };
+
+        let mut simulated = self.compute_budget_placeholder_instructions();
+        simulated.extend_from_slice(instructions);
+        let message = compile_message(&simulated, payer, blockhash, lookup_table_accounts)?;
+
+        let compute_unit_limit = match self.simulate_units_consumed(message)? {
+            Some(units_consumed) => {
+                let advance_nonce_units = match self.durable_nonce {
+                    Some(_) => ADVANCE_NONCE_COMPUTE_UNITS,
+                    None => 0,
+                };
+                get_compute_unit_limit(units_consumed + advance_nonce_units, config)
+            }
+            None => MAX_COMPUTE_UNIT_LIMIT,
+        };
+        let compute_unit_price = self.get_compute_unit_price(config, instructions)?;
+
+        let mut budgeted = compute_budget_instructions(compute_unit_limit, compute_unit_price);
+        budgeted.extend_from_slice(instructions);
+
+        Ok(budgeted)
+    }
+
+    pub fn get_compute_unit_price(
+        &self,
+        config: &ComputeBudgetConfig,
+        instructions: &[Instruction],
+    ) -> Result<Option<u64>> {
+        let mut fees: Vec<u64> = match config.priority_fee {
+            PriorityFeePolicy::Percentile(_) => self
+                .client
+                .get_recent_prioritization_fees(&get_writable_accounts(instructions))?,
+            _ => vec![],
+        };
+
+        Ok(select_compute_unit_price(config, &mut fees))
+    }
+
+    fn simulate_units_consumed(&self, message: VersionedMessage) -> Result<Option<u64>> {
+        let transaction = unsigned_transaction(message);
+        let result = self
+            .client
+            .simulate_transaction(&transaction, units_consumed_simulation_config())?;
+
+        get_units_consumed(&result, &transaction.message)
+    }
+}
+
+pub fn compute_budget_instructions(
+    compute_unit_limit: u32,
+    compute_unit_price: Option<u64>,
+) -> Vec<Instruction> {
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        compute_unit_limit,
+    )];
+
+    if let Some(price) = compute_unit_price {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+
+    instructions
+}
+
+// Same shape as the final compute budget instructions so the planner can reserve their size.
+pub fn placeholder_compute_budget_instructions(
+    config: Option<&ComputeBudgetConfig>,
+) -> Vec<Instruction> {
+    match config {
+        Some(config) => {
+            let price = match config.priority_fee {
+                PriorityFeePolicy::None => None,
+                _ => Some(0),
+            };
+            compute_budget_instructions(MAX_COMPUTE_UNIT_LIMIT, price)
+        }
+        None => vec![],
+    }
+}
+
+// `recent_fees` are only read for `PriorityFeePolicy::Percentile`.
+pub fn select_compute_unit_price(
+    config: &ComputeBudgetConfig,
+    recent_fees: &mut [u64],
+) -> Option<u64> {
+    let price = match config.priority_fee {
+        PriorityFeePolicy::None => return None,
+        PriorityFeePolicy::Fixed(price) => price,
+        PriorityFeePolicy::Percentile(percentile) => get_percentile(recent_fees, percentile),
+    };
+
+    Some(match config.max_priority_fee {
+        Some(max_priority_fee) => price.min(max_priority_fee),
+        None => price,
+    })
+}
+
+pub fn get_compute_unit_limit(units_consumed: u64, config: &ComputeBudgetConfig) -> u32 {
+    let margin = units_consumed * config.compute_unit_margin_bps as u64 / 10_000;
+
+    (units_consumed + margin).min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+pub fn get_writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut writable_accounts: Vec<Pubkey> = vec![];
+
+    for account in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if account.is_writable && !writable_accounts.contains(&account.pubkey) {
+            writable_accounts.push(account.pubkey);
+        }
+    }
+    writable_accounts.truncate(MAX_PRIORITIZATION_FEE_ACCOUNTS);
+
+    writable_accounts
+}
+
+fn get_percentile(values: &mut [u64], percentile: u8) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+
+    values.sort_unstable();
+    let index = (values.len() - 1) * percentile.min(100) as usize / 100;
+
+    values[index]
+}
+
+pub fn units_consumed_simulation_config() -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(CommitmentConfig::processed()),
+        ..Default::default()
+    }
+}
+
+// `None` when the transaction reads accounts an earlier transaction of the plan creates, which
+// haven't landed yet, so it gets the max limit. Other failures would fail the send too, so they
+// are returned, decoded when the registry knows the program.
+pub fn get_units_consumed(
+    result: &RpcSimulateTransactionResult,
+    message: &VersionedMessage,
+) -> Result<Option<u64>> {
+    match &result.err {
+        Some(err) if is_missing_dependency_error(err) => Ok(None),
+        Some(err) => {
+            let context = format!("Compute budget simulation failed: {}", err);
+            let logs = result.logs.clone().unwrap_or_default();
+
+            Err(
+                match decode_transaction_error(err, &logs, &instruction_program_ids(message)) {
+                    Some(decoded) => eyre::Report::new(decoded).wrap_err(context),
+                    None => eyre::eyre!(context),
+                },
+            )
+        }
+        None => result
+            .units_consumed
+            .map(Some)
+            .ok_or_else(|| eyre::eyre!("Simulation did not report units consumed")),
+    }
+}
+
+// Errors of an instruction reading an account that doesn't exist yet: a missing account is
+// uninitialized, empty and owned by the system program.
+pub fn is_missing_dependency_error(err: &TransactionError) -> bool {
+    matches!(
+        err,
+        TransactionError::InstructionError(
+            _,
+            InstructionError::UninitializedAccount
+                | InstructionError::InvalidAccountData
+                | InstructionError::InvalidAccountOwner
+                | InstructionError::InsufficientFunds
+                | InstructionError::Custom(ANCHOR_ACCOUNT_NOT_INITIALIZED)
+        )
+    )
+}
@@ -0,0 +1,275 @@
+pub mod bundle;
+pub mod compute_budget;
+pub mod nonce;
+pub mod partial;
+pub mod planner;
+pub mod send;
+pub mod simulation;
+
+pub use bundle::*;
+pub use compute_budget::*;
+pub use nonce::*;
+pub use partial::*;
+pub use planner::*;
+pub use send::*;
+pub use simulation::*;
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::{eyre, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_message::{v0, AddressLookupTableAccount, Message, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_sdk::{hash::Hash, signature::Signature, transaction::VersionedTransaction};
+use solana_signer::Signer;
+
+use crate::config::BuilderConfig;
+use crate::utils::{
+    get_lookup_table_accounts, AccountCache, AccountSource, BoxedSigner, CachedAccountSource,
+    KeypairSource,
+};
+use crate::view::kamino::get_user_lookup_table;
+use crate::{
+    config::{BundleConfig, ComputeBudgetConfig, KaminoConfig, SendConfig, VaultConfig},
+    KeypairOrPublickey,
+};
+
+pub struct Builder<C = RpcClient> {
+    pub client: C,
+    pub instructions: Vec<Instruction>,
+    // Start index of each group of instructions that must land in the same transaction.
+    pub groups: Vec<usize>,
+    pub signers: HashMap<Pubkey, BoxedSigner>,
+    pub lookup_tables: Vec<Pubkey>,
+    pub compute_budget: Option<ComputeBudgetConfig>,
+    pub bundle_config: BundleConfig,
+    pub send_config: SendConfig,
+    pub durable_nonce: Option<NonceAccount>,
+    // Where `payer` loads the fee payer from when the caller doesn't pass one.
+    pub payer: Option<KeypairSource>,
+    // Accounts read while building the current plan, dropped by `clear`.
+    pub account_cache: AccountCache,
+    // Ids taken by the deploys in the current plan, which aren't in the program config yet.
+    pub pending_vault_ids: Vec<u64>,
+    // Whether manage instructions check new CPI digests against the program first.
+    pub cpi_digest_check: bool,
+    pub kamino_config: KaminoConfig,
+    pub vault_config: VaultConfig,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        BuilderConfig::default().build()
+    }
+}
+
+impl Builder {
+    // TODO: remove
+    pub fn new(rpc_url: String, path_root: Option<String>) -> Self {
+        let mut config = BuilderConfig::default().with_rpc_url(rpc_url);
+
+        if let Some(path) = path_root {
+            config = config.with_data_path(path);
+        }
+
+        config.build()
+    }
+}
+
+impl<C: AccountSource> Builder<C> {
+    pub fn clear(&mut self) -> Result<()> {
+        self.instructions.clear();
+        self.groups.clear();
+        // Keep the nonce authority, since the durable nonce outlives the instructions.
+        let nonce_authority = self.durable_nonce.map(|nonce| nonce.authority);
+        self.signers
+            .retain(|pubkey, _| Some(*pubkey) == nonce_authority);
+        self.pending_vault_ids.clear();
+        self.invalidate_account_cache();
+
+        Ok(())
+    }
+
+    // Client reads go through the plan's account cache, so accounts several instructions need
+    // are only fetched once.
+    pub fn accounts(&self) -> CachedAccountSource<'_, C> {
+        CachedAccountSource::new(&self.client, &self.account_cache)
+    }
+
+    pub fn prefetch_accounts(&self, addresses: &[Pubkey]) -> Result<()> {
+        self.account_cache.prefetch(&self.client, addresses)
+    }
+
+    // Sent transactions change the cached accounts, so this has to run between plans. `clear`
+    // already does.
+    pub fn invalidate_account_cache(&self) {
+        self.account_cache.invalidate();
+    }
+
+    pub fn compile_to_versioned_transaction_b64(&self, payer_pubkey: Pubkey) -> Result<String> {
+        let blockhash = self.get_blockhash()?;
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let message = self.compile_transaction_message(
+            &self.instructions,
+            &payer_pubkey,
+            blockhash,
+            &lookup_table_accounts,
+        )?;
+
+        let tx = self.sign_message(message)?;
+        let serialized_tx = bincode::serialize(&tx)?;
+
+        Ok(STANDARD.encode(&serialized_tx))
+    }
+
+    // Compiles the instructions into a message, prepending compute budget instructions if configured
+    // and advancing the durable nonce if one is in use.
+    pub fn compile_transaction_message(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        blockhash: Hash,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<VersionedMessage> {
+        let mut instructions =
+            self.apply_compute_budget(instructions, payer, blockhash, lookup_table_accounts)?;
+
+        // The advance has to be the first instruction, its compute units are budgeted above.
+        if let Some(advance_nonce_ix) = self.advance_nonce_instruction() {
+            instructions.insert(0, advance_nonce_ix);
+        }
+
+        compile_message(&instructions, payer, blockhash, lookup_table_accounts)
+    }
+
+    pub fn sign_message(&self, message: VersionedMessage) -> Result<VersionedTransaction> {
+        sign_message_with(message, &self.signers)
+    }
+
+    // Removes the first `count` instructions, which must end on a group boundary.
+    pub fn drain_instructions(&mut self, count: usize) {
+        drain_instructions(&mut self.instructions, &mut self.groups, count);
+    }
+
+    // Marks the start of a group of instructions that the planner keeps in one transaction.
+    pub fn start_group(&mut self) {
+        let start = self.instructions.len();
+        if self.groups.last() != Some(&start) {
+            self.groups.push(start);
+        }
+    }
+
+    pub fn add_lookup_table(&mut self, address: Pubkey) {
+        if !self.lookup_tables.contains(&address) {
+            self.lookup_tables.push(address);
+        }
+    }
+
+    // Adds the lookup table created for the sub-account in `init_user_metadata`.
+    pub fn load_vault_lookup_table(&mut self, vault_id: u64, sub_account: u8) -> Result<Pubkey> {
+        let lut = get_user_lookup_table(&self.client, vault_id, sub_account)?;
+        self.add_lookup_table(lut);
+
+        Ok(lut)
+    }
+
+    pub fn fetch_lookup_table_accounts(&self) -> Result<Vec<AddressLookupTableAccount>> {
+        if self.lookup_tables.is_empty() {
+            return Ok(vec![]);
+        }
+
+        get_lookup_table_accounts(&self.client, &self.lookup_tables)
+    }
+
+    pub fn add_signer_if_keypair(&mut self, potential_signer: KeypairOrPublickey) {
+        add_signer_if_keypair(&mut self.signers, potential_signer);
+    }
+
+    pub fn add_signer<S: Signer + Send + Sync + 'static>(&mut self, signer: S) {
+        self.add_signer_if_keypair(KeypairOrPublickey::from_signer(signer));
+    }
+
+    // Resolved on every call, so a `prompt:` payer asks again each time.
+    pub fn payer(&self) -> Result<Keypair> {
+        self.payer
+            .as_ref()
+            .ok_or_else(|| eyre!("No payer configured"))?
+            .resolve()
+    }
+}
+
+// Keeps the first signer added for a pubkey, bare pubkeys are ignored.
+pub fn add_signer_if_keypair(
+    signers: &mut HashMap<Pubkey, BoxedSigner>,
+    potential_signer: KeypairOrPublickey,
+) {
+    let pubkey = potential_signer.pubkey();
+    if let Entry::Vacant(e) = signers.entry(pubkey) {
+        if let Some(signer) = potential_signer.into_signer() {
+            e.insert(signer);
+        }
+    }
+}
+
+// Signs with only the signers the message requires, in the order it expects them.
+pub fn sign_message_with(
+    message: VersionedMessage,
+    signers: &HashMap<Pubkey, BoxedSigner>,
+) -> Result<VersionedTransaction> {
+    let num_required_signatures = message.header().num_required_signatures as usize;
+    let signers = message.static_account_keys()[..num_required_signatures]
+        .iter()
+        .map(|pubkey| {
+            signers
+                .get(pubkey)
+                .ok_or_else(|| eyre::eyre!("Missing keypair for required signer {}", pubkey))
+        })
+        .collect::<Result<Vec<&BoxedSigner>>>()?;
+
+    Ok(VersionedTransaction::try_new(message, &signers)?)
+}
+
+// Removes the first `count` instructions and shifts the group starts that remain.
+pub fn drain_instructions(
+    instructions: &mut Vec<Instruction>,
+    groups: &mut Vec<usize>,
+    count: usize,
+) {
+    instructions.drain(..count);
+    *groups = groups
+        .iter()
+        .filter(|start| **start >= count)
+        .map(|start| start - count)
+        .collect();
+}
+
+// Compiles a v0 message when lookup tables are provided, otherwise falls back to legacy.
+pub fn compile_message(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    blockhash: Hash,
+    lookup_table_accounts: &[AddressLookupTableAccount],
+) -> Result<VersionedMessage> {
+    if lookup_table_accounts.is_empty() {
+        return Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &blockhash,
+        )));
+    }
+
+    let message = v0::Message::try_compile(payer, instructions, lookup_table_accounts, blockhash)?;
+
+    Ok(VersionedMessage::V0(message))
+}
+
+// Transaction with default signatures, used for size estimates and simulation without signing.
+pub fn unsigned_transaction(message: VersionedMessage) -> VersionedTransaction {
+    VersionedTransaction {
+        signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+        message,
+    }
+}
@@ -0,0 +1,92 @@
+use eyre::Result;
+use solana_client::nonce_utils;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+use solana_sdk::{hash::Hash, nonce::state::Data as NonceData};
+use solana_system_interface::instruction as system_instruction;
+
+use crate::KeypairOrPublickey;
+
+use crate::utils::AccountSource;
+
+use super::Builder;
+
+// Durable nonce used in place of a recent blockhash, so transactions don't expire while
+// waiting on signers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceAccount {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl NonceAccount {
+    // Nonce value to use as the blockhash, after checking the account has the expected authority.
+    pub fn blockhash(&self, data: &NonceData) -> Result<Hash> {
+        if data.authority != self.authority {
+            return Err(eyre::eyre!(
+                "Nonce account {} authority is {}, expected {}",
+                self.address,
+                data.authority,
+                self.authority
+            ));
+        }
+
+        Ok(data.blockhash())
+    }
+
+    pub fn advance_instruction(&self) -> Instruction {
+        system_instruction::advance_nonce_account(&self.address, &self.authority)
+    }
+}
+
+impl<C: AccountSource> Builder<C> {
+    pub fn use_durable_nonce(&mut self, address: Pubkey, authority: KeypairOrPublickey) {
+        self.durable_nonce = Some(NonceAccount {
+            address,
+            authority: authority.pubkey(),
+        });
+        self.add_signer_if_keypair(authority);
+    }
+
+    pub fn clear_durable_nonce(&mut self) {
+        self.durable_nonce = None;
+    }
+
+    pub fn get_nonce_data(&self, address: &Pubkey) -> Result<NonceData> {
+        let account = self.client.get_account(address)?;
+
+        Ok(nonce_utils::data_from_account(&account)?)
+    }
+
+    // Current nonce value when a durable nonce is in use, otherwise the latest blockhash.
+    pub fn get_blockhash(&self) -> Result<Hash> {
+        match &self.durable_nonce {
+            Some(nonce) => nonce.blockhash(&self.get_nonce_data(&nonce.address)?),
+            None => Ok(self.client.get_latest_blockhash()?),
+        }
+    }
+
+    pub fn advance_nonce_instruction(&self) -> Option<Instruction> {
+        self.durable_nonce.map(|nonce| nonce.advance_instruction())
+    }
+
+    // Instructions prepended to every transaction, which the planner reserves room for.
+    pub fn transaction_prefix_instructions(&self) -> Vec<Instruction> {
+        self.advance_nonce_instruction()
+            .into_iter()
+            .chain(self.compute_budget_placeholder_instructions())
+            .collect()
+    }
+
+    // Every transaction compiled against a nonce advances it, so only one of them can land.
+    pub fn ensure_single_nonce_transaction(&self, transactions: usize) -> Result<()> {
+        if self.durable_nonce.is_some() && transactions > 1 {
+            return Err(eyre::eyre!(
+                "A durable nonce can only back a single transaction, plan has {}",
+                transactions
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::Result;
+use solana_message::VersionedMessage;
+use solana_pubkey::Pubkey;
+use solana_sdk::{signature::Signature, transaction::VersionedTransaction};
+use solana_signer::Signer;
+
+use crate::utils::AccountSource;
+
+use super::{plan_instruction_groups, unsigned_transaction, Builder};
+
+// Transaction that still needs signatures from signers the builder doesn't hold a keypair for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartiallySignedTransaction {
+    pub transaction: VersionedTransaction,
+}
+
+impl PartiallySignedTransaction {
+    pub fn new(message: VersionedMessage) -> Self {
+        Self {
+            transaction: unsigned_transaction(message),
+        }
+    }
+
+    // Bytes every signer signs.
+    pub fn message_data(&self) -> Vec<u8> {
+        self.transaction.message.serialize()
+    }
+
+    pub fn message_b64(&self) -> String {
+        STANDARD.encode(self.message_data())
+    }
+
+    pub fn required_signers(&self) -> &[Pubkey] {
+        let num_required_signatures =
+            self.transaction.message.header().num_required_signatures as usize;
+
+        &self.transaction.message.static_account_keys()[..num_required_signatures]
+    }
+
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        self.required_signers()
+            .iter()
+            .zip(&self.transaction.signatures)
+            .filter(|(_, signature)| **signature == Signature::default())
+            .map(|(pubkey, _)| *pubkey)
+            .collect()
+    }
+
+    pub fn is_fully_signed(&self) -> bool {
+        self.missing_signers().is_empty()
+    }
+
+    // Adds a signature produced elsewhere, rejecting it if it doesn't verify against the message.
+    pub fn add_signature(&mut self, pubkey: &Pubkey, signature: Signature) -> Result<()> {
+        let index = self
+            .required_signers()
+            .iter()
+            .position(|signer| signer == pubkey)
+            .ok_or_else(|| eyre::eyre!("{} is not a required signer", pubkey))?;
+
+        if !signature.verify(pubkey.as_ref(), &self.message_data()) {
+            return Err(eyre::eyre!("Invalid signature for signer {}", pubkey));
+        }
+
+        self.transaction.signatures[index] = signature;
+
+        Ok(())
+    }
+
+    pub fn sign(&mut self, signer: &dyn Signer) -> Result<()> {
+        let signature = signer.try_sign_message(&self.message_data())?;
+
+        self.add_signature(&signer.pubkey(), signature)
+    }
+
+    pub fn finalize(self) -> Result<VersionedTransaction> {
+        let missing_signers = self.missing_signers();
+        if !missing_signers.is_empty() {
+            return Err(eyre::eyre!(
+                "Missing signatures from: {}",
+                missing_signers
+                    .iter()
+                    .map(|pubkey| pubkey.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        Ok(self.transaction)
+    }
+
+    // Serialized as a regular transaction, with default signatures for the missing signers.
+    pub fn to_b64(&self) -> Result<String> {
+        Ok(STANDARD.encode(bincode::serialize(&self.transaction)?))
+    }
+
+    pub fn from_b64(b64_tx: &str) -> Result<Self> {
+        let transaction: VersionedTransaction = bincode::deserialize(&STANDARD.decode(b64_tx)?)?;
+
+        Ok(Self { transaction })
+    }
+}
+
+impl<C: AccountSource> Builder<C> {
+    // Compiles every instruction into one transaction signed by the keypairs the builder holds.
+    pub fn export_partially_signed(
+        &self,
+        payer_pubkey: Pubkey,
+    ) -> Result<PartiallySignedTransaction> {
+        let blockhash = self.get_blockhash()?;
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let message = self.compile_transaction_message(
+            &self.instructions,
+            &payer_pubkey,
+            blockhash,
+            &lookup_table_accounts,
+        )?;
+
+        self.partially_sign_message(message)
+    }
+
+    pub fn export_plan_partially_signed(
+        &self,
+        payer_pubkey: Pubkey,
+    ) -> Result<Vec<PartiallySignedTransaction>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let plan = plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            &payer_pubkey,
+            &lookup_table_accounts,
+        )?;
+        self.ensure_single_nonce_transaction(plan.len())?;
+        let blockhash = self.get_blockhash()?;
+
+        plan.iter()
+            .map(|instructions| {
+                let message = self.compile_transaction_message(
+                    instructions,
+                    &payer_pubkey,
+                    blockhash,
+                    &lookup_table_accounts,
+                )?;
+
+                self.partially_sign_message(message)
+            })
+            .collect()
+    }
+
+    pub fn partially_sign_message(
+        &self,
+        message: VersionedMessage,
+    ) -> Result<PartiallySignedTransaction> {
+        let mut partial = PartiallySignedTransaction::new(message);
+
+        for pubkey in partial.missing_signers() {
+            if let Some(signer) = self.signers.get(&pubkey) {
+                partial.sign(signer.as_ref())?;
+            }
+        }
+
+        Ok(partial)
+    }
+
+    pub fn send_partially_signed(&self, partial: PartiallySignedTransaction) -> Result<String> {
+        let tx = partial.finalize()?;
+        let signature = self.client.send_and_confirm_transaction(&tx)?;
+
+        Ok(signature.to_string())
+    }
+}
@@ -0,0 +1,233 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::Result;
+use solana_instruction::Instruction;
+use solana_message::{AddressLookupTableAccount, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_sdk::{hash::Hash, packet::PACKET_DATA_SIZE};
+use solana_signer::Signer;
+
+use crate::utils::AccountSource;
+
+use super::{compile_message, unsigned_transaction, Builder, SendOutcome};
+
+pub const MAX_TRANSACTION_SIZE: usize = PACKET_DATA_SIZE;
+// Conservative runtime limit on accounts a single transaction may load.
+pub const MAX_TRANSACTION_ACCOUNTS: usize = 64;
+
+impl<C: AccountSource> Builder<C> {
+    // Returns the instructions split at the group boundaries recorded with `start_group`.
+    pub fn instruction_groups(&self) -> Vec<&[Instruction]> {
+        split_instruction_groups(&self.instructions, &self.groups)
+    }
+
+    pub fn plan_transactions(&self, payer_pubkey: &Pubkey) -> Result<Vec<Vec<Instruction>>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+
+        plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            payer_pubkey,
+            &lookup_table_accounts,
+        )
+    }
+
+    pub fn compile_plan_to_versioned_transactions_b64(
+        &self,
+        payer_pubkey: Pubkey,
+    ) -> Result<Vec<String>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let plan = plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            &payer_pubkey,
+            &lookup_table_accounts,
+        )?;
+        self.ensure_single_nonce_transaction(plan.len())?;
+        let blockhash = self.get_blockhash()?;
+
+        plan.iter()
+            .map(|instructions| {
+                let message = self.compile_transaction_message(
+                    instructions,
+                    &payer_pubkey,
+                    blockhash,
+                    &lookup_table_accounts,
+                )?;
+                let tx = self.sign_message(message)?;
+
+                Ok(STANDARD.encode(bincode::serialize(&tx)?))
+            })
+            .collect()
+    }
+
+    // Sends every planned transaction in order, stopping at the first one that doesn't land.
+    // Instructions of transactions that landed are dropped, so calling it again resumes the plan.
+    pub fn try_send_all<S: Signer + Send + Sync + 'static>(
+        &mut self,
+        payer: S,
+    ) -> Result<Vec<String>> {
+        let payer_pubkey = payer.pubkey();
+        self.signers
+            .entry(payer_pubkey)
+            .or_insert_with(|| Box::new(payer));
+
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let plan = plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            &payer_pubkey,
+            &lookup_table_accounts,
+        )?;
+
+        let mut signatures = vec![];
+        let mut landed_instructions = 0;
+        for (i, instructions) in plan.iter().enumerate() {
+            let outcome = self
+                .send_instructions(instructions, &payer_pubkey, &lookup_table_accounts)
+                .and_then(|outcome| match outcome {
+                    SendOutcome::Landed { signature } => Ok(signature),
+                    // Keeps the decoded error reachable with `downcast_ref`.
+                    SendOutcome::Failed {
+                        decoded: Some(decoded),
+                        ..
+                    } => Err(eyre::Report::new(decoded)),
+                    outcome => Err(eyre::eyre!("{}", outcome)),
+                });
+
+            match outcome {
+                Ok(signature) => {
+                    signatures.push(signature.to_string());
+                    landed_instructions += instructions.len();
+                }
+                Err(e) => {
+                    self.drain_instructions(landed_instructions);
+                    self.invalidate_account_cache();
+
+                    let context = format!(
+                        "Transaction {} of {} failed after {} landed: {}",
+                        i + 1,
+                        plan.len(),
+                        signatures.len(),
+                        e
+                    );
+                    return Err(e.wrap_err(context));
+                }
+            }
+        }
+
+        self.clear()?;
+
+        Ok(signatures)
+    }
+}
+
+// `groups` holds the start index of each group, as recorded by `start_group`.
+pub fn split_instruction_groups<'a>(
+    instructions: &'a [Instruction],
+    groups: &[usize],
+) -> Vec<&'a [Instruction]> {
+    let len = instructions.len();
+    let mut bounds: Vec<usize> = groups
+        .iter()
+        .copied()
+        .filter(|start| *start < len)
+        .chain([0, len])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    bounds
+        .windows(2)
+        .map(|window| &instructions[window[0]..window[1]])
+        .collect()
+}
+
+// Greedily packs ordered groups into as few transactions as possible without splitting a group.
+// `prefix` is room reserved for instructions prepended to every transaction, such as compute budget.
+pub fn plan_instruction_groups(
+    groups: &[&[Instruction]],
+    prefix: &[Instruction],
+    payer: &Pubkey,
+    lookup_table_accounts: &[AddressLookupTableAccount],
+) -> Result<Vec<Vec<Instruction>>> {
+    let mut plan = vec![];
+    let mut current: Vec<Instruction> = vec![];
+
+    for (i, group) in groups.iter().enumerate() {
+        let mut candidate = [prefix, &current, group].concat();
+
+        if fits_in_transaction(&candidate, payer, lookup_table_accounts) {
+            current = candidate.split_off(prefix.len());
+            continue;
+        }
+
+        let message = compile_message(
+            &[prefix, group].concat(),
+            payer,
+            Hash::default(),
+            lookup_table_accounts,
+        )?;
+        let size = get_transaction_size(&message)?;
+        let accounts = get_transaction_account_count(&message);
+        if size > MAX_TRANSACTION_SIZE || accounts > MAX_TRANSACTION_ACCOUNTS {
+            return Err(eyre::eyre!(
+                "Instruction group {} does not fit in a single transaction: {} bytes (max {}), {} accounts (max {})",
+                i,
+                size,
+                MAX_TRANSACTION_SIZE,
+                accounts,
+                MAX_TRANSACTION_ACCOUNTS
+            ));
+        }
+
+        if !current.is_empty() {
+            plan.push(current);
+        }
+        current = group.to_vec();
+    }
+
+    if !current.is_empty() {
+        plan.push(current);
+    }
+
+    Ok(plan)
+}
+
+pub fn fits_in_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_table_accounts: &[AddressLookupTableAccount],
+) -> bool {
+    // Compile errors here mean the account indexes overflowed, so the instructions don't fit.
+    let Ok(message) = compile_message(instructions, payer, Hash::default(), lookup_table_accounts)
+    else {
+        return false;
+    };
+
+    match get_transaction_size(&message) {
+        Ok(size) => {
+            size <= MAX_TRANSACTION_SIZE
+                && get_transaction_account_count(&message) <= MAX_TRANSACTION_ACCOUNTS
+        }
+        Err(_) => false,
+    }
+}
+
+// Serialized size of the transaction once every required signature is present.
+pub fn get_transaction_size(message: &VersionedMessage) -> Result<usize> {
+    Ok(bincode::serialized_size(&unsigned_transaction(message.clone()))? as usize)
+}
+
+pub fn get_transaction_account_count(message: &VersionedMessage) -> usize {
+    let loaded_accounts: usize = message
+        .address_table_lookups()
+        .map(|lookups| {
+            lookups
+                .iter()
+                .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+                .sum()
+        })
+        .unwrap_or(0);
+
+    message.static_account_keys().len() + loaded_accounts
+}
@@ -0,0 +1,275 @@
+use std::{fmt, thread::sleep, time::Instant};
+
+use eyre::Result;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_config::RpcSendTransactionConfig,
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_instruction::Instruction;
+use solana_message::AddressLookupTableAccount;
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use solana_signer::Signer;
+use solana_transaction_status_client_types::TransactionStatus;
+
+use crate::utils::{
+    decode_transaction_error, instruction_program_ids, AccountSource, DecodedError,
+};
+
+use super::Builder;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendOutcome {
+    Landed {
+        signature: Signature,
+    },
+    // `decoded` names the program error when the error registry knows it.
+    Failed {
+        signature: Signature,
+        err: TransactionError,
+        decoded: Option<DecodedError>,
+    },
+    // The blockhash (or durable nonce) expired without the transaction landing.
+    Expired {
+        signature: Signature,
+    },
+}
+
+impl SendOutcome {
+    pub fn signature(&self) -> &Signature {
+        match self {
+            SendOutcome::Landed { signature }
+            | SendOutcome::Failed { signature, .. }
+            | SendOutcome::Expired { signature } => signature,
+        }
+    }
+
+    pub fn is_landed(&self) -> bool {
+        matches!(self, SendOutcome::Landed { .. })
+    }
+
+    // Returns `None` until the status reaches `commitment`.
+    pub fn from_status(
+        signature: &Signature,
+        status: Option<TransactionStatus>,
+        commitment: CommitmentConfig,
+    ) -> Option<Self> {
+        match status {
+            Some(status) if status.satisfies_commitment(commitment) => Some(match status.err {
+                Some(err) => SendOutcome::Failed {
+                    signature: *signature,
+                    err,
+                    decoded: None,
+                },
+                None => SendOutcome::Landed {
+                    signature: *signature,
+                },
+            }),
+            _ => None,
+        }
+    }
+
+    // Decodes the error of a failed send of `tx`. `logs` are the preflight logs when there are
+    // any, which name the program that failed inside a CPI.
+    pub fn decode_error(self, tx: &VersionedTransaction, logs: &[String]) -> Self {
+        match self {
+            SendOutcome::Failed { signature, err, .. } => {
+                let program_ids = instruction_program_ids(&tx.message);
+                let decoded = decode_transaction_error(&err, logs, &program_ids);
+                SendOutcome::Failed {
+                    signature,
+                    err,
+                    decoded,
+                }
+            }
+            outcome => outcome,
+        }
+    }
+}
+
+impl fmt::Display for SendOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendOutcome::Landed { signature } => write!(f, "{} landed", signature),
+            SendOutcome::Failed {
+                signature,
+                decoded: Some(decoded),
+                ..
+            } => write!(f, "{} failed: {}", signature, decoded),
+            SendOutcome::Failed { signature, err, .. } => {
+                write!(f, "{} failed: {}", signature, err)
+            }
+            SendOutcome::Expired { signature } => write!(f, "{} expired", signature),
+        }
+    }
+}
+
+impl<C: AccountSource> Builder<C> {
+    // Sends every instruction as a single transaction, clearing the builder only once it landed.
+    pub fn try_send<S: Signer + Send + Sync + 'static>(&mut self, payer: S) -> Result<SendOutcome> {
+        let payer_pubkey = payer.pubkey();
+        self.signers
+            .entry(payer_pubkey)
+            .or_insert_with(|| Box::new(payer));
+
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let outcome =
+            self.send_instructions(&self.instructions, &payer_pubkey, &lookup_table_accounts);
+
+        // A transaction that didn't land may still have changed accounts, so the plan is rebuilt
+        // from fresh reads.
+        match &outcome {
+            Ok(outcome) if outcome.is_landed() => self.clear()?,
+            _ => self.invalidate_account_cache(),
+        }
+
+        outcome
+    }
+
+    // Compiles, signs and sends the instructions. A fresh blockhash is only used once the
+    // previous one has expired without the transaction landing, so it can't land twice.
+    pub fn send_instructions(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<SendOutcome> {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            let (blockhash, last_valid_block_height) = self.get_blockhash_with_expiry()?;
+            let message = self.compile_transaction_message(
+                instructions,
+                payer,
+                blockhash,
+                lookup_table_accounts,
+            )?;
+            let tx = self.sign_message(message)?;
+
+            let outcome = self.send_until_expired(&tx, &blockhash, last_valid_block_height)?;
+            if !matches!(outcome, SendOutcome::Expired { .. })
+                || attempt >= self.send_config.max_attempts
+            {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    // Sends a signed transaction and rebroadcasts it until it lands, fails or expires.
+    pub fn send_until_expired(
+        &self,
+        tx: &VersionedTransaction,
+        blockhash: &Hash,
+        last_valid_block_height: u64,
+    ) -> Result<SendOutcome> {
+        let signature = tx.signatures[0];
+
+        // The first send runs preflight so failures surface before waiting on the blockhash.
+        if let Err(e) = self.client.send_transaction(tx) {
+            // Preflight failures come back as a client error carrying the transaction error.
+            let client_err = e.downcast_ref::<ClientError>();
+            return match client_err.and_then(|e| e.get_transaction_error()) {
+                Some(err) => Ok(SendOutcome::Failed {
+                    signature,
+                    err,
+                    decoded: None,
+                }
+                .decode_error(tx, &client_err.map(preflight_logs).unwrap_or_default())),
+                None => Err(e),
+            };
+        }
+
+        let rebroadcast_config = rebroadcast_send_config();
+        let mut last_broadcast = Instant::now();
+
+        loop {
+            sleep(self.send_config.poll_interval);
+
+            // A status below the client's commitment means the transaction was processed, so it
+            // is waited on even past expiry instead of being signed again.
+            let status = self.get_signature_status(&signature)?;
+            let processed = status.is_some();
+            if let Some(outcome) =
+                SendOutcome::from_status(&signature, status, self.client.commitment())
+            {
+                return Ok(outcome.decode_error(tx, &[]));
+            }
+
+            if !processed && self.is_blockhash_expired(blockhash, last_valid_block_height)? {
+                // It may have been processed between the status check and the expiry check.
+                if self.get_signature_status(&signature)?.is_none() {
+                    return Ok(SendOutcome::Expired { signature });
+                }
+                continue;
+            }
+
+            if last_broadcast.elapsed() >= self.send_config.rebroadcast_interval {
+                // Errors are ignored here, the signature status is what decides the outcome.
+                let _ = self
+                    .client
+                    .send_transaction_with_config(tx, rebroadcast_config);
+                last_broadcast = Instant::now();
+            }
+        }
+    }
+
+    // The status at any commitment, `None` while the cluster hasn't processed the signature.
+    pub fn get_signature_status(&self, signature: &Signature) -> Result<Option<TransactionStatus>> {
+        Ok(self
+            .client
+            .get_signature_statuses(&[*signature])?
+            .into_iter()
+            .next()
+            .flatten())
+    }
+
+    // A durable nonce has no block height limit, so `u64::MAX` is returned for it.
+    pub fn get_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        if self.durable_nonce.is_some() {
+            return Ok((self.get_blockhash()?, u64::MAX));
+        }
+
+        self.client.get_latest_blockhash_with_expiry()
+    }
+
+    // A durable nonce expires once it has been advanced.
+    pub fn is_blockhash_expired(
+        &self,
+        blockhash: &Hash,
+        last_valid_block_height: u64,
+    ) -> Result<bool> {
+        if self.durable_nonce.is_some() {
+            return Ok(self.get_blockhash()? != *blockhash);
+        }
+
+        Ok(self.client.get_block_height()? > last_valid_block_height)
+    }
+}
+
+// Rebroadcasts skip preflight, which already ran on the first send, and leave retries to us.
+pub fn rebroadcast_send_config() -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig {
+        skip_preflight: true,
+        max_retries: Some(0),
+        ..Default::default()
+    }
+}
+
+// Logs of the simulation a failed preflight ran.
+pub fn preflight_logs(e: &ClientError) -> Vec<String> {
+    match e.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone().unwrap_or_default(),
+        _ => vec![],
+    }
+}
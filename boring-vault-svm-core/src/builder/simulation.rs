@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anchor_lang::Discriminator;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::Result;
+use solana_account::Account;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_instruction::Instruction;
+use solana_message::AddressLookupTableAccount;
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash, transaction::TransactionError};
+use spl_token::state::Account as TokenAccount;
+
+use crate::utils::{
+    boring_vault_svm, decode_transaction_error, get_multiple_accounts_chunked, get_vault_pda,
+    instruction_program_ids, DecodedError,
+};
+
+use crate::utils::AccountSource;
+
+use super::{compile_message, plan_instruction_groups, unsigned_transaction, Builder};
+
+// Token-2022 `AccountType::Account`.
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceKind {
+    // Lamports held by a vault sub-account PDA.
+    SubAccount { vault_id: u64, sub_account: u8 },
+    // Amount held by an SPL Token or Token-2022 account.
+    TokenAccount { mint: Pubkey, owner: Pubkey },
+}
+
+#[derive(Debug, Clone)]
+pub struct BalanceChange {
+    pub address: Pubkey,
+    pub kind: BalanceKind,
+    pub pre: u64,
+    pub post: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    pub err: Option<TransactionError>,
+    pub decoded_error: Option<DecodedError>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    // Program that set the return data and the raw bytes.
+    pub return_data: Option<(Pubkey, Vec<u8>)>,
+    pub balances: Vec<BalanceChange>,
+}
+
+impl SimulationResult {
+    pub fn is_success(&self) -> bool {
+        self.err.is_none()
+    }
+}
+
+impl<C: AccountSource> Builder<C> {
+    // Simulates every instruction as a single transaction.
+    pub fn simulate(&self, payer: &Pubkey) -> Result<SimulationResult> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+
+        self.simulate_instructions(&self.instructions, payer, &lookup_table_accounts)
+    }
+
+    // Simulates each planned transaction against the current state, so a transaction that
+    // depends on an earlier one in the plan landing first will report that failure.
+    pub fn simulate_plan(&self, payer: &Pubkey) -> Result<Vec<SimulationResult>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts()?;
+        let plan = plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            payer,
+            &lookup_table_accounts,
+        )?;
+
+        plan.iter()
+            .map(|instructions| {
+                self.simulate_instructions(instructions, payer, &lookup_table_accounts)
+            })
+            .collect()
+    }
+
+    pub fn simulate_instructions(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<SimulationResult> {
+        // Placeholders run with the max compute unit limit, so simulation isn't cut short.
+        let mut simulated = self.compute_budget_placeholder_instructions();
+        simulated.extend_from_slice(instructions);
+        let message = compile_message(&simulated, payer, Hash::default(), lookup_table_accounts)?;
+        let program_ids = instruction_program_ids(&message);
+
+        let addresses = get_touched_accounts(instructions);
+        let pre_accounts = get_multiple_accounts_chunked(&self.client, &addresses)?;
+
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(CommitmentConfig::processed()),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: addresses
+                    .iter()
+                    .map(|address| address.to_string())
+                    .collect(),
+            }),
+            ..Default::default()
+        };
+
+        let result = self
+            .client
+            .simulate_transaction(&unsigned_transaction(message), config)?;
+
+        let post_accounts: Vec<Option<Account>> = match result.accounts {
+            Some(accounts) => accounts
+                .iter()
+                .map(|account| account.as_ref().and_then(|account| account.decode()))
+                .collect(),
+            None => vec![None; addresses.len()],
+        };
+
+        let logs = result.logs.unwrap_or_default();
+        let decoded_error = result
+            .err
+            .as_ref()
+            .and_then(|err| decode_transaction_error(err, &logs, &program_ids));
+
+        let return_data = match result.return_data {
+            Some(return_data) => Some((
+                Pubkey::from_str(&return_data.program_id)?,
+                STANDARD.decode(&return_data.data.0)?,
+            )),
+            None => None,
+        };
+
+        let sub_accounts = self.get_sub_accounts(instructions);
+        let balances = addresses
+            .iter()
+            .zip(pre_accounts.iter().zip(post_accounts.iter()))
+            .filter_map(|(address, (pre, post))| {
+                get_balance_change(address, pre.as_ref(), post.as_ref(), &sub_accounts)
+            })
+            .collect();
+
+        Ok(SimulationResult {
+            err: result.err,
+            decoded_error,
+            logs,
+            units_consumed: result.units_consumed,
+            return_data,
+            balances,
+        })
+    }
+
+    // Sub-account PDAs of the configured vault and every vault the instructions operate on.
+    fn get_sub_accounts(&self, instructions: &[Instruction]) -> HashMap<Pubkey, (u64, u8)> {
+        let mut vault_ids = vec![self.vault_config.vault_id];
+        for vault_id in instructions.iter().filter_map(get_instruction_vault_id) {
+            if !vault_ids.contains(&vault_id) {
+                vault_ids.push(vault_id);
+            }
+        }
+
+        vault_ids
+            .into_iter()
+            .flat_map(|vault_id| {
+                (0..=u8::MAX).map(move |sub_account| {
+                    (
+                        get_vault_pda(vault_id, sub_account),
+                        (vault_id, sub_account),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+fn get_touched_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = vec![];
+
+    for account in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if !accounts.contains(&account.pubkey) {
+            accounts.push(account.pubkey);
+        }
+    }
+
+    accounts
+}
+
+// Every boring vault instruction except these takes the vault id as its first argument.
+fn get_instruction_vault_id(ix: &Instruction) -> Option<u64> {
+    if ix.program_id != boring_vault_svm::ID {
+        return None;
+    }
+
+    let discriminator = ix.data.get(..8)?;
+    if [
+        boring_vault_svm::client::args::Initialize::DISCRIMINATOR,
+        boring_vault_svm::client::args::Deploy::DISCRIMINATOR,
+        boring_vault_svm::client::args::ViewCpiDigest::DISCRIMINATOR,
+    ]
+    .contains(&discriminator)
+    {
+        return None;
+    }
+
+    Some(u64::from_le_bytes(ix.data.get(8..16)?.try_into().ok()?))
+}
+
+fn get_balance_change(
+    address: &Pubkey,
+    pre: Option<&Account>,
+    post: Option<&Account>,
+    sub_accounts: &HashMap<Pubkey, (u64, u8)>,
+) -> Option<BalanceChange> {
+    if let Some((vault_id, sub_account)) = sub_accounts.get(address) {
+        return Some(BalanceChange {
+            address: *address,
+            kind: BalanceKind::SubAccount {
+                vault_id: *vault_id,
+                sub_account: *sub_account,
+            },
+            pre: pre.map(|account| account.lamports).unwrap_or(0),
+            post: post.map(|account| account.lamports).unwrap_or(0),
+        });
+    }
+
+    // Token accounts created or closed in the transaction only exist on one side.
+    let pre_token = pre.and_then(unpack_token_account);
+    let post_token = post.and_then(unpack_token_account);
+    let (mint, owner, _) = post_token.or(pre_token)?;
+
+    Some(BalanceChange {
+        address: *address,
+        kind: BalanceKind::TokenAccount { mint, owner },
+        pre: pre_token.map(|(_, _, amount)| amount).unwrap_or(0),
+        post: post_token.map(|(_, _, amount)| amount).unwrap_or(0),
+    })
+}
+
+// Returns the mint, owner and amount of an SPL Token or Token-2022 account.
+fn unpack_token_account(account: &Account) -> Option<(Pubkey, Pubkey, u64)> {
+    if account.owner != spl_token::ID && account.owner != spl_token_2022::ID {
+        return None;
+    }
+
+    // Token-2022 accounts share the base layout, followed by the account type and extensions.
+    if account.data.len() > TokenAccount::LEN
+        && account.data[TokenAccount::LEN] != ACCOUNT_TYPE_ACCOUNT
+    {
+        return None;
+    }
+    let token_account = TokenAccount::unpack(account.data.get(..TokenAccount::LEN)?).ok()?;
+
+    Some((
+        token_account.mint,
+        token_account.owner,
+        token_account.amount,
+    ))
+}
@@ -1,25 +1,41 @@
 use std::{collections::HashMap, path::PathBuf};
 
-use solana_client::rpc_client::RpcClient;
-
-use crate::builder::Builder;
-
-use super::{KaminoConfig, VaultConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient as NonblockingRpcClient, rpc_client::RpcClient,
+};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    builder::Builder,
+    nonblocking::{AccountSnapshot, AsyncBuilder},
+    utils::{AccountCache, AccountSource, KeypairSource},
+};
+
+use super::{
+    cluster_block_engine_url, BundleConfig, ComputeBudgetConfig, KaminoConfig, SendConfig,
+    VaultConfig,
+};
 
 const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
 const DEFAULT_DATA_PATH: &str = "../data";
 const DEFAULT_KAMINO_FILE: &str = "kamino.json";
 const DEFAULT_VAULT_FILE: &str = "vaults.json";
-const DEFAULT_LEND_MINT: &str = "jito";
-const DEFAULT_BORROW_MINT: &str = "sol";
+const DEFAULT_LEND_RESERVE: &str = "jito";
+const DEFAULT_BORROW_RESERVE: &str = "sol";
 
 pub struct BuilderConfig {
     rpc_url: String,
     data_path: String,
     kamino_file: String,
     vault_file: String,
-    lend_mint: String,
-    borrow_mint: String,
+    default_lend_reserve: String,
+    default_borrow_reserve: String,
+    lookup_tables: Vec<Pubkey>,
+    compute_budget: Option<ComputeBudgetConfig>,
+    bundle: BundleConfig,
+    send: SendConfig,
+    payer: Option<KeypairSource>,
+    cpi_digest_check: bool,
 }
 
 impl Default for BuilderConfig {
@@ -29,8 +45,14 @@ impl Default for BuilderConfig {
             data_path: DEFAULT_DATA_PATH.to_string(),
             kamino_file: DEFAULT_KAMINO_FILE.to_string(),
             vault_file: DEFAULT_VAULT_FILE.to_string(),
-            lend_mint: DEFAULT_LEND_MINT.to_string(),
-            borrow_mint: DEFAULT_BORROW_MINT.to_string(),
+            default_lend_reserve: DEFAULT_LEND_RESERVE.to_string(),
+            default_borrow_reserve: DEFAULT_BORROW_RESERVE.to_string(),
+            lookup_tables: vec![],
+            compute_budget: Some(ComputeBudgetConfig::default()),
+            bundle: BundleConfig::default(),
+            send: SendConfig::default(),
+            payer: None,
+            cpi_digest_check: false,
         }
     }
 }
@@ -56,21 +78,128 @@ impl BuilderConfig {
         self
     }
 
-    pub fn with_lend_mint(mut self, lend_mint: impl Into<String>) -> Self {
-        self.lend_mint = lend_mint.into();
+    pub fn with_default_lend_reserve(mut self, default_lend_reserve: impl Into<String>) -> Self {
+        self.default_lend_reserve = default_lend_reserve.into();
         self
     }
 
-    pub fn with_borrow_mint(mut self, borrow_mint: impl Into<String>) -> Self {
-        self.borrow_mint = borrow_mint.into();
+    pub fn with_default_borrow_reserve(
+        mut self,
+        default_borrow_reserve: impl Into<String>,
+    ) -> Self {
+        self.default_borrow_reserve = default_borrow_reserve.into();
+        self
+    }
+
+    pub fn with_lookup_tables(mut self, lookup_tables: Vec<Pubkey>) -> Self {
+        self.lookup_tables = lookup_tables;
+        self
+    }
+
+    pub fn with_compute_budget(mut self, compute_budget: ComputeBudgetConfig) -> Self {
+        self.compute_budget = Some(compute_budget);
+        self
+    }
+
+    pub fn without_compute_budget(mut self) -> Self {
+        self.compute_budget = None;
+        self
+    }
+
+    pub fn with_bundle(mut self, bundle: BundleConfig) -> Self {
+        self.bundle = bundle;
+        self
+    }
+
+    pub fn with_send(mut self, send: SendConfig) -> Self {
+        self.send = send;
+        self
+    }
+
+    // Fee payer for sends that don't pass one, e.g. "file:~/.config/solana/id.json".
+    pub fn with_payer(mut self, payer: KeypairSource) -> Self {
+        self.payer = Some(payer);
+        self
+    }
+
+    // Before initializing a CPI digest, check it against the program's `view_cpi_digest`, so a
+    // digest the program would never match isn't created. Costs a simulation per new digest.
+    pub fn with_cpi_digest_check(mut self, cpi_digest_check: bool) -> Self {
+        self.cpi_digest_check = cpi_digest_check;
         self
     }
 
     pub fn build(self) -> Builder {
-        let client = RpcClient::new(self.rpc_url);
-        let instructions = vec![];
-        let signers = HashMap::new();
+        let client = RpcClient::new(self.rpc_url.clone());
+        self.build_with_client(client)
+    }
+
+    // Same as `build` but reads accounts and sends through `client` instead of the RPC url,
+    // e.g. an in-memory or fixture source.
+    pub fn build_with_client<C: AccountSource>(self, client: C) -> Builder<C> {
+        let (kamino_config, vault_config) = self.load_protocol_configs();
+        let bundle_config = self.bundle_config();
 
+        Builder {
+            client,
+            instructions: vec![],
+            groups: vec![],
+            signers: HashMap::new(),
+            lookup_tables: self.lookup_tables,
+            compute_budget: self.compute_budget,
+            bundle_config,
+            send_config: self.send,
+            durable_nonce: None,
+            payer: self.payer,
+            account_cache: AccountCache::new(),
+            pending_vault_ids: vec![],
+            cpi_digest_check: self.cpi_digest_check,
+            kamino_config,
+            vault_config,
+        }
+    }
+
+    // Same as `build` but backed by the nonblocking RPC client.
+    pub fn build_async(self) -> AsyncBuilder {
+        let client = NonblockingRpcClient::new(self.rpc_url.clone());
+        self.build_async_with_client(client)
+    }
+
+    // Same as `build_async` on top of `client`, e.g. one with its own `RpcSender`.
+    pub fn build_async_with_client(self, client: NonblockingRpcClient) -> AsyncBuilder {
+        let (kamino_config, vault_config) = self.load_protocol_configs();
+        let bundle_config = self.bundle_config();
+
+        AsyncBuilder {
+            client,
+            instructions: vec![],
+            groups: vec![],
+            signers: HashMap::new(),
+            lookup_tables: self.lookup_tables,
+            compute_budget: self.compute_budget,
+            bundle_config,
+            send_config: self.send,
+            durable_nonce: None,
+            payer: self.payer,
+            account_cache: AccountSnapshot::new(),
+            pending_vault_ids: vec![],
+            cpi_digest_check: self.cpi_digest_check,
+            kamino_config,
+            vault_config,
+        }
+    }
+
+    // The block engine of the RPC url's cluster, unless one was set.
+    fn bundle_config(&self) -> BundleConfig {
+        let mut bundle = self.bundle.clone();
+        if bundle.block_engine_url.is_none() {
+            bundle.block_engine_url = cluster_block_engine_url(&self.rpc_url).map(str::to_string);
+        }
+
+        bundle
+    }
+
+    fn load_protocol_configs(&self) -> (KaminoConfig, VaultConfig) {
         // Construct file paths
         let kamino_path = PathBuf::from(&self.data_path).join(&self.kamino_file);
         let vault_path = PathBuf::from(&self.data_path).join(&self.vault_file);
@@ -78,20 +207,14 @@ impl BuilderConfig {
         // Load configurations
         let kamino_config = KaminoConfig::new(
             kamino_path.to_str().expect("Invalid path"),
-            &self.lend_mint,
-            &self.borrow_mint,
+            &self.default_lend_reserve,
+            &self.default_borrow_reserve,
         )
         .expect("Failed to load Kamino config");
 
         let vault_config = VaultConfig::new(vault_path.to_str().expect("Invalid path"))
             .expect("Failed to load Vault config");
 
-        Builder {
-            client,
-            instructions,
-            signers,
-            kamino_config,
-            vault_config,
-        }
+        (kamino_config, vault_config)
     }
 }
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use anchor_lang::pubkey;
+use eyre::{eyre, Result};
+use solana_pubkey::Pubkey;
+
+const MAINNET_BLOCK_ENGINE_URL: &str = "https://mainnet.block-engine.jito.wtf/api/v1/bundles";
+const TESTNET_BLOCK_ENGINE_URL: &str =
+    "https://dallas.testnet.block-engine.jito.wtf/api/v1/bundles";
+const DEFAULT_TIP_ACCOUNT: Pubkey = pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5");
+const DEFAULT_TIP_LAMPORTS: u64 = 10_000;
+const DEFAULT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub struct BundleConfig {
+    // Block engine JSON-RPC endpoint serving `sendBundle` and `getBundleStatuses`. Derived from
+    // the RPC url by `BuilderConfig` when not set, bundles error without one.
+    pub block_engine_url: Option<String>,
+    pub tip_account: Pubkey,
+    pub tip_lamports: u64,
+    pub confirm_timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for BundleConfig {
+    fn default() -> Self {
+        Self {
+            block_engine_url: None,
+            tip_account: DEFAULT_TIP_ACCOUNT,
+            tip_lamports: DEFAULT_TIP_LAMPORTS,
+            confirm_timeout: DEFAULT_CONFIRM_TIMEOUT,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+impl BundleConfig {
+    pub fn with_block_engine_url(mut self, block_engine_url: impl Into<String>) -> Self {
+        self.block_engine_url = Some(block_engine_url.into());
+        self
+    }
+
+    pub fn block_engine_url(&self) -> Result<&str> {
+        self.block_engine_url.as_deref().ok_or_else(|| {
+            eyre!("No block engine url for this cluster, set one with `with_block_engine_url`")
+        })
+    }
+
+    pub fn with_tip_account(mut self, tip_account: Pubkey) -> Self {
+        self.tip_account = tip_account;
+        self
+    }
+
+    pub fn with_tip_lamports(mut self, tip_lamports: u64) -> Self {
+        self.tip_lamports = tip_lamports;
+        self
+    }
+
+    pub fn with_confirm_timeout(mut self, confirm_timeout: Duration) -> Self {
+        self.confirm_timeout = confirm_timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+// Jito only runs block engines for mainnet and testnet, other clusters need an explicit url.
+pub fn cluster_block_engine_url(rpc_url: &str) -> Option<&'static str> {
+    if rpc_url.contains("mainnet") {
+        Some(MAINNET_BLOCK_ENGINE_URL)
+    } else if rpc_url.contains("testnet") {
+        Some(TESTNET_BLOCK_ENGINE_URL)
+    } else {
+        None
+    }
+}
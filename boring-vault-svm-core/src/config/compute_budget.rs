@@ -0,0 +1,49 @@
+const DEFAULT_COMPUTE_UNIT_MARGIN_BPS: u16 = 1_000;
+const DEFAULT_PRIORITY_FEE_PERCENTILE: u8 = 50;
+const DEFAULT_MAX_PRIORITY_FEE: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFeePolicy {
+    // No compute unit price instruction is added.
+    None,
+    // Fixed price in micro-lamports per compute unit.
+    Fixed(u64),
+    // Percentile (0-100) of recent prioritization fees paid for the transaction's writable accounts.
+    Percentile(u8),
+}
+
+#[derive(Debug, Clone)]
+pub struct ComputeBudgetConfig {
+    // Extra compute units added on top of the simulated units consumed, in bps.
+    pub compute_unit_margin_bps: u16,
+    pub priority_fee: PriorityFeePolicy,
+    // Upper bound on the compute unit price in micro-lamports.
+    pub max_priority_fee: Option<u64>,
+}
+
+impl Default for ComputeBudgetConfig {
+    fn default() -> Self {
+        Self {
+            compute_unit_margin_bps: DEFAULT_COMPUTE_UNIT_MARGIN_BPS,
+            priority_fee: PriorityFeePolicy::Percentile(DEFAULT_PRIORITY_FEE_PERCENTILE),
+            max_priority_fee: Some(DEFAULT_MAX_PRIORITY_FEE),
+        }
+    }
+}
+
+impl ComputeBudgetConfig {
+    pub fn with_compute_unit_margin_bps(mut self, compute_unit_margin_bps: u16) -> Self {
+        self.compute_unit_margin_bps = compute_unit_margin_bps;
+        self
+    }
+
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFeePolicy) -> Self {
+        self.priority_fee = priority_fee;
+        self
+    }
+
+    pub fn with_max_priority_fee(mut self, max_priority_fee: Option<u64>) -> Self {
+        self.max_priority_fee = max_priority_fee;
+        self
+    }
+}
@@ -0,0 +1,109 @@
+use std::{fs, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::Result;
+use serde::{Deserialize, Deserializer};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::utils::{deserialize_pubkey, deserialize_pubkey_vec};
+
+// A Jupiter `/swap-instructions` response, saved to a file or fetched by the caller. Compute
+// budget and token ledger instructions are left out, the builder sets its own compute budget.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JupiterSwapInstructions {
+    #[serde(default, deserialize_with = "deserialize_instructions")]
+    pub setup_instructions: Vec<Instruction>,
+    #[serde(deserialize_with = "deserialize_instruction")]
+    pub swap_instruction: Instruction,
+    #[serde(default, deserialize_with = "deserialize_optional_instruction")]
+    pub cleanup_instruction: Option<Instruction>,
+    #[serde(default, deserialize_with = "deserialize_pubkey_vec")]
+    pub address_lookup_table_addresses: Vec<Pubkey>,
+}
+
+impl JupiterSwapInstructions {
+    pub fn new(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(Path::new(path))
+            .map_err(|e| eyre::eyre!("Failed to read swap instructions '{}': {}", path, e))?;
+
+        Self::from_json(&content)
+            .map_err(|e| eyre::eyre!("Failed to parse swap instructions '{}': {}", path, e))
+    }
+
+    pub fn from_json(content: &str) -> Result<Self> {
+        Ok(serde_json::from_str(content)?)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterInstruction {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    program_id: Pubkey,
+    accounts: Vec<JupiterAccountMeta>,
+    // Base64 encoded.
+    data: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JupiterAccountMeta {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pubkey: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl TryFrom<JupiterInstruction> for Instruction {
+    type Error = base64::DecodeError;
+
+    fn try_from(instruction: JupiterInstruction) -> Result<Self, Self::Error> {
+        Ok(Instruction {
+            program_id: instruction.program_id,
+            accounts: instruction
+                .accounts
+                .into_iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: STANDARD.decode(instruction.data)?,
+        })
+    }
+}
+
+fn deserialize_instruction<'de, D>(deserializer: D) -> Result<Instruction, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    JupiterInstruction::deserialize(deserializer)?
+        .try_into()
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_optional_instruction<'de, D>(
+    deserializer: D,
+) -> Result<Option<Instruction>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<JupiterInstruction>::deserialize(deserializer)?
+        .map(Instruction::try_from)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_instructions<'de, D>(deserializer: D) -> Result<Vec<Instruction>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<JupiterInstruction>::deserialize(deserializer)?
+        .into_iter()
+        .map(Instruction::try_from)
+        .collect::<Result<_, _>>()
+        .map_err(serde::de::Error::custom)
+}
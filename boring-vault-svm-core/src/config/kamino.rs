@@ -1,24 +1,14 @@
 use crate::utils::{deserialize_pubkey, deserialize_pubkey_vec};
-use eyre::Result;
+use eyre::{eyre, Result};
 use serde::Deserialize;
 use solana_pubkey::Pubkey;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
+use std::str::FromStr;
 
 #[derive(Debug, Deserialize, Clone)]
-pub struct KaminoLendStrategyConfig {
-    #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve: Pubkey,
-    #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_farm_state: Pubkey,
-    #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_liquidity_mint: Pubkey,
-    #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_liquidity_supply: Pubkey,
-    #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_collateral_mint: Pubkey,
-    #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_destination_deposit_collateral: Pubkey,
+pub struct KaminoMarketConfig {
     #[serde(deserialize_with = "deserialize_pubkey")]
     pub lending_market: Pubkey,
     #[serde(deserialize_with = "deserialize_pubkey")]
@@ -27,77 +17,351 @@ pub struct KaminoLendStrategyConfig {
     pub oracle_mapping: Pubkey,
     #[serde(deserialize_with = "deserialize_pubkey")]
     pub oracle_twaps: Pubkey,
+    // The scope prices `refresh_price_list` updates.
     #[serde(deserialize_with = "deserialize_pubkey_vec")]
     pub price_accounts: Vec<Pubkey>,
     pub tokens: Vec<u16>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct KaminoBorrowStrategyConfig {
+// Accounts only needed to lend (collateral) or to borrow (fee receiver) can be left out of reserves
+// that are only used the other way.
+#[derive(Debug, Deserialize, Clone)]
+pub struct KaminoReserveConfig {
+    // Filled in from the reserve's key in the config.
+    #[serde(skip)]
+    pub name: String,
+    // Key of the market in `markets`.
+    pub market: String,
     #[serde(deserialize_with = "deserialize_pubkey")]
     pub reserve: Pubkey,
     #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_source_liquidity_mint: Pubkey,
+    pub liquidity_mint: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub liquidity_supply: Pubkey,
+    #[serde(default, deserialize_with = "deserialize_pubkey")]
+    pub collateral_mint: Pubkey,
+    #[serde(default, deserialize_with = "deserialize_pubkey")]
+    pub collateral_supply: Pubkey,
+    #[serde(default, deserialize_with = "deserialize_pubkey")]
+    pub fee_receiver: Pubkey,
+    #[serde(default, deserialize_with = "deserialize_pubkey")]
+    pub farm_collateral: Pubkey,
+    #[serde(default, deserialize_with = "deserialize_pubkey")]
+    pub farm_debt: Pubkey,
+}
+
+impl KaminoReserveConfig {
+    // The collateral mint and supply, which lending and withdrawing need.
+    pub fn collateral(&self) -> Result<(Pubkey, Pubkey)> {
+        if self.collateral_mint == Pubkey::default() || self.collateral_supply == Pubkey::default()
+        {
+            return Err(eyre!(
+                "Kamino reserve '{}' has no collateral_mint or collateral_supply configured",
+                self.name
+            ));
+        }
+
+        Ok((self.collateral_mint, self.collateral_supply))
+    }
+
+    pub fn fee_receiver(&self) -> Result<Pubkey> {
+        if self.fee_receiver == Pubkey::default() {
+            return Err(eyre!(
+                "Kamino reserve '{}' has no fee_receiver configured",
+                self.name
+            ));
+        }
+
+        Ok(self.fee_receiver)
+    }
+
+    // The collateral farm for mode 0 and the debt farm for mode 1.
+    pub fn farm(&self, mode: u8) -> Result<Pubkey> {
+        let farm = match mode {
+            0 => self.farm_collateral,
+            1 => self.farm_debt,
+            _ => return Err(eyre!("Invalid Kamino farm mode {}", mode)),
+        };
+        if farm == Pubkey::default() {
+            return Err(eyre!(
+                "Kamino reserve '{}' has no farm configured for mode {}",
+                self.name,
+                mode
+            ));
+        }
+
+        Ok(farm)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct KaminoConfigFile {
+    markets: BTreeMap<String, KaminoMarketConfig>,
+    reserves: BTreeMap<String, KaminoReserveConfig>,
+}
+
+// The older layout keyed profiles by name, each with a `lend` and/or `borrow` section. Borrow
+// sections carried no market, they used the lend profile's.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacyKaminoProfile {
+    lend: Option<LegacyKaminoLend>,
+    borrow: Option<LegacyKaminoBorrow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyKaminoLend {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve_farm_state: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve_liquidity_mint: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve_liquidity_supply: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve_collateral_mint: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve_destination_deposit_collateral: Pubkey,
+    #[serde(flatten)]
+    market: KaminoMarketConfig,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyKaminoBorrow {
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve: Pubkey,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    reserve_source_liquidity_mint: Pubkey,
     #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_source_liquidity: Pubkey,
+    reserve_source_liquidity: Pubkey,
     #[serde(deserialize_with = "deserialize_pubkey")]
-    pub reserve_source_liquidity_fee_receiver: Pubkey,
+    reserve_source_liquidity_fee_receiver: Pubkey,
 }
 
-#[derive(Debug)]
+const CONFIG_FORMAT: &str = "expected {\"markets\": {<name>: {lending_market, oracle_prices, \
+    oracle_mapping, oracle_twaps, price_accounts, tokens}}, \"reserves\": {<name>: {market, \
+    reserve, liquidity_mint, liquidity_supply, ...}}}, see data/kamino.json";
+
+impl KaminoConfigFile {
+    fn parse(json_content: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(json_content)?;
+        let is_legacy = value.as_object().is_some_and(|object| {
+            !object.contains_key("markets")
+                && !object.contains_key("reserves")
+                && object
+                    .values()
+                    .any(|profile| profile.get("lend").is_some() || profile.get("borrow").is_some())
+        });
+        if is_legacy {
+            let profiles = serde_json::from_value(value).map_err(|e| {
+                eyre!(
+                    "old lend/borrow profile layout that couldn't be converted ({}), {}",
+                    e,
+                    CONFIG_FORMAT
+                )
+            })?;
+            return Self::from_legacy(profiles);
+        }
+
+        serde_json::from_value(value).map_err(|e| eyre!("{}, {}", e, CONFIG_FORMAT))
+    }
+
+    // Lend profiles become a market per lending market, named after the first profile on it, and
+    // every profile becomes a reserve of the same name.
+    fn from_legacy(profiles: BTreeMap<String, LegacyKaminoProfile>) -> Result<Self> {
+        let mut markets = BTreeMap::<String, KaminoMarketConfig>::new();
+        for (name, profile) in &profiles {
+            if let Some(lend) = &profile.lend {
+                if !markets
+                    .values()
+                    .any(|market| market.lending_market == lend.market.lending_market)
+                {
+                    markets.insert(name.clone(), lend.market.clone());
+                }
+            }
+        }
+        let market_of = |lending_market: Option<Pubkey>| -> Result<String> {
+            let mut names = markets.iter().filter(|(_, market)| {
+                lending_market.is_none_or(|address| market.lending_market == address)
+            });
+            match (names.next(), names.next()) {
+                (Some((name, _)), None) => Ok(name.clone()),
+                _ => Err(eyre!(
+                    "borrow profiles only convert alongside exactly one lend market, {}",
+                    CONFIG_FORMAT
+                )),
+            }
+        };
+
+        let mut reserves = BTreeMap::new();
+        for (name, profile) in profiles {
+            let mut reserve = match (&profile.lend, &profile.borrow) {
+                (Some(lend), _) => KaminoReserveConfig {
+                    name: String::new(),
+                    market: market_of(Some(lend.market.lending_market))?,
+                    reserve: lend.reserve,
+                    liquidity_mint: lend.reserve_liquidity_mint,
+                    liquidity_supply: lend.reserve_liquidity_supply,
+                    collateral_mint: lend.reserve_collateral_mint,
+                    collateral_supply: lend.reserve_destination_deposit_collateral,
+                    fee_receiver: Pubkey::default(),
+                    farm_collateral: lend.reserve_farm_state,
+                    farm_debt: Pubkey::default(),
+                },
+                (None, Some(borrow)) => KaminoReserveConfig {
+                    name: String::new(),
+                    market: market_of(None)?,
+                    reserve: borrow.reserve,
+                    liquidity_mint: borrow.reserve_source_liquidity_mint,
+                    liquidity_supply: borrow.reserve_source_liquidity,
+                    collateral_mint: Pubkey::default(),
+                    collateral_supply: Pubkey::default(),
+                    fee_receiver: Pubkey::default(),
+                    farm_collateral: Pubkey::default(),
+                    farm_debt: Pubkey::default(),
+                },
+                (None, None) => {
+                    return Err(eyre!(
+                        "profile '{}' has neither lend nor borrow, {}",
+                        name,
+                        CONFIG_FORMAT
+                    ))
+                }
+            };
+            if let Some(borrow) = &profile.borrow {
+                if borrow.reserve != reserve.reserve {
+                    return Err(eyre!(
+                        "profile '{}' lends and borrows on different reserves, {}",
+                        name,
+                        CONFIG_FORMAT
+                    ));
+                }
+                reserve.fee_receiver = borrow.reserve_source_liquidity_fee_receiver;
+            }
+            reserves.insert(name, reserve);
+        }
+
+        Ok(Self { markets, reserves })
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct KaminoConfig {
-    pub lend: KaminoLendStrategyConfig,
-    pub borrow: KaminoBorrowStrategyConfig,
-    pub lending_market: Pubkey,
-    pub oracle_prices: Pubkey,
-    pub oracle_mapping: Pubkey,
-    pub oracle_twaps: Pubkey,
-    pub price_accounts: Vec<Pubkey>,
-    pub tokens: Vec<u16>,
+    pub markets: BTreeMap<String, KaminoMarketConfig>,
+    pub reserves: BTreeMap<String, KaminoReserveConfig>,
+    // Names of the reserves used when a call doesn't pick one.
+    pub default_lend: String,
+    pub default_borrow: String,
 }
 
 impl KaminoConfig {
-    pub fn new(
-        json_path: &str,
-        lend_strategy_profile_key: &str,
-        borrow_strategy_profile_key: &str,
-    ) -> Result<Self> {
+    pub fn new(json_path: &str, default_lend: &str, default_borrow: &str) -> Result<Self> {
         let json_content = fs::read_to_string(Path::new(json_path))
             .map_err(|e| eyre::eyre!("Failed to read JSON file '{}': {}", json_path, e))?;
 
-        let all_data: serde_json::Value = serde_json::from_str(&json_content)
+        let KaminoConfigFile {
+            markets,
+            mut reserves,
+        } = KaminoConfigFile::parse(&json_content)
             .map_err(|e| eyre::eyre!("Failed to parse JSON file '{}': {}", json_path, e))?;
 
-        let lend_config: KaminoLendStrategyConfig = serde_json::from_value(
-            all_data[lend_strategy_profile_key]["lend"].clone(),
-        )
-        .map_err(|e| {
-            eyre::eyre!(
-                "Failed to parse lend profile '{}': {}",
-                lend_strategy_profile_key,
-                e
-            )
-        })?;
-
-        let borrow_config: KaminoBorrowStrategyConfig =
-            serde_json::from_value(all_data[borrow_strategy_profile_key]["borrow"].clone())
-                .map_err(|e| {
-                    eyre::eyre!(
-                        "Failed to parse borrow profile '{}': {}",
-                        borrow_strategy_profile_key,
-                        e
-                    )
-                })?;
-
-        Ok(Self {
-            lend: lend_config.clone(),
-            borrow: borrow_config,
-            lending_market: lend_config.lending_market,
-            oracle_prices: lend_config.oracle_prices,
-            oracle_mapping: lend_config.oracle_mapping,
-            oracle_twaps: lend_config.oracle_twaps,
-            price_accounts: lend_config.price_accounts,
-            tokens: lend_config.tokens,
-        })
+        for (name, reserve) in reserves.iter_mut() {
+            if !markets.contains_key(&reserve.market) {
+                return Err(eyre!(
+                    "Kamino reserve '{}' is on unknown market '{}'",
+                    name,
+                    reserve.market
+                ));
+            }
+            reserve.name = name.clone();
+        }
+
+        let mut config = Self {
+            markets,
+            reserves,
+            default_lend: String::new(),
+            default_borrow: String::new(),
+        };
+        config.default_lend = config.reserve(default_lend)?.name.clone();
+        config.default_borrow = config.reserve(default_borrow)?.name.clone();
+
+        Ok(config)
+    }
+
+    // Looks a reserve up by name, or by liquidity mint when only one market lists it.
+    pub fn reserve(&self, key: &str) -> Result<&KaminoReserveConfig> {
+        if let Some(reserve) = self.reserves.get(key) {
+            return Ok(reserve);
+        }
+
+        let mint = Pubkey::from_str(key)
+            .map_err(|_| eyre!("No Kamino reserve named '{}' in the config", key))?;
+        let matches = self
+            .reserves
+            .values()
+            .filter(|reserve| reserve.liquidity_mint == mint)
+            .collect::<Vec<_>>();
+        match matches.as_slice() {
+            [reserve] => Ok(reserve),
+            [] => Err(eyre!("No Kamino reserve for mint {} in the config", mint)),
+            _ => Err(eyre!(
+                "Mint {} has a reserve on several Kamino markets, pick one of {}",
+                mint,
+                matches
+                    .iter()
+                    .map(|reserve| reserve.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    pub fn reserve_by_address(&self, address: &Pubkey) -> Option<&KaminoReserveConfig> {
+        self.reserves
+            .values()
+            .find(|reserve| reserve.reserve == *address)
+    }
+
+    pub fn market(&self, name: &str) -> Result<&KaminoMarketConfig> {
+        self.markets
+            .get(name)
+            .ok_or_else(|| eyre!("No Kamino market named '{}' in the config", name))
+    }
+
+    // The reserve to lend into, `default_lend` when `key` is `None`, with its market.
+    pub fn lend_reserve(
+        &self,
+        key: Option<&str>,
+    ) -> Result<(KaminoReserveConfig, KaminoMarketConfig)> {
+        self.reserve_with_market(key.unwrap_or(&self.default_lend))
+    }
+
+    // The reserve to borrow from, `default_borrow` when `key` is `None`, with its market.
+    pub fn borrow_reserve(
+        &self,
+        key: Option<&str>,
+    ) -> Result<(KaminoReserveConfig, KaminoMarketConfig)> {
+        self.reserve_with_market(key.unwrap_or(&self.default_borrow))
+    }
+
+    pub fn reserve_with_market(
+        &self,
+        key: &str,
+    ) -> Result<(KaminoReserveConfig, KaminoMarketConfig)> {
+        let reserve = self.reserve(key)?;
+        let market = self.market(&reserve.market)?;
+
+        Ok((reserve.clone(), market.clone()))
+    }
+
+    // The market named `name`, or the default lend reserve's.
+    pub fn lending_market(&self, name: Option<&str>) -> Result<KaminoMarketConfig> {
+        let name = match name {
+            Some(name) => name,
+            None => &self.reserve(&self.default_lend)?.market,
+        };
+
+        Ok(self.market(name)?.clone())
     }
 }
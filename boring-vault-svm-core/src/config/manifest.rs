@@ -0,0 +1,922 @@
+use std::{fs, path::Path};
+
+use eyre::Result;
+use serde::Deserialize;
+use solana_pubkey::Pubkey;
+
+use crate::manage_instructions::{
+    AuthorizeStake, CloseAccount, CreateAccountWithSeed, DeactivateStake, DepositSol, DepositStake,
+    ExternalInstruction, KaminoBorrow, KaminoDeposit, KaminoInitObligation,
+    KaminoInitObligationFarmsForReserve, KaminoInitUserMetaData, KaminoRefreshObligation,
+    KaminoRefreshObligationFarmsForReserve, KaminoRefreshPriceList, KaminoRefreshReserve,
+    KaminoRepay, KaminoWithdraw, MintJitoSol, SolendDepositReserveLiquidityAndObligationCollateral,
+    SolendInitObligation, StakeAuthorize, StakePoolAccounts, TransferSol,
+    TransferSolBetweenSubAccounts, WithdrawJitoSol, WithdrawJitoStake, WithdrawPoolStake,
+    WithdrawSol, WithdrawStake,
+};
+use crate::utils::{deserialize_pubkey, deserialize_pubkey_vec};
+
+// The manage operations a strategist should be allowed to run on a vault. Each entry takes the
+// parameters of its `ExternalInstruction` constructor, minus the vault id. Amounts default to 0
+// since they aren't part of any digest.
+//
+// {
+//     "vault_id": 3,
+//     "instructions": [
+//         { "kind": "mint_jito_sol", "sub_account": 0 },
+//         { "kind": "transfer_sol_between_sub_accounts", "sub_account": 0, "to_sub_account": 1 }
+//     ]
+// }
+#[derive(Debug, Clone, Deserialize)]
+pub struct StrategyManifest {
+    pub vault_id: u64,
+    pub instructions: Vec<ManifestInstruction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ManifestInstruction {
+    MintJitoSol {
+        sub_account: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    WithdrawJitoSol {
+        sub_account: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    // The pool's stake accounts aren't part of the digest, so neither are they here.
+    WithdrawJitoStake {
+        sub_account: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    DeactivateStake {
+        sub_account: u8,
+    },
+    WithdrawStake {
+        sub_account: u8,
+    },
+    // Stake pool instructions on any pool. Their digests only depend on the pool's program and the
+    // sub-account's pool token account, so the pool itself isn't needed.
+    DepositSol {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        stake_pool_program: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        pool_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        token_program: Pubkey,
+        #[serde(default)]
+        amount: u64,
+    },
+    WithdrawSol {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        stake_pool_program: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        pool_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        token_program: Pubkey,
+        #[serde(default)]
+        amount: u64,
+    },
+    DepositStake {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        stake_pool_program: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        pool_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        token_program: Pubkey,
+    },
+    WithdrawPoolStake {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        stake_pool_program: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        pool_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        token_program: Pubkey,
+        #[serde(default)]
+        amount: u64,
+    },
+    // "staker" or "withdrawer", handed to `new_authority`.
+    AuthorizeStake {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        new_authority: Pubkey,
+        stake_authorize: StakeAuthorize,
+    },
+    KaminoInitUserMetaData {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lut: Pubkey,
+    },
+    KaminoInitObligation {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        tag: u8,
+        id: u8,
+    },
+    KaminoInitObligationFarmsForReserve {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_farm_state: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        tag: u8,
+        id: u8,
+        mode: u8,
+    },
+    KaminoRefreshReserve {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        pyth_oracle: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        switchboard_price_oracle: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        switchboard_twap_oracle: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        scope_prices: Pubkey,
+    },
+    KaminoRefreshPriceList {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        oracle_prices: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        oracle_mapping: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        oracle_twaps: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey_vec")]
+        price_accounts: Vec<Pubkey>,
+        tokens: Vec<u16>,
+    },
+    KaminoRefreshObligation {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        tag: u8,
+        id: u8,
+    },
+    KaminoRefreshObligationFarmsForReserve {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_farm_state: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        tag: u8,
+        id: u8,
+        mode: u8,
+    },
+    KaminoDeposit {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_liquidity_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_liquidity_supply: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_collateral_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_destination_deposit_collateral: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_farm_state: Pubkey,
+        tag: u8,
+        id: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    KaminoBorrow {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        borrow_reserve_liquidity_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_source_liquidity: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        borrow_reserve_liquidity_fee_receiver: Pubkey,
+        tag: u8,
+        id: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    KaminoRepay {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_liquidity_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_destination_liquidity: Pubkey,
+        // The reserve's debt farm, if it has one.
+        #[serde(default, deserialize_with = "deserialize_pubkey")]
+        reserve_farm_state: Pubkey,
+        tag: u8,
+        id: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    KaminoWithdraw {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_liquidity_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_liquidity_supply: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_collateral_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_source_collateral: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_farm_state: Pubkey,
+        tag: u8,
+        id: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    SolendInitObligation {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        obligation: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+    },
+    SolendDepositReserveLiquidityAndObligationCollateral {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        deposit_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_collateral_mint: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        reserve_liquidity_supply_spl_token_account: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        lending_market_authority: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        destination_deposit_reserve_collateral_supply_spl_token_account: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        obligation: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        pyth_price_oracle: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        switchboard_price_oracle: Pubkey,
+        #[serde(default)]
+        amount: u64,
+    },
+    TransferSol {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        to: Pubkey,
+        #[serde(default)]
+        amount: u64,
+    },
+    TransferSolBetweenSubAccounts {
+        sub_account: u8,
+        to_sub_account: u8,
+        #[serde(default)]
+        amount: u64,
+    },
+    CreateAccountWithSeed {
+        sub_account: u8,
+        seed: String,
+        #[serde(default)]
+        lamports: u64,
+        space: u64,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        owner: Pubkey,
+    },
+    CloseAccount {
+        sub_account: u8,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        account: Pubkey,
+        #[serde(deserialize_with = "deserialize_pubkey")]
+        token_program: Pubkey,
+    },
+}
+
+impl StrategyManifest {
+    // Reads a `.toml` manifest, or JSON for any other extension.
+    pub fn new(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(Path::new(path))
+            .map_err(|e| eyre::eyre!("Failed to read manifest '{}': {}", path, e))?;
+
+        let is_toml = Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        if is_toml {
+            Self::from_toml(&content)
+                .map_err(|e| eyre::eyre!("Failed to parse manifest '{}': {}", path, e))
+        } else {
+            Self::from_json(&content)
+                .map_err(|e| eyre::eyre!("Failed to parse manifest '{}': {}", path, e))
+        }
+    }
+
+    pub fn from_json(content: &str) -> Result<Self> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn external_instructions(&self) -> Vec<Box<dyn ExternalInstruction>> {
+        self.instructions
+            .iter()
+            .map(|instruction| instruction.to_external_instruction(self.vault_id))
+            .collect()
+    }
+}
+
+impl ManifestInstruction {
+    // Name of the `ExternalInstruction` the entry builds.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::MintJitoSol { .. } => "MintJitoSol",
+            Self::WithdrawJitoSol { .. } => "WithdrawJitoSol",
+            Self::WithdrawJitoStake { .. } => "WithdrawJitoStake",
+            Self::DeactivateStake { .. } => "DeactivateStake",
+            Self::WithdrawStake { .. } => "WithdrawStake",
+            Self::DepositSol { .. } => "DepositSol",
+            Self::WithdrawSol { .. } => "WithdrawSol",
+            Self::DepositStake { .. } => "DepositStake",
+            Self::WithdrawPoolStake { .. } => "WithdrawPoolStake",
+            Self::AuthorizeStake { .. } => "AuthorizeStake",
+            Self::KaminoInitUserMetaData { .. } => "KaminoInitUserMetaData",
+            Self::KaminoInitObligation { .. } => "KaminoInitObligation",
+            Self::KaminoInitObligationFarmsForReserve { .. } => {
+                "KaminoInitObligationFarmsForReserve"
+            }
+            Self::KaminoRefreshReserve { .. } => "KaminoRefreshReserve",
+            Self::KaminoRefreshPriceList { .. } => "KaminoRefreshPriceList",
+            Self::KaminoRefreshObligation { .. } => "KaminoRefreshObligation",
+            Self::KaminoRefreshObligationFarmsForReserve { .. } => {
+                "KaminoRefreshObligationFarmsForReserve"
+            }
+            Self::KaminoDeposit { .. } => "KaminoDeposit",
+            Self::KaminoBorrow { .. } => "KaminoBorrow",
+            Self::KaminoRepay { .. } => "KaminoRepay",
+            Self::KaminoWithdraw { .. } => "KaminoWithdraw",
+            Self::SolendInitObligation { .. } => "SolendInitObligation",
+            Self::SolendDepositReserveLiquidityAndObligationCollateral { .. } => {
+                "SolendDepositReserveLiquidityAndObligationCollateral"
+            }
+            Self::TransferSol { .. } => "TransferSol",
+            Self::TransferSolBetweenSubAccounts { .. } => "TransferSolBetweenSubAccounts",
+            Self::CreateAccountWithSeed { .. } => "CreateAccountWithSeed",
+            Self::CloseAccount { .. } => "CloseAccount",
+        }
+    }
+
+    pub fn sub_account(&self) -> u8 {
+        match self {
+            Self::MintJitoSol { sub_account, .. }
+            | Self::WithdrawJitoSol { sub_account, .. }
+            | Self::WithdrawJitoStake { sub_account, .. }
+            | Self::DeactivateStake { sub_account }
+            | Self::WithdrawStake { sub_account }
+            | Self::DepositSol { sub_account, .. }
+            | Self::WithdrawSol { sub_account, .. }
+            | Self::DepositStake { sub_account, .. }
+            | Self::WithdrawPoolStake { sub_account, .. }
+            | Self::AuthorizeStake { sub_account, .. }
+            | Self::KaminoInitUserMetaData { sub_account, .. }
+            | Self::KaminoInitObligation { sub_account, .. }
+            | Self::KaminoInitObligationFarmsForReserve { sub_account, .. }
+            | Self::KaminoRefreshReserve { sub_account, .. }
+            | Self::KaminoRefreshPriceList { sub_account, .. }
+            | Self::KaminoRefreshObligation { sub_account, .. }
+            | Self::KaminoRefreshObligationFarmsForReserve { sub_account, .. }
+            | Self::KaminoDeposit { sub_account, .. }
+            | Self::KaminoBorrow { sub_account, .. }
+            | Self::KaminoRepay { sub_account, .. }
+            | Self::KaminoWithdraw { sub_account, .. }
+            | Self::SolendInitObligation { sub_account, .. }
+            | Self::SolendDepositReserveLiquidityAndObligationCollateral { sub_account, .. }
+            | Self::TransferSol { sub_account, .. }
+            | Self::TransferSolBetweenSubAccounts { sub_account, .. }
+            | Self::CreateAccountWithSeed { sub_account, .. }
+            | Self::CloseAccount { sub_account, .. } => *sub_account,
+        }
+    }
+
+    // The same operation on another sub-account.
+    pub fn with_sub_account(&self, sub_account: u8) -> Self {
+        let mut instruction = self.clone();
+        match &mut instruction {
+            Self::MintJitoSol {
+                sub_account: current,
+                ..
+            }
+            | Self::WithdrawJitoSol {
+                sub_account: current,
+                ..
+            }
+            | Self::WithdrawJitoStake {
+                sub_account: current,
+                ..
+            }
+            | Self::DeactivateStake {
+                sub_account: current,
+            }
+            | Self::WithdrawStake {
+                sub_account: current,
+            }
+            | Self::DepositSol {
+                sub_account: current,
+                ..
+            }
+            | Self::WithdrawSol {
+                sub_account: current,
+                ..
+            }
+            | Self::DepositStake {
+                sub_account: current,
+                ..
+            }
+            | Self::WithdrawPoolStake {
+                sub_account: current,
+                ..
+            }
+            | Self::AuthorizeStake {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoInitUserMetaData {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoInitObligation {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoInitObligationFarmsForReserve {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoRefreshReserve {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoRefreshPriceList {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoRefreshObligation {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoRefreshObligationFarmsForReserve {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoDeposit {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoBorrow {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoRepay {
+                sub_account: current,
+                ..
+            }
+            | Self::KaminoWithdraw {
+                sub_account: current,
+                ..
+            }
+            | Self::SolendInitObligation {
+                sub_account: current,
+                ..
+            }
+            | Self::SolendDepositReserveLiquidityAndObligationCollateral {
+                sub_account: current,
+                ..
+            }
+            | Self::TransferSol {
+                sub_account: current,
+                ..
+            }
+            | Self::TransferSolBetweenSubAccounts {
+                sub_account: current,
+                ..
+            }
+            | Self::CreateAccountWithSeed {
+                sub_account: current,
+                ..
+            }
+            | Self::CloseAccount {
+                sub_account: current,
+                ..
+            } => *current = sub_account,
+        }
+        instruction
+    }
+
+    pub fn to_external_instruction(&self, vault_id: u64) -> Box<dyn ExternalInstruction> {
+        match self.clone() {
+            Self::MintJitoSol {
+                sub_account,
+                amount,
+            } => Box::new(MintJitoSol::new(vault_id, sub_account, amount)),
+            Self::WithdrawJitoSol {
+                sub_account,
+                amount,
+            } => Box::new(WithdrawJitoSol::new(vault_id, sub_account, amount)),
+            Self::WithdrawJitoStake {
+                sub_account,
+                amount,
+            } => Box::new(WithdrawJitoStake::new(
+                vault_id,
+                sub_account,
+                Pubkey::default(),
+                Pubkey::default(),
+                amount,
+            )),
+            Self::DeactivateStake { sub_account } => Box::new(DeactivateStake::new(
+                vault_id,
+                sub_account,
+                Pubkey::default(),
+            )),
+            Self::WithdrawStake { sub_account } => Box::new(WithdrawStake::new(
+                vault_id,
+                sub_account,
+                Pubkey::default(),
+                0,
+            )),
+            Self::DepositSol {
+                sub_account,
+                stake_pool_program,
+                pool_mint,
+                token_program,
+                amount,
+            } => Box::new(DepositSol::new(
+                vault_id,
+                sub_account,
+                stake_pool_accounts(stake_pool_program, pool_mint, token_program),
+                amount,
+            )),
+            Self::WithdrawSol {
+                sub_account,
+                stake_pool_program,
+                pool_mint,
+                token_program,
+                amount,
+            } => Box::new(WithdrawSol::new(
+                vault_id,
+                sub_account,
+                stake_pool_accounts(stake_pool_program, pool_mint, token_program),
+                amount,
+            )),
+            Self::DepositStake {
+                sub_account,
+                stake_pool_program,
+                pool_mint,
+                token_program,
+            } => Box::new(DepositStake::new(
+                vault_id,
+                sub_account,
+                stake_pool_accounts(stake_pool_program, pool_mint, token_program),
+                Pubkey::default(),
+                Pubkey::default(),
+            )),
+            Self::WithdrawPoolStake {
+                sub_account,
+                stake_pool_program,
+                pool_mint,
+                token_program,
+                amount,
+            } => Box::new(WithdrawPoolStake::new(
+                vault_id,
+                sub_account,
+                stake_pool_accounts(stake_pool_program, pool_mint, token_program),
+                Pubkey::default(),
+                Pubkey::default(),
+                amount,
+            )),
+            Self::AuthorizeStake {
+                sub_account,
+                new_authority,
+                stake_authorize,
+            } => Box::new(AuthorizeStake::new(
+                vault_id,
+                sub_account,
+                Pubkey::default(),
+                new_authority,
+                stake_authorize,
+            )),
+            Self::KaminoInitUserMetaData { sub_account, lut } => {
+                Box::new(KaminoInitUserMetaData::new(vault_id, sub_account, lut))
+            }
+            Self::KaminoInitObligation {
+                sub_account,
+                lending_market,
+                tag,
+                id,
+            } => Box::new(KaminoInitObligation::new(
+                vault_id,
+                sub_account,
+                lending_market,
+                tag,
+                id,
+            )),
+            Self::KaminoInitObligationFarmsForReserve {
+                sub_account,
+                reserve,
+                reserve_farm_state,
+                lending_market,
+                tag,
+                id,
+                mode,
+            } => Box::new(KaminoInitObligationFarmsForReserve::new(
+                vault_id,
+                sub_account,
+                reserve,
+                reserve_farm_state,
+                lending_market,
+                tag,
+                id,
+                mode,
+            )),
+            Self::KaminoRefreshReserve {
+                sub_account,
+                reserve,
+                lending_market,
+                pyth_oracle,
+                switchboard_price_oracle,
+                switchboard_twap_oracle,
+                scope_prices,
+            } => Box::new(KaminoRefreshReserve::new(
+                vault_id,
+                sub_account,
+                reserve,
+                lending_market,
+                pyth_oracle,
+                switchboard_price_oracle,
+                switchboard_twap_oracle,
+                scope_prices,
+            )),
+            Self::KaminoRefreshPriceList {
+                sub_account,
+                oracle_prices,
+                oracle_mapping,
+                oracle_twaps,
+                price_accounts,
+                tokens,
+            } => Box::new(KaminoRefreshPriceList::new(
+                vault_id,
+                sub_account,
+                oracle_prices,
+                oracle_mapping,
+                oracle_twaps,
+                price_accounts,
+                tokens,
+            )),
+            Self::KaminoRefreshObligation {
+                sub_account,
+                lending_market,
+                tag,
+                id,
+            } => Box::new(KaminoRefreshObligation::new(
+                vault_id,
+                sub_account,
+                lending_market,
+                tag,
+                id,
+                // The reserves aren't part of the digest.
+                vec![],
+            )),
+            Self::KaminoRefreshObligationFarmsForReserve {
+                sub_account,
+                reserve,
+                reserve_farm_state,
+                lending_market,
+                tag,
+                id,
+                mode,
+            } => Box::new(KaminoRefreshObligationFarmsForReserve::new(
+                vault_id,
+                sub_account,
+                reserve,
+                reserve_farm_state,
+                lending_market,
+                tag,
+                id,
+                mode,
+            )),
+            Self::KaminoDeposit {
+                sub_account,
+                lending_market,
+                reserve,
+                reserve_liquidity_mint,
+                reserve_liquidity_supply,
+                reserve_collateral_mint,
+                reserve_destination_deposit_collateral,
+                reserve_farm_state,
+                tag,
+                id,
+                amount,
+            } => Box::new(KaminoDeposit::new(
+                vault_id,
+                sub_account,
+                lending_market,
+                reserve,
+                reserve_liquidity_mint,
+                reserve_liquidity_supply,
+                reserve_collateral_mint,
+                reserve_destination_deposit_collateral,
+                reserve_farm_state,
+                tag,
+                id,
+                amount,
+            )),
+            Self::KaminoBorrow {
+                sub_account,
+                lending_market,
+                reserve,
+                borrow_reserve_liquidity_mint,
+                reserve_source_liquidity,
+                borrow_reserve_liquidity_fee_receiver,
+                tag,
+                id,
+                amount,
+            } => Box::new(KaminoBorrow::new(
+                vault_id,
+                sub_account,
+                lending_market,
+                reserve,
+                borrow_reserve_liquidity_mint,
+                reserve_source_liquidity,
+                borrow_reserve_liquidity_fee_receiver,
+                tag,
+                id,
+                amount,
+            )),
+            Self::KaminoRepay {
+                sub_account,
+                lending_market,
+                reserve,
+                reserve_liquidity_mint,
+                reserve_destination_liquidity,
+                reserve_farm_state,
+                tag,
+                id,
+                amount,
+            } => Box::new(KaminoRepay::new(
+                vault_id,
+                sub_account,
+                lending_market,
+                reserve,
+                reserve_liquidity_mint,
+                reserve_destination_liquidity,
+                reserve_farm_state,
+                tag,
+                id,
+                amount,
+            )),
+            Self::KaminoWithdraw {
+                sub_account,
+                lending_market,
+                reserve,
+                reserve_liquidity_mint,
+                reserve_liquidity_supply,
+                reserve_collateral_mint,
+                reserve_source_collateral,
+                reserve_farm_state,
+                tag,
+                id,
+                amount,
+            } => Box::new(KaminoWithdraw::new(
+                vault_id,
+                sub_account,
+                lending_market,
+                reserve,
+                reserve_liquidity_mint,
+                reserve_liquidity_supply,
+                reserve_collateral_mint,
+                reserve_source_collateral,
+                reserve_farm_state,
+                tag,
+                id,
+                amount,
+            )),
+            Self::SolendInitObligation {
+                sub_account,
+                obligation,
+                lending_market,
+            } => Box::new(SolendInitObligation::new(
+                vault_id,
+                sub_account,
+                obligation,
+                lending_market,
+            )),
+            Self::SolendDepositReserveLiquidityAndObligationCollateral {
+                sub_account,
+                deposit_mint,
+                reserve_collateral_mint,
+                reserve,
+                reserve_liquidity_supply_spl_token_account,
+                lending_market,
+                lending_market_authority,
+                destination_deposit_reserve_collateral_supply_spl_token_account,
+                obligation,
+                pyth_price_oracle,
+                switchboard_price_oracle,
+                amount,
+            } => Box::new(SolendDepositReserveLiquidityAndObligationCollateral::new(
+                vault_id,
+                sub_account,
+                deposit_mint,
+                reserve_collateral_mint,
+                reserve,
+                reserve_liquidity_supply_spl_token_account,
+                lending_market,
+                lending_market_authority,
+                destination_deposit_reserve_collateral_supply_spl_token_account,
+                obligation,
+                pyth_price_oracle,
+                switchboard_price_oracle,
+                amount,
+            )),
+            Self::TransferSol {
+                sub_account,
+                to,
+                amount,
+            } => Box::new(TransferSol::new(vault_id, sub_account, to, amount)),
+            Self::TransferSolBetweenSubAccounts {
+                sub_account,
+                to_sub_account,
+                amount,
+            } => Box::new(TransferSolBetweenSubAccounts::new(
+                vault_id,
+                sub_account,
+                to_sub_account,
+                amount,
+            )),
+            Self::CreateAccountWithSeed {
+                sub_account,
+                seed,
+                lamports,
+                space,
+                owner,
+            } => Box::new(CreateAccountWithSeed::new(
+                vault_id,
+                sub_account,
+                seed,
+                lamports,
+                space,
+                owner,
+            )),
+            Self::CloseAccount {
+                sub_account,
+                account,
+                token_program,
+            } => Box::new(CloseAccount::new(
+                vault_id,
+                sub_account,
+                account,
+                token_program,
+            )),
+        }
+    }
+}
+
+// A stake pool with only the accounts its instructions' digests depend on.
+fn stake_pool_accounts(
+    program_id: Pubkey,
+    pool_mint: Pubkey,
+    token_program_id: Pubkey,
+) -> StakePoolAccounts {
+    StakePoolAccounts {
+        program_id,
+        pool_mint,
+        token_program_id,
+        ..StakePoolAccounts::default()
+    }
+}
@@ -1,7 +1,17 @@
 pub mod builder;
+pub mod bundle;
+pub mod compute_budget;
+pub mod jupiter;
 pub mod kamino;
+pub mod manifest;
+pub mod send;
 pub mod vault;
 
 pub use builder::*;
+pub use bundle::*;
+pub use compute_budget::*;
+pub use jupiter::*;
 pub use kamino::*;
+pub use manifest::*;
+pub use send::*;
 pub use vault::*;
@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+const DEFAULT_REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_ATTEMPTS: u8 = 3;
+
+#[derive(Debug, Clone)]
+pub struct SendConfig {
+    // How often an unconfirmed transaction is sent again.
+    pub rebroadcast_interval: Duration,
+    // How often the signature status and block height are checked.
+    pub poll_interval: Duration,
+    // Number of blockhashes a transaction is signed with before giving up as expired.
+    pub max_attempts: u8,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            rebroadcast_interval: DEFAULT_REBROADCAST_INTERVAL,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
+
+impl SendConfig {
+    pub fn with_rebroadcast_interval(mut self, rebroadcast_interval: Duration) -> Self {
+        self.rebroadcast_interval = rebroadcast_interval;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u8) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
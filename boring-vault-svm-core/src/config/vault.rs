@@ -15,7 +15,7 @@ pub struct VaultAssetConfig {
     pub oracle: Pubkey,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct VaultConfig {
     pub vault_id: u64,
     pub sub_accounts: Vec<u8>,
@@ -1,20 +1,30 @@
 use anchor_lang::{
     system_program, AccountDeserialize, Discriminator, InstructionData, ToAccountMetas,
 };
-use eyre::Result;
-use solana_client::rpc_client::RpcClient;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::{eyre, Result};
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
 use solana_instruction::{AccountMeta, Instruction};
 use solana_pubkey::Pubkey;
-use spl_associated_token_account::ID as ASSOCIATED_TOKEN_PROGRAM_ID;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, ID as ASSOCIATED_TOKEN_PROGRAM_ID,
+};
 use spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
 
+use crate::builder::{compile_message, unsigned_transaction};
+use crate::utils::AccountSource;
 use crate::utils::{ensure_ata, get_cpi_digest};
 use crate::KeypairOrPublickey;
 use crate::{
     manage_instructions::ExternalInstruction,
     utils::{
-        boring_vault_svm, get_asset_data_pda, get_cpi_digest_pda, get_program_config_pda,
-        get_vault_pda, get_vault_share_mint, get_vault_state_pda,
+        boring_vault_svm::{
+            self,
+            accounts::{AssetData, BoringVault},
+        },
+        get_asset_data_pda, get_cpi_digest_pda, get_program_config_pda, get_vault_pda,
+        get_vault_share_mint, get_vault_state_pda,
     },
 };
 
@@ -40,9 +50,53 @@ pub fn create_initialize_instruction(authority: &Pubkey, signer: &Pubkey) -> Res
     Ok(instruction)
 }
 
-// TODO maybe this should take an optional vault_id, that way if you are deploying multiple in a bundle it works just fine!
-pub fn create_deploy_instruction(
-    client: &RpcClient,
+// Deploys as `vault_id` when given, otherwise as the next vault in the program config.
+pub fn create_deploy_instruction<C: AccountSource>(
+    client: &C,
+    vault_id: Option<u64>,
+    authority: &Pubkey,
+    signer: &Pubkey,
+    base_asset: &Pubkey,
+    name: String,
+    symbol: String,
+    exchange_rate_provider: Option<Pubkey>,
+    exchange_rate: u64,
+    payout_address: Option<Pubkey>,
+    allowed_exchange_rate_change_upper_bound: u16,
+    allowed_exchange_rate_change_lower_bound: u16,
+    minimum_update_delay_in_seconds: u32,
+    platform_fee_bps: Option<u16>,
+    performance_fee_bps: Option<u16>,
+    withdraw_authority: Option<Pubkey>,
+    strategist: Option<Pubkey>,
+) -> Result<Instruction> {
+    let vault_id = match vault_id {
+        Some(vault_id) => vault_id,
+        None => get_vault_id(client)?,
+    };
+
+    build_deploy_instruction(
+        vault_id,
+        authority,
+        signer,
+        base_asset,
+        name,
+        symbol,
+        exchange_rate_provider,
+        exchange_rate,
+        payout_address,
+        allowed_exchange_rate_change_upper_bound,
+        allowed_exchange_rate_change_lower_bound,
+        minimum_update_delay_in_seconds,
+        platform_fee_bps,
+        performance_fee_bps,
+        withdraw_authority,
+        strategist,
+    )
+}
+
+pub fn build_deploy_instruction(
+    vault_id: u64,
     authority: &Pubkey,
     signer: &Pubkey,
     base_asset: &Pubkey,
@@ -59,7 +113,6 @@ pub fn create_deploy_instruction(
     withdraw_authority: Option<Pubkey>,
     strategist: Option<Pubkey>,
 ) -> Result<Instruction> {
-    let vault_id = get_vault_id(client)?;
     let vault_state_pda = get_vault_state_pda(vault_id);
     let accounts = vec![
         AccountMeta::new(*signer, true),
@@ -418,8 +471,8 @@ pub fn create_update_exchange_rate_instruction(
     Ok(instruction)
 }
 
-pub fn create_claim_fees_in_base_instruction(
-    client: &RpcClient,
+pub fn create_claim_fees_in_base_instruction<C: AccountSource>(
+    client: &C,
     vault_id: u64,
     sub_account: u8,
     signer: &Pubkey,
@@ -436,9 +489,18 @@ pub fn create_claim_fees_in_base_instruction(
     let base_mint_account = client.get_account(&base_asset)?;
     let token_program_id = base_mint_account.owner;
 
+    client.prefetch_accounts(&[
+        get_associated_token_address_with_program_id(
+            &payout_address,
+            &base_asset,
+            &token_program_id,
+        ),
+        get_associated_token_address_with_program_id(&vault_pda, &base_asset, &token_program_id),
+    ])?;
+
     let mut instructions = vec![];
 
-    let (payout_ata, payout_instruction) = ensure_ata(
+    let (_, payout_instruction) = ensure_ata(
         client,
         signer,
         &payout_address,
@@ -446,11 +508,42 @@ pub fn create_claim_fees_in_base_instruction(
         &token_program_id,
     )?;
 
-    let (vault_ata, vault_instruction) =
+    let (_, vault_instruction) =
         ensure_ata(client, signer, &vault_pda, &base_asset, &token_program_id)?;
 
     instructions.extend(payout_instruction.into_iter().chain(vault_instruction));
 
+    instructions.push(build_claim_fees_in_base_instruction(
+        vault_id,
+        sub_account,
+        signer,
+        &vault_state,
+        &token_program_id,
+    ));
+
+    Ok(instructions)
+}
+
+// Expects the payout and vault ATAs of the base asset to exist.
+pub fn build_claim_fees_in_base_instruction(
+    vault_id: u64,
+    sub_account: u8,
+    signer: &Pubkey,
+    vault_state: &BoringVault,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let vault_state_pda = get_vault_state_pda(vault_id);
+    let vault_pda = get_vault_pda(vault_id, sub_account);
+
+    let base_asset = vault_state.teller.base_asset;
+    let payout_ata = get_associated_token_address_with_program_id(
+        &vault_state.teller.payout_address,
+        &base_asset,
+        token_program_id,
+    );
+    let vault_ata =
+        get_associated_token_address_with_program_id(&vault_pda, &base_asset, token_program_id);
+
     let accounts = boring_vault_svm::client::accounts::ClaimFeesInBase {
         signer: *signer,
         base_mint: base_asset,
@@ -469,19 +562,51 @@ pub fn create_claim_fees_in_base_instruction(
     }
     .data();
 
-    let instruction = solana_program::instruction::Instruction {
+    solana_program::instruction::Instruction {
         program_id: boring_vault_svm::ID,
         accounts: accounts.to_account_metas(None),
         data: claim_fees_in_base_ix_data,
-    };
+    }
+}
 
-    instructions.push(instruction);
+// Fetches what a deposit or withdraw of `mint` reads in one batch. The token program and sub
+// account are only known once the mint and vault state are read, so the ATAs are prefetched for
+// both token programs and the default sub account. Vaults that moved their sub account pay one
+// more read for the vault ATA.
+fn prefetch_vault_transfer_accounts<C: AccountSource>(
+    client: &C,
+    vault_id: u64,
+    signer: &Pubkey,
+    mint: &Pubkey,
+) -> Result<()> {
+    let vault_state_pda = get_vault_state_pda(vault_id);
+    let vault_pda = get_vault_pda(vault_id, 0);
+    let share_mint = get_vault_share_mint(vault_state_pda);
 
-    Ok(instructions)
+    let mut addresses = vec![
+        vault_state_pda,
+        get_asset_data_pda(vault_state_pda, *mint),
+        *mint,
+        get_associated_token_address_with_program_id(signer, &share_mint, &TOKEN_2022_PROGRAM_ID),
+    ];
+    for token_program_id in [spl_token::ID, TOKEN_2022_PROGRAM_ID] {
+        addresses.push(get_associated_token_address_with_program_id(
+            signer,
+            mint,
+            &token_program_id,
+        ));
+        addresses.push(get_associated_token_address_with_program_id(
+            &vault_pda,
+            mint,
+            &token_program_id,
+        ));
+    }
+
+    client.prefetch_accounts(&addresses)
 }
 
-pub fn create_deposit_instruction(
-    client: &RpcClient,
+pub fn create_deposit_instruction<C: AccountSource>(
+    client: &C,
     vault_id: u64,
     signer: &Pubkey,
     deposit_mint: &Pubkey,
@@ -489,6 +614,9 @@ pub fn create_deposit_instruction(
     min_mint_amount: u64,
 ) -> Result<Vec<Instruction>> {
     let vault_state_pda = get_vault_state_pda(vault_id);
+    let asset_data_pda = get_asset_data_pda(vault_state_pda, *deposit_mint);
+    prefetch_vault_transfer_accounts(client, vault_id, signer, deposit_mint)?;
+
     let vault_state_account = client.get_account(&vault_state_pda)?;
     let vault_state = boring_vault_svm::accounts::BoringVault::try_deserialize(
         &mut &vault_state_account.data[..],
@@ -497,24 +625,22 @@ pub fn create_deposit_instruction(
     let vault_pda = get_vault_pda(vault_id, vault_state.config.deposit_sub_account);
     let share_mint = get_vault_share_mint(vault_state_pda);
 
-    let asset_data_pda = get_asset_data_pda(vault_state_pda, *deposit_mint);
     let asset_data_account = client.get_account(&asset_data_pda)?;
     let asset_data =
         boring_vault_svm::accounts::AssetData::try_deserialize(&mut &asset_data_account.data[..])?;
-    let price_feed = asset_data.price_feed;
 
     let deposit_mint_account = client.get_account(deposit_mint)?;
     let token_program_id = deposit_mint_account.owner;
 
     let mut instructions = vec![];
 
-    let (user_ata, user_instruction) =
+    let (_, user_instruction) =
         ensure_ata(client, signer, signer, deposit_mint, &token_program_id)?;
 
-    let (vault_ata, vault_instruction) =
+    let (_, vault_instruction) =
         ensure_ata(client, signer, &vault_pda, deposit_mint, &token_program_id)?;
 
-    let (user_share_ata, user_share_instruction) =
+    let (_, user_share_instruction) =
         ensure_ata(client, signer, signer, &share_mint, &TOKEN_2022_PROGRAM_ID)?;
 
     instructions.extend(
@@ -524,6 +650,43 @@ pub fn create_deposit_instruction(
             .chain(user_share_instruction),
     );
 
+    instructions.push(build_deposit_instruction(
+        vault_id,
+        signer,
+        deposit_mint,
+        &token_program_id,
+        &vault_state,
+        &asset_data,
+        deposit_amount,
+        min_mint_amount,
+    ));
+
+    Ok(instructions)
+}
+
+// Expects the user, vault and user share ATAs to exist.
+pub fn build_deposit_instruction(
+    vault_id: u64,
+    signer: &Pubkey,
+    deposit_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    vault_state: &BoringVault,
+    asset_data: &AssetData,
+    deposit_amount: u64,
+    min_mint_amount: u64,
+) -> Instruction {
+    let vault_state_pda = get_vault_state_pda(vault_id);
+    let vault_pda = get_vault_pda(vault_id, vault_state.config.deposit_sub_account);
+    let share_mint = get_vault_share_mint(vault_state_pda);
+    let asset_data_pda = get_asset_data_pda(vault_state_pda, *deposit_mint);
+
+    let user_ata =
+        get_associated_token_address_with_program_id(signer, deposit_mint, token_program_id);
+    let vault_ata =
+        get_associated_token_address_with_program_id(&vault_pda, deposit_mint, token_program_id);
+    let user_share_ata =
+        get_associated_token_address_with_program_id(signer, &share_mint, &TOKEN_2022_PROGRAM_ID);
+
     let accounts = boring_vault_svm::client::accounts::Deposit {
         signer: *signer,
         boring_vault_state: vault_state_pda,
@@ -538,7 +701,7 @@ pub fn create_deposit_instruction(
         associated_token_program: ASSOCIATED_TOKEN_PROGRAM_ID,
         share_mint,
         user_shares: user_share_ata,
-        price_feed,
+        price_feed: asset_data.price_feed,
     };
 
     let args = boring_vault_svm::types::DepositArgs {
@@ -549,19 +712,15 @@ pub fn create_deposit_instruction(
 
     let deposit_ix_data = boring_vault_svm::client::args::Deposit { args }.data();
 
-    let instruction = solana_program::instruction::Instruction {
+    solana_program::instruction::Instruction {
         program_id: boring_vault_svm::ID,
         accounts: accounts.to_account_metas(None),
         data: deposit_ix_data,
-    };
-
-    instructions.push(instruction);
-
-    Ok(instructions)
+    }
 }
 
-pub fn create_withdraw_instruction(
-    client: &RpcClient,
+pub fn create_withdraw_instruction<C: AccountSource>(
+    client: &C,
     vault_id: u64,
     signer: &Pubkey,
     withdraw_mint: &Pubkey,
@@ -569,6 +728,9 @@ pub fn create_withdraw_instruction(
     min_assets_amount: u64,
 ) -> Result<Vec<Instruction>> {
     let vault_state_pda = get_vault_state_pda(vault_id);
+    let asset_data_pda = get_asset_data_pda(vault_state_pda, *withdraw_mint);
+    prefetch_vault_transfer_accounts(client, vault_id, signer, withdraw_mint)?;
+
     let vault_state_account = client.get_account(&vault_state_pda)?;
     let vault_state = boring_vault_svm::accounts::BoringVault::try_deserialize(
         &mut &vault_state_account.data[..],
@@ -578,24 +740,22 @@ pub fn create_withdraw_instruction(
     let vault_pda = get_vault_pda(vault_id, withdraw_sub_account);
     let share_mint = get_vault_share_mint(vault_state_pda);
 
-    let asset_data_pda = get_asset_data_pda(vault_state_pda, *withdraw_mint);
     let asset_data_account = client.get_account(&asset_data_pda)?;
     let asset_data =
         boring_vault_svm::accounts::AssetData::try_deserialize(&mut &asset_data_account.data[..])?;
-    let price_feed = asset_data.price_feed;
 
     let withdraw_mint_account = client.get_account(withdraw_mint)?;
     let token_program_id = withdraw_mint_account.owner;
 
     let mut instructions = vec![];
 
-    let (user_ata, user_instruction) =
+    let (_, user_instruction) =
         ensure_ata(client, signer, signer, withdraw_mint, &token_program_id)?;
 
-    let (vault_ata, vault_instruction) =
+    let (_, vault_instruction) =
         ensure_ata(client, signer, &vault_pda, withdraw_mint, &token_program_id)?;
 
-    let (user_share_ata, user_share_instruction) =
+    let (_, user_share_instruction) =
         ensure_ata(client, signer, signer, &share_mint, &TOKEN_2022_PROGRAM_ID)?;
 
     instructions.extend(
@@ -605,6 +765,43 @@ pub fn create_withdraw_instruction(
             .chain(user_share_instruction),
     );
 
+    instructions.push(build_withdraw_instruction(
+        vault_id,
+        signer,
+        withdraw_mint,
+        &token_program_id,
+        &vault_state,
+        &asset_data,
+        share_amount,
+        min_assets_amount,
+    ));
+
+    Ok(instructions)
+}
+
+// Expects the user, vault and user share ATAs to exist.
+pub fn build_withdraw_instruction(
+    vault_id: u64,
+    signer: &Pubkey,
+    withdraw_mint: &Pubkey,
+    token_program_id: &Pubkey,
+    vault_state: &BoringVault,
+    asset_data: &AssetData,
+    share_amount: u64,
+    min_assets_amount: u64,
+) -> Instruction {
+    let vault_state_pda = get_vault_state_pda(vault_id);
+    let vault_pda = get_vault_pda(vault_id, vault_state.config.withdraw_sub_account);
+    let share_mint = get_vault_share_mint(vault_state_pda);
+    let asset_data_pda = get_asset_data_pda(vault_state_pda, *withdraw_mint);
+
+    let user_ata =
+        get_associated_token_address_with_program_id(signer, withdraw_mint, token_program_id);
+    let vault_ata =
+        get_associated_token_address_with_program_id(&vault_pda, withdraw_mint, token_program_id);
+    let user_share_ata =
+        get_associated_token_address_with_program_id(signer, &share_mint, &TOKEN_2022_PROGRAM_ID);
+
     let accounts = boring_vault_svm::client::accounts::Withdraw {
         signer: *signer,
         boring_vault_state: vault_state_pda,
@@ -617,7 +814,7 @@ pub fn create_withdraw_instruction(
         token_program_2022: TOKEN_2022_PROGRAM_ID,
         share_mint,
         user_shares: user_share_ata,
-        price_feed,
+        price_feed: asset_data.price_feed,
     };
 
     let args = boring_vault_svm::types::WithdrawArgs {
@@ -628,71 +825,168 @@ pub fn create_withdraw_instruction(
 
     let withdraw_ix_data = boring_vault_svm::client::args::Withdraw { args }.data();
 
-    let instruction = solana_program::instruction::Instruction {
+    solana_program::instruction::Instruction {
         program_id: boring_vault_svm::ID,
         accounts: accounts.to_account_metas(None),
         data: withdraw_ix_data,
-    };
-
-    instructions.extend(vec![instruction]);
-
-    Ok(instructions)
+    }
 }
 
 // STRATEGIST INSTRUCTIONS
 
-pub fn create_manage_instruction<T: ExternalInstruction>(
-    client: &RpcClient,
+pub fn create_manage_instruction<C: AccountSource, T: ExternalInstruction>(
+    client: &C,
     signer: &KeypairOrPublickey,
     authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
     eix: T,
 ) -> Result<Vec<Instruction>> {
-    let mut instructions = vec![];
+    let (cpi_digest_pda, _) = get_external_instruction_cpi_digest(&eix)?;
+    let cpi_digest_exists = client.get_account(&cpi_digest_pda).is_ok();
 
-    // Get the signer's public key
-    // let signer_pubkey = signer.pubkey();
+    if !cpi_digest_exists && cpi_digest_check {
+        if let Some(authority) = authority {
+            verify_cpi_digest(client, &authority.pubkey(), &eix)?;
+        }
+    }
 
-    // Call get_cpi_digest with the signer's public key
-    let (cpi_digest_pda, digest) = get_cpi_digest(
-        // &signer_pubkey, // Pass the public key reference
+    build_manage_instructions(signer, authority, eix, cpi_digest_exists)
+}
+
+pub fn get_external_instruction_cpi_digest<T: ExternalInstruction + ?Sized>(
+    eix: &T,
+) -> Result<(Pubkey, [u8; 32])> {
+    get_cpi_digest(
         eix.vault_id(),
-        &eix.ix_program_id(), // Pass reference to the returned Pubkey
+        &eix.ix_program_id(),
         eix.ix_data(),
         eix.ix_remaining_accounts(),
         eix.ix_operators(),
-    )?;
+    )
+}
 
-    // If the authority pubkey is provided, then we have the ability to setup the CPI digest.
-    // Check if the PDA exists.
-    match client.get_account(&cpi_digest_pda) {
-        Ok(_) => {
-            // The account exists so nothing to do.
-        }
-        Err(_) => {
-            // This is okay if the authority was provided.
-            if let Some(authority) = authority {
-                // Authority needs to be able to sign the initialize CPI digest instruction.
-                // Check if the provided authority can sign.
-                if !authority.can_sign() {
-                    return Err(eyre::eyre!(
-                        "Authority provided for initializing CPI digest must be a Keypair, not just a Pubkey"
-                    ));
-                }
-                // Add initialize CPI digest to instructions.
-                instructions.push(create_initialize_cpi_digest_instruction(
-                    &authority.pubkey(), // Use authority's pubkey
-                    eix.vault_id(),
-                    &cpi_digest_pda, // <<< Pass reference
-                    digest,
-                    eix.ix_operators(),
-                )?);
-            } else {
+pub fn create_view_cpi_digest_instruction<T: ExternalInstruction + ?Sized>(
+    eix: &T,
+) -> Result<Instruction> {
+    let mut accounts = vec![AccountMeta::new_readonly(eix.ix_program_id(), false)];
+    accounts.extend(eix.ix_remaining_accounts());
+
+    let args = boring_vault_svm::types::ViewCpiDigestArgs {
+        ix_data: eix.ix_data(),
+        operators: eix.ix_operators(),
+    };
+
+    let view_ix_data = boring_vault_svm::client::args::ViewCpiDigest { args }.data();
+
+    Ok(solana_program::instruction::Instruction {
+        program_id: boring_vault_svm::ID,
+        accounts,
+        data: view_ix_data,
+    })
+}
+
+// Simulates `view_cpi_digest` and returns the digest the program computes for the instruction.
+// The payer only needs to exist, signatures aren't verified.
+pub fn view_cpi_digest<C: AccountSource, T: ExternalInstruction + ?Sized>(
+    client: &C,
+    payer: &Pubkey,
+    eix: &T,
+) -> Result<[u8; 32]> {
+    let instruction = create_view_cpi_digest_instruction(eix)?;
+    let message = compile_message(&[instruction], payer, Hash::default(), &[])?;
+
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(CommitmentConfig::processed()),
+        ..Default::default()
+    };
+    let result = client.simulate_transaction(&unsigned_transaction(message), config)?;
+
+    if let Some(err) = result.err {
+        return Err(eyre!(
+            "view_cpi_digest simulation failed: {:?}\n{}",
+            err,
+            result.logs.unwrap_or_default().join("\n")
+        ));
+    }
+
+    let return_data = result
+        .return_data
+        .filter(|return_data| return_data.program_id == boring_vault_svm::ID.to_string())
+        .ok_or_else(|| eyre!("view_cpi_digest returned no data"))?;
+    let mut data = STANDARD.decode(&return_data.data.0)?;
+    // Trailing zero bytes are trimmed from return data.
+    if data.len() > 32 {
+        return Err(eyre!(
+            "view_cpi_digest returned {} bytes, expected 32",
+            data.len()
+        ));
+    }
+    data.resize(32, 0);
+
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&data);
+
+    Ok(digest)
+}
+
+// Errors when the locally computed digest differs from the one the program would check against.
+pub fn verify_cpi_digest<C: AccountSource, T: ExternalInstruction + ?Sized>(
+    client: &C,
+    payer: &Pubkey,
+    eix: &T,
+) -> Result<()> {
+    let (_, local) = get_external_instruction_cpi_digest(eix)?;
+    let program = view_cpi_digest(client, payer, eix)?;
+
+    if local != program {
+        return Err(eyre!(
+            "CPI digest mismatch for program {}: local {}, program {}",
+            eix.ix_program_id(),
+            hex::encode(local),
+            hex::encode(program)
+        ));
+    }
+
+    Ok(())
+}
+
+// Prepends the CPI digest initialization when it doesn't exist yet, which requires a signing authority.
+pub fn build_manage_instructions<T: ExternalInstruction>(
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    eix: T,
+    cpi_digest_exists: bool,
+) -> Result<Vec<Instruction>> {
+    let mut instructions = vec![];
+
+    let (cpi_digest_pda, digest) = get_external_instruction_cpi_digest(&eix)?;
+
+    if !cpi_digest_exists {
+        // This is okay if the authority was provided.
+        if let Some(authority) = authority {
+            // Authority needs to be able to sign the initialize CPI digest instruction.
+            // Check if the provided authority can sign.
+            if !authority.can_sign() {
                 return Err(eyre::eyre!(
-                    "CPI digest account does not exist, and no authority Keypair was provided to initialize it"
+                    "Authority provided for initializing CPI digest must be a Keypair, not just a Pubkey"
                 ));
             }
+            // Add initialize CPI digest to instructions.
+            instructions.push(create_initialize_cpi_digest_instruction(
+                &authority.pubkey(), // Use authority's pubkey
+                eix.vault_id(),
+                &cpi_digest_pda, // <<< Pass reference
+                digest,
+                eix.ix_operators(),
+            )?);
+        } else {
+            return Err(eyre::eyre!(
+                "CPI digest account does not exist, and no authority Keypair was provided to initialize it"
+            ));
         }
-    };
+    }
 
     let vault_state_pda = get_vault_state_pda(eix.vault_id());
     let vault_account = get_vault_pda(eix.vault_id(), eix.sub_account());
@@ -776,8 +1070,8 @@ pub fn create_update_asset_data_instruction(
     Ok(instruction)
 }
 
-pub fn create_deposit_sol_instruction(
-    client: &RpcClient,
+pub fn create_deposit_sol_instruction<C: AccountSource>(
+    client: &C,
     signer: &Pubkey,
     vault_id: u64,
     deposit_amount: u64,
@@ -788,18 +1082,42 @@ pub fn create_deposit_sol_instruction(
     let vault_state_data = boring_vault_svm::accounts::BoringVault::try_deserialize(
         &mut &vault_state_account.data[..],
     )?;
-    let native_mint = Pubkey::new_from_array([0; 32]);
-    let asset_data_pda = get_asset_data_pda(vault_state_pda, native_mint);
-    let vault_pda = get_vault_pda(vault_id, vault_state_data.config.deposit_sub_account);
     let share_mint = get_vault_share_mint(vault_state_pda);
 
     let mut instructions = vec![];
 
-    let (user_share_ata, user_instruction) =
+    let (_, user_instruction) =
         ensure_ata(client, signer, signer, &share_mint, &TOKEN_2022_PROGRAM_ID)?;
 
     instructions.extend(user_instruction);
 
+    instructions.push(build_deposit_sol_instruction(
+        signer,
+        vault_id,
+        &vault_state_data,
+        deposit_amount,
+        min_mint_amount,
+    ));
+
+    Ok(instructions)
+}
+
+// Expects the user share ATA to exist.
+pub fn build_deposit_sol_instruction(
+    signer: &Pubkey,
+    vault_id: u64,
+    vault_state: &BoringVault,
+    deposit_amount: u64,
+    min_mint_amount: u64,
+) -> Instruction {
+    let vault_state_pda = get_vault_state_pda(vault_id);
+    let native_mint = Pubkey::new_from_array([0; 32]);
+    let asset_data_pda = get_asset_data_pda(vault_state_pda, native_mint);
+    let vault_pda = get_vault_pda(vault_id, vault_state.config.deposit_sub_account);
+    let share_mint = get_vault_share_mint(vault_state_pda);
+    let user_share_ata =
+        get_associated_token_address_with_program_id(signer, &share_mint, &TOKEN_2022_PROGRAM_ID);
+
     let accounts = boring_vault_svm::client::accounts::DepositSol {
         signer: *signer,
         token_program_2022: TOKEN_2022_PROGRAM_ID,
@@ -822,15 +1140,11 @@ pub fn create_deposit_sol_instruction(
     let deposit_sol_ix_data = boring_vault_svm::client::args::DepositSol { args }.data();
 
     // Create the instruction.
-    let instruction = solana_program::instruction::Instruction {
+    solana_program::instruction::Instruction {
         program_id: boring_vault_svm::ID,
         accounts: accounts.to_account_metas(None),
         data: deposit_sol_ix_data,
-    };
-
-    instructions.extend(vec![instruction]);
-
-    Ok(instructions)
+    }
 }
 
 pub fn create_set_deposit_sub_account_instruction(
@@ -923,14 +1237,18 @@ pub fn create_initialize_cpi_digest_instruction(
     Ok(instruction)
 }
 
-fn get_vault_id(client: &RpcClient) -> Result<u64> {
+// Id the next deployed vault gets, which is the number of vaults deployed so far.
+pub fn get_vault_id<C: AccountSource>(client: &C) -> Result<u64> {
     let program_config_pda = get_program_config_pda();
     match client.get_account(&program_config_pda) {
-        Ok(account) => {
-            let program_config =
-                boring_vault_svm::accounts::ProgramConfig::try_deserialize(&mut &account.data[..])?;
-            Ok(program_config.vault_count)
-        }
+        Ok(account) => get_vault_count(&account.data),
         Err(_) => Ok(0), // Return 0 if account not found
     }
 }
+
+pub fn get_vault_count(program_config_data: &[u8]) -> Result<u64> {
+    let program_config =
+        boring_vault_svm::accounts::ProgramConfig::try_deserialize(&mut &program_config_data[..])?;
+
+    Ok(program_config.vault_count)
+}
@@ -1,15 +1,20 @@
 use eyre::Result;
-use solana_client::rpc_client::RpcClient;
 use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
 
-use crate::{manage_instructions::MintJitoSol, KeypairOrPublickey};
+use crate::utils::AccountSource;
+use crate::{
+    manage_instructions::{MintJitoSol, WithdrawJitoSol, JITO_STAKE_POOL},
+    KeypairOrPublickey,
+};
 
-use super::create_manage_instruction;
+use super::{create_manage_instruction, create_unstake_instructions};
 
-pub fn create_mint_jito_sol_instructions(
-    client: &RpcClient,
+pub fn create_mint_jito_sol_instructions<C: AccountSource>(
+    client: &C,
     signer: &KeypairOrPublickey,
     authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
     vault_id: u64,
     sub_account: u8,
     amount: u64,
@@ -17,7 +22,50 @@ pub fn create_mint_jito_sol_instructions(
     let mut instructions = vec![];
 
     let eix = MintJitoSol::new(vault_id, sub_account, amount);
-    instructions.extend(create_manage_instruction(client, signer, authority, eix)?);
+    instructions.extend(create_manage_instruction(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        eix,
+    )?);
 
     Ok(instructions)
 }
+
+pub fn create_withdraw_jito_sol_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    amount: u64,
+) -> Result<Vec<Instruction>> {
+    let eix = WithdrawJitoSol::new(vault_id, sub_account, amount);
+
+    create_manage_instruction(client, signer, authority, cpi_digest_check, eix)
+}
+
+pub fn create_unstake_jito_sol_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    amount: u64,
+    in_use: &[Pubkey],
+) -> Result<(Vec<Instruction>, Pubkey)> {
+    create_unstake_instructions(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        vault_id,
+        sub_account,
+        &JITO_STAKE_POOL,
+        amount,
+        in_use,
+    )
+}
@@ -0,0 +1,96 @@
+use eyre::{eyre, Result};
+use solana_instruction::Instruction;
+use spl_associated_token_account::ID as ASSOCIATED_TOKEN_PROGRAM_ID;
+use spl_token::ID as TOKEN_PROGRAM_ID;
+use spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
+
+use crate::utils::AccountSource;
+use crate::{
+    config::JupiterSwapInstructions,
+    manage_instructions::{CloseAccount, JupiterRoute},
+    KeypairOrPublickey,
+};
+
+use super::create_manage_instruction;
+
+// Token program `CloseAccount`.
+const CLOSE_ACCOUNT: u8 = 9;
+
+// Setup instructions creating the sub-account's ATAs are paid by the signer. Cleanup closing the
+// wSOL account is managed like `unwrap_sol`. Wrapping SOL needs the sub-account to sign, so
+// request the route with `wrapAndUnwrapSol=false` and use `wrap_sol` instead.
+pub fn create_jupiter_swap_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    swap: &JupiterSwapInstructions,
+) -> Result<Vec<Instruction>> {
+    let mut instructions = vec![];
+
+    let route = JupiterRoute::new(vault_id, sub_account, &swap.swap_instruction)?;
+
+    for setup in &swap.setup_instructions {
+        if setup.program_id != ASSOCIATED_TOKEN_PROGRAM_ID {
+            return Err(eyre!(
+                "Unsupported Jupiter setup instruction for program {}, request the route with wrapAndUnwrapSol=false",
+                setup.program_id
+            ));
+        }
+
+        instructions.push(Instruction {
+            program_id: setup.program_id,
+            accounts: route.rewrite_accounts(&setup.accounts, Some(&signer.pubkey())),
+            data: setup.data.clone(),
+        });
+    }
+
+    let cleanup = match &swap.cleanup_instruction {
+        Some(cleanup) => {
+            let is_close_account = [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID]
+                .contains(&cleanup.program_id)
+                && cleanup.data == [CLOSE_ACCOUNT];
+            let account = route
+                .rewrite_accounts(&cleanup.accounts, None)
+                .first()
+                .map(|account| account.pubkey)
+                .filter(|_| is_close_account)
+                .ok_or_else(|| {
+                    eyre!(
+                        "Unsupported Jupiter cleanup instruction for program {}",
+                        cleanup.program_id
+                    )
+                })?;
+
+            Some(CloseAccount::new(
+                vault_id,
+                sub_account,
+                account,
+                cleanup.program_id,
+            ))
+        }
+        None => None,
+    };
+
+    instructions.extend(create_manage_instruction(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        route,
+    )?);
+
+    if let Some(eix) = cleanup {
+        instructions.extend(create_manage_instruction(
+            client,
+            signer,
+            authority,
+            cpi_digest_check,
+            eix,
+        )?);
+    }
+
+    Ok(instructions)
+}
@@ -1,9 +1,13 @@
 pub mod boring_vault;
 pub mod jito;
+pub mod jupiter;
 pub mod solend;
+pub mod stake_pool;
 pub mod system;
 
 pub use boring_vault::*;
 pub use jito::*;
+pub use jupiter::*;
 pub use solend::*;
+pub use stake_pool::*;
 pub use system::*;
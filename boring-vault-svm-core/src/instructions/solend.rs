@@ -1,10 +1,10 @@
 use anchor_lang::pubkey;
 use eyre::Result;
-use solana_client::rpc_client::RpcClient;
 use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
 use spl_token::ID as TOKEN_PROGRAM_ID;
 
+use crate::utils::AccountSource;
 use crate::{
     manage_instructions::SolendDepositReserveLiquidityAndObligationCollateral,
     utils::get_vault_pda, KeypairOrPublickey,
@@ -13,10 +13,11 @@ use crate::{
 use super::{create_manage_instruction, init_associated_token_account_if_needed};
 
 // TODO this tx is too big if u send all at once
-pub fn create_deposit_solend_instructions(
-    client: &RpcClient,
+pub fn create_deposit_solend_instructions<C: AccountSource>(
+    client: &C,
     signer: &KeypairOrPublickey,
     authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
     vault_id: u64,
     sub_account: u8,
     deposit_mint: &Pubkey,
@@ -54,7 +55,7 @@ pub fn create_deposit_solend_instructions(
     //     1300,
     //     pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo"),
     // );
-    // let ixs = create_manage_instruction(client, signer, authority, eix_0)?;
+    // let ixs = create_manage_instruction(client, signer, authority, cpi_digest_check, eix_0)?;
     // instructions.extend(ixs);
 
     // Init obligation.
@@ -64,7 +65,7 @@ pub fn create_deposit_solend_instructions(
         &pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo"),
     )?;
     // let eix_1 = SolendInitObligation::new(vault_id, sub_account, obligation, *lending_market);
-    // let ixs = create_manage_instruction(client, signer, authority, eix_1)?;
+    // let ixs = create_manage_instruction(client, signer, authority, cpi_digest_check, eix_1)?;
     // instructions.extend(ixs);
 
     // Deposit
@@ -83,7 +84,7 @@ pub fn create_deposit_solend_instructions(
         *switchboard_price_oracle,
         amount,
     );
-    let ixs = create_manage_instruction(client, signer, authority, eix_2)?;
+    let ixs = create_manage_instruction(client, signer, authority, cpi_digest_check, eix_2)?;
     instructions.extend(ixs);
 
     Ok(instructions)
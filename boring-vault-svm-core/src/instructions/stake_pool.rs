@@ -0,0 +1,365 @@
+use anchor_lang::prelude::borsh::BorshDeserialize;
+use eyre::{eyre, Result};
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+use crate::utils::AccountSource;
+use crate::{
+    manage_instructions::{
+        get_stake_pool_deposit_authority, AuthorizeStake, CreateAccountWithSeed, DeactivateStake,
+        DepositSol, DepositStake, StakeAuthorize, StakePoolAccounts, WithdrawPoolStake,
+        WithdrawSol, WithdrawStake, STAKE_ACCOUNT_SIZE, STAKE_PROGRAM_ID,
+    },
+    state::{StakePool, ValidatorList},
+    utils::{get_account_data, get_vault_pda},
+    KeypairOrPublickey,
+};
+
+use super::create_manage_instruction;
+
+// `AccountType::StakePool`.
+const STAKE_POOL_ACCOUNT_TYPE: u8 = 1;
+// `StakeStateV2::Stake` tag and where its delegation's vote account is.
+const STAKE_STATE_STAKE: u32 = 2;
+const VOTER_PUBKEY_OFFSET: usize = 124;
+// The stake pool program's `MINIMUM_ACTIVE_STAKE`, delegated on top of the rent in every
+// validator stake account.
+const MINIMUM_ACTIVE_STAKE: u64 = 1_000_000;
+
+// Stake accounts unstaked into are created with seeds "unstake-00" to "unstake-31" of the
+// sub-account. The seeds have the same length, so they share a `CreateAccountWithSeed` digest.
+pub const MAX_UNSTAKE_ACCOUNTS: u8 = 32;
+
+pub fn get_unstake_seed(index: u8) -> String {
+    format!("unstake-{:02}", index)
+}
+
+pub fn get_unstake_account(vault_id: u64, sub_account: u8, index: u8) -> Result<(Pubkey, String)> {
+    let seed = get_unstake_seed(index);
+    let vault_pda = get_vault_pda(vault_id, sub_account);
+
+    Ok((
+        Pubkey::create_with_seed(&vault_pda, &seed, &STAKE_PROGRAM_ID)?,
+        seed,
+    ))
+}
+
+// Decodes the stake pool at `stake_pool`, owned by the SPL stake pool program or a fork of it.
+pub fn get_stake_pool_accounts<C: AccountSource>(
+    client: &C,
+    stake_pool: &Pubkey,
+) -> Result<StakePoolAccounts> {
+    let account = client.get_account(stake_pool)?;
+    let state = StakePool::deserialize(&mut &account.data[..])
+        .map_err(|e| eyre!("Failed to decode stake pool {}: {}", stake_pool, e))?;
+    if state.account_type != STAKE_POOL_ACCOUNT_TYPE {
+        return Err(eyre!("Account {} is not a stake pool", stake_pool));
+    }
+
+    Ok(StakePoolAccounts::new(*stake_pool, account.owner, &state))
+}
+
+// The stake account to withdraw stake from: the pool's preferred withdraw validator while it has
+// stake above the minimum, otherwise the validator with the most active stake, or the reserve when
+// there are none. The bool is whether it's a validator's.
+pub fn get_stake_to_split<C: AccountSource>(
+    client: &C,
+    stake_pool: &StakePoolAccounts,
+) -> Result<(Pubkey, bool)> {
+    let validator_list: ValidatorList = get_account_data(client, &stake_pool.validator_list)?;
+    let active = || {
+        validator_list
+            .validators
+            .iter()
+            .filter(|info| info.status == 0 && info.active_stake_lamports > 0)
+    };
+
+    // The pool only lets stake be withdrawn from other validators once the preferred one is down
+    // to the rent and minimum delegation of its stake account.
+    let minimum_lamports = client
+        .get_minimum_balance_for_rent_exemption(STAKE_ACCOUNT_SIZE as usize)?
+        + MINIMUM_ACTIVE_STAKE;
+    let preferred = stake_pool
+        .preferred_withdraw_validator
+        .and_then(|vote_account| {
+            active().find(|info| {
+                info.vote_account_address == vote_account
+                    && info.active_stake_lamports > minimum_lamports
+            })
+        });
+
+    match preferred.or_else(|| active().max_by_key(|info| info.active_stake_lamports)) {
+        Some(info) => Ok((
+            stake_pool
+                .validator_stake_account(&info.vote_account_address, info.validator_seed_suffix),
+            true,
+        )),
+        None => Ok((stake_pool.reserve_stake, false)),
+    }
+}
+
+pub fn create_deposit_sol_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>> {
+    let stake_pool = get_stake_pool_accounts(client, stake_pool)?;
+    if let Some(sol_deposit_authority) = stake_pool.sol_deposit_authority {
+        return Err(eyre!(
+            "Stake pool {} only takes SOL deposits signed by {}",
+            stake_pool.address,
+            sol_deposit_authority
+        ));
+    }
+
+    let eix = DepositSol::new(vault_id, sub_account, stake_pool, amount);
+
+    create_manage_instruction(client, signer, authority, cpi_digest_check, eix)
+}
+
+pub fn create_withdraw_sol_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>> {
+    let stake_pool = get_stake_pool_accounts(client, stake_pool)?;
+    if let Some(sol_withdraw_authority) = stake_pool.sol_withdraw_authority {
+        return Err(eyre!(
+            "Stake pool {} only allows SOL withdrawals signed by {}",
+            stake_pool.address,
+            sol_withdraw_authority
+        ));
+    }
+
+    let eix = WithdrawSol::new(vault_id, sub_account, stake_pool, amount);
+
+    create_manage_instruction(client, signer, authority, cpi_digest_check, eix)
+}
+
+// Hands the stake account's staker and withdrawer roles to the pool's deposit authority and
+// deposits it into the validator stake account it's delegated to.
+pub fn create_deposit_stake_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: &Pubkey,
+    stake_account: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let mut instructions = vec![];
+
+    let stake_pool = get_stake_pool_accounts(client, stake_pool)?;
+    let default_deposit_authority =
+        get_stake_pool_deposit_authority(&stake_pool.address, &stake_pool.program_id);
+    if stake_pool.stake_deposit_authority != default_deposit_authority {
+        return Err(eyre!(
+            "Stake pool {} only takes stake deposits signed by {}",
+            stake_pool.address,
+            stake_pool.stake_deposit_authority
+        ));
+    }
+
+    let vote_account = get_stake_vote_account(client, &stake_account)?;
+    if let Some(preferred) = stake_pool.preferred_deposit_validator {
+        if preferred != vote_account {
+            return Err(eyre!(
+                "Stake pool {} only takes stake delegated to {}",
+                stake_pool.address,
+                preferred
+            ));
+        }
+    }
+    let validator_list: ValidatorList = get_account_data(client, &stake_pool.validator_list)?;
+    let validator_stake_account = validator_list
+        .validators
+        .iter()
+        .find(|info| info.vote_account_address == vote_account)
+        .map(|info| stake_pool.validator_stake_account(&vote_account, info.validator_seed_suffix))
+        .ok_or_else(|| {
+            eyre!(
+                "Stake pool {} has no validator with vote account {}",
+                stake_pool.address,
+                vote_account
+            )
+        })?;
+
+    for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+        let eix = AuthorizeStake::new(
+            vault_id,
+            sub_account,
+            stake_account,
+            stake_pool.stake_deposit_authority,
+            stake_authorize,
+        );
+        instructions.extend(create_manage_instruction(
+            client,
+            signer,
+            authority,
+            cpi_digest_check,
+            eix,
+        )?);
+    }
+
+    let eix = DepositStake::new(
+        vault_id,
+        sub_account,
+        stake_pool,
+        stake_account,
+        validator_stake_account,
+    );
+    instructions.extend(create_manage_instruction(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        eix,
+    )?);
+
+    Ok(instructions)
+}
+
+// Creates a stake account with the next free seed, withdraws `amount` pool tokens of stake into it
+// and deactivates it if it came from a validator. Seeds of `in_use` accounts, e.g. ones created by
+// instructions not sent yet, are skipped. Returns the instructions and the stake account.
+pub fn create_unstake_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: &Pubkey,
+    amount: u64,
+    in_use: &[Pubkey],
+) -> Result<(Vec<Instruction>, Pubkey)> {
+    let mut instructions = vec![];
+
+    let unstake_accounts = (0..MAX_UNSTAKE_ACCOUNTS)
+        .map(|index| get_unstake_account(vault_id, sub_account, index))
+        .collect::<Result<Vec<_>>>()?;
+    let addresses = unstake_accounts
+        .iter()
+        .map(|(address, _)| *address)
+        .collect::<Vec<_>>();
+    let (stake_account, seed) = client
+        .get_multiple_accounts(&addresses)?
+        .iter()
+        .zip(&addresses)
+        .position(|(account, address)| account.is_none() && !in_use.contains(address))
+        .map(|index| unstake_accounts[index].clone())
+        .ok_or_else(|| {
+            eyre!(
+                "All {} unstake accounts are in use, withdraw some first",
+                MAX_UNSTAKE_ACCOUNTS
+            )
+        })?;
+
+    let stake_pool = get_stake_pool_accounts(client, stake_pool)?;
+    let (stake_to_split, from_validator) = get_stake_to_split(client, &stake_pool)?;
+
+    let lamports = client.get_minimum_balance_for_rent_exemption(STAKE_ACCOUNT_SIZE as usize)?;
+    let eix_0 = CreateAccountWithSeed::new(
+        vault_id,
+        sub_account,
+        seed,
+        lamports,
+        STAKE_ACCOUNT_SIZE,
+        STAKE_PROGRAM_ID,
+    );
+    instructions.extend(create_manage_instruction(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        eix_0,
+    )?);
+
+    let eix_1 = WithdrawPoolStake::new(
+        vault_id,
+        sub_account,
+        stake_pool,
+        stake_to_split,
+        stake_account,
+        amount,
+    );
+    instructions.extend(create_manage_instruction(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        eix_1,
+    )?);
+
+    // Stake split from the reserve is already inactive.
+    if from_validator {
+        let eix_2 = DeactivateStake::new(vault_id, sub_account, stake_account);
+        instructions.extend(create_manage_instruction(
+            client,
+            signer,
+            authority,
+            cpi_digest_check,
+            eix_2,
+        )?);
+    }
+
+    Ok((instructions, stake_account))
+}
+
+pub fn create_deactivate_stake_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    stake_account: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let eix = DeactivateStake::new(vault_id, sub_account, stake_account);
+
+    create_manage_instruction(client, signer, authority, cpi_digest_check, eix)
+}
+
+// Withdraws every lamport, which closes the stake account.
+pub fn create_withdraw_stake_instructions<C: AccountSource>(
+    client: &C,
+    signer: &KeypairOrPublickey,
+    authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
+    vault_id: u64,
+    sub_account: u8,
+    stake_account: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let lamports = client.get_account(&stake_account)?.lamports;
+    let eix = WithdrawStake::new(vault_id, sub_account, stake_account, lamports);
+
+    create_manage_instruction(client, signer, authority, cpi_digest_check, eix)
+}
+
+fn get_stake_vote_account<C: AccountSource>(client: &C, stake_account: &Pubkey) -> Result<Pubkey> {
+    let account = client.get_account(stake_account)?;
+    let tag = account
+        .data
+        .get(0..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()));
+    if account.owner != STAKE_PROGRAM_ID || tag != Some(STAKE_STATE_STAKE) {
+        return Err(eyre!("Stake account {} is not delegated", stake_account));
+    }
+
+    account
+        .data
+        .get(VOTER_PUBKEY_OFFSET..VOTER_PUBKEY_OFFSET + 32)
+        .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+        .ok_or_else(|| eyre!("Invalid stake account {}", stake_account))
+}
@@ -1,15 +1,16 @@
 use anchor_lang::pubkey;
 use eyre::Result;
 use solana_address_lookup_table_interface::instruction::create_lookup_table;
-use solana_client::rpc_client::RpcClient;
 use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
-use solana_sdk::system_instruction;
+use solana_sdk::nonce::State as NonceState;
+use solana_system_interface::instruction as system_instruction;
 use spl_associated_token_account::{
     get_associated_token_address_with_program_id, instruction::create_associated_token_account,
 };
 use spl_token::ID as TOKEN_PROGRAM_ID;
 
+use crate::utils::AccountSource;
 use crate::{
     manage_instructions::{CloseAccount, TransferSol},
     utils::get_vault_pda,
@@ -35,6 +36,23 @@ pub fn create_lut_instruction(
     Ok(lookup_table_ix)
 }
 
+pub fn create_nonce_account_instructions<C: AccountSource>(
+    client: &C,
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Vec<Instruction>> {
+    let lamports = client.get_minimum_balance_for_rent_exemption(NonceState::size())?;
+
+    // Creates the account and initializes it with the current blockhash as its nonce.
+    Ok(system_instruction::create_nonce_account(
+        payer,
+        nonce_account,
+        authority,
+        lamports,
+    ))
+}
+
 pub fn create_account_with_seed_instruction(
     signer: &Pubkey,
     vault_id: u64,
@@ -76,10 +94,11 @@ pub fn create_associated_token_account_instruction(
     ))
 }
 
-pub fn create_wrap_sol_instructions(
-    client: &RpcClient,
+pub fn create_wrap_sol_instructions<C: AccountSource>(
+    client: &C,
     signer: &KeypairOrPublickey,
     authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
     vault_id: u64,
     sub_account: u8,
     amount: u64,
@@ -92,7 +111,13 @@ pub fn create_wrap_sol_instructions(
 
     // Transfer amount to wSOL ata.
     let eix_0 = TransferSol::new(vault_id, sub_account, w_sol_ata, amount);
-    instructions.extend(create_manage_instruction(client, signer, authority, eix_0)?);
+    instructions.extend(create_manage_instruction(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        eix_0,
+    )?);
 
     // Init ata if needed(which wraps it).
     if let Some(ix) = init_associated_token_account_if_needed(
@@ -109,10 +134,11 @@ pub fn create_wrap_sol_instructions(
     Ok(instructions)
 }
 
-pub fn create_unwrap_sol_instructions(
-    client: &RpcClient,
+pub fn create_unwrap_sol_instructions<C: AccountSource>(
+    client: &C,
     signer: &KeypairOrPublickey,
     authority: Option<&KeypairOrPublickey>,
+    cpi_digest_check: bool,
     vault_id: u64,
     sub_account: u8,
 ) -> Result<Vec<Instruction>> {
@@ -122,13 +148,19 @@ pub fn create_unwrap_sol_instructions(
         get_associated_token_address_with_program_id(&vault_pda, WSOL_MINT, &TOKEN_PROGRAM_ID);
 
     let eix = CloseAccount::new(vault_id, sub_account, w_sol_ata, TOKEN_PROGRAM_ID);
-    instructions.extend(create_manage_instruction(client, signer, authority, eix)?);
+    instructions.extend(create_manage_instruction(
+        client,
+        signer,
+        authority,
+        cpi_digest_check,
+        eix,
+    )?);
 
     Ok(instructions)
 }
 
-pub fn init_associated_token_account_if_needed(
-    client: &RpcClient,
+pub fn init_associated_token_account_if_needed<C: AccountSource>(
+    client: &C,
     signer: &Pubkey,
     vault_id: u64,
     sub_account: u8,
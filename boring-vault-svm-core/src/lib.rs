@@ -3,35 +3,77 @@ pub mod builder;
 pub mod config;
 pub mod instructions;
 pub mod manage_instructions;
+pub mod nonblocking;
+pub mod state;
 pub mod transaction;
 pub mod utils;
 pub mod view;
 
 use solana_keypair::Keypair;
+use solana_presigner::Presigner;
 use solana_pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use solana_signer::Signer;
 
+use crate::utils::{BoxedSigner, KeypairSource};
+
 pub enum KeypairOrPublickey {
     Keypair(Keypair),
     Publickey(Pubkey),
+    // Any other signer, e.g. a presigned signature or a `RemoteSigner`.
+    Signer(BoxedSigner),
 }
 
 impl KeypairOrPublickey {
+    pub fn from_signer<S: Signer + Send + Sync + 'static>(signer: S) -> Self {
+        Self::Signer(Box::new(signer))
+    }
+
+    pub fn from_keypair_file(path: &str) -> eyre::Result<Self> {
+        Ok(Self::Keypair(KeypairSource::File(path.into()).resolve()?))
+    }
+
+    // Signature made over the exact message elsewhere. Signing any other message fails.
+    pub fn presigned(pubkey: Pubkey, signature: Signature) -> Self {
+        Self::from_signer(Presigner::new(&pubkey, &signature))
+    }
+
     pub fn pubkey(&self) -> Pubkey {
         match self {
             Self::Keypair(keypair) => keypair.pubkey(),
             Self::Publickey(pubkey) => *pubkey,
+            Self::Signer(signer) => signer.pubkey(),
         }
     }
 
     pub fn into_keypair(self) -> Option<Keypair> {
         match self {
             Self::Keypair(keypair) => Some(keypair),
+            _ => None,
+        }
+    }
+
+    pub fn into_signer(self) -> Option<BoxedSigner> {
+        match self {
+            Self::Keypair(keypair) => Some(Box::new(keypair)),
             Self::Publickey(_) => None,
+            Self::Signer(signer) => Some(signer),
         }
     }
 
     pub fn can_sign(&self) -> bool {
-        matches!(self, Self::Keypair(_))
+        !matches!(self, Self::Publickey(_))
+    }
+}
+
+impl From<Keypair> for KeypairOrPublickey {
+    fn from(keypair: Keypair) -> Self {
+        Self::Keypair(keypair)
+    }
+}
+
+impl From<Pubkey> for KeypairOrPublickey {
+    fn from(pubkey: Pubkey) -> Self {
+        Self::Publickey(pubkey)
     }
 }
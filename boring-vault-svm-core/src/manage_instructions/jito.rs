@@ -1,80 +1,129 @@
-use crate::impl_external_instruction_common;
-use crate::manage_instructions::ExternalInstruction;
-use crate::utils::bindings::boring_vault_svm::types::{Operator, Operators};
-use crate::utils::pdas;
+use crate::manage_instructions::{
+    get_stake_pool_deposit_authority, DepositSol, ExternalInstruction, StakePoolAccounts,
+    WithdrawPoolStake, WithdrawSol, SPL_STAKE_POOL_PROGRAM_ID,
+};
+use crate::utils::bindings::boring_vault_svm::types::Operators;
 
-use solana_instruction::account_meta::AccountMeta;
-use solana_program::system_program;
+use solana_instruction::{account_meta::AccountMeta, Instruction};
 use solana_pubkey::{pubkey, Pubkey};
-use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token::ID as TOKEN_PROGRAM_ID;
 
 pub const JITO_MINT: Pubkey = pubkey!("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn");
-const JITO_STAKE_POOL: Pubkey = pubkey!("Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb");
+pub const JITO_STAKE_POOL: Pubkey = pubkey!("Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb");
 const JITO_STAKE_POOL_WITHDRAW_AUTHORITY: Pubkey =
     pubkey!("6iQKfEyhr3bZMotVkW6beNZz5CPAkiwvgV2CTje9pVSS");
+const JITO_VALIDATOR_LIST: Pubkey = pubkey!("3R3nGZpQs2aZo5FDQvd2MUQ6R7KhAPainds6uT6uE2mn");
 const JITO_RESERVE_STAKE_ACCOUNT: Pubkey = pubkey!("BgKUXdS29YcHCFrPm5M8oLHiTzZaMDjsebggjoaQ6KFL");
 const JITO_FEE_ACCOUNT: Pubkey = pubkey!("feeeFLLsam6xZJFc6UQFrHqkvVt4jfmVvi2BRLkUZ4i");
 
-pub struct MintJitoSol {
-    vault_id: u64,
-    sub_account: u8,
-    amount: u64,
+// The Jito stake pool's accounts, so its instructions can be built without fetching the pool.
+// Stake deposits go through `get_stake_pool_accounts` instead, which reads the deposit authority
+// the pool actually uses.
+pub fn jito_stake_pool() -> StakePoolAccounts {
+    StakePoolAccounts {
+        address: JITO_STAKE_POOL,
+        program_id: SPL_STAKE_POOL_PROGRAM_ID,
+        withdraw_authority: JITO_STAKE_POOL_WITHDRAW_AUTHORITY,
+        stake_deposit_authority: get_stake_pool_deposit_authority(
+            &JITO_STAKE_POOL,
+            &SPL_STAKE_POOL_PROGRAM_ID,
+        ),
+        sol_deposit_authority: None,
+        sol_withdraw_authority: None,
+        validator_list: JITO_VALIDATOR_LIST,
+        reserve_stake: JITO_RESERVE_STAKE_ACCOUNT,
+        pool_mint: JITO_MINT,
+        manager_fee_account: JITO_FEE_ACCOUNT,
+        token_program_id: TOKEN_PROGRAM_ID,
+        preferred_deposit_validator: None,
+        preferred_withdraw_validator: None,
+    }
 }
 
+// Presets wrap the stake pool instruction and forward to it, so their digests are the same.
+macro_rules! impl_stake_pool_preset {
+    ($preset:ty) => {
+        impl ExternalInstruction for $preset {
+            fn vault_id(&self) -> u64 {
+                self.0.vault_id()
+            }
+            fn sub_account(&self) -> u8 {
+                self.0.sub_account()
+            }
+            fn ix_program_id(&self) -> Pubkey {
+                self.0.ix_program_id()
+            }
+            fn ix_data(&self) -> Vec<u8> {
+                self.0.ix_data()
+            }
+            fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+                self.0.ix_remaining_accounts()
+            }
+            fn ix_operators(&self) -> Operators {
+                self.0.ix_operators()
+            }
+            fn to_instruction(&self) -> Instruction {
+                self.0.to_instruction()
+            }
+            fn get_digest(&self) -> (Pubkey, String) {
+                self.0.get_digest()
+            }
+        }
+    };
+}
+
+// `DepositSol` into the Jito stake pool.
+pub struct MintJitoSol(DepositSol);
+
 impl MintJitoSol {
     pub fn new(vault_id: u64, sub_account: u8, amount: u64) -> Self {
-        Self {
+        Self(DepositSol::new(
             vault_id,
             sub_account,
+            jito_stake_pool(),
             amount,
-        }
+        ))
     }
 }
 
-impl ExternalInstruction for MintJitoSol {
-    impl_external_instruction_common!();
-
-    fn ix_program_id(&self) -> Pubkey {
-        pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy")
-    }
+impl_stake_pool_preset!(MintJitoSol);
 
-    fn ix_data(&self) -> Vec<u8> {
-        let mut ix_data = vec![14]; // 14 is the discriminator for minting JitoSol.
-        ix_data.extend(self.amount.to_le_bytes());
+// `WithdrawSol` from the Jito stake pool.
+pub struct WithdrawJitoSol(WithdrawSol);
 
-        ix_data
+impl WithdrawJitoSol {
+    pub fn new(vault_id: u64, sub_account: u8, amount: u64) -> Self {
+        Self(WithdrawSol::new(
+            vault_id,
+            sub_account,
+            jito_stake_pool(),
+            amount,
+        ))
     }
+}
 
-    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
-        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
-        let jito_sol_ata =
-            get_associated_token_address_with_program_id(&vault_pda, &JITO_MINT, &TOKEN_PROGRAM_ID);
+impl_stake_pool_preset!(WithdrawJitoSol);
 
-        vec![
-            AccountMeta::new(JITO_STAKE_POOL, false), // Jito (JitoSol) Stake Pool
-            AccountMeta::new_readonly(JITO_STAKE_POOL_WITHDRAW_AUTHORITY, false), // stake pool withdraw authority
-            AccountMeta::new(JITO_RESERVE_STAKE_ACCOUNT, false), // reserve stake account
-            AccountMeta::new(vault_pda, false),                  // depositor
-            AccountMeta::new(jito_sol_ata, false),               // user account
-            AccountMeta::new(JITO_FEE_ACCOUNT, false),           // fee account
-            AccountMeta::new(jito_sol_ata, false),               // referral fee account
-            AccountMeta::new(JITO_MINT, false),                  // token mint
-            AccountMeta::new_readonly(system_program::ID, false), // system program
-            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),  // token program
-        ]
-    }
+// `WithdrawPoolStake` from the Jito stake pool.
+pub struct WithdrawJitoStake(WithdrawPoolStake);
 
-    fn ix_operators(&self) -> Operators {
-        let operators = vec![
-            Operator::IngestInstruction(0, 1),
-            Operator::IngestAccount(3),
-            Operator::IngestAccount(4),
-            Operator::IngestInstructionDataSize,
-        ];
-
-        Operators { operators }
+impl WithdrawJitoStake {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        stake_to_split: Pubkey,
+        stake_to_receive: Pubkey,
+        amount: u64,
+    ) -> Self {
+        Self(WithdrawPoolStake::new(
+            vault_id,
+            sub_account,
+            jito_stake_pool(),
+            stake_to_split,
+            stake_to_receive,
+            amount,
+        ))
     }
 }
 
-// call unstake on pool, then need to call deactivate, then finally withdraw after some days
+impl_stake_pool_preset!(WithdrawJitoStake);
@@ -1,8 +1,165 @@
-// TODO Jupiter DEX plan
-// Looks like an anchor program, so ingest discriminator
-// Do not ingest ix data size as that will change betweeen calls
-// Ingest destination token account, source mint, destination mint
-// example txs
+use std::collections::HashMap;
+
+use crate::impl_external_instruction_common;
+use crate::manage_instructions::ExternalInstruction;
+use crate::utils::bindings::boring_vault_svm::types::{Operator, Operators};
+use crate::utils::pdas;
+
+use eyre::{eyre, Result};
+use solana_instruction::{account_meta::AccountMeta, Instruction};
+use solana_pubkey::{pubkey, Pubkey};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token::ID as TOKEN_PROGRAM_ID;
+use spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
+
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+pub const ROUTE_DISCRIMINATOR: [u8; 8] = [229, 23, 203, 151, 122, 227, 173, 42];
+pub const SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
+
+// Example txs
 // https://solscan.io/tx/5LqV9oZUitaPieA3pth1JZoyo2XPcSfZawWioEtPUWAqTdRbDqqwy2Mjuw3cLM2mkcXeA4E5yAhFdhuYR9yYPtBz
 // https://solscan.io/tx/27fTvBLo2GuBLLL1jSEGKLqKtcioFwQQG6mNw4vcF54G9iE8FLBU1wQZWNZGkeqRocnf772Qx1DWSkyiQ37ocZx2
-// https://solscan.io/tx/4Cgipr3V7qgxmAFNv4ZqxowgBRxBiUtsGE4QC3gKav7FT3GZQUuB9R6z9KBms25SdvQd6pLEhhxphyaEmDnHMXaU
\ No newline at end of file
+// https://solscan.io/tx/4Cgipr3V7qgxmAFNv4ZqxowgBRxBiUtsGE4QC3gKav7FT3GZQUuB9R6z9KBms25SdvQd6pLEhhxphyaEmDnHMXaU
+//
+// The route data and the AMM accounts change between quotes, so the digest only ingests the
+// discriminator and the accounts that decide where tokens come from and go to: the source and
+// destination token accounts, the mints and the platform fee account.
+pub struct JupiterRoute {
+    vault_id: u64,
+    sub_account: u8,
+    instruction: Instruction,
+    user: Pubkey,
+}
+
+impl JupiterRoute {
+    // Takes the swap instruction of a `/swap-instructions` response. The user it was requested
+    // for, and that user's token accounts, are replaced with the sub-account PDA and its ATAs.
+    pub fn new(vault_id: u64, sub_account: u8, swap_instruction: &Instruction) -> Result<Self> {
+        if swap_instruction.program_id != JUPITER_PROGRAM_ID {
+            return Err(eyre!(
+                "Expected a Jupiter swap instruction, got program {}",
+                swap_instruction.program_id
+            ));
+        }
+
+        let user_index = match swap_instruction.data.get(..8) {
+            Some(discriminator) if discriminator == ROUTE_DISCRIMINATOR => 1,
+            Some(discriminator) if discriminator == SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR => 2,
+            _ => {
+                return Err(eyre!(
+                    "Unsupported Jupiter instruction, only route and shared_accounts_route are"
+                ))
+            }
+        };
+        let user = swap_instruction
+            .accounts
+            .get(user_index)
+            .ok_or_else(|| eyre!("Jupiter swap instruction is missing accounts"))?
+            .pubkey;
+
+        let mut route = Self {
+            vault_id,
+            sub_account,
+            instruction: swap_instruction.clone(),
+            user,
+        };
+        route.instruction.accounts = route.rewrite_accounts(&swap_instruction.accounts, None);
+
+        Ok(route)
+    }
+
+    // The authority the swap instructions were requested for.
+    pub fn user(&self) -> Pubkey {
+        self.user
+    }
+
+    fn is_shared_accounts_route(&self) -> bool {
+        self.instruction.data[..8] == SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR
+    }
+
+    // Replaces the user with the sub-account PDA, which the program signs for, and the user's ATAs
+    // with the PDA's. A user signature can be given to someone else, e.g. the payer of setup
+    // instructions.
+    pub fn rewrite_accounts(
+        &self,
+        accounts: &[AccountMeta],
+        signer: Option<&Pubkey>,
+    ) -> Vec<AccountMeta> {
+        let vault = pdas::get_vault_pda(self.vault_id, self.sub_account);
+
+        let mut replacements = HashMap::from([(self.user, vault)]);
+        if self.user != vault {
+            // Any account the instructions mention could be a mint.
+            for mint in accounts.iter().chain(&self.instruction.accounts) {
+                for token_program in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+                    replacements.insert(
+                        get_associated_token_address_with_program_id(
+                            &self.user,
+                            &mint.pubkey,
+                            &token_program,
+                        ),
+                        get_associated_token_address_with_program_id(
+                            &vault,
+                            &mint.pubkey,
+                            &token_program,
+                        ),
+                    );
+                }
+            }
+        }
+
+        accounts
+            .iter()
+            .map(|account| match (account.pubkey == self.user, signer) {
+                (true, Some(signer)) if account.is_signer => AccountMeta {
+                    pubkey: *signer,
+                    is_signer: true,
+                    is_writable: account.is_writable,
+                },
+                (true, _) => AccountMeta {
+                    pubkey: vault,
+                    is_signer: false,
+                    is_writable: account.is_writable,
+                },
+                _ => AccountMeta {
+                    pubkey: *replacements.get(&account.pubkey).unwrap_or(&account.pubkey),
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                },
+            })
+            .collect()
+    }
+}
+
+impl ExternalInstruction for JupiterRoute {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        JUPITER_PROGRAM_ID
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        self.instruction.data.clone()
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        self.instruction.accounts.clone()
+    }
+
+    fn ix_operators(&self) -> Operators {
+        let accounts: &[u8] = if self.is_shared_accounts_route() {
+            // Source and destination token accounts, source and destination mints, platform fee.
+            &[3, 6, 7, 8, 9]
+        } else {
+            // Source, destination and optional destination override token accounts, destination
+            // mint, platform fee.
+            &[2, 3, 4, 5, 6]
+        };
+
+        let mut operators = vec![Operator::IngestInstruction(0, 8)];
+        operators.extend(accounts.iter().map(|index| Operator::IngestAccount(*index)));
+
+        Operators { operators }
+    }
+}
@@ -1,5 +1,3 @@
-use std::str::FromStr;
-
 use crate::impl_external_instruction_common;
 use crate::manage_instructions::ExternalInstruction;
 use crate::utils::bindings::boring_vault_svm::types::{Operator, Operators};
@@ -395,16 +393,26 @@ pub struct KaminoRefreshObligation {
     lending_market: Pubkey,
     tag: u8,
     id: u8,
+    // The obligation's deposit reserves then its borrow reserves.
+    reserves: Vec<Pubkey>,
 }
 
 impl KaminoRefreshObligation {
-    pub fn new(vault_id: u64, sub_account: u8, lending_market: Pubkey, tag: u8, id: u8) -> Self {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        lending_market: Pubkey,
+        tag: u8,
+        id: u8,
+        reserves: Vec<Pubkey>,
+    ) -> Self {
         Self {
             vault_id,
             sub_account,
             lending_market,
             tag,
             id,
+            reserves,
         }
     }
 }
@@ -432,14 +440,17 @@ impl ExternalInstruction for KaminoRefreshObligation {
             &system_program::ID,
             &self.ix_program_id(),
         );
-        vec![
+        let mut accounts = vec![
             AccountMeta::new_readonly(self.lending_market, false),
             AccountMeta::new(obligation, false),
-            AccountMeta::new(
-                Pubkey::from_str("F9HdecRG8GPs9LEn4S5VfeJVEZVqrDJFR6bvmQTi22na").unwrap(),
-                false,
-            ), // farm accounts obligation farm user state
-        ]
+        ];
+        accounts.extend(
+            self.reserves
+                .iter()
+                .map(|reserve| AccountMeta::new(*reserve, false)),
+        );
+
+        accounts
     }
 
     fn ix_operators(&self) -> Operators {
@@ -772,3 +783,249 @@ impl ExternalInstruction for KaminoBorrow {
         Operators { operators }
     }
 }
+
+// `reserve_farm_state` is the reserve's debt farm, `Pubkey::default()` when it has none.
+pub struct KaminoRepay {
+    vault_id: u64,
+    sub_account: u8,
+    lending_market: Pubkey,
+    reserve: Pubkey,
+    reserve_liquidity_mint: Pubkey,
+    reserve_destination_liquidity: Pubkey,
+    reserve_farm_state: Pubkey,
+    tag: u8,
+    id: u8,
+    amount: u64,
+}
+
+impl KaminoRepay {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        lending_market: Pubkey,
+        reserve: Pubkey,
+        reserve_liquidity_mint: Pubkey,
+        reserve_destination_liquidity: Pubkey,
+        reserve_farm_state: Pubkey,
+        tag: u8,
+        id: u8,
+        amount: u64,
+    ) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            lending_market,
+            reserve,
+            reserve_liquidity_mint,
+            reserve_destination_liquidity,
+            reserve_farm_state,
+            tag,
+            id,
+            amount,
+        }
+    }
+}
+
+impl ExternalInstruction for KaminoRepay {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        KAMINO_PROGRAM_ID
+    }
+
+    // `u64::MAX` repays the whole debt, interest accrued by the refresh included.
+    fn ix_data(&self) -> Vec<u8> {
+        let discriminator =
+            discriminator::get_anchor_discriminator("repay_obligation_liquidity_v2");
+        let mut ix_data = discriminator.to_vec();
+        ix_data.extend_from_slice(&self.amount.to_le_bytes());
+        ix_data
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let owner = pdas::get_vault_pda(self.vault_id, self.sub_account);
+        let lending_market_authority =
+            pdas::get_lending_market_authority(&self.lending_market, &self.ix_program_id());
+        let vault_mint_ata = get_associated_token_address_with_program_id(
+            &owner,
+            &self.reserve_liquidity_mint,
+            &TOKEN_PROGRAM_ID,
+        );
+        let obligation = pdas::get_obligation(
+            self.tag,
+            self.id,
+            &owner,
+            &self.lending_market,
+            &system_program::ID,
+            &system_program::ID,
+            &self.ix_program_id(),
+        );
+        let (obligation_farm, reserve_farm_state) =
+            get_farm_accounts(&self.reserve_farm_state, &obligation);
+
+        vec![
+            AccountMeta::new(owner, false),                                // owner
+            AccountMeta::new(obligation, false),                           // obligation
+            AccountMeta::new_readonly(self.lending_market, false),         // lending market
+            AccountMeta::new(self.reserve, false),                         // repay reserve
+            AccountMeta::new_readonly(self.reserve_liquidity_mint, false), // reserve liquidity mint
+            AccountMeta::new(self.reserve_destination_liquidity, false), // reserve destination liquidity
+            AccountMeta::new(vault_mint_ata, false),                     // user source liquidity
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),          // token program
+            AccountMeta::new_readonly(SYSVAR_INSTRUCTIONS_ID, false),    // sysvar instruction
+            AccountMeta::new(obligation_farm, false), // farm accounts obligation farm user state
+            AccountMeta::new(reserve_farm_state, false), // farms accounts reserve farm state
+            AccountMeta::new_readonly(lending_market_authority, false), // lending market authority
+            AccountMeta::new_readonly(KAMINO_FARMS_PROGRAM_ID, false), // farms program
+        ]
+    }
+
+    // Anyone's obligation can be repaid, so the digest pins the sub-account's own obligation and
+    // the ATA the liquidity comes from.
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 8),
+            Operator::IngestAccount(1),
+            Operator::IngestAccount(2),
+            Operator::IngestAccount(4),
+            Operator::IngestAccount(6),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+// Withdraws `amount` of collateral (cTokens) and redeems it for liquidity into the sub-account's
+// ATA, undoing a `KaminoDeposit`.
+pub struct KaminoWithdraw {
+    vault_id: u64,
+    sub_account: u8,
+    lending_market: Pubkey,
+    reserve: Pubkey,
+    reserve_liquidity_mint: Pubkey,
+    reserve_liquidity_supply: Pubkey,
+    reserve_collateral_mint: Pubkey,
+    reserve_source_collateral: Pubkey,
+    reserve_farm_state: Pubkey,
+    tag: u8,
+    id: u8,
+    amount: u64,
+}
+
+impl KaminoWithdraw {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        lending_market: Pubkey,
+        reserve: Pubkey,
+        reserve_liquidity_mint: Pubkey,
+        reserve_liquidity_supply: Pubkey,
+        reserve_collateral_mint: Pubkey,
+        reserve_source_collateral: Pubkey,
+        reserve_farm_state: Pubkey,
+        tag: u8,
+        id: u8,
+        amount: u64,
+    ) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            lending_market,
+            reserve,
+            reserve_liquidity_mint,
+            reserve_liquidity_supply,
+            reserve_collateral_mint,
+            reserve_source_collateral,
+            reserve_farm_state,
+            tag,
+            id,
+            amount,
+        }
+    }
+}
+
+impl ExternalInstruction for KaminoWithdraw {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        KAMINO_PROGRAM_ID
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        let discriminator = discriminator::get_anchor_discriminator(
+            "withdraw_obligation_collateral_and_redeem_reserve_collateral_v2",
+        );
+        let mut ix_data = discriminator.to_vec();
+        ix_data.extend_from_slice(&self.amount.to_le_bytes());
+        ix_data
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let owner = pdas::get_vault_pda(self.vault_id, self.sub_account);
+        let lending_market_authority =
+            pdas::get_lending_market_authority(&self.lending_market, &self.ix_program_id());
+        let vault_mint_ata = get_associated_token_address_with_program_id(
+            &owner,
+            &self.reserve_liquidity_mint,
+            &TOKEN_PROGRAM_ID,
+        );
+        let obligation = pdas::get_obligation(
+            self.tag,
+            self.id,
+            &owner,
+            &self.lending_market,
+            &system_program::ID,
+            &system_program::ID,
+            &self.ix_program_id(),
+        );
+        let (obligation_farm, reserve_farm_state) =
+            get_farm_accounts(&self.reserve_farm_state, &obligation);
+
+        vec![
+            AccountMeta::new(owner, false),                                // owner
+            AccountMeta::new(obligation, false),                           // obligation
+            AccountMeta::new_readonly(self.lending_market, false),         // lending market
+            AccountMeta::new_readonly(lending_market_authority, false), // lending market authority
+            AccountMeta::new(self.reserve, false),                      // withdraw reserve
+            AccountMeta::new_readonly(self.reserve_liquidity_mint, false), // reserve liquidity mint
+            AccountMeta::new(self.reserve_source_collateral, false),    // reserve source collateral
+            AccountMeta::new(self.reserve_collateral_mint, false),      // reserve collateral mint
+            AccountMeta::new(self.reserve_liquidity_supply, false),     // reserve liquidity supply
+            AccountMeta::new(vault_mint_ata, false), // user destination liquidity
+            AccountMeta::new_readonly(self.ix_program_id(), false), // placeholder user destination collateral
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),     // collateral token program
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),     // liquidity token program
+            AccountMeta::new_readonly(SYSVAR_INSTRUCTIONS_ID, false), // sysvar instruction
+            AccountMeta::new(obligation_farm, false), // farm accounts obligation farm user state
+            AccountMeta::new(reserve_farm_state, false), // farms accounts reserve farm state
+            AccountMeta::new_readonly(KAMINO_FARMS_PROGRAM_ID, false), // farms program
+        ]
+    }
+
+    // The liquidity has to land in the sub-account's ATA.
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 8),
+            Operator::IngestAccount(2),
+            Operator::IngestAccount(5),
+            Operator::IngestAccount(9),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+// The obligation farm and reserve farm state, or the program id placeholders Kamino takes for
+// reserves without a farm.
+fn get_farm_accounts(reserve_farm_state: &Pubkey, obligation: &Pubkey) -> (Pubkey, Pubkey) {
+    if *reserve_farm_state == Pubkey::default() {
+        return (KAMINO_PROGRAM_ID, KAMINO_PROGRAM_ID);
+    }
+
+    (
+        pdas::get_obligation_farm(reserve_farm_state, obligation, &KAMINO_FARMS_PROGRAM_ID),
+        *reserve_farm_state,
+    )
+}
@@ -1,11 +1,17 @@
 pub mod jito;
+pub mod jupiter;
 pub mod kamino;
+pub mod raw;
 pub mod solend;
+pub mod stake_pool;
 pub mod system;
 
 pub use jito::*;
+pub use jupiter::*;
 pub use kamino::*;
+pub use raw::*;
 pub use solend::*;
+pub use stake_pool::*;
 pub use system::*;
 
 use crate::utils::bindings::boring_vault_svm::types::Operators;
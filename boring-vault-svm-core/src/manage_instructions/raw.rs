@@ -0,0 +1,113 @@
+use crate::impl_external_instruction_common;
+use crate::manage_instructions::ExternalInstruction;
+use crate::utils::bindings::boring_vault_svm::types::{Operator, Operators};
+use crate::utils::pdas;
+use eyre::{eyre, Result};
+use solana_instruction::{account_meta::AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+// Any instruction, e.g. one produced by another protocol's SDK, managed with the given operators.
+pub struct RawExternalInstruction {
+    vault_id: u64,
+    sub_account: u8,
+    instruction: Instruction,
+    operators: Operators,
+}
+
+impl RawExternalInstruction {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        instruction: Instruction,
+        operators: Operators,
+    ) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            instruction,
+            operators,
+        }
+    }
+}
+
+impl ExternalInstruction for RawExternalInstruction {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        self.instruction.program_id
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        self.instruction.data.clone()
+    }
+
+    // SDKs mark the sub-account as a signer since it's the authority, but the program signs for
+    // it with the vault seeds, so the manage instruction must not.
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault = pdas::get_vault_pda(self.vault_id, self.sub_account);
+
+        self.instruction
+            .accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer && account.pubkey != vault,
+                is_writable: account.is_writable,
+            })
+            .collect()
+    }
+
+    fn ix_operators(&self) -> Operators {
+        self.operators.clone()
+    }
+}
+
+// Parses operators written as `format_operators` prints them, e.g.
+// "IngestInstruction(0, 8), IngestAccount(1), IngestInstructionDataSize".
+pub fn parse_operators(spec: &str) -> Result<Operators> {
+    let mut operators = vec![];
+    let mut rest = spec.trim();
+
+    while !rest.is_empty() {
+        let end = match (rest.find(','), rest.find('(')) {
+            (Some(comma), Some(open)) if open < comma => {
+                rest.find(')')
+                    .ok_or_else(|| eyre!("Unclosed operator in {}", spec))?
+                    + 1
+            }
+            (Some(comma), _) => comma,
+            (None, _) => rest.len(),
+        };
+        operators.push(parse_operator(rest[..end].trim())?);
+        rest = rest[end..]
+            .trim_start()
+            .trim_start_matches(',')
+            .trim_start();
+    }
+
+    Ok(Operators { operators })
+}
+
+fn parse_operator(operator: &str) -> Result<Operator> {
+    let (name, args) = match operator.split_once('(') {
+        Some((name, args)) => (
+            name.trim(),
+            args.strip_suffix(')')
+                .ok_or_else(|| eyre!("Invalid operator {}", operator))?
+                .split(',')
+                .map(|arg| arg.trim().parse::<u32>())
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => (operator, vec![]),
+    };
+
+    match (name, args.as_slice()) {
+        ("Noop", []) => Ok(Operator::Noop),
+        ("IngestInstruction", [index, length]) => {
+            Ok(Operator::IngestInstruction(*index, u8::try_from(*length)?))
+        }
+        ("IngestAccount", [index]) => Ok(Operator::IngestAccount(u8::try_from(*index)?)),
+        ("IngestInstructionDataSize", []) => Ok(Operator::IngestInstructionDataSize),
+        _ => Err(eyre!("Invalid operator {}", operator)),
+    }
+}
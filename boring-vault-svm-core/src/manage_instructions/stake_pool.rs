@@ -0,0 +1,544 @@
+use crate::impl_external_instruction_common;
+use crate::manage_instructions::ExternalInstruction;
+use crate::state::StakePool;
+use crate::utils::bindings::boring_vault_svm::types::{Operator, Operators};
+use crate::utils::pdas;
+
+use serde::Deserialize;
+use solana_instruction::account_meta::AccountMeta;
+use solana_program::system_program;
+use solana_program::sysvar::{
+    clock::ID as CLOCK_SYSVAR_ID, stake_history::ID as STAKE_HISTORY_SYSVAR_ID,
+};
+use solana_pubkey::{pubkey, Pubkey};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+pub const STAKE_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+// Size of a `StakeStateV2` account.
+pub const STAKE_ACCOUNT_SIZE: u64 = 200;
+
+// The accounts of a stake pool its instructions need, decoded from the pool account or preset
+// for well known pools. `program_id` is the stake pool program owning the pool, which may be a
+// fork of the SPL one.
+#[derive(Debug, Clone, Default)]
+pub struct StakePoolAccounts {
+    pub address: Pubkey,
+    pub program_id: Pubkey,
+    pub withdraw_authority: Pubkey,
+    pub stake_deposit_authority: Pubkey,
+    pub sol_deposit_authority: Option<Pubkey>,
+    pub sol_withdraw_authority: Option<Pubkey>,
+    pub validator_list: Pubkey,
+    pub reserve_stake: Pubkey,
+    pub pool_mint: Pubkey,
+    pub manager_fee_account: Pubkey,
+    pub token_program_id: Pubkey,
+    // Vote accounts the pool requires stake deposits and withdrawals to go through.
+    pub preferred_deposit_validator: Option<Pubkey>,
+    pub preferred_withdraw_validator: Option<Pubkey>,
+}
+
+impl StakePoolAccounts {
+    pub fn new(address: Pubkey, program_id: Pubkey, stake_pool: &StakePool) -> Self {
+        Self {
+            address,
+            program_id,
+            withdraw_authority: get_stake_pool_withdraw_authority(&address, &program_id),
+            stake_deposit_authority: stake_pool.stake_deposit_authority,
+            sol_deposit_authority: stake_pool.sol_deposit_authority,
+            sol_withdraw_authority: stake_pool.sol_withdraw_authority,
+            validator_list: stake_pool.validator_list,
+            reserve_stake: stake_pool.reserve_stake,
+            pool_mint: stake_pool.pool_mint,
+            manager_fee_account: stake_pool.manager_fee_account,
+            token_program_id: stake_pool.token_program_id,
+            preferred_deposit_validator: stake_pool.preferred_deposit_validator_vote_address,
+            preferred_withdraw_validator: stake_pool.preferred_withdraw_validator_vote_address,
+        }
+    }
+
+    pub fn pool_token_account(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.pool_mint, &self.token_program_id)
+    }
+
+    pub fn validator_stake_account(&self, vote_account: &Pubkey, seed_suffix: u32) -> Pubkey {
+        let suffix = seed_suffix.to_le_bytes();
+        let mut seeds = vec![vote_account.as_ref(), self.address.as_ref()];
+        if seed_suffix != 0 {
+            seeds.push(&suffix);
+        }
+
+        Pubkey::find_program_address(&seeds, &self.program_id).0
+    }
+}
+
+pub fn get_stake_pool_withdraw_authority(stake_pool: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), b"withdraw"], program_id).0
+}
+
+pub fn get_stake_pool_deposit_authority(stake_pool: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), b"deposit"], program_id).0
+}
+
+// Mints pool tokens for SOL, which goes to the pool reserve.
+pub struct DepositSol {
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: StakePoolAccounts,
+    amount: u64,
+}
+
+impl DepositSol {
+    pub fn new(vault_id: u64, sub_account: u8, stake_pool: StakePoolAccounts, amount: u64) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            stake_pool,
+            amount,
+        }
+    }
+}
+
+impl ExternalInstruction for DepositSol {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        self.stake_pool.program_id
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        let mut ix_data = vec![14]; // 14 is the discriminator for depositing SOL.
+        ix_data.extend(self.amount.to_le_bytes());
+
+        ix_data
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
+        let pool_token_account = self.stake_pool.pool_token_account(&vault_pda);
+
+        vec![
+            AccountMeta::new(self.stake_pool.address, false), // stake pool
+            AccountMeta::new_readonly(self.stake_pool.withdraw_authority, false), // stake pool withdraw authority
+            AccountMeta::new(self.stake_pool.reserve_stake, false), // reserve stake account
+            AccountMeta::new(vault_pda, false),                     // depositor
+            AccountMeta::new(pool_token_account, false),            // user account
+            AccountMeta::new(self.stake_pool.manager_fee_account, false), // fee account
+            AccountMeta::new(pool_token_account, false),            // referral fee account
+            AccountMeta::new(self.stake_pool.pool_mint, false),     // token mint
+            AccountMeta::new_readonly(system_program::ID, false),   // system program
+            AccountMeta::new_readonly(self.stake_pool.token_program_id, false), // token program
+        ]
+    }
+
+    // The pool token account pins the pool, only its withdraw authority can mint the pool tokens.
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 1),
+            Operator::IngestAccount(3),
+            Operator::IngestAccount(4),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+// Burns pool tokens for SOL from the pool reserve, right away but with the pool's withdrawal fee.
+pub struct WithdrawSol {
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: StakePoolAccounts,
+    amount: u64,
+}
+
+impl WithdrawSol {
+    pub fn new(vault_id: u64, sub_account: u8, stake_pool: StakePoolAccounts, amount: u64) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            stake_pool,
+            amount,
+        }
+    }
+}
+
+impl ExternalInstruction for WithdrawSol {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        self.stake_pool.program_id
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        let mut ix_data = vec![16]; // 16 is the discriminator for withdrawing SOL.
+        ix_data.extend(self.amount.to_le_bytes());
+
+        ix_data
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
+        let pool_token_account = self.stake_pool.pool_token_account(&vault_pda);
+
+        vec![
+            AccountMeta::new(self.stake_pool.address, false), // stake pool
+            AccountMeta::new_readonly(self.stake_pool.withdraw_authority, false), // stake pool withdraw authority
+            AccountMeta::new_readonly(vault_pda, false), // user transfer authority
+            AccountMeta::new(pool_token_account, false), // pool tokens source
+            AccountMeta::new(self.stake_pool.reserve_stake, false), // reserve stake account
+            AccountMeta::new(vault_pda, false),          // lamports destination
+            AccountMeta::new(self.stake_pool.manager_fee_account, false), // fee account
+            AccountMeta::new(self.stake_pool.pool_mint, false), // token mint
+            AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false), // clock sysvar
+            AccountMeta::new_readonly(STAKE_HISTORY_SYSVAR_ID, false), // stake history sysvar
+            AccountMeta::new_readonly(STAKE_PROGRAM_ID, false), // stake program
+            AccountMeta::new_readonly(self.stake_pool.token_program_id, false), // token program
+        ]
+    }
+
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 1),
+            Operator::IngestAccount(2),
+            Operator::IngestAccount(3),
+            Operator::IngestAccount(5),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+// Mints pool tokens for an active stake account delegated to one of the pool's validators. The
+// stake account's staker and withdrawer must be moved to the pool's deposit authority first, see
+// `AuthorizeStake`.
+pub struct DepositStake {
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: StakePoolAccounts,
+    stake_account: Pubkey,
+    validator_stake_account: Pubkey,
+}
+
+impl DepositStake {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: StakePoolAccounts,
+        stake_account: Pubkey,
+        validator_stake_account: Pubkey,
+    ) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            stake_pool,
+            stake_account,
+            validator_stake_account,
+        }
+    }
+}
+
+impl ExternalInstruction for DepositStake {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        self.stake_pool.program_id
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        vec![9] // 9 is the discriminator for depositing stake.
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
+        let pool_token_account = self.stake_pool.pool_token_account(&vault_pda);
+
+        vec![
+            AccountMeta::new(self.stake_pool.address, false), // stake pool
+            AccountMeta::new(self.stake_pool.validator_list, false), // validator list
+            AccountMeta::new_readonly(self.stake_pool.stake_deposit_authority, false), // stake pool deposit authority
+            AccountMeta::new_readonly(self.stake_pool.withdraw_authority, false), // stake pool withdraw authority
+            AccountMeta::new(self.stake_account, false), // stake account to deposit
+            AccountMeta::new(self.validator_stake_account, false), // validator stake account
+            AccountMeta::new(self.stake_pool.reserve_stake, false), // reserve stake account
+            AccountMeta::new(pool_token_account, false), // user account
+            AccountMeta::new(self.stake_pool.manager_fee_account, false), // fee account
+            AccountMeta::new(pool_token_account, false), // referral fee account
+            AccountMeta::new(self.stake_pool.pool_mint, false), // token mint
+            AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false), // clock sysvar
+            AccountMeta::new_readonly(STAKE_HISTORY_SYSVAR_ID, false), // stake history sysvar
+            AccountMeta::new_readonly(self.stake_pool.token_program_id, false), // token program
+            AccountMeta::new_readonly(STAKE_PROGRAM_ID, false), // stake program
+        ]
+    }
+
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 1),
+            Operator::IngestAccount(7),
+            Operator::IngestAccount(9),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+// Burns pool tokens for an active stake split from one of the pool's validators, or from the
+// reserve, into an uninitialized stake account. The sub-account becomes its staker and withdrawer.
+pub struct WithdrawPoolStake {
+    vault_id: u64,
+    sub_account: u8,
+    stake_pool: StakePoolAccounts,
+    stake_to_split: Pubkey,
+    stake_to_receive: Pubkey,
+    amount: u64,
+}
+
+impl WithdrawPoolStake {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: StakePoolAccounts,
+        stake_to_split: Pubkey,
+        stake_to_receive: Pubkey,
+        amount: u64,
+    ) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            stake_pool,
+            stake_to_split,
+            stake_to_receive,
+            amount,
+        }
+    }
+}
+
+impl ExternalInstruction for WithdrawPoolStake {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        self.stake_pool.program_id
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        let mut ix_data = vec![10]; // 10 is the discriminator for withdrawing stake.
+        ix_data.extend(self.amount.to_le_bytes());
+
+        ix_data
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
+        let pool_token_account = self.stake_pool.pool_token_account(&vault_pda);
+
+        vec![
+            AccountMeta::new(self.stake_pool.address, false), // stake pool
+            AccountMeta::new(self.stake_pool.validator_list, false), // validator list
+            AccountMeta::new_readonly(self.stake_pool.withdraw_authority, false), // stake pool withdraw authority
+            AccountMeta::new(self.stake_to_split, false), // validator or reserve stake account
+            AccountMeta::new(self.stake_to_receive, false), // uninitialized stake account
+            AccountMeta::new_readonly(vault_pda, false),  // user stake authority
+            AccountMeta::new_readonly(vault_pda, false),  // user transfer authority
+            AccountMeta::new(pool_token_account, false),  // pool tokens source
+            AccountMeta::new(self.stake_pool.manager_fee_account, false), // fee account
+            AccountMeta::new(self.stake_pool.pool_mint, false), // token mint
+            AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false), // clock sysvar
+            AccountMeta::new_readonly(self.stake_pool.token_program_id, false), // token program
+            AccountMeta::new_readonly(STAKE_PROGRAM_ID, false), // stake program
+        ]
+    }
+
+    // The stake accounts change with every withdrawal, what's fixed is who ends up owning the stake.
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 1),
+            Operator::IngestAccount(5),
+            Operator::IngestAccount(6),
+            Operator::IngestAccount(7),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeAuthorize {
+    Staker,
+    Withdrawer,
+}
+
+// Hands the staker or withdrawer role of a stake account the sub-account holds to another
+// authority, e.g. a stake pool's deposit authority.
+pub struct AuthorizeStake {
+    vault_id: u64,
+    sub_account: u8,
+    stake_account: Pubkey,
+    new_authority: Pubkey,
+    stake_authorize: StakeAuthorize,
+}
+
+impl AuthorizeStake {
+    pub fn new(
+        vault_id: u64,
+        sub_account: u8,
+        stake_account: Pubkey,
+        new_authority: Pubkey,
+        stake_authorize: StakeAuthorize,
+    ) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            stake_account,
+            new_authority,
+            stake_authorize,
+        }
+    }
+}
+
+impl ExternalInstruction for AuthorizeStake {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        STAKE_PROGRAM_ID
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        let mut ix_data = vec![0x01, 0x00, 0x00, 0x00]; // Authorize instruction discriminator
+        ix_data.extend_from_slice(self.new_authority.as_ref());
+        ix_data.extend_from_slice(&(self.stake_authorize as u32).to_le_bytes());
+        ix_data
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
+
+        vec![
+            AccountMeta::new(self.stake_account, false), // stake account
+            AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false), // clock sysvar
+            AccountMeta::new_readonly(vault_pda, false), // current authority
+        ]
+    }
+
+    // The new authority is part of the data, so the digest only allows handing it to that one.
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 40),
+            Operator::IngestAccount(2),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+// Starts the cooldown of a stake account the sub-account is the staker of.
+pub struct DeactivateStake {
+    vault_id: u64,
+    sub_account: u8,
+    stake_account: Pubkey,
+}
+
+impl DeactivateStake {
+    pub fn new(vault_id: u64, sub_account: u8, stake_account: Pubkey) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            stake_account,
+        }
+    }
+}
+
+impl ExternalInstruction for DeactivateStake {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        STAKE_PROGRAM_ID
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        vec![0x05, 0x00, 0x00, 0x00] // Deactivate instruction discriminator
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
+
+        vec![
+            AccountMeta::new(self.stake_account, false), // stake account
+            AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false), // clock sysvar
+            AccountMeta::new_readonly(vault_pda, false), // stake authority
+        ]
+    }
+
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 4),
+            Operator::IngestAccount(2),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
+
+// Withdraws lamports of an inactive stake account back to the sub-account, its withdrawer.
+pub struct WithdrawStake {
+    vault_id: u64,
+    sub_account: u8,
+    stake_account: Pubkey,
+    lamports: u64,
+}
+
+impl WithdrawStake {
+    pub fn new(vault_id: u64, sub_account: u8, stake_account: Pubkey, lamports: u64) -> Self {
+        Self {
+            vault_id,
+            sub_account,
+            stake_account,
+            lamports,
+        }
+    }
+}
+
+impl ExternalInstruction for WithdrawStake {
+    impl_external_instruction_common!();
+
+    fn ix_program_id(&self) -> Pubkey {
+        STAKE_PROGRAM_ID
+    }
+
+    fn ix_data(&self) -> Vec<u8> {
+        let mut ix_data = vec![0x04, 0x00, 0x00, 0x00]; // Withdraw instruction discriminator
+        ix_data.extend_from_slice(&self.lamports.to_le_bytes());
+        ix_data
+    }
+
+    fn ix_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let vault_pda = pdas::get_vault_pda(self.vault_id, self.sub_account);
+
+        vec![
+            AccountMeta::new(self.stake_account, false), // stake account
+            AccountMeta::new(vault_pda, false),          // recipient
+            AccountMeta::new_readonly(CLOCK_SYSVAR_ID, false), // clock sysvar
+            AccountMeta::new_readonly(STAKE_HISTORY_SYSVAR_ID, false), // stake history sysvar
+            AccountMeta::new_readonly(vault_pda, false), // withdraw authority
+        ]
+    }
+
+    fn ix_operators(&self) -> Operators {
+        let operators = vec![
+            Operator::IngestInstruction(0, 4),
+            Operator::IngestAccount(1),
+            Operator::IngestAccount(4),
+            Operator::IngestInstructionDataSize,
+        ];
+
+        Operators { operators }
+    }
+}
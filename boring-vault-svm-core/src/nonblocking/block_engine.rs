@@ -0,0 +1,31 @@
+use eyre::Result;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_response::Response};
+
+use crate::utils::{get_bundle_statuses_request, send_bundle_request, BundleStatus};
+
+// `BlockEngineClient` on top of the nonblocking RPC client.
+pub struct BlockEngineClient {
+    client: RpcClient,
+}
+
+impl BlockEngineClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: RpcClient::new(url.into()),
+        }
+    }
+
+    pub async fn send_bundle(&self, transactions: &[String]) -> Result<String> {
+        let (request, params) = send_bundle_request(transactions);
+
+        Ok(self.client.send(request, params).await?)
+    }
+
+    pub async fn get_bundle_status(&self, bundle_id: &str) -> Result<Option<BundleStatus>> {
+        let (request, params) = get_bundle_statuses_request(&[bundle_id.to_string()]);
+        let response: Response<Vec<Option<BundleStatus>>> =
+            self.client.send(request, params).await?;
+
+        Ok(response.value.into_iter().next().flatten())
+    }
+}
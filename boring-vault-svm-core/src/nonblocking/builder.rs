@@ -0,0 +1,645 @@
+use std::{collections::HashMap, time::Instant};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::{eyre, Result};
+use solana_client::{nonblocking::rpc_client::RpcClient, nonce_utils::nonblocking as nonce_utils};
+use solana_instruction::Instruction;
+use solana_keypair::Keypair;
+use solana_message::{AddressLookupTableAccount, VersionedMessage};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    hash::Hash, nonce::state::Data as NonceData, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_signer::Signer;
+use solana_transaction_status_client_types::TransactionStatus;
+use tokio::time::sleep;
+
+use crate::{
+    builder::{
+        add_signer_if_keypair, compile_message, compute_budget_instructions,
+        confirmed_bundle_status, drain_instructions, get_compute_unit_limit, get_units_consumed,
+        get_writable_accounts, placeholder_compute_budget_instructions, plan_bundle,
+        plan_instruction_groups, preflight_logs, rebroadcast_send_config,
+        select_compute_unit_price, sign_message_with, split_instruction_groups, tip_instruction,
+        units_consumed_simulation_config, unsigned_transaction, NonceAccount, SendOutcome,
+        ADVANCE_NONCE_COMPUTE_UNITS, MAX_COMPUTE_UNIT_LIMIT,
+    },
+    config::{
+        BuilderConfig, BundleConfig, ComputeBudgetConfig, KaminoConfig, PriorityFeePolicy,
+        SendConfig, VaultConfig,
+    },
+    utils::{get_lookup_table_accounts, BoxedSigner, BundleStatus, KeypairSource},
+    view::get_user_lookup_table,
+    KeypairOrPublickey,
+};
+
+use super::{AccountSnapshot, BlockEngineClient};
+
+// `Builder` on top of the nonblocking RPC client. Planning, compute budget and the send pipeline
+// behave the same as the blocking builder.
+pub struct AsyncBuilder {
+    pub client: RpcClient,
+    pub instructions: Vec<Instruction>,
+    // Start index of each group of instructions that must land in the same transaction.
+    pub groups: Vec<usize>,
+    pub signers: HashMap<Pubkey, BoxedSigner>,
+    pub lookup_tables: Vec<Pubkey>,
+    pub compute_budget: Option<ComputeBudgetConfig>,
+    pub bundle_config: BundleConfig,
+    pub send_config: SendConfig,
+    pub durable_nonce: Option<NonceAccount>,
+    // Where `payer` loads the fee payer from when the caller doesn't pass one.
+    pub payer: Option<KeypairSource>,
+    // Accounts read while building the current plan, dropped by `clear`.
+    pub account_cache: AccountSnapshot,
+    // Ids taken by the deploys in the current plan, which aren't in the program config yet.
+    pub pending_vault_ids: Vec<u64>,
+    // Whether manage instructions check new CPI digests against the program first.
+    pub cpi_digest_check: bool,
+    pub kamino_config: KaminoConfig,
+    pub vault_config: VaultConfig,
+}
+
+impl Default for AsyncBuilder {
+    fn default() -> Self {
+        BuilderConfig::default().build_async()
+    }
+}
+
+impl AsyncBuilder {
+    pub fn clear(&mut self) -> Result<()> {
+        self.instructions.clear();
+        self.groups.clear();
+        // Keep the nonce authority, since the durable nonce outlives the instructions.
+        let nonce_authority = self.durable_nonce.map(|nonce| nonce.authority);
+        self.signers
+            .retain(|pubkey, _| Some(*pubkey) == nonce_authority);
+        self.pending_vault_ids.clear();
+        self.invalidate_account_cache();
+
+        Ok(())
+    }
+
+    // Sent transactions change the cached accounts, so this has to run between plans. `clear`
+    // already does.
+    pub fn invalidate_account_cache(&self) {
+        self.account_cache.invalidate();
+    }
+
+    // Marks the start of a group of instructions that the planner keeps in one transaction.
+    pub fn start_group(&mut self) {
+        let start = self.instructions.len();
+        if self.groups.last() != Some(&start) {
+            self.groups.push(start);
+        }
+    }
+
+    pub fn instruction_groups(&self) -> Vec<&[Instruction]> {
+        split_instruction_groups(&self.instructions, &self.groups)
+    }
+
+    // Removes the first `count` instructions, which must end on a group boundary.
+    pub fn drain_instructions(&mut self, count: usize) {
+        drain_instructions(&mut self.instructions, &mut self.groups, count);
+    }
+
+    pub fn add_signer_if_keypair(&mut self, potential_signer: KeypairOrPublickey) {
+        add_signer_if_keypair(&mut self.signers, potential_signer);
+    }
+
+    pub fn payer(&self) -> Result<Keypair> {
+        self.payer
+            .as_ref()
+            .ok_or_else(|| eyre!("No payer configured"))?
+            .resolve()
+    }
+
+    pub fn add_lookup_table(&mut self, address: Pubkey) {
+        if !self.lookup_tables.contains(&address) {
+            self.lookup_tables.push(address);
+        }
+    }
+
+    // Adds the lookup table created for the sub-account in `init_user_metadata`.
+    pub async fn load_vault_lookup_table(
+        &mut self,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<Pubkey> {
+        let lut = self
+            .read_accounts(|snapshot| get_user_lookup_table(snapshot, vault_id, sub_account))
+            .await?;
+        self.add_lookup_table(lut);
+
+        Ok(lut)
+    }
+
+    pub async fn fetch_lookup_table_accounts(&self) -> Result<Vec<AddressLookupTableAccount>> {
+        if self.lookup_tables.is_empty() {
+            return Ok(vec![]);
+        }
+
+        self.read_accounts(|snapshot| get_lookup_table_accounts(snapshot, &self.lookup_tables))
+            .await
+    }
+
+    pub fn use_durable_nonce(&mut self, address: Pubkey, authority: KeypairOrPublickey) {
+        self.durable_nonce = Some(NonceAccount {
+            address,
+            authority: authority.pubkey(),
+        });
+        self.add_signer_if_keypair(authority);
+    }
+
+    pub fn clear_durable_nonce(&mut self) {
+        self.durable_nonce = None;
+    }
+
+    pub async fn get_nonce_data(&self, address: &Pubkey) -> Result<NonceData> {
+        let account = nonce_utils::get_account(&self.client, address).await?;
+
+        Ok(nonce_utils::data_from_account(&account)?)
+    }
+
+    // Current nonce value when a durable nonce is in use, otherwise the latest blockhash.
+    pub async fn get_blockhash(&self) -> Result<Hash> {
+        match &self.durable_nonce {
+            Some(nonce) => nonce.blockhash(&self.get_nonce_data(&nonce.address).await?),
+            None => Ok(self.client.get_latest_blockhash().await?),
+        }
+    }
+
+    // Instructions prepended to every transaction, which the planner reserves room for.
+    pub fn transaction_prefix_instructions(&self) -> Vec<Instruction> {
+        self.durable_nonce
+            .map(|nonce| nonce.advance_instruction())
+            .into_iter()
+            .chain(placeholder_compute_budget_instructions(
+                self.compute_budget.as_ref(),
+            ))
+            .collect()
+    }
+
+    pub async fn apply_compute_budget(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        blockhash: Hash,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<Vec<Instruction>> {
+        let Some(config) = &self.compute_budget else {
+            return Ok(instructions.to_vec());
+        };
+
+        let mut simulated = placeholder_compute_budget_instructions(Some(config));
+        simulated.extend_from_slice(instructions);
+        let message = compile_message(&simulated, payer, blockhash, lookup_table_accounts)?;
+
+        let compute_unit_limit = match self.simulate_units_consumed(message).await? {
+            Some(units_consumed) => {
+                let advance_nonce_units = match self.durable_nonce {
+                    Some(_) => ADVANCE_NONCE_COMPUTE_UNITS,
+                    None => 0,
+                };
+                get_compute_unit_limit(units_consumed + advance_nonce_units, config)
+            }
+            None => MAX_COMPUTE_UNIT_LIMIT,
+        };
+        let compute_unit_price = self.get_compute_unit_price(config, instructions).await?;
+
+        let mut budgeted = compute_budget_instructions(compute_unit_limit, compute_unit_price);
+        budgeted.extend_from_slice(instructions);
+
+        Ok(budgeted)
+    }
+
+    pub async fn get_compute_unit_price(
+        &self,
+        config: &ComputeBudgetConfig,
+        instructions: &[Instruction],
+    ) -> Result<Option<u64>> {
+        let mut fees: Vec<u64> = match config.priority_fee {
+            PriorityFeePolicy::Percentile(_) => self
+                .client
+                .get_recent_prioritization_fees(&get_writable_accounts(instructions))
+                .await?
+                .iter()
+                .map(|fee| fee.prioritization_fee)
+                .collect(),
+            _ => vec![],
+        };
+
+        Ok(select_compute_unit_price(config, &mut fees))
+    }
+
+    async fn simulate_units_consumed(&self, message: VersionedMessage) -> Result<Option<u64>> {
+        let transaction = unsigned_transaction(message);
+        let result = self
+            .client
+            .simulate_transaction_with_config(&transaction, units_consumed_simulation_config())
+            .await?
+            .value;
+
+        get_units_consumed(&result, &transaction.message)
+    }
+
+    // Compiles the instructions into a message, prepending compute budget instructions if configured
+    // and advancing the durable nonce if one is in use.
+    pub async fn compile_transaction_message(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        blockhash: Hash,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<VersionedMessage> {
+        let mut instructions = self
+            .apply_compute_budget(instructions, payer, blockhash, lookup_table_accounts)
+            .await?;
+
+        // The advance has to be the first instruction, its compute units are budgeted above.
+        if let Some(nonce) = self.durable_nonce {
+            instructions.insert(0, nonce.advance_instruction());
+        }
+
+        compile_message(&instructions, payer, blockhash, lookup_table_accounts)
+    }
+
+    pub fn sign_message(&self, message: VersionedMessage) -> Result<VersionedTransaction> {
+        sign_message_with(message, &self.signers)
+    }
+
+    pub async fn compile_to_versioned_transaction_b64(
+        &self,
+        payer_pubkey: Pubkey,
+    ) -> Result<String> {
+        let blockhash = self.get_blockhash().await?;
+        let lookup_table_accounts = self.fetch_lookup_table_accounts().await?;
+        let message = self
+            .compile_transaction_message(
+                &self.instructions,
+                &payer_pubkey,
+                blockhash,
+                &lookup_table_accounts,
+            )
+            .await?;
+
+        let tx = self.sign_message(message)?;
+
+        Ok(STANDARD.encode(bincode::serialize(&tx)?))
+    }
+
+    pub async fn plan_transactions(&self, payer_pubkey: &Pubkey) -> Result<Vec<Vec<Instruction>>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts().await?;
+
+        plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            payer_pubkey,
+            &lookup_table_accounts,
+        )
+    }
+
+    pub async fn compile_plan_to_versioned_transactions_b64(
+        &self,
+        payer_pubkey: Pubkey,
+    ) -> Result<Vec<String>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts().await?;
+        let plan = plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            &payer_pubkey,
+            &lookup_table_accounts,
+        )?;
+        self.ensure_single_nonce_transaction(plan.len())?;
+        let blockhash = self.get_blockhash().await?;
+
+        let mut transactions = Vec::with_capacity(plan.len());
+        for instructions in &plan {
+            let message = self
+                .compile_transaction_message(
+                    instructions,
+                    &payer_pubkey,
+                    blockhash,
+                    &lookup_table_accounts,
+                )
+                .await?;
+            let tx = self.sign_message(message)?;
+
+            transactions.push(STANDARD.encode(bincode::serialize(&tx)?));
+        }
+
+        Ok(transactions)
+    }
+
+    // Every transaction compiled against a nonce advances it, so only one of them can land.
+    pub fn ensure_single_nonce_transaction(&self, transactions: usize) -> Result<()> {
+        if self.durable_nonce.is_some() && transactions > 1 {
+            return Err(eyre::eyre!(
+                "A durable nonce can only back a single transaction, plan has {}",
+                transactions
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Sends every instruction as a single transaction, clearing the builder only once it landed.
+    pub async fn try_send<S: Signer + Send + Sync + 'static>(
+        &mut self,
+        payer: S,
+    ) -> Result<SendOutcome> {
+        let payer_pubkey = payer.pubkey();
+        self.signers
+            .entry(payer_pubkey)
+            .or_insert_with(|| Box::new(payer));
+
+        let lookup_table_accounts = self.fetch_lookup_table_accounts().await?;
+        let outcome = self
+            .send_instructions(&self.instructions, &payer_pubkey, &lookup_table_accounts)
+            .await;
+
+        // A transaction that didn't land may still have changed accounts, so the plan is rebuilt
+        // from fresh reads.
+        match &outcome {
+            Ok(outcome) if outcome.is_landed() => self.clear()?,
+            _ => self.invalidate_account_cache(),
+        }
+
+        outcome
+    }
+
+    // Sends every planned transaction in order, stopping at the first one that doesn't land.
+    // Instructions of transactions that landed are dropped, so calling it again resumes the plan.
+    pub async fn try_send_all<S: Signer + Send + Sync + 'static>(
+        &mut self,
+        payer: S,
+    ) -> Result<Vec<String>> {
+        let payer_pubkey = payer.pubkey();
+        self.signers
+            .entry(payer_pubkey)
+            .or_insert_with(|| Box::new(payer));
+
+        let lookup_table_accounts = self.fetch_lookup_table_accounts().await?;
+        let plan = plan_instruction_groups(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            &payer_pubkey,
+            &lookup_table_accounts,
+        )?;
+
+        let mut signatures = vec![];
+        let mut landed_instructions = 0;
+        for (i, instructions) in plan.iter().enumerate() {
+            let outcome = self
+                .send_instructions(instructions, &payer_pubkey, &lookup_table_accounts)
+                .await
+                .and_then(|outcome| match outcome {
+                    SendOutcome::Landed { signature } => Ok(signature),
+                    // Keeps the decoded error reachable with `downcast_ref`.
+                    SendOutcome::Failed {
+                        decoded: Some(decoded),
+                        ..
+                    } => Err(eyre::Report::new(decoded)),
+                    outcome => Err(eyre::eyre!("{}", outcome)),
+                });
+
+            match outcome {
+                Ok(signature) => {
+                    signatures.push(signature.to_string());
+                    landed_instructions += instructions.len();
+                }
+                Err(e) => {
+                    self.drain_instructions(landed_instructions);
+                    self.invalidate_account_cache();
+
+                    let context = format!(
+                        "Transaction {} of {} failed after {} landed: {}",
+                        i + 1,
+                        plan.len(),
+                        signatures.len(),
+                        e
+                    );
+                    return Err(e.wrap_err(context));
+                }
+            }
+        }
+
+        self.clear()?;
+
+        Ok(signatures)
+    }
+
+    // Compiles, signs and sends the instructions. A fresh blockhash is only used once the
+    // previous one has expired without the transaction landing, so it can't land twice.
+    pub async fn send_instructions(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<SendOutcome> {
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            let (blockhash, last_valid_block_height) = self.get_blockhash_with_expiry().await?;
+            let message = self
+                .compile_transaction_message(instructions, payer, blockhash, lookup_table_accounts)
+                .await?;
+            let tx = self.sign_message(message)?;
+
+            let outcome = self
+                .send_until_expired(&tx, &blockhash, last_valid_block_height)
+                .await?;
+            if !matches!(outcome, SendOutcome::Expired { .. })
+                || attempt >= self.send_config.max_attempts
+            {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    // Sends a signed transaction and rebroadcasts it until it lands, fails or expires.
+    pub async fn send_until_expired(
+        &self,
+        tx: &VersionedTransaction,
+        blockhash: &Hash,
+        last_valid_block_height: u64,
+    ) -> Result<SendOutcome> {
+        let signature = tx.signatures[0];
+
+        // The first send runs preflight so failures surface before waiting on the blockhash.
+        if let Err(e) = self.client.send_transaction(tx).await {
+            return match e.get_transaction_error() {
+                Some(err) => Ok(SendOutcome::Failed {
+                    signature,
+                    err,
+                    decoded: None,
+                }
+                .decode_error(tx, &preflight_logs(&e))),
+                None => Err(e.into()),
+            };
+        }
+
+        let mut last_broadcast = Instant::now();
+
+        loop {
+            sleep(self.send_config.poll_interval).await;
+
+            // A status below the client's commitment means the transaction was processed, so it
+            // is waited on even past expiry instead of being signed again.
+            let status = self.get_signature_status(&signature).await?;
+            let processed = status.is_some();
+            if let Some(outcome) =
+                SendOutcome::from_status(&signature, status, self.client.commitment())
+            {
+                return Ok(outcome.decode_error(tx, &[]));
+            }
+
+            if !processed
+                && self
+                    .is_blockhash_expired(blockhash, last_valid_block_height)
+                    .await?
+            {
+                // It may have been processed between the status check and the expiry check.
+                if self.get_signature_status(&signature).await?.is_none() {
+                    return Ok(SendOutcome::Expired { signature });
+                }
+                continue;
+            }
+
+            if last_broadcast.elapsed() >= self.send_config.rebroadcast_interval {
+                // Errors are ignored here, the signature status is what decides the outcome.
+                let _ = self
+                    .client
+                    .send_transaction_with_config(tx, rebroadcast_send_config())
+                    .await;
+                last_broadcast = Instant::now();
+            }
+        }
+    }
+
+    // The status at any commitment, `None` while the cluster hasn't processed the signature.
+    pub async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionStatus>> {
+        Ok(self
+            .client
+            .get_signature_statuses_with_history(&[*signature])
+            .await?
+            .value
+            .into_iter()
+            .next()
+            .flatten())
+    }
+
+    // A durable nonce has no block height limit, so `u64::MAX` is returned for it.
+    pub async fn get_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        if self.durable_nonce.is_some() {
+            return Ok((self.get_blockhash().await?, u64::MAX));
+        }
+
+        Ok(self
+            .client
+            .get_latest_blockhash_with_commitment(self.client.commitment())
+            .await?)
+    }
+
+    // A durable nonce expires once it has been advanced.
+    pub async fn is_blockhash_expired(
+        &self,
+        blockhash: &Hash,
+        last_valid_block_height: u64,
+    ) -> Result<bool> {
+        if self.durable_nonce.is_some() {
+            return Ok(self.get_blockhash().await? != *blockhash);
+        }
+
+        Ok(self.client.get_block_height().await? > last_valid_block_height)
+    }
+
+    // Same as the blocking `compile_bundle_b64`.
+    pub async fn compile_bundle_b64(&self, payer_pubkey: Pubkey) -> Result<Vec<String>> {
+        let lookup_table_accounts = self.fetch_lookup_table_accounts().await?;
+        let plan = plan_bundle(
+            &self.instruction_groups(),
+            &self.transaction_prefix_instructions(),
+            tip_instruction(&self.bundle_config, &payer_pubkey),
+            &payer_pubkey,
+            &lookup_table_accounts,
+        )?;
+        self.ensure_single_nonce_transaction(plan.len())?;
+        let blockhash = self.get_blockhash().await?;
+
+        let mut transactions = Vec::with_capacity(plan.len());
+        for instructions in &plan {
+            let message = self
+                .compile_transaction_message(
+                    instructions,
+                    &payer_pubkey,
+                    blockhash,
+                    &lookup_table_accounts,
+                )
+                .await?;
+            let tx = self.sign_message(message)?;
+
+            transactions.push(STANDARD.encode(bincode::serialize(&tx)?));
+        }
+
+        Ok(transactions)
+    }
+
+    // Submits the plan as a Jito bundle and waits for it to confirm, returning the bundle id.
+    pub async fn try_bundle_all<S: Signer + Send + Sync + 'static>(
+        &mut self,
+        payer: S,
+    ) -> Result<String> {
+        let payer_pubkey = payer.pubkey();
+        self.signers
+            .entry(payer_pubkey)
+            .or_insert_with(|| Box::new(payer));
+
+        let block_engine = BlockEngineClient::new(self.bundle_config.block_engine_url()?);
+        let transactions = self.compile_bundle_b64(payer_pubkey).await?;
+        let bundle_id = block_engine.send_bundle(&transactions).await?;
+
+        if let Err(e) = self
+            .confirm_bundle(&block_engine, &bundle_id, &transactions)
+            .await
+        {
+            self.invalidate_account_cache();
+            return Err(e);
+        }
+
+        self.clear()?;
+
+        Ok(bundle_id)
+    }
+
+    pub async fn confirm_bundle(
+        &self,
+        block_engine: &BlockEngineClient,
+        bundle_id: &str,
+        transactions: &[String],
+    ) -> Result<BundleStatus> {
+        let start = Instant::now();
+
+        loop {
+            let status = block_engine.get_bundle_status(bundle_id).await?;
+            if let Some(status) = confirmed_bundle_status(bundle_id, status, transactions)? {
+                return Ok(status);
+            }
+
+            if start.elapsed() >= self.bundle_config.confirm_timeout {
+                return Err(eyre!(
+                    "Bundle {} not confirmed after {:?}",
+                    bundle_id,
+                    self.bundle_config.confirm_timeout
+                ));
+            }
+            sleep(self.bundle_config.poll_interval).await;
+        }
+    }
+}
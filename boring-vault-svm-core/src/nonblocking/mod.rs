@@ -0,0 +1,14 @@
+// Async counterparts of the RPC backed helpers and `Builder`, on top of the nonblocking
+// `RpcClient`. Instruction building itself is shared with the blocking code.
+pub mod block_engine;
+pub mod builder;
+pub mod snapshot;
+pub mod transaction;
+pub mod utils;
+pub mod view;
+
+pub use block_engine::*;
+pub use builder::*;
+pub use snapshot::*;
+pub use utils::*;
+pub use view::*;
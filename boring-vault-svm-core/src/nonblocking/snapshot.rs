@@ -0,0 +1,407 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use eyre::{eyre, Result};
+use solana_account::Account;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{hash::Hash, signature::Signature, transaction::VersionedTransaction};
+use solana_signer::null_signer::NullSigner;
+
+use crate::{
+    builder::Builder,
+    utils::{AccountCache, AccountSource},
+    KeypairOrPublickey,
+};
+
+use super::{get_multiple_accounts_chunked, AsyncBuilder};
+
+// Chain state fetched ahead of time, so the blocking instruction builders can run on top of the
+// nonblocking client. Reads of anything not fetched yet are recorded as misses, for
+// `fetch_misses` to load before the builder runs again.
+#[derive(Debug, Clone, Default)]
+pub struct AccountSnapshot {
+    state: Arc<Mutex<SnapshotState>>,
+}
+
+// Program id and data prefix of a `get_program_accounts` call.
+type ProgramAccountsQuery = (Pubkey, Vec<u8>);
+
+// A simulation is keyed by the serialized transaction, which the builders only simulate with one
+// config each, e.g. the CPI digest check's `view_cpi_digest`.
+type SimulationQuery = (Vec<u8>, VersionedTransaction, RpcSimulateTransactionConfig);
+
+#[derive(Debug, Default)]
+struct SnapshotState {
+    accounts: HashMap<Pubkey, Option<Account>>,
+    rent: HashMap<usize, u64>,
+    slot: Option<u64>,
+    program_accounts: HashMap<ProgramAccountsQuery, Vec<(Pubkey, Account)>>,
+    simulations: HashMap<Vec<u8>, RpcSimulateTransactionResult>,
+    misses: SnapshotMisses,
+}
+
+#[derive(Debug, Default)]
+struct SnapshotMisses {
+    accounts: Vec<Pubkey>,
+    rent: Vec<usize>,
+    slot: bool,
+    program_accounts: Vec<ProgramAccountsQuery>,
+    simulations: Vec<SimulationQuery>,
+}
+
+impl SnapshotMisses {
+    fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.rent.is_empty()
+            && !self.slot
+            && self.program_accounts.is_empty()
+            && self.simulations.is_empty()
+    }
+}
+
+impl AccountSnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Drops everything fetched, so the next reads go to the chain again.
+    pub fn invalidate(&self) {
+        *self.state.lock().unwrap() = SnapshotState::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.lock().unwrap().accounts.is_empty()
+    }
+
+    // Loads everything read since the last call that wasn't in the snapshot. Returns false when
+    // nothing was missing.
+    pub async fn fetch_misses(&self, client: &RpcClient) -> Result<bool> {
+        let misses = std::mem::take(&mut self.state.lock().unwrap().misses);
+        if misses.is_empty() {
+            return Ok(false);
+        }
+
+        let accounts = get_multiple_accounts_chunked(client, &misses.accounts).await?;
+        let mut rent = vec![];
+        for data_len in &misses.rent {
+            rent.push(
+                client
+                    .get_minimum_balance_for_rent_exemption(*data_len)
+                    .await?,
+            );
+        }
+        let slot = match misses.slot {
+            true => Some(client.get_slot().await?),
+            false => None,
+        };
+        let mut program_accounts = vec![];
+        for (program_id, data_prefix) in &misses.program_accounts {
+            program_accounts.push(get_program_accounts(client, program_id, data_prefix).await?);
+        }
+        let mut simulations = vec![];
+        for (key, transaction, config) in misses.simulations {
+            let result = client
+                .simulate_transaction_with_config(&transaction, config)
+                .await?
+                .value;
+            simulations.push((key, result));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state
+            .accounts
+            .extend(misses.accounts.into_iter().zip(accounts));
+        state.rent.extend(misses.rent.into_iter().zip(rent));
+        if slot.is_some() {
+            state.slot = slot;
+        }
+        state
+            .program_accounts
+            .extend(misses.program_accounts.into_iter().zip(program_accounts));
+        state.simulations.extend(simulations);
+
+        Ok(true)
+    }
+
+    fn lookup_accounts(&self, addresses: &[Pubkey]) -> Vec<Option<Account>> {
+        let mut state = self.state.lock().unwrap();
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            match state.accounts.get(address) {
+                Some(account) => accounts.push(account.clone()),
+                None => {
+                    if !state.misses.accounts.contains(address) {
+                        state.misses.accounts.push(*address);
+                    }
+                    accounts.push(None);
+                }
+            }
+        }
+
+        accounts
+    }
+}
+
+impl AccountSource for AccountSnapshot {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        self.lookup_accounts(&[*address])
+            .pop()
+            .flatten()
+            .ok_or_else(|| eyre!("Account {} not found", address))
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(self.lookup_accounts(addresses))
+    }
+
+    fn prefetch_accounts(&self, addresses: &[Pubkey]) -> Result<()> {
+        self.lookup_accounts(addresses);
+        Ok(())
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        let mut state = self.state.lock().unwrap();
+        if state.slot.is_none() {
+            state.misses.slot = true;
+        }
+
+        state.slot.ok_or_else(|| eyre!("Slot not fetched yet"))
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Err(eyre!("Account snapshot does not provide blockhashes"))
+    }
+
+    fn send_transaction(&self, _transaction: &VersionedTransaction) -> Result<Signature> {
+        Err(eyre!("Account snapshot can't send transactions"))
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(lamports) = state.rent.get(&data_len) {
+            return Ok(*lamports);
+        }
+        if !state.misses.rent.contains(&data_len) {
+            state.misses.rent.push(data_len);
+        }
+
+        Err(eyre!("Rent for {} bytes not fetched yet", data_len))
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let key = (*program_id, data_prefix.to_vec());
+        let mut state = self.state.lock().unwrap();
+        if let Some(accounts) = state.program_accounts.get(&key) {
+            return Ok(accounts.clone());
+        }
+        if !state.misses.program_accounts.contains(&key) {
+            state.misses.program_accounts.push(key);
+        }
+
+        Err(eyre!("Accounts of program {} not fetched yet", program_id))
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        let key = bincode::serialize(transaction)?;
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = state.simulations.get(&key) {
+            return Ok(result.clone());
+        }
+        if !state
+            .misses
+            .simulations
+            .iter()
+            .any(|(missed, _, _)| *missed == key)
+        {
+            state
+                .misses
+                .simulations
+                .push((key, transaction.clone(), config));
+        }
+
+        Err(eyre!("Simulation not run yet"))
+    }
+}
+
+// Runs `read` on a snapshot of chain state, fetching whatever it was missing and running it again
+// until it only read fetched accounts.
+pub async fn read_accounts<T>(
+    client: &RpcClient,
+    read: impl Fn(&AccountSnapshot) -> Result<T>,
+) -> Result<T> {
+    read_accounts_with(client, &AccountSnapshot::new(), read).await
+}
+
+// Same as `read_accounts` on top of what `snapshot` already holds.
+pub async fn read_accounts_with<T>(
+    client: &RpcClient,
+    snapshot: &AccountSnapshot,
+    read: impl Fn(&AccountSnapshot) -> Result<T>,
+) -> Result<T> {
+    loop {
+        let result = read(snapshot);
+        if !snapshot.fetch_misses(client).await? {
+            return result;
+        }
+    }
+}
+
+impl AsyncBuilder {
+    pub async fn read_accounts<T>(
+        &self,
+        read: impl Fn(&AccountSnapshot) -> Result<T>,
+    ) -> Result<T> {
+        read_accounts(&self.client, read).await
+    }
+
+    // Runs a read-only blocking `Builder` method, e.g. a view, on a fresh snapshot.
+    pub async fn read_builder<T>(
+        &self,
+        read: impl Fn(&Builder<AccountSnapshot>) -> Result<T>,
+    ) -> Result<T> {
+        self.read_accounts(|snapshot| read(&self.snapshot_builder(snapshot.clone())))
+            .await
+    }
+
+    // Runs a blocking `Builder` method against this builder's plan, so both builders share the
+    // instruction building. `build` gets placeholders for `signer` and `authority` that only
+    // carry whether they can sign, the real ones are added once it succeeds.
+    pub async fn with_builder<T>(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        build: impl Fn(
+            &mut Builder<AccountSnapshot>,
+            KeypairOrPublickey,
+            Option<KeypairOrPublickey>,
+        ) -> Result<T>,
+    ) -> Result<T> {
+        let signer_key = placeholder_key(&signer);
+        let authority_key = authority.as_ref().map(placeholder_key);
+        let signers = std::iter::once(signer).chain(authority).collect();
+
+        self.build_on_snapshot(signers, |builder| {
+            build(
+                builder,
+                placeholder(signer_key),
+                authority_key.map(placeholder),
+            )
+        })
+        .await
+    }
+
+    // Same as `with_builder` for methods taking any other set of signers.
+    pub async fn with_signers<T, const N: usize>(
+        &mut self,
+        signers: [KeypairOrPublickey; N],
+        build: impl Fn(&mut Builder<AccountSnapshot>, [KeypairOrPublickey; N]) -> Result<T>,
+    ) -> Result<T> {
+        let keys = signers.each_ref().map(placeholder_key);
+
+        self.build_on_snapshot(Vec::from(signers), |builder| {
+            build(builder, keys.map(placeholder))
+        })
+        .await
+    }
+
+    async fn build_on_snapshot<T>(
+        &mut self,
+        signers: Vec<KeypairOrPublickey>,
+        build: impl Fn(&mut Builder<AccountSnapshot>) -> Result<T>,
+    ) -> Result<T> {
+        let (result, mut builder) =
+            read_accounts_with(&self.client, &self.account_cache, |snapshot| {
+                let mut builder = self.snapshot_builder(snapshot.clone());
+                let result = build(&mut builder)?;
+                Ok((result, builder))
+            })
+            .await?;
+
+        self.instructions = builder.instructions;
+        self.groups = builder.groups;
+        self.lookup_tables = builder.lookup_tables;
+        self.pending_vault_ids = builder.pending_vault_ids;
+        // Keep the keypairs the builder generated itself, not the placeholders.
+        builder
+            .signers
+            .retain(|pubkey, _| !signers.iter().any(|signer| signer.pubkey() == *pubkey));
+        self.signers.extend(builder.signers);
+        for signer in signers {
+            self.add_signer_if_keypair(signer);
+        }
+
+        Ok(result)
+    }
+
+    fn snapshot_builder(&self, snapshot: AccountSnapshot) -> Builder<AccountSnapshot> {
+        Builder {
+            client: snapshot,
+            instructions: self.instructions.clone(),
+            groups: self.groups.clone(),
+            signers: HashMap::new(),
+            lookup_tables: self.lookup_tables.clone(),
+            compute_budget: None,
+            bundle_config: self.bundle_config.clone(),
+            send_config: self.send_config.clone(),
+            durable_nonce: self.durable_nonce,
+            payer: None,
+            account_cache: AccountCache::new(),
+            pending_vault_ids: self.pending_vault_ids.clone(),
+            cpi_digest_check: self.cpi_digest_check,
+            kamino_config: self.kamino_config.clone(),
+            vault_config: self.vault_config.clone(),
+        }
+    }
+}
+
+// The pubkey of a signer and whether it can sign, which is all a placeholder keeps.
+fn placeholder_key(signer: &KeypairOrPublickey) -> (Pubkey, bool) {
+    (signer.pubkey(), signer.can_sign())
+}
+
+fn placeholder((pubkey, can_sign): (Pubkey, bool)) -> KeypairOrPublickey {
+    match can_sign {
+        true => KeypairOrPublickey::from_signer(NullSigner::new(&pubkey)),
+        false => KeypairOrPublickey::Publickey(pubkey),
+    }
+}
+
+async fn get_program_accounts(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    data_prefix: &[u8],
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            data_prefix,
+        ))]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+
+    Ok(client
+        .get_program_accounts_with_config(program_id, config)
+        .await?)
+}
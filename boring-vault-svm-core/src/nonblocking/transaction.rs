@@ -0,0 +1,921 @@
+use eyre::Result;
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    config::{JupiterSwapInstructions, StrategyManifest},
+    transaction::boring_vault::CpiDigestProvision,
+    utils::{boring_vault_svm::types::Operators, VaultPdas},
+    view::{ObligationPosition, PendingStakeAccount},
+    KeypairOrPublickey,
+};
+
+use super::AsyncBuilder;
+
+// Every operation is built by the blocking `Builder` on a snapshot of the accounts it reads, see
+// `with_builder`.
+impl AsyncBuilder {
+    pub async fn initialize(
+        &mut self,
+        authority: Pubkey,
+        signer: KeypairOrPublickey,
+        program_signer: KeypairOrPublickey,
+    ) -> Result<()> {
+        self.with_signers(
+            [signer, program_signer],
+            |builder, [signer, program_signer]| {
+                builder.initialize(authority, signer, program_signer)
+            },
+        )
+        .await
+    }
+
+    // Deploys as `vault_id`, or the id after the last deploy in this plan when `None`, and returns
+    // the vault's addresses so it can be configured in the same plan.
+    pub async fn deploy(
+        &mut self,
+        authority: Pubkey,
+        signer: KeypairOrPublickey,
+        vault_id: Option<u64>,
+        base_asset: Pubkey,
+        name: String,
+        symbol: String,
+        exchange_rate_provider: Option<Pubkey>,
+        exchange_rate: u64,
+        payout_address: Option<Pubkey>,
+        allowed_exchange_rate_change_upper_bound: u16,
+        allowed_exchange_rate_change_lower_bound: u16,
+        minimum_update_delay_in_seconds: u32,
+        platform_fee_bps: Option<u16>,
+        performance_fee_bps: Option<u16>,
+        withdraw_authority: Option<Pubkey>,
+        strategist: Option<Pubkey>,
+    ) -> Result<VaultPdas> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.deploy(
+                authority,
+                signer,
+                vault_id,
+                base_asset,
+                name.clone(),
+                symbol.clone(),
+                exchange_rate_provider,
+                exchange_rate,
+                payout_address,
+                allowed_exchange_rate_change_upper_bound,
+                allowed_exchange_rate_change_lower_bound,
+                minimum_update_delay_in_seconds,
+                platform_fee_bps,
+                performance_fee_bps,
+                withdraw_authority,
+                strategist,
+            )
+        })
+        .await
+    }
+
+    // Id the next deploy gets, counting the deploys already in this plan.
+    pub async fn next_vault_id(&self) -> Result<u64> {
+        self.read_builder(|builder| builder.next_vault_id()).await
+    }
+
+    pub async fn update_asset_data(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        mint: Pubkey,
+        allow_deposits: bool,
+        allow_withdrawals: bool,
+        share_premium_bps: u16,
+        is_pegged_to_base_asset: bool,
+        price_feed: Pubkey,
+        inverse_price_feed: bool,
+        max_staleness: u64,
+        min_samples: u32,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.update_asset_data(
+                signer,
+                vault_id,
+                mint,
+                allow_deposits,
+                allow_withdrawals,
+                share_premium_bps,
+                is_pegged_to_base_asset,
+                price_feed,
+                inverse_price_feed,
+                max_staleness,
+                min_samples,
+            )
+        })
+        .await
+    }
+
+    pub async fn pause(&mut self, signer: KeypairOrPublickey, vault_id: u64) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.pause(signer, vault_id)
+        })
+        .await
+    }
+
+    pub async fn unpause(&mut self, signer: KeypairOrPublickey, vault_id: u64) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.unpause(signer, vault_id)
+        })
+        .await
+    }
+
+    pub async fn accept_authority(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.accept_authority(signer, vault_id)
+        })
+        .await
+    }
+
+    pub async fn transfer_authority(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        pending_authority: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.transfer_authority(signer, vault_id, pending_authority)
+        })
+        .await
+    }
+
+    pub async fn close_cpi_digest(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        digest: [u8; 32],
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.close_cpi_digest(signer, vault_id, digest)
+        })
+        .await
+    }
+
+    pub async fn update_exchange_rate_provider(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        new_provider: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.update_exchange_rate_provider(signer, vault_id, new_provider)
+        })
+        .await
+    }
+
+    pub async fn set_withdraw_authority(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.set_withdraw_authority(signer, vault_id, new_authority)
+        })
+        .await
+    }
+
+    pub async fn set_payout(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        new_payout: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.set_payout(signer, vault_id, new_payout)
+        })
+        .await
+    }
+
+    pub async fn configure_exchange_rate_update_bounds(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        upper_bound: u16,
+        lower_bound: u16,
+        minimum_update_delay: u32,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.configure_exchange_rate_update_bounds(
+                signer,
+                vault_id,
+                upper_bound,
+                lower_bound,
+                minimum_update_delay,
+            )
+        })
+        .await
+    }
+
+    pub async fn set_fees(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        platform_fee_bps: u16,
+        performance_fee_bps: u16,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.set_fees(signer, vault_id, platform_fee_bps, performance_fee_bps)
+        })
+        .await
+    }
+
+    pub async fn set_strategist(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        new_strategist: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.set_strategist(signer, vault_id, new_strategist)
+        })
+        .await
+    }
+
+    pub async fn claim_fees_in_base(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.claim_fees_in_base(signer, vault_id, sub_account)
+        })
+        .await
+    }
+
+    pub async fn deposit_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        deposit_amount: u64,
+        min_mint_amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.deposit_sol(signer, vault_id, deposit_amount, min_mint_amount)
+        })
+        .await
+    }
+
+    pub async fn deposit(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        deposit_mint: Pubkey,
+        deposit_amount: u64,
+        min_mint_amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.deposit(
+                signer,
+                vault_id,
+                deposit_mint,
+                deposit_amount,
+                min_mint_amount,
+            )
+        })
+        .await
+    }
+
+    pub async fn withdraw(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        withdraw_mint: Pubkey,
+        share_amount: u64,
+        min_asset_amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.withdraw(
+                signer,
+                vault_id,
+                withdraw_mint,
+                share_amount,
+                min_asset_amount,
+            )
+        })
+        .await
+    }
+
+    pub async fn update_exchange_rate(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        new_exchange_rate: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.update_exchange_rate(signer, vault_id, new_exchange_rate)
+        })
+        .await
+    }
+
+    pub async fn transfer_sol_between_sub_accounts(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        to_sub_account: u8,
+        amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.transfer_sol_between_sub_accounts(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                to_sub_account,
+                amount,
+            )
+        })
+        .await
+    }
+
+    pub async fn set_deposit_sub_account(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        new_sub_account: u8,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.set_deposit_sub_account(signer, vault_id, new_sub_account)
+        })
+        .await
+    }
+
+    pub async fn set_withdraw_sub_account(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        new_sub_account: u8,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.set_withdraw_sub_account(signer, vault_id, new_sub_account)
+        })
+        .await
+    }
+
+    pub async fn wrap_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.wrap_sol(signer, authority, vault_id, sub_account, amount)
+        })
+        .await
+    }
+
+    pub async fn unwrap_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.unwrap_sol(signer, authority, vault_id, sub_account)
+        })
+        .await
+    }
+
+    pub async fn create_nonce_account(
+        &mut self,
+        payer: KeypairOrPublickey,
+        nonce_account: KeypairOrPublickey,
+        authority: Pubkey,
+    ) -> Result<()> {
+        self.with_signers([payer, nonce_account], |builder, [payer, nonce_account]| {
+            builder.create_nonce_account(payer, nonce_account, authority)
+        })
+        .await
+    }
+
+    pub async fn mint_jito_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.mint_jito_sol(signer, authority, vault_id, sub_account, amount)
+        })
+        .await
+    }
+
+    pub async fn init_user_metadata(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.init_user_metadata(signer, authority, vault_id, sub_account)
+        })
+        .await
+    }
+
+    pub async fn init_obligation(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        tag: u8,
+        id: u8,
+        market: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.init_obligation(signer, authority, vault_id, sub_account, tag, id, market)
+        })
+        .await
+    }
+
+    pub async fn init_obligation_farms_for_reserve(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        tag: Option<u8>,
+        id: Option<u8>,
+        mode: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.init_obligation_farms_for_reserve(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                tag,
+                id,
+                mode,
+                reserve,
+            )
+        })
+        .await
+    }
+
+    pub async fn refresh_reserve(
+        &mut self,
+        vault_id: u64,
+        sub_account: u8,
+        reserve: Pubkey,
+    ) -> Result<()> {
+        self.with_signers([], |builder, []| {
+            builder.refresh_reserve(vault_id, sub_account, reserve)
+        })
+        .await
+    }
+
+    pub async fn refresh_reserves(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        reserves: Option<&[&str]>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.refresh_reserves(signer, authority, vault_id, sub_account, reserves)
+        })
+        .await
+    }
+
+    pub async fn refresh_obligation(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        tag: u8,
+        id: u8,
+        market: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.refresh_obligation(signer, authority, vault_id, sub_account, tag, id, market)
+        })
+        .await
+    }
+
+    pub async fn refresh_obligation_farms_for_reserve(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        tag: u8,
+        id: u8,
+        mode: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.refresh_obligation_farms_for_reserve(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                tag,
+                id,
+                mode,
+                reserve,
+            )
+        })
+        .await
+    }
+
+    pub async fn refresh_price_list(
+        &mut self,
+        signer: KeypairOrPublickey,
+        vault_id: u64,
+        sub_account: u8,
+        market: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, None, |builder, signer, _| {
+            builder.refresh_price_list(signer, vault_id, sub_account, market)
+        })
+        .await
+    }
+
+    pub async fn refresh_kamino_accounts(
+        &mut self,
+        vault_id: u64,
+        sub_account: u8,
+        tag: u8,
+        id: u8,
+        reserves: Option<&[&str]>,
+    ) -> Result<()> {
+        self.with_signers([], |builder, []| {
+            builder.refresh_kamino_accounts(vault_id, sub_account, tag, id, reserves)
+        })
+        .await
+    }
+
+    pub async fn lend(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+        tag: u8,
+        id: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.lend(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                amount,
+                tag,
+                id,
+                reserve,
+            )
+        })
+        .await
+    }
+
+    pub async fn borrow(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+        tag: u8,
+        id: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.borrow(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                amount,
+                tag,
+                id,
+                reserve,
+            )
+        })
+        .await
+    }
+
+    pub async fn repay(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: Option<u64>,
+        tag: u8,
+        id: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.repay(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                amount,
+                tag,
+                id,
+                reserve,
+            )
+        })
+        .await
+    }
+
+    pub async fn withdraw_lend(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: Option<u64>,
+        tag: u8,
+        id: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.withdraw_lend(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                amount,
+                tag,
+                id,
+                reserve,
+            )
+        })
+        .await
+    }
+
+    pub async fn get_kamino_position(
+        &self,
+        vault_id: u64,
+        sub_account: u8,
+        tag: u8,
+        id: u8,
+        market: Option<&str>,
+    ) -> Result<ObligationPosition> {
+        self.read_builder(|builder| {
+            builder.get_kamino_position(vault_id, sub_account, tag, id, market)
+        })
+        .await
+    }
+
+    pub async fn swap(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        swap: &JupiterSwapInstructions,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.swap(signer, authority, vault_id, sub_account, swap)
+        })
+        .await
+    }
+
+    pub async fn stake_pool_deposit_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.stake_pool_deposit_sol(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                stake_pool,
+                amount,
+            )
+        })
+        .await
+    }
+
+    pub async fn stake_pool_withdraw_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.stake_pool_withdraw_sol(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                stake_pool,
+                amount,
+            )
+        })
+        .await
+    }
+
+    pub async fn stake_pool_deposit_stake(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: Pubkey,
+        stake_account: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.stake_pool_deposit_stake(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                stake_pool,
+                stake_account,
+            )
+        })
+        .await
+    }
+
+    // Instant exit through the pool reserve.
+    pub async fn withdraw_jito_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.withdraw_jito_sol(signer, authority, vault_id, sub_account, amount)
+        })
+        .await
+    }
+
+    // Fee-free exit into a deactivating stake account of the sub-account, which is returned.
+    pub async fn unstake_jito_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> Result<Pubkey> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.unstake_jito_sol(signer, authority, vault_id, sub_account, amount)
+        })
+        .await
+    }
+
+    pub async fn deactivate_stake(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_account: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.deactivate_stake(signer, authority, vault_id, sub_account, stake_account)
+        })
+        .await
+    }
+
+    pub async fn withdraw_stake(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_account: Pubkey,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.withdraw_stake(signer, authority, vault_id, sub_account, stake_account)
+        })
+        .await
+    }
+
+    // Withdraws every unstake account of the sub-account that finished cooling down.
+    pub async fn withdraw_unstaked_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<Vec<Pubkey>> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.withdraw_unstaked_sol(signer, authority, vault_id, sub_account)
+        })
+        .await
+    }
+
+    pub async fn get_pending_stake_accounts(
+        &self,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<Vec<PendingStakeAccount>> {
+        self.read_builder(|builder| builder.get_pending_stake_accounts(vault_id, sub_account))
+            .await
+    }
+
+    pub async fn deposit_solend(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        deposit_mint: Pubkey,
+        reserve_collateral_mint: Pubkey,
+        lending_market: Pubkey,
+        reserve: Pubkey,
+        reserve_liquidity_supply_spl_token_account: Pubkey,
+        lending_market_authority: Pubkey,
+        destination_deposit_reserve_collateral_supply_spl_token_account: Pubkey,
+        pyth_price_oracle: Pubkey,
+        switchboard_price_oracle: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.deposit_solend(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                deposit_mint,
+                reserve_collateral_mint,
+                lending_market,
+                reserve,
+                reserve_liquidity_supply_spl_token_account,
+                lending_market_authority,
+                destination_deposit_reserve_collateral_supply_spl_token_account,
+                pyth_price_oracle,
+                switchboard_price_oracle,
+                amount,
+            )
+        })
+        .await
+    }
+
+    // Manages any instruction built elsewhere, e.g. by another protocol's SDK.
+    pub async fn manage_raw_instruction(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        instruction: Instruction,
+        operators: Operators,
+    ) -> Result<()> {
+        self.with_builder(signer, authority, |builder, signer, authority| {
+            builder.manage_raw_instruction(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                instruction.clone(),
+                operators.clone(),
+            )
+        })
+        .await
+    }
+
+    // Initializes the CPI digest of every operation in the manifest that isn't on chain yet.
+    pub async fn provision_cpi_digests(
+        &mut self,
+        authority: KeypairOrPublickey,
+        manifest: &StrategyManifest,
+    ) -> Result<Vec<CpiDigestProvision>> {
+        self.with_builder(authority, None, |builder, authority, _| {
+            builder.provision_cpi_digests(authority, manifest)
+        })
+        .await
+    }
+}
@@ -0,0 +1,17 @@
+use eyre::Result;
+use solana_account::Account;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::MAX_MULTIPLE_ACCOUNTS};
+use solana_pubkey::Pubkey;
+
+// `get_multiple_accounts` split into requests the RPC accepts.
+pub async fn get_multiple_accounts_chunked(
+    client: &RpcClient,
+    addresses: &[Pubkey],
+) -> Result<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(client.get_multiple_accounts(chunk).await?);
+    }
+
+    Ok(accounts)
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use eyre::Result;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_pubkey::Pubkey;
+
+use crate::{
+    config::VaultConfig,
+    utils::boring_vault_svm::accounts::{AssetData, BoringVault},
+    view::{self, ObligationPosition},
+};
+
+use super::read_accounts;
+
+pub async fn get_asset_data(
+    client: &RpcClient,
+    vault_id: u64,
+    mint: Pubkey,
+) -> Result<(Pubkey, AssetData)> {
+    read_accounts(client, |snapshot| {
+        view::get_asset_data(snapshot, vault_id, mint)
+    })
+    .await
+}
+
+pub async fn get_vault_state(client: &RpcClient, vault_id: u64) -> Result<(Pubkey, BoringVault)> {
+    read_accounts(client, |snapshot| view::get_vault_state(snapshot, vault_id)).await
+}
+
+pub async fn get_sub_account_token_totals(
+    client: &RpcClient,
+    vault_config: &VaultConfig,
+) -> Result<HashMap<Pubkey, u64>> {
+    read_accounts(client, |snapshot| {
+        view::get_sub_account_token_totals(snapshot, vault_config)
+    })
+    .await
+}
+
+pub async fn get_user_lookup_table(
+    client: &RpcClient,
+    vault_id: u64,
+    sub_account: u8,
+) -> Result<Pubkey> {
+    read_accounts(client, |snapshot| {
+        view::get_user_lookup_table(snapshot, vault_id, sub_account)
+    })
+    .await
+}
+
+// Empty when the obligation doesn't exist yet.
+pub async fn get_obligation_position(
+    client: &RpcClient,
+    vault_id: u64,
+    sub_account: u8,
+    lending_market: &Pubkey,
+    tag: u8,
+    id: u8,
+) -> Result<ObligationPosition> {
+    read_accounts(client, |snapshot| {
+        view::get_obligation_position(snapshot, vault_id, sub_account, lending_market, tag, id)
+    })
+    .await
+}
@@ -175,3 +175,14 @@ pub struct BorrowRateCurve {
     pub base_rate: u64,
     pub padding: [u64; 4],
 }
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct UserMetadata {
+    pub referrer: Pubkey,
+    pub bump: u64,
+    pub user_lookup_table: Pubkey,
+    pub owner: Pubkey,
+    pub padding_1: [u64; 51],
+    pub padding_2: [u64; 64],
+}
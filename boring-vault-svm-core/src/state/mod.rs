@@ -1,3 +1,5 @@
 pub mod kamino;
+pub mod stake_pool;
 
-pub use kamino::*;
\ No newline at end of file
+pub use kamino::*;
+pub use stake_pool::*;
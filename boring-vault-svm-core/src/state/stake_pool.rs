@@ -0,0 +1,79 @@
+use anchor_lang::prelude::borsh::{self, BorshDeserialize};
+use solana_pubkey::Pubkey;
+
+// The SPL stake pool program's `StakePool` account, also used by forks of the program like
+// Sanctum's.
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct StakePool {
+    pub account_type: u8,
+    pub manager: Pubkey,
+    pub staker: Pubkey,
+    pub stake_deposit_authority: Pubkey,
+    pub stake_withdraw_bump_seed: u8,
+    pub validator_list: Pubkey,
+    pub reserve_stake: Pubkey,
+    pub pool_mint: Pubkey,
+    pub manager_fee_account: Pubkey,
+    pub token_program_id: Pubkey,
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    pub last_update_epoch: u64,
+    pub lockup: Lockup,
+    pub epoch_fee: Fee,
+    pub next_epoch_fee: FutureEpoch<Fee>,
+    pub preferred_deposit_validator_vote_address: Option<Pubkey>,
+    pub preferred_withdraw_validator_vote_address: Option<Pubkey>,
+    pub stake_deposit_fee: Fee,
+    pub stake_withdrawal_fee: Fee,
+    pub next_stake_withdrawal_fee: FutureEpoch<Fee>,
+    pub stake_referral_fee: u8,
+    pub sol_deposit_authority: Option<Pubkey>,
+    pub sol_deposit_fee: Fee,
+    pub sol_referral_fee: u8,
+    pub sol_withdraw_authority: Option<Pubkey>,
+    pub sol_withdrawal_fee: Fee,
+    pub next_sol_withdrawal_fee: FutureEpoch<Fee>,
+    pub last_epoch_pool_token_supply: u64,
+    pub last_epoch_total_lamports: u64,
+}
+
+// The pool's `ValidatorList` account. Entries past the vec length are preallocated space.
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct ValidatorList {
+    pub account_type: u8,
+    pub max_validators: u32,
+    pub validators: Vec<ValidatorStakeInfo>,
+}
+
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct ValidatorStakeInfo {
+    pub active_stake_lamports: u64,
+    pub transient_stake_lamports: u64,
+    pub last_update_epoch: u64,
+    pub transient_seed_suffix: u64,
+    pub unused: u32,
+    pub validator_seed_suffix: u32,
+    // `StakeStatus`, 0 is active.
+    pub status: u8,
+    pub vote_account_address: Pubkey,
+}
+
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct Lockup {
+    pub unix_timestamp: i64,
+    pub epoch: u64,
+    pub custodian: Pubkey,
+}
+
+#[derive(Debug, Clone, BorshDeserialize)]
+pub struct Fee {
+    pub denominator: u64,
+    pub numerator: u64,
+}
+
+#[derive(Debug, Clone, BorshDeserialize)]
+pub enum FutureEpoch<T> {
+    None,
+    One(T),
+    Two(T),
+}
@@ -1,4 +1,7 @@
+use std::collections::HashSet;
+
 use eyre::Result;
+use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
 
 use crate::{
@@ -6,7 +9,8 @@ use crate::{
         create_accept_authority_instruction, create_claim_fees_in_base_instruction,
         create_close_cpi_digest_instruction,
         create_configure_exchange_rate_update_bounds_instruction, create_deploy_instruction,
-        create_deposit_instruction, create_deposit_sol_instruction, create_initialize_instruction,
+        create_deposit_instruction, create_deposit_sol_instruction,
+        create_initialize_cpi_digest_instruction, create_initialize_instruction,
         create_manage_instruction, create_pause_instruction,
         create_set_deposit_sub_account_instruction, create_set_fees_instruction,
         create_set_payout_instruction, create_set_strategist_instruction,
@@ -14,20 +18,34 @@ use crate::{
         create_transfer_authority_instruction, create_unpause_instruction,
         create_update_asset_data_instruction, create_update_exchange_rate_instruction,
         create_update_exchange_rate_provider_instruction, create_withdraw_instruction,
+        get_external_instruction_cpi_digest, get_vault_id,
     },
-    manage_instructions::TransferSolBetweenSubAccounts,
+    manage_instructions::{RawExternalInstruction, TransferSolBetweenSubAccounts},
+    utils::{boring_vault_svm::types::Operators, VaultPdas},
     KeypairOrPublickey,
 };
 
 use crate::builder::Builder;
+use crate::config::StrategyManifest;
+use crate::utils::AccountSource;
+
+#[derive(Debug, Clone)]
+pub struct CpiDigestProvision {
+    pub label: String,
+    pub cpi_digest_pda: Pubkey,
+    pub digest: [u8; 32],
+    pub exists: bool,
+}
 
-impl Builder {
+impl<C: AccountSource> Builder<C> {
     pub fn initialize(
         &mut self,
         authority: Pubkey,
         signer: KeypairOrPublickey,
         program_signer: KeypairOrPublickey,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_initialize_instruction(&authority, &signer.pubkey())?;
 
         // Add instruction
@@ -39,10 +57,13 @@ impl Builder {
         Ok(())
     }
 
+    // Deploys as `vault_id`, or the id after the last deploy in this plan when `None`, and returns
+    // the vault's addresses so it can be configured in the same plan.
     pub fn deploy(
         &mut self,
         authority: Pubkey,
         signer: KeypairOrPublickey,
+        vault_id: Option<u64>,
         base_asset: Pubkey,
         name: String,
         symbol: String,
@@ -56,9 +77,17 @@ impl Builder {
         performance_fee_bps: Option<u16>,
         withdraw_authority: Option<Pubkey>,
         strategist: Option<Pubkey>,
-    ) -> Result<()> {
+    ) -> Result<VaultPdas> {
+        self.start_group();
+
+        let vault_id = match vault_id {
+            Some(vault_id) => vault_id,
+            None => self.next_vault_id()?,
+        };
+
         let ix = create_deploy_instruction(
-            &self.client,
+            &self.accounts(),
+            Some(vault_id),
             &authority,
             &signer.pubkey(),
             &base_asset,
@@ -78,8 +107,20 @@ impl Builder {
 
         self.instructions.push(ix);
         self.add_signer_if_keypair(signer);
+        self.pending_vault_ids.push(vault_id);
 
-        Ok(())
+        Ok(VaultPdas::new(vault_id))
+    }
+
+    // Id the next deploy gets, counting the deploys already in this plan.
+    pub fn next_vault_id(&self) -> Result<u64> {
+        let vault_count = get_vault_id(&self.accounts())?;
+
+        Ok(self
+            .pending_vault_ids
+            .iter()
+            .map(|vault_id| vault_id + 1)
+            .fold(vault_count, u64::max))
     }
 
     pub fn update_asset_data(
@@ -96,6 +137,8 @@ impl Builder {
         max_staleness: u64,
         min_samples: u32,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_update_asset_data_instruction(
             &signer.pubkey(),
             vault_id,
@@ -118,6 +161,8 @@ impl Builder {
     }
 
     pub fn pause(&mut self, signer: KeypairOrPublickey, vault_id: u64) -> Result<()> {
+        self.start_group();
+
         let ix = create_pause_instruction(vault_id, &signer.pubkey())?;
 
         self.instructions.push(ix);
@@ -127,6 +172,8 @@ impl Builder {
     }
 
     pub fn unpause(&mut self, signer: KeypairOrPublickey, vault_id: u64) -> Result<()> {
+        self.start_group();
+
         let ix = create_unpause_instruction(vault_id, &signer.pubkey())?;
 
         self.instructions.push(ix);
@@ -141,6 +188,8 @@ impl Builder {
         vault_id: u64,
         pending_authority: Pubkey,
     ) -> Result<()> {
+        self.start_group();
+
         let ix =
             create_transfer_authority_instruction(vault_id, &signer.pubkey(), &pending_authority)?;
 
@@ -151,6 +200,8 @@ impl Builder {
     }
 
     pub fn accept_authority(&mut self, signer: KeypairOrPublickey, vault_id: u64) -> Result<()> {
+        self.start_group();
+
         let ix = create_accept_authority_instruction(vault_id, &signer.pubkey())?;
 
         self.instructions.push(ix);
@@ -165,6 +216,8 @@ impl Builder {
         vault_id: u64,
         digest: [u8; 32],
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_close_cpi_digest_instruction(vault_id, &signer.pubkey(), digest)?;
 
         self.instructions.push(ix);
@@ -173,12 +226,73 @@ impl Builder {
         Ok(())
     }
 
+    // Initializes the CPI digest of every operation in the manifest that isn't on chain yet,
+    // without running any of them. Each initialization is its own group, so `try_send_all`
+    // packs them into as few transactions as fit.
+    pub fn provision_cpi_digests(
+        &mut self,
+        authority: KeypairOrPublickey,
+        manifest: &StrategyManifest,
+    ) -> Result<Vec<CpiDigestProvision>> {
+        let mut seen = HashSet::new();
+        let mut digests = vec![];
+        for (instruction, eix) in manifest
+            .instructions
+            .iter()
+            .zip(manifest.external_instructions())
+        {
+            let (cpi_digest_pda, digest) = get_external_instruction_cpi_digest(eix.as_ref())?;
+            // Entries that only differ in amounts share a digest.
+            if seen.insert(cpi_digest_pda) {
+                let label = format!("{} (sub-account {})", instruction.kind(), eix.sub_account());
+                digests.push((label, cpi_digest_pda, digest, eix));
+            }
+        }
+
+        let pdas = digests
+            .iter()
+            .map(|(_, pda, _, _)| *pda)
+            .collect::<Vec<_>>();
+        let accounts = self.accounts().get_multiple_accounts(&pdas)?;
+
+        let mut provisions = vec![];
+        for ((label, cpi_digest_pda, digest, eix), account) in digests.into_iter().zip(accounts) {
+            let exists = account.is_some();
+            if !exists {
+                self.start_group();
+                self.instructions
+                    .push(create_initialize_cpi_digest_instruction(
+                        &authority.pubkey(),
+                        manifest.vault_id,
+                        &cpi_digest_pda,
+                        digest,
+                        eix.ix_operators(),
+                    )?);
+            }
+
+            provisions.push(CpiDigestProvision {
+                label,
+                cpi_digest_pda,
+                digest,
+                exists,
+            });
+        }
+
+        if provisions.iter().any(|provision| !provision.exists) {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(provisions)
+    }
+
     pub fn update_exchange_rate_provider(
         &mut self,
         signer: KeypairOrPublickey,
         vault_id: u64,
         new_provider: Pubkey,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_update_exchange_rate_provider_instruction(
             vault_id,
             &signer.pubkey(),
@@ -197,6 +311,8 @@ impl Builder {
         vault_id: u64,
         new_authority: Pubkey,
     ) -> Result<()> {
+        self.start_group();
+
         let ix =
             create_set_withdraw_authority_instruction(vault_id, &signer.pubkey(), &new_authority)?;
 
@@ -212,6 +328,8 @@ impl Builder {
         vault_id: u64,
         new_payout: Pubkey,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_set_payout_instruction(vault_id, &signer.pubkey(), &new_payout)?;
 
         self.instructions.push(ix);
@@ -228,6 +346,8 @@ impl Builder {
         lower_bound: u16,
         minimum_update_delay: u32,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_configure_exchange_rate_update_bounds_instruction(
             vault_id,
             &signer.pubkey(),
@@ -249,6 +369,8 @@ impl Builder {
         platform_fee_bps: u16,
         performance_fee_bps: u16,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_set_fees_instruction(
             vault_id,
             &signer.pubkey(),
@@ -268,6 +390,8 @@ impl Builder {
         vault_id: u64,
         new_strategist: Pubkey,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_set_strategist_instruction(vault_id, &signer.pubkey(), &new_strategist)?;
 
         self.instructions.push(ix);
@@ -282,8 +406,10 @@ impl Builder {
         vault_id: u64,
         sub_account: u8,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_claim_fees_in_base_instruction(
-            &self.client,
+            &self.accounts(),
             vault_id,
             sub_account,
             &signer.pubkey(),
@@ -302,8 +428,10 @@ impl Builder {
         deposit_amount: u64,
         min_mint_amount: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_deposit_sol_instruction(
-            &self.client,
+            &self.accounts(),
             &signer.pubkey(),
             vault_id,
             deposit_amount,
@@ -326,8 +454,10 @@ impl Builder {
         deposit_amount: u64,
         min_mint_amount: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let ixs = create_deposit_instruction(
-            &self.client,
+            &self.accounts(),
             vault_id,
             &signer.pubkey(),
             &deposit_mint,
@@ -349,8 +479,10 @@ impl Builder {
         share_amount: u64,
         min_asset_amount: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_withdraw_instruction(
-            &self.client,
+            &self.accounts(),
             vault_id,
             &signer.pubkey(),
             &withdraw_mint,
@@ -370,6 +502,8 @@ impl Builder {
         vault_id: u64,
         new_exchange_rate: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let ix =
             create_update_exchange_rate_instruction(vault_id, &signer.pubkey(), new_exchange_rate)?;
 
@@ -379,6 +513,41 @@ impl Builder {
         Ok(())
     }
 
+    // Manages any instruction built elsewhere, e.g. by another protocol's SDK. The instruction's
+    // accounts should use the sub-account PDA as the authority.
+    pub fn manage_raw_instruction(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        instruction: Instruction,
+        operators: Operators,
+    ) -> Result<()> {
+        self.start_group();
+
+        let eix = RawExternalInstruction::new(vault_id, sub_account, instruction, operators);
+
+        let ixs = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+
     pub fn transfer_sol_between_sub_accounts(
         &mut self,
         signer: KeypairOrPublickey,
@@ -388,14 +557,17 @@ impl Builder {
         to_sub_account: u8,
         amount: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let eix = TransferSolBetweenSubAccounts::new(vault_id, sub_account, to_sub_account, amount);
 
-        let ixs = match authority.as_ref() {
-            Some(authority) => {
-                create_manage_instruction(&self.client, &signer, Some(authority), eix)?
-            }
-            None => create_manage_instruction(&self.client, &signer, None, eix)?,
-        };
+        let ixs = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
 
         for ix in ixs {
             self.instructions.push(ix);
@@ -415,6 +587,8 @@ impl Builder {
         vault_id: u64,
         new_sub_account: u8,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_set_deposit_sub_account_instruction(
             &signer.pubkey(),
             vault_id,
@@ -433,6 +607,8 @@ impl Builder {
         vault_id: u64,
         new_sub_account: u8,
     ) -> Result<()> {
+        self.start_group();
+
         let ix = create_set_withdraw_sub_account_instruction(
             &signer.pubkey(),
             vault_id,
@@ -1,9 +1,20 @@
-use crate::{instructions::create_mint_jito_sol_instructions, KeypairOrPublickey};
+use crate::{
+    instructions::{
+        build_manage_instructions, create_deactivate_stake_instructions, create_manage_instruction,
+        create_mint_jito_sol_instructions, create_unstake_jito_sol_instructions,
+        create_withdraw_jito_sol_instructions, create_withdraw_stake_instructions,
+    },
+    manage_instructions::WithdrawStake,
+    view::StakeAccountStatus,
+    KeypairOrPublickey,
+};
 use eyre::Result;
+use solana_pubkey::Pubkey;
 
 use crate::builder::Builder;
+use crate::utils::AccountSource;
 
-impl Builder {
+impl<C: AccountSource> Builder<C> {
     pub fn mint_jito_sol(
         &mut self,
         signer: KeypairOrPublickey,
@@ -12,10 +23,46 @@ impl Builder {
         sub_account: u8,
         amount: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let ixs = create_mint_jito_sol_instructions(
-            &self.client,
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            amount,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+
+    // Instant exit through the pool reserve.
+    pub fn withdraw_jito_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_withdraw_jito_sol_instructions(
+            &self.accounts(),
             &signer,
             authority.as_ref(),
+            self.cpi_digest_check,
             vault_id,
             sub_account,
             amount,
@@ -32,4 +79,164 @@ impl Builder {
 
         Ok(())
     }
+
+    // Fee-free exit: withdraws stake into a new stake account of the sub-account and deactivates
+    // it. Once `get_pending_stake_accounts` reports it withdrawable, `withdraw_unstaked_sol`
+    // returns the lamports. Returns the stake account.
+    pub fn unstake_jito_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> Result<Pubkey> {
+        self.start_group();
+
+        let in_use = self
+            .instructions
+            .iter()
+            .flat_map(|ix| ix.accounts.iter().map(|account| account.pubkey))
+            .collect::<Vec<_>>();
+        let (ixs, stake_account) = create_unstake_jito_sol_instructions(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            amount,
+            &in_use,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(stake_account)
+    }
+
+    pub fn deactivate_stake(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_account: Pubkey,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_deactivate_stake_instructions(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            stake_account,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+
+    pub fn withdraw_stake(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_account: Pubkey,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_withdraw_stake_instructions(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            stake_account,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+
+    // Withdraws every unstake account of the sub-account that finished cooling down, each in its
+    // own group. Returns the stake accounts withdrawn from.
+    pub fn withdraw_unstaked_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<Vec<Pubkey>> {
+        let withdrawable = self
+            .get_pending_stake_accounts(vault_id, sub_account)?
+            .into_iter()
+            .filter(|stake_account| stake_account.status == StakeAccountStatus::Withdrawable)
+            .collect::<Vec<_>>();
+
+        for (index, stake_account) in withdrawable.iter().enumerate() {
+            self.start_group();
+
+            let eix = WithdrawStake::new(
+                vault_id,
+                sub_account,
+                stake_account.address,
+                stake_account.lamports,
+            );
+            // The withdrawals share a digest, so only the first initializes it.
+            let ixs = if index == 0 {
+                create_manage_instruction(
+                    &self.accounts(),
+                    &signer,
+                    authority.as_ref(),
+                    self.cpi_digest_check,
+                    eix,
+                )?
+            } else {
+                build_manage_instructions(&signer, None, eix, true)?
+            };
+
+            for ix in ixs {
+                self.instructions.push(ix);
+            }
+        }
+
+        if !withdrawable.is_empty() {
+            self.add_signer_if_keypair(signer);
+            if let Some(authority) = authority {
+                self.add_signer_if_keypair(authority);
+            }
+        }
+
+        Ok(withdrawable
+            .into_iter()
+            .map(|stake_account| stake_account.address)
+            .collect())
+    }
 }
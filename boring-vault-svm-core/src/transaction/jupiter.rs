@@ -0,0 +1,47 @@
+use crate::{
+    config::JupiterSwapInstructions, instructions::create_jupiter_swap_instructions,
+    KeypairOrPublickey,
+};
+use eyre::Result;
+
+use crate::builder::Builder;
+use crate::utils::AccountSource;
+
+impl<C: AccountSource> Builder<C> {
+    // Routes a Jupiter swap through the sub-account, along with the lookup tables it needs.
+    pub fn swap(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        swap: &JupiterSwapInstructions,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_jupiter_swap_instructions(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            swap,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        for address in &swap.address_lookup_table_addresses {
+            self.add_lookup_table(*address);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+}
@@ -1,21 +1,28 @@
 use crate::{
-    instructions::{create_lut_instruction, create_manage_instruction},
+    config::KaminoMarketConfig,
+    instructions::{
+        create_lut_instruction, create_manage_instruction, get_external_instruction_cpi_digest,
+    },
     manage_instructions::{
         ExternalInstruction, KaminoBorrow, KaminoDeposit, KaminoInitObligation,
         KaminoInitObligationFarmsForReserve, KaminoInitUserMetaData, KaminoRefreshObligation,
         KaminoRefreshObligationFarmsForReserve, KaminoRefreshPriceList, KaminoRefreshReserve,
-        KAMINO_PROGRAM_ID,
+        KaminoRepay, KaminoWithdraw, KAMINO_PROGRAM_ID,
     },
     utils::{ensure_ata, get_lut_pda, get_vault_pda, pdas},
+    view::{get_obligation_address, get_obligation_position, get_reserve_farms},
     KeypairOrPublickey,
 };
 
 use crate::builder::Builder;
+use crate::utils::AccountSource;
 
-use eyre::Result;
+use eyre::{eyre, Result};
+use solana_instruction::Instruction;
 use solana_pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
-impl Builder {
+impl<C: AccountSource> Builder<C> {
     pub fn init_user_metadata(
         &mut self,
         signer: KeypairOrPublickey,
@@ -23,6 +30,8 @@ impl Builder {
         vault_id: u64,
         sub_account: u8,
     ) -> Result<()> {
+        self.start_group();
+
         // Add create lut instruction.
         let vault_pda = get_vault_pda(vault_id, sub_account);
         let recent_slot = self.client.get_slot()?;
@@ -34,12 +43,13 @@ impl Builder {
 
         let eix = KaminoInitUserMetaData::new(vault_id, sub_account, lut_account);
 
-        let ixs = match authority.as_ref() {
-            Some(authority) => {
-                create_manage_instruction(&self.client, &signer, Some(authority), eix)?
-            }
-            None => create_manage_instruction(&self.client, &signer, None, eix)?,
-        };
+        let ixs = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
 
         for ix in ixs {
             self.instructions.push(ix);
@@ -61,21 +71,21 @@ impl Builder {
         sub_account: u8,
         tag: u8,
         id: u8,
+        market: Option<&str>,
     ) -> Result<()> {
-        let eix = KaminoInitObligation::new(
-            vault_id,
-            sub_account,
-            self.kamino_config.lending_market,
-            tag,
-            id,
-        );
+        let market = self.kamino_config.lending_market(market)?;
 
-        let ixs = match authority.as_ref() {
-            Some(authority) => {
-                create_manage_instruction(&self.client, &signer, Some(authority), eix)?
-            }
-            None => create_manage_instruction(&self.client, &signer, None, eix)?,
-        };
+        self.start_group();
+
+        let eix = KaminoInitObligation::new(vault_id, sub_account, market.lending_market, tag, id);
+
+        let ixs = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
 
         for ix in ixs {
             self.instructions.push(ix);
@@ -89,6 +99,8 @@ impl Builder {
         Ok(())
     }
 
+    // Mode 0 is the reserve's collateral farm, mode 1 its debt farm. The reserve defaults to the
+    // default lend reserve for mode 0 and the default borrow reserve for mode 1.
     pub fn init_obligation_farms_for_reserve(
         &mut self,
         signer: KeypairOrPublickey,
@@ -98,24 +110,33 @@ impl Builder {
         tag: Option<u8>,
         id: Option<u8>,
         mode: u8,
+        reserve: Option<&str>,
     ) -> Result<()> {
+        let (reserve, market) = match mode {
+            1 => self.kamino_config.borrow_reserve(reserve)?,
+            _ => self.kamino_config.lend_reserve(reserve)?,
+        };
+
+        self.start_group();
+
         let eix = KaminoInitObligationFarmsForReserve::new(
             vault_id,
             sub_account,
-            self.kamino_config.lend.reserve,
-            self.kamino_config.lend.reserve_farm_state,
-            self.kamino_config.lending_market,
+            reserve.reserve,
+            reserve.farm(mode)?,
+            market.lending_market,
             tag.unwrap_or(0),
             id.unwrap_or(0),
             mode,
         );
 
-        let ixs = match authority.as_ref() {
-            Some(authority) => {
-                create_manage_instruction(&self.client, &signer, Some(authority), eix)?
-            }
-            None => create_manage_instruction(&self.client, &signer, None, eix)?,
-        };
+        let ixs = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
 
         for ix in ixs {
             self.instructions.push(ix);
@@ -129,37 +150,63 @@ impl Builder {
         Ok(())
     }
 
+    // Refreshes a reserve from the Kamino config, found by its address.
     pub fn refresh_reserve(
         &mut self,
         vault_id: u64,
         sub_account: u8,
         reserve: Pubkey,
     ) -> Result<()> {
-        let ix = KaminoRefreshReserve::new(
+        let market = self
+            .kamino_config
+            .reserve_by_address(&reserve)
+            .ok_or_else(|| eyre!("Reserve {} is not in the Kamino config", reserve))
+            .and_then(|reserve| self.kamino_config.market(&reserve.market))?
+            .clone();
+
+        self.start_group();
+
+        self.instructions.push(refresh_reserve_instruction(
             vault_id,
             sub_account,
             reserve,
-            self.kamino_config.lending_market,
-            KAMINO_PROGRAM_ID,
-            KAMINO_PROGRAM_ID,
-            KAMINO_PROGRAM_ID,
-            self.kamino_config.oracle_prices,
-        );
-
-        self.instructions.push(ix.to_instruction());
+            &market,
+        ));
 
         Ok(())
     }
 
+    // Refreshes the given reserves, or the default lend and borrow reserves when `None`.
     pub fn refresh_reserves(
         &mut self,
         signer: KeypairOrPublickey,
         authority: Option<KeypairOrPublickey>,
         vault_id: u64,
         sub_account: u8,
+        reserves: Option<&[&str]>,
     ) -> Result<()> {
-        self.refresh_reserve(vault_id, sub_account, self.kamino_config.lend.reserve)?;
-        self.refresh_reserve(vault_id, sub_account, self.kamino_config.borrow.reserve)?;
+        let reserves = match reserves {
+            Some(keys) => keys
+                .iter()
+                .map(|key| self.kamino_config.reserve_with_market(key))
+                .collect::<Result<Vec<_>>>()?,
+            None => vec![
+                self.kamino_config.lend_reserve(None)?,
+                self.kamino_config.borrow_reserve(None)?,
+            ],
+        };
+
+        self.start_group();
+
+        // The refreshes are pushed here directly so they stay in a single group.
+        for (reserve, market) in reserves {
+            self.instructions.push(refresh_reserve_instruction(
+                vault_id,
+                sub_account,
+                reserve.reserve,
+                &market,
+            ));
+        }
 
         self.add_signer_if_keypair(signer);
         if let Some(authority) = authority {
@@ -177,21 +224,36 @@ impl Builder {
         sub_account: u8,
         tag: u8,
         id: u8,
+        market: Option<&str>,
     ) -> Result<()> {
+        let market = self.kamino_config.lending_market(market)?;
+        let position = get_obligation_position(
+            &self.accounts(),
+            vault_id,
+            sub_account,
+            &market.lending_market,
+            tag,
+            id,
+        )?;
+
+        self.start_group();
+
         let eix = KaminoRefreshObligation::new(
             vault_id,
             sub_account,
-            self.kamino_config.lending_market,
+            market.lending_market,
             tag,
             id,
+            position.reserves(),
         );
 
-        let ixs = match authority.as_ref() {
-            Some(authority) => {
-                create_manage_instruction(&self.client, &signer, Some(authority), eix)?
-            }
-            None => create_manage_instruction(&self.client, &signer, None, eix)?,
-        };
+        let ixs = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
 
         for ix in ixs {
             self.instructions.push(ix);
@@ -205,6 +267,7 @@ impl Builder {
         Ok(())
     }
 
+    // Same reserve selection as `init_obligation_farms_for_reserve`.
     pub fn refresh_obligation_farms_for_reserve(
         &mut self,
         signer: KeypairOrPublickey,
@@ -214,13 +277,21 @@ impl Builder {
         tag: u8,
         id: u8,
         mode: u8,
+        reserve: Option<&str>,
     ) -> Result<()> {
+        let (reserve, market) = match mode {
+            1 => self.kamino_config.borrow_reserve(reserve)?,
+            _ => self.kamino_config.lend_reserve(reserve)?,
+        };
+
+        self.start_group();
+
         let eix = KaminoRefreshObligationFarmsForReserve::new(
             vault_id,
             sub_account,
-            self.kamino_config.lend.reserve,
-            self.kamino_config.lend.reserve_farm_state,
-            self.kamino_config.lending_market,
+            reserve.reserve,
+            reserve.farm(mode)?,
+            market.lending_market,
             tag,
             id,
             mode,
@@ -241,18 +312,20 @@ impl Builder {
         signer: KeypairOrPublickey,
         vault_id: u64,
         sub_account: u8,
+        market: Option<&str>,
     ) -> Result<()> {
-        let price_accounts = self.kamino_config.price_accounts.clone();
-        let tokens = self.kamino_config.tokens.clone();
+        let market = self.kamino_config.lending_market(market)?;
+
+        self.start_group();
 
         let eix = KaminoRefreshPriceList::new(
             vault_id,
             sub_account,
-            self.kamino_config.oracle_prices,
-            self.kamino_config.oracle_mapping,
-            self.kamino_config.oracle_twaps,
-            price_accounts,
-            tokens,
+            market.oracle_prices,
+            market.oracle_mapping,
+            market.oracle_twaps,
+            market.price_accounts,
+            market.tokens,
         );
 
         self.instructions.push(eix.to_instruction());
@@ -261,50 +334,81 @@ impl Builder {
         Ok(())
     }
 
+    // Refreshes the given reserves, or the default lend and borrow reserves when `None`, along
+    // with every reserve the obligation has a position in, then the obligation. The reserves must
+    // be on the same market.
     pub fn refresh_kamino_accounts(
         &mut self,
         vault_id: u64,
         sub_account: u8,
         tag: u8,
         id: u8,
+        reserves: Option<&[&str]>,
     ) -> Result<()> {
-        let refresh_obligation = KaminoRefreshObligation::new(
+        let selected = match reserves {
+            Some(keys) => keys
+                .iter()
+                .map(|key| self.kamino_config.reserve_with_market(key))
+                .collect::<Result<Vec<_>>>()?,
+            None => vec![
+                self.kamino_config.lend_reserve(None)?,
+                self.kamino_config.borrow_reserve(None)?,
+            ],
+        };
+        let market = match selected.first() {
+            Some((_, market)) => market.clone(),
+            None => self.kamino_config.lending_market(None)?,
+        };
+        if let Some((reserve, _)) = selected
+            .iter()
+            .find(|(_, other)| other.lending_market != market.lending_market)
+        {
+            return Err(eyre!(
+                "Kamino reserve '{}' is not on lending market {}",
+                reserve.name,
+                market.lending_market
+            ));
+        }
+
+        let position = get_obligation_position(
+            &self.accounts(),
             vault_id,
             sub_account,
-            self.kamino_config.lending_market,
+            &market.lending_market,
             tag,
             id,
-        );
-
-        let refresh_lend = KaminoRefreshReserve::new(
-            vault_id,
-            sub_account,
-            self.kamino_config.lend.reserve,
-            self.kamino_config.lending_market,
-            KAMINO_PROGRAM_ID,
-            KAMINO_PROGRAM_ID,
-            KAMINO_PROGRAM_ID,
-            self.kamino_config.oracle_prices,
-        );
+        )?;
+        let selected = selected
+            .iter()
+            .map(|(reserve, _)| reserve.reserve)
+            .collect::<Vec<_>>();
+
+        self.start_group();
+
+        for reserve in position.reserves_to_refresh(&selected) {
+            self.instructions.push(refresh_reserve_instruction(
+                vault_id,
+                sub_account,
+                reserve,
+                &market,
+            ));
+        }
 
-        let refresh_borrow = KaminoRefreshReserve::new(
+        let refresh_obligation = KaminoRefreshObligation::new(
             vault_id,
             sub_account,
-            self.kamino_config.borrow.reserve,
-            self.kamino_config.lending_market,
-            KAMINO_PROGRAM_ID,
-            KAMINO_PROGRAM_ID,
-            KAMINO_PROGRAM_ID,
-            self.kamino_config.oracle_prices,
+            market.lending_market,
+            tag,
+            id,
+            position.reserves(),
         );
 
-        self.instructions.push(refresh_lend.to_instruction());
-        self.instructions.push(refresh_borrow.to_instruction());
         self.instructions.push(refresh_obligation.to_instruction());
 
         Ok(())
     }
 
+    // Lends into `reserve`, or the default lend reserve when `None`.
     pub fn lend(
         &mut self,
         signer: KeypairOrPublickey,
@@ -314,32 +418,49 @@ impl Builder {
         amount: u64,
         tag: u8,
         id: u8,
+        reserve: Option<&str>,
     ) -> Result<()> {
-        self.refresh_kamino_accounts(vault_id, sub_account, tag, id)?;
+        let (reserve, market) = self.kamino_config.lend_reserve(reserve)?;
+        let (collateral_mint, collateral_supply) = reserve.collateral()?;
 
         let lend_ix = KaminoDeposit::new(
             vault_id,
             sub_account,
-            self.kamino_config.lending_market,
-            self.kamino_config.lend.reserve,
-            self.kamino_config.lend.reserve_liquidity_mint,
-            self.kamino_config.lend.reserve_liquidity_supply,
-            self.kamino_config.lend.reserve_collateral_mint,
-            self.kamino_config
-                .lend
-                .reserve_destination_deposit_collateral,
-            self.kamino_config.lend.reserve_farm_state,
+            market.lending_market,
+            reserve.reserve,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            collateral_mint,
+            collateral_supply,
+            reserve.farm_collateral,
             tag,
             id,
             amount,
         );
 
-        let manage_lend_ix = match authority.as_ref() {
-            Some(authority) => {
-                create_manage_instruction(&self.client, &signer, Some(authority), lend_ix)?
-            }
-            None => create_manage_instruction(&self.client, &signer, None, lend_ix)?,
-        };
+        // The obligation read of the refresh and the CPI digest check share one fetch.
+        let obligation =
+            get_obligation_address(vault_id, sub_account, &market.lending_market, tag, id);
+        let (cpi_digest_pda, _) = get_external_instruction_cpi_digest(&lend_ix)?;
+        self.prefetch_accounts(&[obligation, cpi_digest_pda])?;
+
+        self.start_group();
+
+        self.refresh_kamino_accounts(
+            vault_id,
+            sub_account,
+            tag,
+            id,
+            Some(&[reserve.name.as_str()]),
+        )?;
+
+        let manage_lend_ix = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            lend_ix,
+        )?;
 
         self.instructions.extend(manage_lend_ix);
 
@@ -350,6 +471,7 @@ impl Builder {
         Ok(())
     }
 
+    // Borrows from `reserve`, or the default borrow reserve when `None`.
     pub fn borrow(
         &mut self,
         signer: KeypairOrPublickey,
@@ -359,14 +481,48 @@ impl Builder {
         amount: u64,
         tag: u8,
         id: u8,
+        reserve: Option<&str>,
     ) -> Result<()> {
-        self.refresh_kamino_accounts(vault_id, sub_account, tag, id)?;
+        let (reserve, market) = self.kamino_config.borrow_reserve(reserve)?;
+        let fee_receiver = reserve.fee_receiver()?;
+
+        self.start_group();
+
+        self.refresh_kamino_accounts(
+            vault_id,
+            sub_account,
+            tag,
+            id,
+            Some(&[reserve.name.as_str()]),
+        )?;
+
+        let eix = KaminoBorrow::new(
+            vault_id,
+            sub_account,
+            market.lending_market,
+            reserve.reserve,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            fee_receiver,
+            tag,
+            id,
+            amount,
+        );
+
+        // The ATA check and CPI digest check share one fetch.
+        let vault_pda = pdas::get_vault_pda(vault_id, sub_account);
+        let borrow_mint = reserve.liquidity_mint;
+        let (cpi_digest_pda, _) = get_external_instruction_cpi_digest(&eix)?;
+        self.prefetch_accounts(&[
+            get_associated_token_address_with_program_id(&vault_pda, &borrow_mint, &spl_token::ID),
+            cpi_digest_pda,
+        ])?;
 
         let (_, ata_ix) = ensure_ata(
-            &self.client,
+            &self.accounts(),
             &signer.pubkey(),
-            &pdas::get_vault_pda(vault_id, sub_account),
-            &self.kamino_config.borrow.reserve_source_liquidity_mint,
+            &vault_pda,
+            &borrow_mint,
             &spl_token::ID,
         )?;
 
@@ -374,30 +530,219 @@ impl Builder {
             self.instructions.push(uix);
         }
 
-        let eix = KaminoBorrow::new(
+        let manage_borrow_ix = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
+
+        self.instructions.extend(manage_borrow_ix);
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+
+    // Repays `amount` of the borrow reserve's liquidity, or the whole debt when `None`.
+    pub fn repay(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: Option<u64>,
+        tag: u8,
+        id: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        let (reserve, market) = self.kamino_config.borrow_reserve(reserve)?;
+        let amount = match amount {
+            Some(amount) => amount,
+            None => {
+                let position = get_obligation_position(
+                    &self.accounts(),
+                    vault_id,
+                    sub_account,
+                    &market.lending_market,
+                    tag,
+                    id,
+                )?;
+                position
+                    .borrow(&reserve.reserve)
+                    .filter(|borrow| borrow.borrowed_amount_sf > 0)
+                    .ok_or_else(|| {
+                        eyre!("Obligation has no debt on reserve {}", reserve.reserve)
+                    })?;
+
+                // The debt grows until the refresh, so let Kamino settle all of it.
+                u64::MAX
+            }
+        };
+        let (_, reserve_farm_state) = get_reserve_farms(&self.accounts(), &reserve.reserve)?;
+
+        self.start_group();
+
+        self.refresh_kamino_accounts(
             vault_id,
             sub_account,
-            self.kamino_config.lending_market,
-            self.kamino_config.borrow.reserve,
-            self.kamino_config.borrow.reserve_source_liquidity_mint,
-            self.kamino_config.borrow.reserve_source_liquidity,
-            self.kamino_config
-                .borrow
-                .reserve_source_liquidity_fee_receiver,
+            tag,
+            id,
+            Some(&[reserve.name.as_str()]),
+        )?;
+
+        let eix = KaminoRepay::new(
+            vault_id,
+            sub_account,
+            market.lending_market,
+            reserve.reserve,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            reserve_farm_state,
             tag,
             id,
             amount,
         );
 
-        let manage_borrow_ix = match authority.as_ref() {
-            Some(authority) => {
-                create_manage_instruction(&self.client, &signer, Some(authority), eix)?
-            }
-            None => create_manage_instruction(&self.client, &signer, None, eix)?,
+        let manage_repay_ix = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
+
+        self.instructions.extend(manage_repay_ix);
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+        Ok(())
+    }
+
+    // Withdraws `amount` of the lend reserve's collateral as liquidity, or all of it when `None`.
+    pub fn withdraw_lend(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: Option<u64>,
+        tag: u8,
+        id: u8,
+        reserve: Option<&str>,
+    ) -> Result<()> {
+        let (reserve, market) = self.kamino_config.lend_reserve(reserve)?;
+        let (collateral_mint, collateral_supply) = reserve.collateral()?;
+        let amount = match amount {
+            Some(amount) => amount,
+            None => get_obligation_position(
+                &self.accounts(),
+                vault_id,
+                sub_account,
+                &market.lending_market,
+                tag,
+                id,
+            )?
+            .deposit(&reserve.reserve)
+            .map(|deposit| deposit.deposited_amount)
+            .filter(|deposited_amount| *deposited_amount > 0)
+            .ok_or_else(|| {
+                eyre!(
+                    "Obligation has no collateral on reserve {}",
+                    reserve.reserve
+                )
+            })?,
         };
 
-        self.instructions.extend(manage_borrow_ix);
+        self.start_group();
+
+        self.refresh_kamino_accounts(
+            vault_id,
+            sub_account,
+            tag,
+            id,
+            Some(&[reserve.name.as_str()]),
+        )?;
+
+        let eix = KaminoWithdraw::new(
+            vault_id,
+            sub_account,
+            market.lending_market,
+            reserve.reserve,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            collateral_mint,
+            collateral_supply,
+            reserve.farm_collateral,
+            tag,
+            id,
+            amount,
+        );
+
+        // The ATA check and CPI digest check share one fetch.
+        let vault_pda = pdas::get_vault_pda(vault_id, sub_account);
+        let liquidity_mint = reserve.liquidity_mint;
+        let (cpi_digest_pda, _) = get_external_instruction_cpi_digest(&eix)?;
+        self.prefetch_accounts(&[
+            get_associated_token_address_with_program_id(
+                &vault_pda,
+                &liquidity_mint,
+                &spl_token::ID,
+            ),
+            cpi_digest_pda,
+        ])?;
 
+        let (_, ata_ix) = ensure_ata(
+            &self.accounts(),
+            &signer.pubkey(),
+            &vault_pda,
+            &liquidity_mint,
+            &spl_token::ID,
+        )?;
+
+        if let Some(uix) = ata_ix {
+            self.instructions.push(uix);
+        }
+
+        let manage_withdraw_ix = create_manage_instruction(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            eix,
+        )?;
+
+        self.instructions.extend(manage_withdraw_ix);
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
         Ok(())
     }
 }
+
+fn refresh_reserve_instruction(
+    vault_id: u64,
+    sub_account: u8,
+    reserve: Pubkey,
+    market: &KaminoMarketConfig,
+) -> Instruction {
+    KaminoRefreshReserve::new(
+        vault_id,
+        sub_account,
+        reserve,
+        market.lending_market,
+        KAMINO_PROGRAM_ID,
+        KAMINO_PROGRAM_ID,
+        KAMINO_PROGRAM_ID,
+        market.oracle_prices,
+    )
+    .to_instruction()
+}
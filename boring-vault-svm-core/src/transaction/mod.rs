@@ -1,5 +1,7 @@
 pub mod boring_vault;
 pub mod jito;
+pub mod jupiter;
 pub mod kamino;
 pub mod solend;
+pub mod stake_pool;
 pub mod system;
@@ -4,8 +4,9 @@ use solana_pubkey::Pubkey;
 use crate::{instructions::create_deposit_solend_instructions, KeypairOrPublickey};
 
 use crate::builder::Builder;
+use crate::utils::AccountSource;
 
-impl Builder {
+impl<C: AccountSource> Builder<C> {
     pub fn deposit_solend(
         &mut self,
         signer: KeypairOrPublickey,
@@ -23,10 +24,13 @@ impl Builder {
         switchboard_price_oracle: Pubkey,
         amount: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let ixs = create_deposit_solend_instructions(
-            &self.client,
+            &self.accounts(),
             &signer,
             authority.as_ref(),
+            self.cpi_digest_check,
             vault_id,
             sub_account,
             &deposit_mint,
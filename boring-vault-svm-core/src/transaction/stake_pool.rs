@@ -0,0 +1,120 @@
+use crate::{
+    instructions::{
+        create_deposit_sol_instructions, create_deposit_stake_instructions,
+        create_withdraw_sol_instructions,
+    },
+    KeypairOrPublickey,
+};
+use eyre::Result;
+use solana_pubkey::Pubkey;
+
+use crate::builder::Builder;
+use crate::utils::AccountSource;
+
+// Any SPL stake pool, or fork of the program, by pool address. The pool tokens are held in the
+// sub-account's ATA, which has to exist.
+impl<C: AccountSource> Builder<C> {
+    pub fn stake_pool_deposit_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_deposit_sol_instructions(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            &stake_pool,
+            amount,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+
+    // Instant exit through the pool reserve.
+    pub fn stake_pool_withdraw_sol(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_withdraw_sol_instructions(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            &stake_pool,
+            amount,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+
+    // The stake account has to be active and delegated to one of the pool's validators.
+    pub fn stake_pool_deposit_stake(
+        &mut self,
+        signer: KeypairOrPublickey,
+        authority: Option<KeypairOrPublickey>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: Pubkey,
+        stake_account: Pubkey,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_deposit_stake_instructions(
+            &self.accounts(),
+            &signer,
+            authority.as_ref(),
+            self.cpi_digest_check,
+            vault_id,
+            sub_account,
+            &stake_pool,
+            stake_account,
+        )?;
+
+        for ix in ixs {
+            self.instructions.push(ix);
+        }
+
+        self.add_signer_if_keypair(signer);
+        if let Some(authority) = authority {
+            self.add_signer_if_keypair(authority);
+        }
+
+        Ok(())
+    }
+}
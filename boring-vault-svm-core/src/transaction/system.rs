@@ -1,12 +1,17 @@
 use crate::{
-    instructions::{create_unwrap_sol_instructions, create_wrap_sol_instructions},
+    instructions::{
+        create_nonce_account_instructions, create_unwrap_sol_instructions,
+        create_wrap_sol_instructions,
+    },
     KeypairOrPublickey,
 };
 use eyre::Result;
+use solana_pubkey::Pubkey;
 
 use crate::builder::Builder;
+use crate::utils::AccountSource;
 
-impl Builder {
+impl<C: AccountSource> Builder<C> {
     pub fn wrap_sol(
         &mut self,
         signer: KeypairOrPublickey,
@@ -15,10 +20,13 @@ impl Builder {
         sub_account: u8,
         amount: u64,
     ) -> Result<()> {
+        self.start_group();
+
         let ixs = create_wrap_sol_instructions(
-            &self.client,
+            &self.accounts(),
             &signer,
             authority.as_ref(),
+            self.cpi_digest_check,
             vault_id,
             sub_account,
             amount,
@@ -43,10 +51,13 @@ impl Builder {
         vault_id: u64,
         sub_account: u8,
     ) -> Result<()> {
+        self.start_group();
+
         let ixs = create_unwrap_sol_instructions(
-            &self.client,
+            &self.accounts(),
             &signer,
             authority.as_ref(),
+            self.cpi_digest_check,
             vault_id,
             sub_account,
         )?;
@@ -62,4 +73,27 @@ impl Builder {
 
         Ok(())
     }
+
+    pub fn create_nonce_account(
+        &mut self,
+        payer: KeypairOrPublickey,
+        nonce_account: KeypairOrPublickey,
+        authority: Pubkey,
+    ) -> Result<()> {
+        self.start_group();
+
+        let ixs = create_nonce_account_instructions(
+            &self.accounts(),
+            &payer.pubkey(),
+            &nonce_account.pubkey(),
+            &authority,
+        )?;
+
+        self.instructions.extend(ixs);
+
+        self.add_signer_if_keypair(payer);
+        self.add_signer_if_keypair(nonce_account);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,183 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use eyre::{eyre, Result};
+use solana_account::Account;
+use solana_client::{
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::TransactionStatus;
+
+use super::{get_multiple_accounts_chunked, AccountSource};
+
+// Accounts read while building one plan, missing accounts included. Only valid until the plan is
+// sent, so it has to be invalidated before building the next one.
+#[derive(Debug, Default)]
+pub struct AccountCache {
+    accounts: Mutex<HashMap<Pubkey, Option<Account>>>,
+}
+
+impl AccountCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, address: &Pubkey) -> Option<Option<Account>> {
+        self.accounts.lock().unwrap().get(address).cloned()
+    }
+
+    pub fn insert(&self, address: Pubkey, account: Option<Account>) {
+        self.accounts.lock().unwrap().insert(address, account);
+    }
+
+    pub fn contains(&self, address: &Pubkey) -> bool {
+        self.accounts.lock().unwrap().contains_key(address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn invalidate(&self) {
+        self.accounts.lock().unwrap().clear();
+    }
+
+    // Fetches every address that isn't cached yet in as few `get_multiple_accounts` calls as the
+    // RPC allows.
+    pub fn prefetch<C: AccountSource>(&self, client: &C, addresses: &[Pubkey]) -> Result<()> {
+        let mut missing: Vec<Pubkey> = vec![];
+        for address in addresses {
+            if !self.contains(address) && !missing.contains(address) {
+                missing.push(*address);
+            }
+        }
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let accounts = get_multiple_accounts_chunked(client, &missing)?;
+        let mut cached = self.accounts.lock().unwrap();
+        for (address, account) in missing.into_iter().zip(accounts) {
+            cached.insert(address, account);
+        }
+
+        Ok(())
+    }
+}
+
+// Serves account reads from an `AccountCache`, fetching and keeping whatever it doesn't hold yet.
+// Everything else goes straight to the client.
+pub struct CachedAccountSource<'a, C> {
+    pub client: &'a C,
+    pub cache: &'a AccountCache,
+}
+
+impl<'a, C: AccountSource> CachedAccountSource<'a, C> {
+    pub fn new(client: &'a C, cache: &'a AccountCache) -> Self {
+        Self { client, cache }
+    }
+}
+
+impl<C: AccountSource> AccountSource for CachedAccountSource<'_, C> {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        self.cache.prefetch(self.client, &[*address])?;
+
+        self.cache
+            .get(address)
+            .flatten()
+            .ok_or_else(|| eyre!("Account {} not found", address))
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.cache.prefetch(self.client, addresses)?;
+
+        Ok(addresses
+            .iter()
+            .map(|address| self.cache.get(address).flatten())
+            .collect())
+    }
+
+    fn prefetch_accounts(&self, addresses: &[Pubkey]) -> Result<()> {
+        self.cache.prefetch(self.client, addresses)
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        self.client.get_slot()
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.client.get_latest_blockhash()
+    }
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        self.client.send_transaction(transaction)
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        self.client.commitment()
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        self.client.get_block_height()
+    }
+
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        self.client.get_latest_blockhash_with_expiry()
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.client.get_minimum_balance_for_rent_exemption(data_len)
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        self.client
+            .send_transaction_with_config(transaction, config)
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature> {
+        self.client.send_and_confirm_transaction(transaction)
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        self.client.get_signature_statuses(signatures)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        self.client.simulate_transaction(transaction, config)
+    }
+
+    fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> Result<Vec<u64>> {
+        self.client.get_recent_prioritization_fees(addresses)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.client.get_program_accounts(program_id, data_prefix)
+    }
+}
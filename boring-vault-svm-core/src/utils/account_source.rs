@@ -0,0 +1,751 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    str::FromStr,
+    sync::Mutex,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::{eyre, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_account::Account;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
+        RpcSimulateTransactionConfig,
+    },
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    clock::MAX_PROCESSING_AGE, commitment_config::CommitmentConfig, hash::Hash, rent::Rent,
+    signature::Signature, transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::{TransactionConfirmationStatus, TransactionStatus};
+
+// Where the builders read chain state from and send transactions to. Only the first five methods
+// are required, the rest default to what an offline source can answer.
+pub trait AccountSource {
+    // Errors when the account doesn't exist.
+    fn get_account(&self, address: &Pubkey) -> Result<Account>;
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    fn get_slot(&self) -> Result<u64>;
+
+    fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature>;
+
+    // Hint that the addresses are about to be read, so a caching source can fetch them in one
+    // batch. Sources without a cache ignore it.
+    fn prefetch_accounts(&self, _addresses: &[Pubkey]) -> Result<()> {
+        Ok(())
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        CommitmentConfig::default()
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        self.get_slot()
+    }
+
+    // Blockhash with the last block height it is valid at.
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        Ok((
+            self.get_latest_blockhash()?,
+            self.get_block_height()? + MAX_PROCESSING_AGE as u64,
+        ))
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(Rent::default().minimum_balance(data_len))
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        _config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        self.send_transaction(transaction)
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature> {
+        self.send_transaction(transaction)
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        Ok(vec![None; signatures.len()])
+    }
+
+    fn simulate_transaction(
+        &self,
+        _transaction: &VersionedTransaction,
+        _config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        Err(eyre!("Account source does not support simulation"))
+    }
+
+    // Fees paid by recent transactions that locked any of the accounts.
+    fn get_recent_prioritization_fees(&self, _addresses: &[Pubkey]) -> Result<Vec<u64>> {
+        Ok(vec![])
+    }
+
+    // Accounts owned by the program whose data starts with the prefix, usually an Anchor
+    // account discriminator.
+    fn get_program_accounts(
+        &self,
+        _program_id: &Pubkey,
+        _data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Err(eyre!("Account source does not support getProgramAccounts"))
+    }
+}
+
+impl AccountSource for RpcClient {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        Ok(RpcClient::get_account(self, address)?)
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(RpcClient::get_multiple_accounts(self, addresses)?)
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        Ok(RpcClient::get_slot(self)?)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_latest_blockhash(self)?)
+    }
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        Ok(RpcClient::send_transaction(self, transaction)?)
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        RpcClient::commitment(self)
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        Ok(RpcClient::get_block_height(self)?)
+    }
+
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        Ok(self.get_latest_blockhash_with_commitment(RpcClient::commitment(self))?)
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(RpcClient::get_minimum_balance_for_rent_exemption(
+            self, data_len,
+        )?)
+    }
+
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        Ok(RpcClient::send_transaction_with_config(
+            self,
+            transaction,
+            config,
+        )?)
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature> {
+        Ok(RpcClient::send_and_confirm_transaction(self, transaction)?)
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        Ok(self.get_signature_statuses_with_history(signatures)?.value)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        Ok(self
+            .simulate_transaction_with_config(transaction, config)?
+            .value)
+    }
+
+    fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> Result<Vec<u64>> {
+        Ok(RpcClient::get_recent_prioritization_fees(self, addresses)?
+            .iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect())
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                data_prefix,
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(RpcClient::commitment(self)),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        Ok(self.get_program_accounts_with_config(program_id, config)?)
+    }
+}
+
+// Accounts held in memory. Sent transactions are recorded and reported as finalized without
+// executing them.
+#[derive(Debug, Default)]
+pub struct InMemoryAccountSource {
+    pub accounts: HashMap<Pubkey, Account>,
+    pub slot: u64,
+    pub blockhash: Hash,
+    sent_transactions: Mutex<Vec<VersionedTransaction>>,
+}
+
+impl InMemoryAccountSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_account(mut self, address: Pubkey, account: Account) -> Self {
+        self.accounts.insert(address, account);
+        self
+    }
+
+    pub fn with_slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    pub fn with_blockhash(mut self, blockhash: Hash) -> Self {
+        self.blockhash = blockhash;
+        self
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.accounts.insert(address, account);
+    }
+
+    pub fn remove_account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.accounts.remove(address)
+    }
+
+    pub fn sent_transactions(&self) -> Vec<VersionedTransaction> {
+        self.sent_transactions.lock().unwrap().clone()
+    }
+}
+
+impl AccountSource for InMemoryAccountSource {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        self.accounts
+            .get(address)
+            .cloned()
+            .ok_or_else(|| eyre!("Account {} not found", address))
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(addresses
+            .iter()
+            .map(|address| self.accounts.get(address).cloned())
+            .collect())
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        Ok(self.slot)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.blockhash)
+    }
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        let signature = *transaction
+            .signatures
+            .first()
+            .ok_or_else(|| eyre!("Transaction has no signatures"))?;
+        self.sent_transactions
+            .lock()
+            .unwrap()
+            .push(transaction.clone());
+
+        Ok(signature)
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        let sent_transactions = self.sent_transactions.lock().unwrap();
+
+        Ok(signatures
+            .iter()
+            .map(|signature| {
+                sent_transactions
+                    .iter()
+                    .any(|tx| tx.signatures.first() == Some(signature))
+                    .then_some(TransactionStatus {
+                        slot: self.slot,
+                        confirmations: None,
+                        status: Ok(()),
+                        err: None,
+                        confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+                    })
+            })
+            .collect())
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .accounts
+            .iter()
+            .filter(|(_, account)| {
+                account.owner == *program_id && account.data.starts_with(data_prefix)
+            })
+            .map(|(address, account)| (*address, account.clone()))
+            .collect())
+    }
+}
+
+// On disk format of a fixture, with account data base64 encoded.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AccountFixture {
+    pub slot: u64,
+    pub blockhash: String,
+    pub accounts: BTreeMap<String, FixtureAccount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FixtureAccount {
+    pub lamports: u64,
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data: String,
+}
+
+impl FixtureAccount {
+    pub fn from_account(account: &Account) -> Self {
+        Self {
+            lamports: account.lamports,
+            owner: account.owner.to_string(),
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            data: STANDARD.encode(&account.data),
+        }
+    }
+
+    pub fn to_account(&self) -> Result<Account> {
+        Ok(Account {
+            lamports: self.lamports,
+            data: STANDARD.decode(&self.data)?,
+            owner: Pubkey::from_str(&self.owner)?,
+            executable: self.executable,
+            rent_epoch: self.rent_epoch,
+        })
+    }
+}
+
+impl AccountFixture {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixture: {}", path))?;
+
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse fixture: {}", path))
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write fixture: {}", path))
+    }
+}
+
+// Accounts replayed from a fixture file written by `RecordingAccountSource`.
+#[derive(Debug)]
+pub struct FixtureAccountSource {
+    source: InMemoryAccountSource,
+}
+
+impl FixtureAccountSource {
+    pub fn load(path: &str) -> Result<Self> {
+        Self::from_fixture(&AccountFixture::load(path)?)
+    }
+
+    pub fn from_fixture(fixture: &AccountFixture) -> Result<Self> {
+        let mut source = InMemoryAccountSource::new()
+            .with_slot(fixture.slot)
+            .with_blockhash(Hash::from_str(&fixture.blockhash)?);
+        for (address, account) in &fixture.accounts {
+            source.set_account(Pubkey::from_str(address)?, account.to_account()?);
+        }
+
+        Ok(Self { source })
+    }
+
+    pub fn sent_transactions(&self) -> Vec<VersionedTransaction> {
+        self.source.sent_transactions()
+    }
+}
+
+impl AccountSource for FixtureAccountSource {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        self.source.get_account(address)
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.source.get_multiple_accounts(addresses)
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        self.source.get_slot()
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.source.get_latest_blockhash()
+    }
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        self.source.send_transaction(transaction)
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        self.source.get_signature_statuses(signatures)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.source.get_program_accounts(program_id, data_prefix)
+    }
+}
+
+// Passes reads through to another source and keeps every account it returned, so a run against
+// a live cluster can be saved as a fixture. Sends are not forwarded.
+pub struct RecordingAccountSource<C> {
+    pub inner: C,
+    accounts: Mutex<BTreeMap<Pubkey, Account>>,
+    slot: Mutex<u64>,
+    blockhash: Mutex<Hash>,
+}
+
+impl<C: AccountSource> RecordingAccountSource<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            accounts: Mutex::new(BTreeMap::new()),
+            slot: Mutex::new(0),
+            blockhash: Mutex::new(Hash::default()),
+        }
+    }
+
+    pub fn fixture(&self) -> AccountFixture {
+        AccountFixture {
+            slot: *self.slot.lock().unwrap(),
+            blockhash: self.blockhash.lock().unwrap().to_string(),
+            accounts: self
+                .accounts
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(address, account)| {
+                    (address.to_string(), FixtureAccount::from_account(account))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        self.fixture().save(path)
+    }
+
+    fn record(&self, address: &Pubkey, account: &Account) {
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert(*address, account.clone());
+    }
+}
+
+impl<C: AccountSource> AccountSource for RecordingAccountSource<C> {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        let account = self.inner.get_account(address)?;
+        self.record(address, &account);
+
+        Ok(account)
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let accounts = self.inner.get_multiple_accounts(addresses)?;
+        for (address, account) in addresses.iter().zip(&accounts) {
+            if let Some(account) = account {
+                self.record(address, account);
+            }
+        }
+
+        Ok(accounts)
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        let slot = self.inner.get_slot()?;
+        *self.slot.lock().unwrap() = slot;
+
+        Ok(slot)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        let blockhash = self.inner.get_latest_blockhash()?;
+        *self.blockhash.lock().unwrap() = blockhash;
+
+        Ok(blockhash)
+    }
+
+    fn send_transaction(&self, _transaction: &VersionedTransaction) -> Result<Signature> {
+        Err(eyre!("Recording account source does not send transactions"))
+    }
+
+    fn commitment(&self) -> CommitmentConfig {
+        self.inner.commitment()
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        self.inner.get_block_height()
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.inner.get_minimum_balance_for_rent_exemption(data_len)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        self.inner.simulate_transaction(transaction, config)
+    }
+
+    fn get_recent_prioritization_fees(&self, addresses: &[Pubkey]) -> Result<Vec<u64>> {
+        self.inner.get_recent_prioritization_fees(addresses)
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let accounts = self.inner.get_program_accounts(program_id, data_prefix)?;
+        for (address, account) in &accounts {
+            self.record(address, account);
+        }
+
+        Ok(accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use solana_sdk::message::{Message, VersionedMessage};
+
+    use super::*;
+
+    fn account(lamports: u64, data: &[u8]) -> Account {
+        Account {
+            lamports,
+            data: data.to_vec(),
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    // A fixture path no other test uses.
+    fn fixture_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("account-source-{}-{}.json", process::id(), name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn in_memory_replays_accounts_and_sends() -> Result<()> {
+        let address = Pubkey::new_unique();
+        let stored = account(5, &[1, 2, 3]);
+        let blockhash = Hash::new_unique();
+        let source = InMemoryAccountSource::new()
+            .with_account(address, stored.clone())
+            .with_slot(42)
+            .with_blockhash(blockhash);
+
+        assert_eq!(source.get_account(&address)?, stored);
+        assert!(source.get_account(&Pubkey::new_unique()).is_err());
+        assert_eq!(source.get_slot()?, 42);
+        assert_eq!(source.get_latest_blockhash()?, blockhash);
+
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::from([7; 64])],
+            message: VersionedMessage::Legacy(Message::default()),
+        };
+        let signature = source.send_transaction(&transaction)?;
+        assert_eq!(source.sent_transactions(), vec![transaction]);
+
+        let statuses = source.get_signature_statuses(&[signature, Signature::default()])?;
+        assert_eq!(
+            statuses[0].as_ref().unwrap().confirmation_status,
+            Some(TransactionConfirmationStatus::Finalized)
+        );
+        assert!(statuses[1].is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn recorded_fixture_replays() -> Result<()> {
+        let (first, second, missing) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let live = InMemoryAccountSource::new()
+            .with_account(first, account(1, &[1]))
+            .with_account(second, account(2, &[2, 2]))
+            .with_slot(7)
+            .with_blockhash(Hash::new_unique());
+
+        let recording = RecordingAccountSource::new(live);
+        recording.get_account(&first)?;
+        recording.get_multiple_accounts(&[second, missing])?;
+        recording.get_slot()?;
+        recording.get_latest_blockhash()?;
+        assert!(recording
+            .send_transaction(&VersionedTransaction::default())
+            .is_err());
+
+        let path = fixture_path("recorded");
+        recording.save(&path)?;
+        let replay = FixtureAccountSource::load(&path);
+        fs::remove_file(&path)?;
+        let replay = replay?;
+
+        // Only what was read is in the fixture, missing accounts stay missing.
+        for address in [first, second] {
+            assert_eq!(
+                replay.get_account(&address)?,
+                recording.inner.get_account(&address)?
+            );
+        }
+        assert!(replay.get_account(&missing).is_err());
+        assert_eq!(replay.get_slot()?, 7);
+        assert_eq!(
+            replay.get_latest_blockhash()?,
+            recording.inner.get_latest_blockhash()?
+        );
+
+        // Accounts the recording never read aren't replayed either.
+        let unread = RecordingAccountSource::new(
+            InMemoryAccountSource::new().with_account(first, account(1, &[1])),
+        );
+        let replay = FixtureAccountSource::from_fixture(&unread.fixture())?;
+        assert!(replay.get_account(&first).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fixture_load_errors() -> Result<()> {
+        let missing = fixture_path("missing");
+        let err = FixtureAccountSource::load(&missing).unwrap_err();
+        assert!(err.to_string().contains("Failed to read fixture"));
+
+        let malformed = fixture_path("malformed");
+        fs::write(&malformed, "{ \"slot\": ")?;
+        let err = FixtureAccountSource::load(&malformed).unwrap_err();
+        fs::remove_file(&malformed)?;
+        assert!(err.to_string().contains("Failed to parse fixture"));
+
+        // Valid JSON with an address that isn't one.
+        let mut fixture = AccountFixture {
+            blockhash: Hash::default().to_string(),
+            ..AccountFixture::default()
+        };
+        fixture.accounts.insert(
+            "not-an-address".to_string(),
+            FixtureAccount::from_account(&account(1, &[])),
+        );
+        assert!(FixtureAccountSource::from_fixture(&fixture).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_accounts_keep_request_order() -> Result<()> {
+        let addresses = (0..4).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let source = InMemoryAccountSource::new()
+            .with_account(addresses[1], account(1, &[]))
+            .with_account(addresses[3], account(3, &[]));
+
+        let requested = [
+            addresses[3],
+            addresses[0],
+            addresses[1],
+            addresses[2],
+            addresses[3],
+        ];
+        let lamports = |accounts: Vec<Option<Account>>| {
+            accounts
+                .into_iter()
+                .map(|account| account.map(|account| account.lamports))
+                .collect::<Vec<_>>()
+        };
+        let expected = vec![Some(3), None, Some(1), None, Some(3)];
+        assert_eq!(
+            lamports(source.get_multiple_accounts(&requested)?),
+            expected
+        );
+
+        let recording = RecordingAccountSource::new(source);
+        assert_eq!(
+            lamports(recording.get_multiple_accounts(&requested)?),
+            expected
+        );
+        let replay = FixtureAccountSource::from_fixture(&recording.fixture())?;
+        assert_eq!(
+            lamports(replay.get_multiple_accounts(&requested)?),
+            expected
+        );
+
+        Ok(())
+    }
+}
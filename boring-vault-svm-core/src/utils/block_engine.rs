@@ -0,0 +1,87 @@
+use eyre::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest, rpc_response::Response};
+use solana_sdk::transaction::TransactionError;
+use solana_transaction_status_client_types::TransactionConfirmationStatus;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BundleStatus {
+    pub bundle_id: String,
+    pub transactions: Vec<String>,
+    pub slot: u64,
+    pub confirmation_status: Option<TransactionConfirmationStatus>,
+    // `{"Ok": null}` when the bundle executed successfully.
+    pub err: Value,
+}
+
+impl BundleStatus {
+    pub fn is_ok(&self) -> bool {
+        self.err.get("Ok").is_some()
+    }
+
+    // The error of the failed transaction, `{"Err": <TransactionError>}`.
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        serde_json::from_value(self.err.get("Err")?.clone()).ok()
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        matches!(
+            self.confirmation_status,
+            Some(TransactionConfirmationStatus::Confirmed)
+                | Some(TransactionConfirmationStatus::Finalized)
+        )
+    }
+}
+
+// JSON-RPC client for a Jito block engine bundle endpoint.
+pub struct BlockEngineClient {
+    client: RpcClient,
+}
+
+impl BlockEngineClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: RpcClient::new(url.into()),
+        }
+    }
+
+    // Submits base64 encoded transactions as a bundle and returns the bundle id.
+    pub fn send_bundle(&self, transactions: &[String]) -> Result<String> {
+        let (request, params) = send_bundle_request(transactions);
+
+        Ok(self.client.send(request, params)?)
+    }
+
+    // Statuses are only reported for bundles that landed, missing ones are `None`.
+    pub fn get_bundle_statuses(&self, bundle_ids: &[String]) -> Result<Vec<Option<BundleStatus>>> {
+        let (request, params) = get_bundle_statuses_request(bundle_ids);
+        let response: Response<Vec<Option<BundleStatus>>> = self.client.send(request, params)?;
+
+        Ok(response.value)
+    }
+
+    pub fn get_bundle_status(&self, bundle_id: &str) -> Result<Option<BundleStatus>> {
+        let statuses = self.get_bundle_statuses(&[bundle_id.to_string()])?;
+
+        Ok(statuses.into_iter().next().flatten())
+    }
+}
+
+pub fn send_bundle_request(transactions: &[String]) -> (RpcRequest, Value) {
+    (
+        RpcRequest::Custom {
+            method: "sendBundle",
+        },
+        json!([transactions, { "encoding": "base64" }]),
+    )
+}
+
+pub fn get_bundle_statuses_request(bundle_ids: &[String]) -> (RpcRequest, Value) {
+    (
+        RpcRequest::Custom {
+            method: "getBundleStatuses",
+        },
+        json!([bundle_ids]),
+    )
+}
@@ -0,0 +1,481 @@
+use std::{collections::HashMap, fmt, str::FromStr, sync::OnceLock};
+
+use eyre::{eyre, Result};
+use num_traits::FromPrimitive;
+use serde_json::Value;
+use solana_message::VersionedMessage;
+use solana_pubkey::Pubkey;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use spl_associated_token_account::error::AssociatedTokenAccountError;
+
+use crate::manage_instructions::{KAMINO_PROGRAM_ID, SPL_STAKE_POOL_PROGRAM_ID};
+
+const BORING_VAULT_IDL: &str = include_str!("../../../idls/boring_vault_svm.json");
+const BORING_QUEUE_IDL: &str = include_str!("../../../idls/boring_onchain_queue.json");
+
+// Program names decoded errors carry, the IDL names for Anchor programs.
+pub const BORING_VAULT_PROGRAM: &str = "boring_vault_svm";
+pub const BORING_QUEUE_PROGRAM: &str = "boring_onchain_queue";
+pub const SPL_TOKEN_PROGRAM: &str = "spl_token";
+pub const SPL_TOKEN_2022_PROGRAM: &str = "spl_token_2022";
+pub const ASSOCIATED_TOKEN_PROGRAM: &str = "spl_associated_token_account";
+pub const KAMINO_LENDING_PROGRAM: &str = "kamino_lending";
+pub const SPL_STAKE_POOL_PROGRAM: &str = "spl_stake_pool";
+
+// Kamino lending's `LendingError`. Codes added by later klend releases are usually decoded from
+// the Anchor error log, otherwise they keep their code with a generic message. Add the IDL of the
+// deployed release with `ErrorRegistry::add_idl` to name them.
+#[rustfmt::skip]
+const KAMINO_LENDING_ERRORS: &[(u32, &str, &str)] = &[
+    (6000, "InvalidMarketAuthority", "Market authority is invalid"),
+    (6001, "InvalidMarketOwner", "Market owner is invalid"),
+    (6002, "InvalidAccountOwner", "Input account owner is not the program address"),
+    (6003, "InvalidAmount", "Input amount is invalid"),
+    (6004, "InvalidConfig", "Input config value is invalid"),
+    (6005, "InvalidSigner", "Input account must be a signer"),
+    (6006, "InvalidAccountInput", "Invalid account input"),
+    (6007, "MathOverflow", "Math operation overflow"),
+    (6008, "InsufficientLiquidity", "Insufficient liquidity available"),
+    (6009, "ReserveStale", "Reserve state needs to be refreshed"),
+    (6010, "WithdrawTooSmall", "Withdraw amount too small"),
+    (6011, "WithdrawTooLarge", "Withdraw amount too large"),
+    (6012, "BorrowTooSmall", "Borrow amount too small to receive liquidity after fees"),
+    (6013, "BorrowTooLarge", "Borrow amount too large for deposited collateral"),
+    (6014, "RepayTooSmall", "Repay amount too small to transfer liquidity"),
+    (6015, "LiquidationTooSmall", "Liquidation amount too small to receive collateral"),
+    (6016, "ObligationHealthy", "Cannot liquidate healthy obligations"),
+    (6017, "ObligationStale", "Obligation state needs to be refreshed"),
+    (6018, "ObligationReserveLimit", "Obligation reserve limit exceeded"),
+    (6019, "InvalidObligationOwner", "Obligation owner is invalid"),
+    (6020, "ObligationDepositsEmpty", "Obligation deposits are empty"),
+    (6021, "ObligationBorrowsEmpty", "Obligation borrows are empty"),
+    (6022, "ObligationDepositsZero", "Obligation deposits have zero value"),
+    (6023, "ObligationBorrowsZero", "Obligation borrows have zero value"),
+    (6024, "InvalidObligationCollateral", "Invalid obligation collateral"),
+    (6025, "InvalidObligationLiquidity", "Invalid obligation liquidity"),
+    (6026, "ObligationCollateralEmpty", "Obligation collateral is empty"),
+    (6027, "ObligationLiquidityEmpty", "Obligation liquidity is empty"),
+    (6028, "NegativeInterestRate", "Interest rate is negative"),
+    (6029, "InvalidOracleConfig", "Input oracle config is invalid"),
+    (6030, "InsufficientProtocolFeesToRedeem", "Insufficient protocol fees to claim or no liquidity available"),
+    (6031, "FlashBorrowCpi", "No cpi flash borrows allowed"),
+    (6032, "NoFlashRepayFound", "No corresponding repay found for flash borrow"),
+    (6033, "InvalidFlashRepay", "Invalid repay found"),
+    (6034, "FlashRepayCpi", "No cpi flash repays allowed"),
+    (6035, "MultipleFlashBorrows", "Multiple flash borrows not allowed in the same transaction"),
+    (6036, "FlashLoansDisabled", "Flash loans are disabled for this reserve"),
+    (6037, "SwitchboardV2Error", "Switchboard error"),
+    (6038, "CouldNotDeserializeScope", "Cannot deserialize the scope price account"),
+    (6039, "PriceTooOld", "Price too old"),
+    (6040, "PriceTooDivergentFromTwap", "Price too divergent from twap"),
+    (6041, "InvalidTwapPrice", "Invalid twap price"),
+    (6042, "GlobalEmergencyMode", "Emergency mode is enabled"),
+    (6043, "InvalidFlag", "Invalid lending market config"),
+    (6044, "PriceNotValid", "Price is not valid"),
+    (6045, "PriceIsBiggerThanHeuristic", "Price is bigger than allowed by heuristic"),
+    (6046, "PriceIsLowerThanHeuristic", "Price lower than allowed by heuristic"),
+    (6047, "PriceIsZero", "Price is zero"),
+    (6048, "PriceConfidenceTooWide", "Price confidence too wide"),
+    (6049, "IntegerOverflow", "Conversion between integers failed"),
+    (6050, "NoFarmForReserve", "This reserve does not have a farm"),
+    (6051, "IncorrectInstructionInPosition", "Wrong instruction at expected position"),
+    (6052, "NoPriceFound", "No price found"),
+    (6053, "InvalidTwapConfig", "Invalid Twap configuration: Twap is enabled but one of the enabled price doesn't have a twap"),
+    (6054, "InvalidPythPriceAccount", "Pyth price account does not match configuration"),
+    (6055, "InvalidSwitchboardAccount", "Switchboard account(s) do not match configuration"),
+    (6056, "InvalidScopePriceAccount", "Scope price account does not match configuration"),
+    (6057, "ObligationCollateralLtvZero", "The obligation has one collateral with an LTV set to 0. Withdraw it before withdrawing other collaterals"),
+    (6058, "InvalidObligationSeedsValue", "Seeds must be default pubkeys for tag 0, and mint addresses for tag 1 or 2"),
+    (6059, "DeprecatedInvalidObligationId", "[DEPRECATED] Obligation id must be 0"),
+    (6060, "InvalidBorrowRateCurvePoint", "Invalid borrow rate curve point"),
+    (6061, "InvalidUtilizationRate", "Invalid utilization rate"),
+    (6062, "CannotSocializeObligationWithCollateral", "Obligation hasn't been fully liquidated and debt cannot be socialized."),
+    (6063, "ObligationEmpty", "Obligation has no borrows or deposits."),
+    (6064, "WithdrawalCapReached", "Withdrawal cap is reached"),
+    (6065, "LastTimestampGreaterThanCurrent", "The last interval start timestamp is greater than the current timestamp"),
+    (6066, "LiquidationRewardTooSmall", "The reward amount is less than the minimum acceptable received liquidity"),
+    (6067, "IsolatedAssetTierViolation", "Isolated Asset Tier Violation"),
+    (6068, "InconsistentElevationGroup", "The obligation's elevation group and the reserve's are not the same"),
+    (6069, "InvalidElevationGroup", "The elevation group chosen for the reserve does not exist in the lending market"),
+    (6070, "InvalidElevationGroupConfig", "The elevation group updated has wrong parameters set"),
+    (6071, "UnhealthyElevationGroupLtv", "The current obligation must have most or all its debt repaid before changing the elevation group"),
+    (6072, "ElevationGroupNewLoansDisabled", "Elevation group does not accept any new loans or any new borrows/withdrawals"),
+    (6073, "ReserveDeprecated", "Reserve was deprecated, no longer usable"),
+    (6074, "ReferrerAccountNotInitialized", "Referrer account not initialized"),
+    (6075, "ReferrerAccountMintMissmatch", "Referrer account mint does not match the operation reserve mint"),
+    (6076, "ReferrerAccountWrongAddress", "Referrer account address is not a valid program address"),
+    (6077, "ReferrerAccountReferrerMissmatch", "Referrer account referrer does not match the owner referrer"),
+    (6078, "ReferrerAccountMissing", "Referrer account missing for obligation with referrer"),
+    (6079, "InsufficientReferralFeesToRedeem", "Insufficient referral fees to claim or no liquidity available"),
+    (6080, "CpiDisabled", "CPI disabled for this instruction"),
+    (6081, "ShortUrlNotAsciiAlphanumeric", "Referrer short_url is not ascii alphanumeric"),
+    (6082, "ReserveObsolete", "Reserve is marked as obsolete"),
+    (6083, "ElevationGroupAlreadyActivated", "Obligation already part of the same elevation group"),
+    (6084, "ObligationInObsoleteReserve", "Obligation has a deposit or borrow in an obsolete reserve"),
+    (6085, "ReferrerStateOwnerMismatch", "Referrer state owner does not match the given signer"),
+    (6086, "UserMetadataOwnerAlreadySet", "User metadata owner is already set"),
+    (6087, "CollateralNonLiquidatable", "This collateral cannot be liquidated (LTV set to 0)"),
+    (6088, "BorrowingDisabled", "Borrowing is disabled"),
+    (6089, "BorrowLimitExceeded", "Cannot borrow above borrow limit"),
+    (6090, "DepositLimitExceeded", "Cannot deposit above deposit limit"),
+    (6091, "BorrowingDisabledOutsideElevationGroup", "Reserve does not accept any new borrows outside elevation group"),
+    (6092, "NetValueRemainingTooSmall", "Net value remaining too small"),
+    (6093, "WorseLtvBlocked", "Cannot get the obligation in a worse position"),
+    (6094, "LiabilitiesBiggerThanAssets", "Cannot have more liabilities than assets in a position"),
+    (6095, "ReserveTokenBalanceMismatch", "Reserve state and token account cannot drift"),
+    (6096, "ReserveVaultBalanceMismatch", "Reserve token account has been unexpectedly modified"),
+    (6097, "ReserveAccountingMismatch", "Reserve internal state accounting has been unexpectedly modified"),
+    (6098, "BorrowingAboveUtilizationRateDisabled", "Borrowing above set utilization rate is disabled"),
+    (6099, "LiquidationBorrowFactorPriority", "Liquidation must prioritize the debt with the highest borrow factor"),
+    (6100, "LiquidationLowestLiquidationLtvPriority", "Liquidation must prioritize the collateral with the lowest liquidation LTV"),
+    (6101, "ElevationGroupBorrowLimitExceeded", "Elevation group borrow limit exceeded"),
+    (6102, "ElevationGroupWithoutDebtReserve", "The elevation group does not have a debt reserve defined"),
+    (6103, "ElevationGroupMaxCollateralReserveZero", "The elevation group does not allow any collateral reserves"),
+    (6104, "ElevationGroupHasAnotherDebtReserve", "In elevation group attempt to borrow from a reserve that is not the debt reserve"),
+    (6105, "ElevationGroupDebtReserveAsCollateral", "The elevation group's debt reserve cannot be used as a collateral reserve"),
+    (6106, "ObligationCollateralExceedsElevationGroupLimit", "Obligation have more collateral than the maximum allowed by the elevation group"),
+    (6107, "ObligationElevationGroupMultipleDebtReserve", "Obligation is an elevation group but have more than one debt reserve"),
+    (6108, "UnsupportedTokenExtension", "Mint has a token (2022) extension that is not supported"),
+    (6109, "InvalidTokenAccount", "Can't have an spl token mint with a t22 account"),
+    (6110, "DepositDisabledOutsideElevationGroup", "Can't deposit into this reserve outside elevation group"),
+    (6111, "CannotCalculateReferralAmountDueToSlotsMismatch", "Cannot calculate referral amount due to slots mismatch"),
+    (6112, "ObligationOwnersMustMatch", "Obligation owners must match"),
+    (6113, "ObligationsMustMatch", "Obligations must match"),
+    (6114, "LendingMarketsMustMatch", "Lending markets must match"),
+    (6115, "ObligationCurrentlyMarkedForDeleveraging", "Obligation is already marked for deleveraging"),
+    (6116, "MaximumWithdrawValueZero", "Maximum withdrawable value of this collateral is zero, LTV needs improved"),
+    (6117, "ZeroMaxLtvAssetsInDeposits", "No max LTV 0 assets allowed in deposits for repay and withdraw"),
+    (6118, "LowestLtvAssetsPriority", "Withdrawing must prioritize the collateral with the lowest reserve max-LTV"),
+    (6119, "WorseLtvThanUnhealthyLtv", "Cannot get the obligation liquidatable"),
+    (6120, "FarmAccountsMissing", "Farm accounts to refresh are missing"),
+    (6121, "RepayTooSmallForFullLiquidation", "Repay amount is too small to satisfy the mandatory full liquidation"),
+    (6122, "InsufficientRepayAmount", "Liquidator provided repay amount lower than required by liquidation rules"),
+    (6123, "OrderIndexOutOfBounds", "Obligation order of the given index cannot exist"),
+    (6124, "InvalidOrderConfig", "Given order configuration has wrong parameters"),
+    (6125, "OrderConfigNotApplicable", "Given order configuration cannot be used with the current state of the obligation"),
+];
+
+// The SPL stake pool program's `StakePoolError`, which the Jito stake pool runs on.
+#[rustfmt::skip]
+const SPL_STAKE_POOL_ERRORS: &[(u32, &str, &str)] = &[
+    (0, "AlreadyInUse", "Stake pool account already in use"),
+    (1, "InvalidProgramAddress", "Invalid program address generated from bump seed and key"),
+    (2, "InvalidState", "Stake pool state is invalid"),
+    (3, "CalculationFailure", "Calculation failure"),
+    (4, "FeeTooHigh", "Stake pool fee > 1"),
+    (5, "WrongAccountMint", "Token account is associated with the wrong mint"),
+    (6, "WrongManager", "Wrong pool manager account"),
+    (7, "SignatureMissing", "Required signature is missing"),
+    (8, "InvalidValidatorStakeList", "Invalid validator stake list account"),
+    (9, "InvalidFeeAccount", "Invalid manager fee account"),
+    (10, "WrongPoolMint", "Specified pool mint account is wrong"),
+    (11, "WrongStakeStake", "Stake account is not in the state expected by the program"),
+    (12, "UserStakeNotActive", "User stake is not active"),
+    (13, "ValidatorAlreadyAdded", "Stake account voting for this validator already exists in the pool"),
+    (14, "ValidatorNotFound", "Stake account for this validator not found in the pool"),
+    (15, "InvalidStakeAccountAddress", "Stake account address not properly derived from the validator address"),
+    (16, "StakeListOutOfDate", "Identify validator stake accounts with old balances and update them"),
+    (17, "StakeListAndPoolOutOfDate", "First update old validator stake account balances and then pool stake balance"),
+    (18, "UnknownValidatorStakeAccount", "Validator stake account is not found in the list storage"),
+    (19, "WrongMintingAuthority", "Wrong minting authority set for mint pool account"),
+    (20, "UnexpectedValidatorListAccountSize", "The size of the given validator stake list does match the expected amount"),
+    (21, "WrongStaker", "Wrong pool staker account"),
+    (22, "NonZeroPoolTokenSupply", "Pool token supply is not zero on initialization"),
+    (23, "StakeLamportsNotEqualToMinimum", "The lamports in the validator stake account is not equal to the minimum"),
+    (24, "IncorrectDepositVoteAddress", "The provided deposit stake account is not delegated to the preferred deposit vote account"),
+    (25, "IncorrectWithdrawVoteAddress", "The provided withdraw stake account is not the preferred deposit vote account"),
+    (26, "InvalidMintFreezeAuthority", "The mint has an invalid freeze authority"),
+    (27, "FeeIncreaseTooHigh", "The fee cannot increase by a factor exceeding the stipulated ratio"),
+    (28, "WithdrawalTooSmall", "Not enough pool tokens provided to withdraw stake with one lamport"),
+    (29, "DepositTooSmall", "Not enough lamports provided for deposit to result in one pool token"),
+    (30, "InvalidStakeDepositAuthority", "Provided stake deposit authority does not match the program's"),
+    (31, "InvalidSolDepositAuthority", "Provided sol deposit authority does not match the program's"),
+    (32, "InvalidPreferredValidator", "Provided preferred validator is invalid"),
+    (33, "TransientAccountInUse", "Provided validator stake account already has a transient stake account in use"),
+    (34, "InvalidSolWithdrawAuthority", "Provided sol withdraw authority does not match the program's"),
+    (35, "SolWithdrawalTooLarge", "Too much SOL withdrawn from the stake pool's reserve account"),
+    (36, "InvalidMetadataAccount", "Provided metadata account does not match metadata account derived for pool mint"),
+    (37, "UnsupportedMintExtension", "The mint has an unsupported extension"),
+    (38, "UnsupportedFeeAccountExtension", "The fee account has an unsupported extension"),
+    (39, "ExceededSlippage", "Instruction exceeds desired slippage limit"),
+    (40, "IncorrectMintDecimals", "Provided mint does not have 9 decimals to match SOL"),
+    (41, "ReserveDepleted", "Pool reserve does not have enough lamports to fund rent-exempt reserve in split destination"),
+    (42, "MissingRequiredSysvar", "Missing required sysvar account"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedError {
+    pub instruction_index: u8,
+    // Program that raised the error. The innermost `Program <id> failed` log when the error came
+    // out of a CPI, otherwise the program of the failing instruction.
+    pub program_id: Option<Pubkey>,
+    // Registry name of `program_id`, e.g. "boring_vault_svm" or "kamino_lending".
+    pub program_name: Option<String>,
+    pub code: Option<u32>,
+    pub name: Option<String>,
+    pub message: Option<String>,
+}
+
+impl fmt::Display for DecodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Instruction {} failed", self.instruction_index)?;
+        match (&self.program_name, &self.program_id) {
+            (Some(program_name), _) => write!(f, " in {}", program_name)?,
+            (None, Some(program_id)) => write!(f, " in {}", program_id)?,
+            (None, None) => {}
+        }
+        if let Some(name) = &self.name {
+            write!(f, ": {}", name)?;
+        }
+        if let Some(code) = self.code {
+            write!(f, " ({})", code)?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for DecodedError {}
+
+struct ProgramErrors {
+    name: String,
+    errors: HashMap<u32, (String, String)>,
+}
+
+// Named errors of the programs vault transactions touch, keyed by program id and error code.
+// `Default` holds the bundled boring vault and queue IDLs, SPL Token, Token-2022, the associated
+// token program, Kamino lending and the SPL stake pool.
+pub struct ErrorRegistry {
+    programs: HashMap<Pubkey, ProgramErrors>,
+}
+
+impl Default for ErrorRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .add_idl(BORING_VAULT_IDL)
+            .expect("Invalid boring vault IDL");
+        registry
+            .add_idl(BORING_QUEUE_IDL)
+            .expect("Invalid boring queue IDL");
+        registry.add(
+            spl_token::ID,
+            SPL_TOKEN_PROGRAM,
+            enum_errors::<spl_token::error::TokenError>(),
+        );
+        registry.add(
+            spl_token_2022::ID,
+            SPL_TOKEN_2022_PROGRAM,
+            enum_errors::<spl_token_2022::error::TokenError>(),
+        );
+        registry.add(
+            spl_associated_token_account::ID,
+            ASSOCIATED_TOKEN_PROGRAM,
+            enum_errors::<AssociatedTokenAccountError>(),
+        );
+        registry.add(
+            KAMINO_PROGRAM_ID,
+            KAMINO_LENDING_PROGRAM,
+            table_errors(KAMINO_LENDING_ERRORS),
+        );
+        registry.add(
+            SPL_STAKE_POOL_PROGRAM_ID,
+            SPL_STAKE_POOL_PROGRAM,
+            table_errors(SPL_STAKE_POOL_ERRORS),
+        );
+
+        registry
+    }
+}
+
+impl ErrorRegistry {
+    pub fn empty() -> Self {
+        Self {
+            programs: HashMap::new(),
+        }
+    }
+
+    // Replaces whatever was registered for `program_id`.
+    pub fn add(
+        &mut self,
+        program_id: Pubkey,
+        name: impl Into<String>,
+        errors: impl IntoIterator<Item = (u32, String, String)>,
+    ) {
+        let errors = errors
+            .into_iter()
+            .map(|(code, name, message)| (code, (name, message)))
+            .collect();
+
+        self.programs.insert(
+            program_id,
+            ProgramErrors {
+                name: name.into(),
+                errors,
+            },
+        );
+    }
+
+    // Registers the `errors` of an Anchor IDL under its `address` and `metadata.name`.
+    pub fn add_idl(&mut self, idl: &str) -> Result<()> {
+        let idl: Value = serde_json::from_str(idl)?;
+        let program_id = idl["address"]
+            .as_str()
+            .ok_or_else(|| eyre!("IDL has no address"))?;
+        let program_id = Pubkey::from_str(program_id)?;
+        let name = idl["metadata"]["name"]
+            .as_str()
+            .ok_or_else(|| eyre!("IDL has no metadata name"))?;
+
+        let mut errors = vec![];
+        for error in idl["errors"].as_array().into_iter().flatten() {
+            let code = error["code"]
+                .as_u64()
+                .and_then(|code| u32::try_from(code).ok())
+                .ok_or_else(|| eyre!("Invalid error code in {} IDL", name))?;
+            let error_name = error["name"]
+                .as_str()
+                .ok_or_else(|| eyre!("Error {} in {} IDL has no name", code, name))?;
+            let message = error["msg"].as_str().unwrap_or(error_name);
+
+            errors.push((code, error_name.to_string(), message.to_string()));
+        }
+
+        self.add(program_id, name, errors);
+
+        Ok(())
+    }
+
+    pub fn program_name(&self, program_id: &Pubkey) -> Option<&str> {
+        self.programs
+            .get(program_id)
+            .map(|program| program.name.as_str())
+    }
+
+    // Name and message of a custom error code.
+    pub fn lookup(&self, program_id: &Pubkey, code: u32) -> Option<(&str, &str)> {
+        self.programs
+            .get(program_id)?
+            .errors
+            .get(&code)
+            .map(|(name, message)| (name.as_str(), message.as_str()))
+    }
+
+    // Decodes the failing instruction of a transaction error. `program_ids` are the programs of
+    // the transaction's instructions in order, see `instruction_program_ids`. The Anchor error log
+    // wins over the registry when present, since it also covers Anchor's own error codes.
+    pub fn decode(
+        &self,
+        err: &TransactionError,
+        logs: &[String],
+        program_ids: &[Pubkey],
+    ) -> Option<DecodedError> {
+        let TransactionError::InstructionError(instruction_index, instruction_error) = err else {
+            return None;
+        };
+
+        let program_id = logs
+            .iter()
+            .find_map(|log| parse_failed_program(log))
+            .or_else(|| program_ids.get(*instruction_index as usize).copied());
+
+        let mut decoded = DecodedError {
+            instruction_index: *instruction_index,
+            program_id,
+            program_name: program_id
+                .and_then(|program_id| self.program_name(&program_id))
+                .map(str::to_string),
+            code: None,
+            name: None,
+            message: None,
+        };
+
+        match instruction_error {
+            InstructionError::Custom(code) => decoded.code = Some(*code),
+            other => decoded.message = Some(other.to_string()),
+        }
+
+        if let Some((name, code, message)) =
+            logs.iter().rev().find_map(|log| parse_anchor_error(log))
+        {
+            decoded.code = Some(code);
+            decoded.name = Some(name);
+            decoded.message = Some(message);
+        } else if let (Some(program_id), Some(code)) = (program_id, decoded.code) {
+            if let Some((name, message)) = self.lookup(&program_id, code) {
+                decoded.name = Some(name.to_string());
+                decoded.message = Some(message.to_string());
+            }
+        }
+
+        // Codes the registry doesn't know keep the generic "custom program error" message.
+        if decoded.message.is_none() {
+            decoded.message = Some(instruction_error.to_string());
+        }
+
+        Some(decoded)
+    }
+}
+
+pub fn error_registry() -> &'static ErrorRegistry {
+    static REGISTRY: OnceLock<ErrorRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ErrorRegistry::default)
+}
+
+// Decodes against the default registry.
+pub fn decode_transaction_error(
+    err: &TransactionError,
+    logs: &[String],
+    program_ids: &[Pubkey],
+) -> Option<DecodedError> {
+    error_registry().decode(err, logs, program_ids)
+}
+
+pub fn instruction_program_ids(message: &VersionedMessage) -> Vec<Pubkey> {
+    let account_keys = message.static_account_keys();
+
+    message
+        .instructions()
+        .iter()
+        .map(|instruction| *instruction.program_id(account_keys))
+        .collect()
+}
+
+fn enum_errors<E: FromPrimitive + fmt::Debug + fmt::Display>() -> Vec<(u32, String, String)> {
+    (0..)
+        .map_while(|code| E::from_u32(code).map(|error| (code, error)))
+        .map(|(code, error)| (code, format!("{:?}", error), error.to_string()))
+        .collect()
+}
+
+fn table_errors(errors: &[(u32, &str, &str)]) -> Vec<(u32, String, String)> {
+    errors
+        .iter()
+        .map(|(code, name, message)| (*code, name.to_string(), message.to_string()))
+        .collect()
+}
+
+// Parses `Program <id> failed: <reason>`.
+fn parse_failed_program(log: &str) -> Option<Pubkey> {
+    let rest = log.strip_prefix("Program ")?;
+    let (program_id, _) = rest.split_once(" failed")?;
+
+    Pubkey::from_str(program_id).ok()
+}
+
+// Parses `... Error Code: <name>. Error Number: <code>. Error Message: <message>.`
+fn parse_anchor_error(log: &str) -> Option<(String, u32, String)> {
+    if !log.contains("AnchorError") {
+        return None;
+    }
+
+    let (_, rest) = log.split_once("Error Code: ")?;
+    let (name, rest) = rest.split_once(". Error Number: ")?;
+    let (code, message) = rest.split_once(". Error Message: ")?;
+    let message = message.strip_suffix('.').unwrap_or(message);
+
+    Some((name.to_string(), code.parse().ok()?, message.to_string()))
+}
@@ -0,0 +1,206 @@
+use std::{
+    env, fmt,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use eyre::{eyre, Result};
+use solana_derivation_path::DerivationPath;
+use solana_keypair::{
+    keypair_from_seed_phrase_and_passphrase, seed_derivable::keypair_from_seed_and_derivation_path,
+    Keypair,
+};
+use solana_seed_phrase::generate_seed_from_seed_phrase_and_passphrase;
+
+pub const DEFAULT_KEYPAIR_PATH: &str = "~/.config/solana/id.json";
+
+// Where to load a keypair from, written as a signer spec:
+//   file:<path> or a bare path          Solana CLI keypair file, `~` expands to $HOME
+//   env:<VAR>                           JSON byte array or base58 secret key in $VAR
+//   prompt:[?key=..|?full-path=..]      seed phrase and passphrase typed on stdin
+//   mnemonic:<words>[?key=..|?full-path=..][&passphrase=..]
+// `key=<account>/<change>` derives m/44'/501'/<account>'/<change>' and `full-path=m/..` any path.
+// Without either, a seed phrase resolves the way `solana-keygen recover` does by default.
+#[derive(Clone, PartialEq, Eq)]
+pub enum KeypairSource {
+    File(PathBuf),
+    Env(String),
+    Prompt {
+        derivation_path: Option<DerivationPath>,
+    },
+    Mnemonic {
+        phrase: String,
+        passphrase: String,
+        derivation_path: Option<DerivationPath>,
+    },
+}
+
+impl KeypairSource {
+    pub fn resolve(&self) -> Result<Keypair> {
+        match self {
+            Self::File(path) => solana_keypair::read_keypair_file(path)
+                .map_err(|e| eyre!("Failed to read keypair file {}: {}", path.display(), e)),
+            Self::Env(var) => {
+                let value =
+                    env::var(var).map_err(|e| eyre!("Failed to read env var {}: {}", var, e))?;
+                keypair_from_secret(value.trim())
+                    .map_err(|e| eyre!("Invalid keypair in env var {}: {}", var, e))
+            }
+            Self::Prompt { derivation_path } => {
+                let phrase = prompt("Seed phrase: ")?;
+                let passphrase = prompt("Passphrase (empty for none): ")?;
+                keypair_from_seed_phrase(&phrase, &passphrase, derivation_path.clone())
+            }
+            Self::Mnemonic {
+                phrase,
+                passphrase,
+                derivation_path,
+            } => keypair_from_seed_phrase(phrase, passphrase, derivation_path.clone()),
+        }
+    }
+}
+
+impl FromStr for KeypairSource {
+    type Err = eyre::Report;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Err(eyre!("Empty signer spec"));
+        }
+
+        let Some((scheme, rest)) = spec.split_once(':') else {
+            return Ok(Self::File(expand_home(spec)));
+        };
+
+        match scheme {
+            "file" => Ok(Self::File(expand_home(rest))),
+            "env" => {
+                if rest.is_empty() {
+                    return Err(eyre!("Missing env var name in signer spec"));
+                }
+                Ok(Self::Env(rest.to_string()))
+            }
+            "prompt" => {
+                let (_, query) = split_query(rest)?;
+                Ok(Self::Prompt {
+                    derivation_path: query.derivation_path,
+                })
+            }
+            "mnemonic" => {
+                let (phrase, query) = split_query(rest)?;
+                let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+                if phrase.is_empty() {
+                    return Err(eyre!("Missing seed phrase in signer spec"));
+                }
+                Ok(Self::Mnemonic {
+                    phrase,
+                    passphrase: query.passphrase,
+                    derivation_path: query.derivation_path,
+                })
+            }
+            // Windows style paths, e.g. C:\keys\id.json
+            _ if scheme.len() == 1 => Ok(Self::File(PathBuf::from(spec))),
+            _ => Err(eyre!("Unknown signer spec scheme {}:", scheme)),
+        }
+    }
+}
+
+// Never prints secrets.
+impl fmt::Debug for KeypairSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Env(var) => write!(f, "env:{}", var),
+            Self::Prompt { derivation_path } => write!(f, "prompt:{:?}", derivation_path),
+            Self::Mnemonic {
+                derivation_path, ..
+            } => write!(f, "mnemonic:<redacted>{:?}", derivation_path),
+        }
+    }
+}
+
+pub fn read_keypair(spec: &str) -> Result<Keypair> {
+    spec.parse::<KeypairSource>()?.resolve()
+}
+
+#[derive(Default)]
+struct SpecQuery {
+    passphrase: String,
+    derivation_path: Option<DerivationPath>,
+}
+
+fn split_query(rest: &str) -> Result<(&str, SpecQuery)> {
+    let Some((head, query)) = rest.split_once('?') else {
+        return Ok((rest, SpecQuery::default()));
+    };
+
+    let mut parsed = SpecQuery::default();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let derivation_path = match key {
+            "key" => DerivationPath::from_key_str(value),
+            "full-path" => DerivationPath::from_absolute_path_str(value),
+            "passphrase" => {
+                parsed.passphrase = value.to_string();
+                continue;
+            }
+            _ => return Err(eyre!("Unknown signer spec query {}", key)),
+        }
+        .map_err(|e| eyre!("Invalid derivation path {}: {}", value, e))?;
+
+        if parsed.derivation_path.is_some() {
+            return Err(eyre!("Signer spec has more than one derivation path"));
+        }
+        parsed.derivation_path = Some(derivation_path);
+    }
+
+    Ok((head, parsed))
+}
+
+fn keypair_from_seed_phrase(
+    phrase: &str,
+    passphrase: &str,
+    derivation_path: Option<DerivationPath>,
+) -> Result<Keypair> {
+    match derivation_path {
+        Some(derivation_path) => {
+            let seed = generate_seed_from_seed_phrase_and_passphrase(phrase, passphrase);
+            keypair_from_seed_and_derivation_path(&seed, Some(derivation_path))
+        }
+        None => keypair_from_seed_phrase_and_passphrase(phrase, passphrase),
+    }
+    .map_err(|e| eyre!("Failed to derive keypair from seed phrase: {}", e))
+}
+
+fn keypair_from_secret(secret: &str) -> Result<Keypair> {
+    let bytes: Vec<u8> = if secret.starts_with('[') {
+        serde_json::from_str(secret)?
+    } else {
+        bs58::decode(secret).into_vec()?
+    };
+
+    Keypair::try_from(bytes.as_slice()).map_err(|e| eyre!("{}", e))
+}
+
+fn prompt(message: &str) -> Result<String> {
+    let mut stderr = io::stderr();
+    stderr.write_all(message.as_bytes())?;
+    stderr.flush()?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+
+    Ok(line.trim().to_string())
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => match env::var("HOME") {
+            Ok(home) => PathBuf::from(format!("{}{}", home, rest)),
+            Err(_) => PathBuf::from(path),
+        },
+        _ => PathBuf::from(path),
+    }
+}
@@ -1,16 +1,28 @@
+pub mod account_cache;
+pub mod account_source;
 pub mod bindings;
+pub mod block_engine;
 pub mod constants;
 pub mod discriminator;
+pub mod errors;
 pub mod json;
 pub mod keypair;
+pub mod keypair_source;
 pub mod pdas;
 pub mod rpc;
+pub mod signer;
 pub mod token;
+pub use account_cache::*;
+pub use account_source::*;
 pub use bindings::*;
+pub use block_engine::*;
 pub use constants::*;
 pub use discriminator::*;
+pub use errors::*;
 pub use json::*;
 pub use keypair::*;
+pub use keypair_source::*;
 pub use pdas::*;
 pub use rpc::*;
+pub use signer::*;
 pub use token::*;
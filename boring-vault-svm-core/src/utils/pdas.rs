@@ -1,8 +1,8 @@
 use super::bindings::boring_vault_svm;
 use super::constants::*;
+use super::AccountSource;
 use eyre::Result;
 use solana_address_lookup_table_interface::instruction::derive_lookup_table_address;
-use solana_client::rpc_client::RpcClient;
 use solana_instruction::{AccountMeta, Instruction};
 use solana_pubkey::Pubkey;
 use solana_sdk::hash::hash;
@@ -64,6 +64,34 @@ pub fn get_asset_data_pda(vault_state_pda: Pubkey, mint: Pubkey) -> Pubkey {
     asset_data_pda
 }
 
+// Addresses of a vault, derived from its id alone so they're known before it is deployed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaultPdas {
+    pub vault_id: u64,
+    pub vault_state: Pubkey,
+    pub share_mint: Pubkey,
+}
+
+impl VaultPdas {
+    pub fn new(vault_id: u64) -> Self {
+        let vault_state = get_vault_state_pda(vault_id);
+
+        Self {
+            vault_id,
+            vault_state,
+            share_mint: get_vault_share_mint(vault_state),
+        }
+    }
+
+    pub fn sub_account(&self, sub_account: u8) -> Pubkey {
+        get_vault_pda(self.vault_id, sub_account)
+    }
+
+    pub fn asset_data(&self, mint: Pubkey) -> Pubkey {
+        get_asset_data_pda(self.vault_state, mint)
+    }
+}
+
 pub fn get_user_metadata_pda(user_pubkey: &Pubkey, program_id: &Pubkey) -> Pubkey {
     Pubkey::find_program_address(&[b"user_meta", &user_pubkey.to_bytes()], program_id).0
 }
@@ -103,8 +131,8 @@ pub fn get_obligation_farm(farm_state: &Pubkey, delegatee: &Pubkey, program_id:
     .0
 }
 
-pub fn ensure_ata(
-    client: &RpcClient,
+pub fn ensure_ata<C: AccountSource>(
+    client: &C,
     signer: &Pubkey,
     owner: &Pubkey,
     mint: &Pubkey,
@@ -2,16 +2,84 @@ use std::ptr::read_unaligned;
 
 use anchor_lang::prelude::borsh::{self, BorshDeserialize};
 use eyre::{eyre, Result};
-use solana_client::rpc_client::RpcClient;
+use solana_account::Account;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_client::rpc_request::MAX_MULTIPLE_ACCOUNTS;
+use solana_message::AddressLookupTableAccount;
 use solana_pubkey::Pubkey;
 
-pub fn get_account_data<T: BorshDeserialize>(client: &RpcClient, address: &Pubkey) -> Result<T> {
+use super::AccountSource;
+
+pub fn get_account_data<C: AccountSource, T: BorshDeserialize>(
+    client: &C,
+    address: &Pubkey,
+) -> Result<T> {
     let account = client.get_account(address)?;
     borsh::BorshDeserialize::deserialize(&mut &account.data[..])
         .map_err(|_| eyre!("Failed to deserialize data"))
 }
 
-pub fn get_account_data_unsafe<T>(client: &RpcClient, address: &Pubkey) -> Result<T> {
+pub fn get_account_data_unsafe<C: AccountSource, T>(client: &C, address: &Pubkey) -> Result<T> {
     let account = client.get_account(address)?;
     Ok(unsafe { read_unaligned(account.data.as_ptr() as *const T) })
 }
+
+// Same as `get_account_data_unsafe` but skips the 8 byte anchor discriminator.
+pub fn get_anchor_account_data_unsafe<C: AccountSource, T>(
+    client: &C,
+    address: &Pubkey,
+) -> Result<T> {
+    let account = client.get_account(address)?;
+    read_anchor_account_data_unsafe(address, &account.data)
+}
+
+pub fn read_anchor_account_data_unsafe<T>(address: &Pubkey, data: &[u8]) -> Result<T> {
+    if data.len() < 8 + std::mem::size_of::<T>() {
+        return Err(eyre!(
+            "Account {} data too small: {} bytes",
+            address,
+            data.len()
+        ));
+    }
+    Ok(unsafe { read_unaligned(data[8..].as_ptr() as *const T) })
+}
+
+pub fn get_lookup_table_accounts<C: AccountSource>(
+    client: &C,
+    addresses: &[Pubkey],
+) -> Result<Vec<AddressLookupTableAccount>> {
+    let accounts = client.get_multiple_accounts(addresses)?;
+
+    addresses
+        .iter()
+        .zip(accounts)
+        .map(|(address, account)| read_lookup_table_account(address, account))
+        .collect()
+}
+
+pub fn read_lookup_table_account(
+    address: &Pubkey,
+    account: Option<Account>,
+) -> Result<AddressLookupTableAccount> {
+    let account = account.ok_or_else(|| eyre!("Lookup table {} does not exist", address))?;
+    let table = AddressLookupTable::deserialize(&account.data)
+        .map_err(|e| eyre!("Failed to deserialize lookup table {}: {}", address, e))?;
+
+    Ok(AddressLookupTableAccount {
+        key: *address,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+// `get_multiple_accounts` split into requests the RPC accepts.
+pub fn get_multiple_accounts_chunked<C: AccountSource>(
+    client: &C,
+    addresses: &[Pubkey],
+) -> Result<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        accounts.extend(client.get_multiple_accounts(chunk)?);
+    }
+
+    Ok(accounts)
+}
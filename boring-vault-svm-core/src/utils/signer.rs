@@ -0,0 +1,84 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use eyre::Result;
+use serde_json::json;
+use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+use solana_pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_signer::{Signer, SignerError};
+
+// Signers the builders hold on to, `Send + Sync` so a builder can move across threads.
+pub type BoxedSigner = Box<dyn Signer + Send + Sync>;
+
+// Signs through a signing service that speaks JSON-RPC over HTTP:
+//   getPubkey()                          -> base58 pubkey
+//   signMessage(pubkey, base64 message)  -> base58 signature
+// Requests go through the blocking RPC client, so inside an async runtime it needs a
+// multi-threaded one.
+pub struct RemoteSigner {
+    client: RpcClient,
+    pubkey: Pubkey,
+}
+
+impl RemoteSigner {
+    pub fn new(url: impl Into<String>, pubkey: Pubkey) -> Self {
+        Self {
+            client: RpcClient::new(url.into()),
+            pubkey,
+        }
+    }
+
+    // Asks the service which key it signs with.
+    pub fn connect(url: impl Into<String>) -> Result<Self> {
+        let client = RpcClient::new(url.into());
+        let pubkey: String = client.send(
+            RpcRequest::Custom {
+                method: "getPubkey",
+            },
+            json!([]),
+        )?;
+
+        Ok(Self {
+            client,
+            pubkey: Pubkey::from_str(&pubkey)?,
+        })
+    }
+
+    pub fn url(&self) -> String {
+        self.client.url()
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let signature: String = self
+            .client
+            .send(
+                RpcRequest::Custom {
+                    method: "signMessage",
+                },
+                json!([self.pubkey.to_string(), STANDARD.encode(message)]),
+            )
+            .map_err(|e| SignerError::Connection(e.to_string()))?;
+
+        let signature =
+            Signature::from_str(&signature).map_err(|e| SignerError::Protocol(e.to_string()))?;
+        if !signature.verify(self.pubkey.as_ref(), message) {
+            return Err(SignerError::Protocol(format!(
+                "Remote signer returned an invalid signature for {}",
+                self.pubkey
+            )));
+        }
+
+        Ok(signature)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
@@ -1,12 +1,13 @@
 use eyre::Result;
-use solana_client::rpc_client::RpcClient;
 use solana_program_pack::Pack;
 use solana_pubkey::Pubkey;
 use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token::state::Account as TokenAccount;
 
-pub fn get_token_account_balance(
-    client: &RpcClient,
+use super::AccountSource;
+
+pub fn get_token_account_balance<C: AccountSource>(
+    client: &C,
     owner: &Pubkey,
     mint: &Pubkey,
     is_token_2022: bool,
@@ -0,0 +1,674 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt,
+};
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use eyre::Result;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    builder::Builder,
+    config::{KaminoConfig, ManifestInstruction, StrategyManifest},
+    instructions::{
+        get_external_instruction_cpi_digest, get_unstake_seed, get_vault_id, view_cpi_digest,
+    },
+    manage_instructions::{
+        get_stake_pool_deposit_authority, ExternalInstruction, JupiterRoute,
+        RawExternalInstruction, StakeAuthorize, JITO_MINT, JITO_STAKE_POOL, JUPITER_PROGRAM_ID,
+        KAMINO_PROGRAM_ID, ROUTE_DISCRIMINATOR, SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR,
+        SPL_STAKE_POOL_PROGRAM_ID, STAKE_ACCOUNT_SIZE, STAKE_PROGRAM_ID,
+    },
+    utils::{
+        boring_vault_svm::{
+            self,
+            accounts::CpiDigest,
+            types::{Operator, Operators},
+        },
+        AccountSource,
+    },
+};
+
+// A locally computed digest next to the one the program's `view_cpi_digest` returns.
+#[derive(Debug, Clone)]
+pub struct CpiDigestCheck {
+    pub label: String,
+    pub local: [u8; 32],
+    pub program: [u8; 32],
+}
+
+impl CpiDigestCheck {
+    pub fn matches(&self) -> bool {
+        self.local == self.program
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CpiDigestEntry {
+    pub address: Pubkey,
+    // The operations the digest allows, None when no known instruction derives it.
+    pub label: Option<String>,
+    pub digest: Option<[u8; 32]>,
+    pub operators: Operators,
+}
+
+// The CPI digests initialized for a vault. The vault id is only part of the PDA seeds, so digests
+// no candidate instruction derives for any vault can't be attributed and are listed as unknown.
+#[derive(Debug, Clone)]
+pub struct CpiDigestInventory {
+    pub vault_id: u64,
+    pub known: Vec<CpiDigestEntry>,
+    pub unknown: Vec<CpiDigestEntry>,
+}
+
+impl fmt::Display for CpiDigestInventory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Vault {} CPI digests:", self.vault_id)?;
+        for entry in &self.known {
+            writeln!(
+                f,
+                "  {} {} [{}]",
+                entry.address,
+                entry.label.as_deref().unwrap_or_default(),
+                format_operators(&entry.operators)
+            )?;
+        }
+
+        if !self.unknown.is_empty() {
+            writeln!(f, "Unknown CPI digests (any vault):")?;
+            for entry in &self.unknown {
+                writeln!(
+                    f,
+                    "  {} [{}]",
+                    entry.address,
+                    format_operators(&entry.operators)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Lists every CPI digest account of the program and labels the ones the candidate instructions
+// derive for the vault on any of the sub-accounts.
+pub fn get_cpi_digest_inventory<C: AccountSource>(
+    client: &C,
+    vault_id: u64,
+    sub_accounts: &[u8],
+    instructions: &[ManifestInstruction],
+) -> Result<CpiDigestInventory> {
+    let accounts = client.get_program_accounts(&boring_vault_svm::ID, CpiDigest::DISCRIMINATOR)?;
+
+    let candidates = candidate_cpi_digests(vault_id, sub_accounts, instructions)?;
+    let other_vaults = (0..get_vault_id(client)?)
+        .into_par_iter()
+        .filter(|id| *id != vault_id)
+        .map(|id| candidate_cpi_digests(id, sub_accounts, instructions))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flat_map(|candidates| candidates.into_keys())
+        .collect::<HashSet<_>>();
+
+    let mut known = vec![];
+    let mut unknown = vec![];
+    for (address, account) in accounts {
+        let operators = CpiDigest::try_deserialize(&mut &account.data[..])?.operators;
+
+        match candidates.get(&address) {
+            Some((digest, labels)) => known.push(CpiDigestEntry {
+                address,
+                label: Some(labels.join(" | ")),
+                digest: Some(*digest),
+                operators,
+            }),
+            None if other_vaults.contains(&address) => {}
+            None => unknown.push(CpiDigestEntry {
+                address,
+                label: None,
+                digest: None,
+                operators,
+            }),
+        }
+    }
+
+    known.sort_by(|a, b| a.label.cmp(&b.label));
+    unknown.sort_by_key(|entry| entry.address);
+
+    Ok(CpiDigestInventory {
+        vault_id,
+        known,
+        unknown,
+    })
+}
+
+// The manage operations the builder runs with its Kamino config, for the sub-accounts given.
+// Every entry is on sub-account 0, `get_cpi_digest_inventory` tries each sub-account.
+pub fn known_manifest_instructions(
+    sub_accounts: &[u8],
+    kamino_config: &KaminoConfig,
+) -> Vec<ManifestInstruction> {
+    let mut instructions = vec![
+        ManifestInstruction::MintJitoSol {
+            sub_account: 0,
+            amount: 0,
+        },
+        ManifestInstruction::WithdrawJitoSol {
+            sub_account: 0,
+            amount: 0,
+        },
+        ManifestInstruction::WithdrawJitoStake {
+            sub_account: 0,
+            amount: 0,
+        },
+        ManifestInstruction::CreateAccountWithSeed {
+            sub_account: 0,
+            seed: get_unstake_seed(0),
+            lamports: 0,
+            space: STAKE_ACCOUNT_SIZE,
+            owner: STAKE_PROGRAM_ID,
+        },
+        ManifestInstruction::DeactivateStake { sub_account: 0 },
+        ManifestInstruction::WithdrawStake { sub_account: 0 },
+        ManifestInstruction::CloseAccount {
+            sub_account: 0,
+            account: Pubkey::default(),
+            token_program: spl_token::ID,
+        },
+        ManifestInstruction::CloseAccount {
+            sub_account: 0,
+            account: Pubkey::default(),
+            token_program: spl_token_2022::ID,
+        },
+        ManifestInstruction::KaminoInitUserMetaData {
+            sub_account: 0,
+            lut: Pubkey::default(),
+        },
+    ];
+    instructions.extend(stake_pool_instructions(
+        SPL_STAKE_POOL_PROGRAM_ID,
+        JITO_MINT,
+        spl_token::ID,
+        get_stake_pool_deposit_authority(&JITO_STAKE_POOL, &SPL_STAKE_POOL_PROGRAM_ID),
+    ));
+
+    for market in kamino_config.markets.values() {
+        instructions.extend([
+            ManifestInstruction::KaminoInitObligation {
+                sub_account: 0,
+                lending_market: market.lending_market,
+                tag: 0,
+                id: 0,
+            },
+            // The digest is the same for every reserve.
+            ManifestInstruction::KaminoRefreshReserve {
+                sub_account: 0,
+                reserve: KAMINO_PROGRAM_ID,
+                lending_market: market.lending_market,
+                pyth_oracle: KAMINO_PROGRAM_ID,
+                switchboard_price_oracle: KAMINO_PROGRAM_ID,
+                switchboard_twap_oracle: KAMINO_PROGRAM_ID,
+                scope_prices: market.oracle_prices,
+            },
+            ManifestInstruction::KaminoRefreshPriceList {
+                sub_account: 0,
+                oracle_prices: market.oracle_prices,
+                oracle_mapping: market.oracle_mapping,
+                oracle_twaps: market.oracle_twaps,
+                price_accounts: market.price_accounts.clone(),
+                tokens: market.tokens.clone(),
+            },
+            ManifestInstruction::KaminoRefreshObligation {
+                sub_account: 0,
+                lending_market: market.lending_market,
+                tag: 0,
+                id: 0,
+            },
+        ]);
+    }
+
+    for reserve in kamino_config.reserves.values() {
+        let lending_market = kamino_config.markets[&reserve.market].lending_market;
+
+        for (mode, farm) in [(0, reserve.farm_collateral), (1, reserve.farm_debt)] {
+            if farm == Pubkey::default() {
+                continue;
+            }
+            instructions.extend([
+                ManifestInstruction::KaminoInitObligationFarmsForReserve {
+                    sub_account: 0,
+                    reserve: reserve.reserve,
+                    reserve_farm_state: farm,
+                    lending_market,
+                    tag: 0,
+                    id: 0,
+                    mode,
+                },
+                ManifestInstruction::KaminoRefreshObligationFarmsForReserve {
+                    sub_account: 0,
+                    reserve: reserve.reserve,
+                    reserve_farm_state: farm,
+                    lending_market,
+                    tag: 0,
+                    id: 0,
+                    mode,
+                },
+            ]);
+        }
+
+        if let Ok((collateral_mint, collateral_supply)) = reserve.collateral() {
+            instructions.extend([
+                ManifestInstruction::KaminoDeposit {
+                    sub_account: 0,
+                    lending_market,
+                    reserve: reserve.reserve,
+                    reserve_liquidity_mint: reserve.liquidity_mint,
+                    reserve_liquidity_supply: reserve.liquidity_supply,
+                    reserve_collateral_mint: collateral_mint,
+                    reserve_destination_deposit_collateral: collateral_supply,
+                    reserve_farm_state: reserve.farm_collateral,
+                    tag: 0,
+                    id: 0,
+                    amount: 0,
+                },
+                ManifestInstruction::KaminoWithdraw {
+                    sub_account: 0,
+                    lending_market,
+                    reserve: reserve.reserve,
+                    reserve_liquidity_mint: reserve.liquidity_mint,
+                    reserve_liquidity_supply: reserve.liquidity_supply,
+                    reserve_collateral_mint: collateral_mint,
+                    reserve_source_collateral: collateral_supply,
+                    reserve_farm_state: reserve.farm_collateral,
+                    tag: 0,
+                    id: 0,
+                    amount: 0,
+                },
+            ]);
+        }
+
+        if let Ok(fee_receiver) = reserve.fee_receiver() {
+            instructions.extend([
+                ManifestInstruction::KaminoBorrow {
+                    sub_account: 0,
+                    lending_market,
+                    reserve: reserve.reserve,
+                    borrow_reserve_liquidity_mint: reserve.liquidity_mint,
+                    reserve_source_liquidity: reserve.liquidity_supply,
+                    borrow_reserve_liquidity_fee_receiver: fee_receiver,
+                    tag: 0,
+                    id: 0,
+                    amount: 0,
+                },
+                // The digest doesn't cover the farm accounts.
+                ManifestInstruction::KaminoRepay {
+                    sub_account: 0,
+                    lending_market,
+                    reserve: reserve.reserve,
+                    reserve_liquidity_mint: reserve.liquidity_mint,
+                    reserve_destination_liquidity: reserve.liquidity_supply,
+                    reserve_farm_state: Pubkey::default(),
+                    tag: 0,
+                    id: 0,
+                    amount: 0,
+                },
+            ]);
+        }
+    }
+
+    for to_sub_account in sub_accounts {
+        instructions.push(ManifestInstruction::TransferSolBetweenSubAccounts {
+            sub_account: 0,
+            to_sub_account: *to_sub_account,
+            amount: 0,
+        });
+    }
+
+    instructions
+}
+
+// The stake pool instructions on a pool, and the authorizations a stake deposit into it starts
+// with.
+fn stake_pool_instructions(
+    stake_pool_program: Pubkey,
+    pool_mint: Pubkey,
+    token_program: Pubkey,
+    stake_deposit_authority: Pubkey,
+) -> Vec<ManifestInstruction> {
+    vec![
+        ManifestInstruction::DepositSol {
+            sub_account: 0,
+            stake_pool_program,
+            pool_mint,
+            token_program,
+            amount: 0,
+        },
+        ManifestInstruction::WithdrawSol {
+            sub_account: 0,
+            stake_pool_program,
+            pool_mint,
+            token_program,
+            amount: 0,
+        },
+        ManifestInstruction::DepositStake {
+            sub_account: 0,
+            stake_pool_program,
+            pool_mint,
+            token_program,
+        },
+        ManifestInstruction::WithdrawPoolStake {
+            sub_account: 0,
+            stake_pool_program,
+            pool_mint,
+            token_program,
+            amount: 0,
+        },
+        ManifestInstruction::AuthorizeStake {
+            sub_account: 0,
+            new_authority: stake_deposit_authority,
+            stake_authorize: StakeAuthorize::Staker,
+        },
+        ManifestInstruction::AuthorizeStake {
+            sub_account: 0,
+            new_authority: stake_deposit_authority,
+            stake_authorize: StakeAuthorize::Withdrawer,
+        },
+    ]
+}
+
+// Candidate CPI digest PDAs of the vault, with the digest and a label per operation deriving it.
+type CandidateCpiDigests = HashMap<Pubkey, ([u8; 32], Vec<String>)>;
+
+fn candidate_cpi_digests(
+    vault_id: u64,
+    sub_accounts: &[u8],
+    instructions: &[ManifestInstruction],
+) -> Result<CandidateCpiDigests> {
+    // A sub-account none of the others are, to tell digests that don't depend on the sub-account.
+    let unused_sub_account = (0..=u8::MAX)
+        .rev()
+        .find(|sub_account| !sub_accounts.contains(sub_account))
+        .unwrap_or(u8::MAX);
+
+    let mut candidates = CandidateCpiDigests::new();
+    for instruction in instructions {
+        let mut digests = BTreeSet::new();
+        for sub_account in sub_accounts {
+            let eix = instruction
+                .with_sub_account(*sub_account)
+                .to_external_instruction(vault_id);
+            digests.insert((
+                get_external_instruction_cpi_digest(eix.as_ref())?,
+                *sub_account,
+            ));
+        }
+
+        let eix = instruction
+            .with_sub_account(unused_sub_account)
+            .to_external_instruction(vault_id);
+        let any_sub_account = get_external_instruction_cpi_digest(eix.as_ref())?;
+
+        for ((cpi_digest_pda, digest), sub_account) in digests {
+            let label = if (cpi_digest_pda, digest) == any_sub_account {
+                format!("{}, any sub-account", describe(instruction))
+            } else {
+                format!("{}, sub-account {}", describe(instruction), sub_account)
+            };
+
+            let (_, labels) = candidates
+                .entry(cpi_digest_pda)
+                .or_insert_with(|| (digest, vec![]));
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+    }
+
+    Ok(candidates)
+}
+
+// Names the operation by the parameters its digest ingests, which are all the digest allows.
+fn describe(instruction: &ManifestInstruction) -> String {
+    let kind = instruction.kind();
+    match instruction {
+        ManifestInstruction::KaminoInitObligation { lending_market, .. }
+        | ManifestInstruction::KaminoInitObligationFarmsForReserve { lending_market, .. }
+        | ManifestInstruction::KaminoRefreshObligationFarmsForReserve { lending_market, .. }
+        | ManifestInstruction::KaminoBorrow { lending_market, .. }
+        | ManifestInstruction::SolendInitObligation { lending_market, .. } => {
+            format!("{} on market {}", kind, short(lending_market))
+        }
+        ManifestInstruction::KaminoDeposit {
+            lending_market,
+            reserve_liquidity_mint,
+            ..
+        }
+        | ManifestInstruction::KaminoRepay {
+            lending_market,
+            reserve_liquidity_mint,
+            ..
+        }
+        | ManifestInstruction::KaminoWithdraw {
+            lending_market,
+            reserve_liquidity_mint,
+            ..
+        } => format!(
+            "{} of {} on market {}",
+            kind,
+            short(reserve_liquidity_mint),
+            short(lending_market)
+        ),
+        ManifestInstruction::DepositSol { pool_mint, .. }
+        | ManifestInstruction::WithdrawSol { pool_mint, .. }
+        | ManifestInstruction::DepositStake { pool_mint, .. }
+        | ManifestInstruction::WithdrawPoolStake { pool_mint, .. } => {
+            format!("{} of {}", kind, short(pool_mint))
+        }
+        ManifestInstruction::AuthorizeStake {
+            new_authority,
+            stake_authorize,
+            ..
+        } => format!("{} {:?} to {}", kind, stake_authorize, short(new_authority)),
+        ManifestInstruction::KaminoRefreshPriceList { tokens, .. } => {
+            format!("{} of {} tokens", kind, tokens.len())
+        }
+        ManifestInstruction::SolendDepositReserveLiquidityAndObligationCollateral {
+            reserve_collateral_mint,
+            ..
+        } => format!("{} for collateral {}", kind, short(reserve_collateral_mint)),
+        ManifestInstruction::TransferSol { to, .. } => format!("{} to {}", kind, short(to)),
+        ManifestInstruction::TransferSolBetweenSubAccounts { to_sub_account, .. } => {
+            format!("{} to sub-account {}", kind, to_sub_account)
+        }
+        ManifestInstruction::CreateAccountWithSeed { seed, .. } => {
+            format!("{} with a {} byte seed", kind, seed.len())
+        }
+        ManifestInstruction::CloseAccount { token_program, .. } => {
+            format!("{} through {}", kind, short(token_program))
+        }
+        _ => kind.to_string(),
+    }
+}
+
+fn short(pubkey: &Pubkey) -> String {
+    format!("{}…", &pubkey.to_string()[..4])
+}
+
+pub fn format_operators(operators: &Operators) -> String {
+    operators
+        .operators
+        .iter()
+        .map(|operator| match operator {
+            Operator::Noop => "Noop".to_string(),
+            Operator::IngestInstruction(index, length) => {
+                format!("IngestInstruction({}, {})", index, length)
+            }
+            Operator::IngestAccount(index) => format!("IngestAccount({})", index),
+            Operator::IngestInstructionDataSize => "IngestInstructionDataSize".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl<C: AccountSource> Builder<C> {
+    // Inventory of the vault's CPI digests, matched against the operations the builder's Kamino
+    // config and the optional manifest describe, on the configured sub-accounts.
+    pub fn get_cpi_digest_inventory(
+        &self,
+        vault_id: u64,
+        manifest: Option<&StrategyManifest>,
+    ) -> Result<CpiDigestInventory> {
+        let mut sub_accounts = BTreeSet::from([0]);
+        sub_accounts.extend(&self.vault_config.sub_accounts);
+        if let Some(manifest) = manifest {
+            sub_accounts.extend(manifest.instructions.iter().map(|ix| ix.sub_account()));
+        }
+        let sub_accounts = sub_accounts.into_iter().collect::<Vec<_>>();
+
+        let mut instructions = known_manifest_instructions(&sub_accounts, &self.kamino_config);
+        if let Some(manifest) = manifest {
+            instructions.extend(manifest.instructions.iter().cloned());
+        }
+
+        get_cpi_digest_inventory(&self.client, vault_id, &sub_accounts, &instructions)
+    }
+
+    // Compares the local digest of every manage instruction type with the program's, to catch the
+    // operators or account lists drifting from what the deployed program ingests. Nothing is sent.
+    pub fn self_test_cpi_digests(
+        &self,
+        payer: &Pubkey,
+        vault_id: u64,
+    ) -> Result<Vec<CpiDigestCheck>> {
+        let mut instructions =
+            known_manifest_instructions(&self.vault_config.sub_accounts, &self.kamino_config);
+        instructions.extend(self_test_instructions(payer));
+
+        let mut eixs = instructions
+            .iter()
+            .map(|instruction| {
+                (
+                    describe(instruction),
+                    instruction.to_external_instruction(vault_id),
+                )
+            })
+            .collect::<Vec<_>>();
+        eixs.extend(self_test_external_instructions(payer, vault_id)?);
+
+        eixs.into_iter()
+            .map(|(label, eix)| {
+                let (_, local) = get_external_instruction_cpi_digest(eix.as_ref())?;
+                let program = view_cpi_digest(&self.client, payer, eix.as_ref())?;
+
+                Ok(CpiDigestCheck {
+                    label,
+                    local,
+                    program,
+                })
+            })
+            .collect()
+    }
+}
+
+// Instruction types `known_manifest_instructions` doesn't cover, with placeholder accounts. The
+// stake pool instructions are also run on a placeholder pool under token-2022.
+fn self_test_instructions(payer: &Pubkey) -> Vec<ManifestInstruction> {
+    let mut instructions = vec![
+        ManifestInstruction::TransferSol {
+            sub_account: 0,
+            to: *payer,
+            amount: 0,
+        },
+        ManifestInstruction::CreateAccountWithSeed {
+            sub_account: 0,
+            seed: "self-test".to_string(),
+            lamports: 0,
+            space: 0,
+            owner: *payer,
+        },
+        ManifestInstruction::SolendInitObligation {
+            sub_account: 0,
+            obligation: Pubkey::default(),
+            lending_market: Pubkey::default(),
+        },
+        ManifestInstruction::SolendDepositReserveLiquidityAndObligationCollateral {
+            sub_account: 0,
+            deposit_mint: Pubkey::default(),
+            reserve_collateral_mint: Pubkey::default(),
+            reserve: Pubkey::default(),
+            reserve_liquidity_supply_spl_token_account: Pubkey::default(),
+            lending_market: Pubkey::default(),
+            lending_market_authority: Pubkey::default(),
+            destination_deposit_reserve_collateral_supply_spl_token_account: Pubkey::default(),
+            obligation: Pubkey::default(),
+            pyth_price_oracle: Pubkey::default(),
+            switchboard_price_oracle: Pubkey::default(),
+            amount: 0,
+        },
+    ];
+    instructions.extend(stake_pool_instructions(
+        *payer,
+        *payer,
+        spl_token_2022::ID,
+        *payer,
+    ));
+
+    instructions
+}
+
+// Instruction types a manifest can't describe, since they wrap an instruction built elsewhere.
+// The operators of the raw instruction cover every operator kind.
+fn self_test_external_instructions(
+    payer: &Pubkey,
+    vault_id: u64,
+) -> Result<Vec<(String, Box<dyn ExternalInstruction>)>> {
+    let raw = RawExternalInstruction::new(
+        vault_id,
+        0,
+        Instruction {
+            program_id: *payer,
+            accounts: vec![AccountMeta::new(*payer, false)],
+            data: vec![0; 8],
+        },
+        Operators {
+            operators: vec![
+                Operator::Noop,
+                Operator::IngestInstruction(0, 8),
+                Operator::IngestAccount(0),
+                Operator::IngestInstructionDataSize,
+            ],
+        },
+    );
+
+    let mut eixs: Vec<(String, Box<dyn ExternalInstruction>)> =
+        vec![("RawExternalInstruction".to_string(), Box::new(raw))];
+    for (label, discriminator, user_index) in [
+        ("JupiterRoute route", ROUTE_DISCRIMINATOR, 1),
+        (
+            "JupiterRoute shared_accounts_route",
+            SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR,
+            2,
+        ),
+    ] {
+        let accounts = (0..10u8)
+            .map(|index| match index == user_index {
+                true => AccountMeta::new_readonly(*payer, true),
+                false => AccountMeta::new(Pubkey::new_from_array([index + 1; 32]), false),
+            })
+            .collect();
+        let swap_instruction = Instruction {
+            program_id: JUPITER_PROGRAM_ID,
+            accounts,
+            data: discriminator.to_vec(),
+        };
+
+        eixs.push((
+            label.to_string(),
+            Box::new(JupiterRoute::new(vault_id, 0, &swap_instruction)?),
+        ));
+    }
+
+    Ok(eixs)
+}
@@ -1,9 +1,138 @@
 use crate::{
     builder::Builder,
-    manage_instructions::{ExternalInstruction, MintJitoSol},
+    instructions::{get_unstake_account, MAX_UNSTAKE_ACCOUNTS},
+    manage_instructions::{ExternalInstruction, MintJitoSol, STAKE_PROGRAM_ID},
+    utils::AccountSource,
 };
-use eyre::Result;
-use solana_pubkey::Pubkey;
+use eyre::{eyre, Result};
+use solana_program::{
+    epoch_schedule::EpochSchedule,
+    stake::state::StakeStateV2,
+    stake_history::StakeHistory,
+    sysvar::{
+        clock::ID as CLOCK_SYSVAR_ID, epoch_schedule::ID as EPOCH_SCHEDULE_SYSVAR_ID,
+        stake_history::ID as STAKE_HISTORY_SYSVAR_ID,
+    },
+};
+use solana_pubkey::{pubkey, Pubkey};
+use solana_sdk::clock::Clock;
+
+// The feature that lowered the stake warmup and cooldown rate. Its account holds the slot it
+// was activated in.
+const REDUCE_STAKE_WARMUP_COOLDOWN: Pubkey =
+    pubkey!("GwtDQBghCTBgmX2cpEGNPxTEBUTQRaDMGTr5qychdGMj");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakeAccountStatus {
+    // Created but not split into yet, there is nothing to withdraw.
+    Uninitialized,
+    // Still delegated, it needs to be deactivated first.
+    Active,
+    // Cooling down, `deactivating` lamports of stake are still effective.
+    Deactivating { deactivating: u64 },
+    Withdrawable,
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingStakeAccount {
+    pub address: Pubkey,
+    pub seed: String,
+    pub lamports: u64,
+    pub status: StakeAccountStatus,
+}
+
+// The sub-account's unstake accounts that haven't been withdrawn yet.
+pub fn get_pending_stake_accounts<C: AccountSource>(
+    client: &C,
+    vault_id: u64,
+    sub_account: u8,
+) -> Result<Vec<PendingStakeAccount>> {
+    let unstake_accounts = (0..MAX_UNSTAKE_ACCOUNTS)
+        .map(|index| get_unstake_account(vault_id, sub_account, index))
+        .collect::<Result<Vec<_>>>()?;
+    let addresses = unstake_accounts
+        .iter()
+        .map(|(address, _)| *address)
+        .collect::<Vec<_>>();
+    let accounts = client.get_multiple_accounts(&addresses)?;
+
+    let sysvars = client.get_multiple_accounts(&[
+        CLOCK_SYSVAR_ID,
+        STAKE_HISTORY_SYSVAR_ID,
+        EPOCH_SCHEDULE_SYSVAR_ID,
+        REDUCE_STAKE_WARMUP_COOLDOWN,
+    ])?;
+    let sysvar = |index: usize| {
+        sysvars[index]
+            .as_ref()
+            .map(|account| account.data.as_slice())
+            .ok_or_else(|| eyre!("Sysvar account not found"))
+    };
+    let clock: Clock = bincode::deserialize(sysvar(0)?)?;
+    let stake_history: StakeHistory = bincode::deserialize(sysvar(1)?)?;
+    let epoch_schedule: EpochSchedule = bincode::deserialize(sysvar(2)?)?;
+    let new_rate_activation_epoch = sysvars[3]
+        .as_ref()
+        .and_then(|account| bincode::deserialize::<Option<u64>>(&account.data).ok())
+        .flatten()
+        .map(|slot| epoch_schedule.get_epoch(slot));
+
+    unstake_accounts
+        .into_iter()
+        .zip(accounts)
+        .filter_map(|((address, seed), account)| {
+            account
+                .filter(|account| account.owner == STAKE_PROGRAM_ID)
+                .map(|account| (address, seed, account))
+        })
+        .map(|(address, seed, account)| {
+            Ok(PendingStakeAccount {
+                address,
+                seed,
+                lamports: account.lamports,
+                status: get_stake_account_status(
+                    &account.data,
+                    clock.epoch,
+                    &stake_history,
+                    new_rate_activation_epoch,
+                )?,
+            })
+        })
+        .collect()
+}
+
+fn get_stake_account_status(
+    data: &[u8],
+    epoch: u64,
+    stake_history: &StakeHistory,
+    new_rate_activation_epoch: Option<u64>,
+) -> Result<StakeAccountStatus> {
+    match bincode::deserialize::<StakeStateV2>(data)? {
+        StakeStateV2::Uninitialized => Ok(StakeAccountStatus::Uninitialized),
+        // Split from the pool reserve, never delegated.
+        StakeStateV2::Initialized(_) => Ok(StakeAccountStatus::Withdrawable),
+        StakeStateV2::Stake(_, stake, _) => {
+            let delegation = stake.delegation;
+            if delegation.deactivation_epoch == u64::MAX {
+                return Ok(StakeAccountStatus::Active);
+            }
+
+            let status = delegation.stake_activating_and_deactivating(
+                epoch,
+                stake_history,
+                new_rate_activation_epoch,
+            );
+            Ok(if status.effective == 0 {
+                StakeAccountStatus::Withdrawable
+            } else {
+                StakeAccountStatus::Deactivating {
+                    deactivating: status.deactivating,
+                }
+            })
+        }
+        StakeStateV2::RewardsPool => Err(eyre!("Unexpected rewards pool stake account")),
+    }
+}
 
 impl Builder {
     pub fn get_jito_digest(&self, vault_id: u64, sub_account: u8) -> Result<(Pubkey, String)> {
@@ -12,3 +141,13 @@ impl Builder {
         Ok(ix.get_digest())
     }
 }
+
+impl<C: AccountSource> Builder<C> {
+    pub fn get_pending_stake_accounts(
+        &self,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> Result<Vec<PendingStakeAccount>> {
+        get_pending_stake_accounts(&self.client, vault_id, sub_account)
+    }
+}
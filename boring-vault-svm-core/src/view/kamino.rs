@@ -1,11 +1,190 @@
-use eyre::Result;
+use eyre::{eyre, Result};
+use solana_program::system_program;
 use solana_pubkey::Pubkey;
 
+use crate::utils::AccountSource;
 use crate::{
     builder::Builder,
-    manage_instructions::{ExternalInstruction, KaminoBorrow, KaminoDeposit},
+    manage_instructions::{ExternalInstruction, KaminoBorrow, KaminoDeposit, KAMINO_PROGRAM_ID},
+    state::UserMetadata,
+    utils::{get_anchor_account_data_unsafe, get_obligation, get_user_metadata_pda, get_vault_pda},
 };
 
+// `Reserve` offsets, after the discriminator, version and last update.
+const RESERVE_FARM_COLLATERAL_OFFSET: usize = 64;
+const RESERVE_FARM_DEBT_OFFSET: usize = 96;
+// `Obligation` offsets of its fixed size deposit and borrow arrays.
+const OBLIGATION_DEPOSITS_OFFSET: usize = 96;
+const OBLIGATION_COLLATERAL_SIZE: usize = 136;
+const OBLIGATION_MAX_DEPOSITS: usize = 8;
+const OBLIGATION_BORROWS_OFFSET: usize = 1208;
+const OBLIGATION_LIQUIDITY_SIZE: usize = 200;
+const OBLIGATION_MAX_BORROWS: usize = 5;
+const BORROWED_AMOUNT_SF_OFFSET: usize = 88;
+// Kamino's scaled fractions have 60 fractional bits.
+const FRACTION_BITS: u32 = 60;
+
+#[derive(Debug, Clone)]
+pub struct ObligationDeposit {
+    pub reserve: Pubkey,
+    // In collateral (cToken) units.
+    pub deposited_amount: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ObligationBorrow {
+    pub reserve: Pubkey,
+    pub borrowed_amount_sf: u128,
+}
+
+impl ObligationBorrow {
+    // The debt in liquidity units as of the obligation's last refresh, rounded up.
+    pub fn borrowed_amount(&self) -> u64 {
+        let whole = self.borrowed_amount_sf >> FRACTION_BITS;
+        let fraction = self.borrowed_amount_sf & ((1u128 << FRACTION_BITS) - 1);
+        (whole + u128::from(fraction != 0)) as u64
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObligationPosition {
+    pub deposits: Vec<ObligationDeposit>,
+    pub borrows: Vec<ObligationBorrow>,
+}
+
+impl ObligationPosition {
+    pub fn deposit(&self, reserve: &Pubkey) -> Option<&ObligationDeposit> {
+        self.deposits
+            .iter()
+            .find(|deposit| deposit.reserve == *reserve)
+    }
+
+    pub fn borrow(&self, reserve: &Pubkey) -> Option<&ObligationBorrow> {
+        self.borrows
+            .iter()
+            .find(|borrow| borrow.reserve == *reserve)
+    }
+
+    // Deposit reserves then borrow reserves, the order `refresh_obligation` takes them in.
+    pub fn reserves(&self) -> Vec<Pubkey> {
+        self.deposits
+            .iter()
+            .map(|deposit| deposit.reserve)
+            .chain(self.borrows.iter().map(|borrow| borrow.reserve))
+            .collect()
+    }
+
+    // The reserves to refresh before the obligation: the ones it has positions in, then `selected`.
+    pub fn reserves_to_refresh(&self, selected: &[Pubkey]) -> Vec<Pubkey> {
+        let mut reserves = vec![];
+        for reserve in self.reserves().into_iter().chain(selected.iter().copied()) {
+            if !reserves.contains(&reserve) {
+                reserves.push(reserve);
+            }
+        }
+
+        reserves
+    }
+}
+
+pub fn get_user_lookup_table<C: AccountSource>(
+    client: &C,
+    vault_id: u64,
+    sub_account: u8,
+) -> Result<Pubkey> {
+    let vault_pda = get_vault_pda(vault_id, sub_account);
+    let user_metadata_pda = get_user_metadata_pda(&vault_pda, &KAMINO_PROGRAM_ID);
+    let user_metadata: UserMetadata = get_anchor_account_data_unsafe(client, &user_metadata_pda)?;
+
+    Ok(user_metadata.user_lookup_table)
+}
+
+pub fn get_obligation_address(
+    vault_id: u64,
+    sub_account: u8,
+    lending_market: &Pubkey,
+    tag: u8,
+    id: u8,
+) -> Pubkey {
+    get_obligation(
+        tag,
+        id,
+        &get_vault_pda(vault_id, sub_account),
+        lending_market,
+        &system_program::ID,
+        &system_program::ID,
+        &KAMINO_PROGRAM_ID,
+    )
+}
+
+// The sub-account's deposits and borrows in its `tag`/`id` obligation on `lending_market`, empty
+// when the obligation doesn't exist yet.
+pub fn get_obligation_position<C: AccountSource>(
+    client: &C,
+    vault_id: u64,
+    sub_account: u8,
+    lending_market: &Pubkey,
+    tag: u8,
+    id: u8,
+) -> Result<ObligationPosition> {
+    let obligation = get_obligation_address(vault_id, sub_account, lending_market, tag, id);
+    match client.get_multiple_accounts(&[obligation])?.remove(0) {
+        Some(account) => decode_obligation_position(&obligation, &account.data),
+        None => Ok(ObligationPosition::default()),
+    }
+}
+
+pub fn decode_obligation_position(obligation: &Pubkey, data: &[u8]) -> Result<ObligationPosition> {
+    if data.len() < OBLIGATION_BORROWS_OFFSET + OBLIGATION_MAX_BORROWS * OBLIGATION_LIQUIDITY_SIZE {
+        return Err(eyre!("Invalid obligation account {}", obligation));
+    }
+    let read_pubkey =
+        |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+
+    let deposits = (0..OBLIGATION_MAX_DEPOSITS)
+        .map(|index| OBLIGATION_DEPOSITS_OFFSET + index * OBLIGATION_COLLATERAL_SIZE)
+        .map(|offset| ObligationDeposit {
+            reserve: read_pubkey(offset),
+            deposited_amount: u64::from_le_bytes(
+                data[offset + 32..offset + 40].try_into().unwrap(),
+            ),
+        })
+        .filter(|deposit| deposit.reserve != Pubkey::default())
+        .collect();
+    let borrows = (0..OBLIGATION_MAX_BORROWS)
+        .map(|index| OBLIGATION_BORROWS_OFFSET + index * OBLIGATION_LIQUIDITY_SIZE)
+        .map(|offset| ObligationBorrow {
+            reserve: read_pubkey(offset),
+            borrowed_amount_sf: u128::from_le_bytes(
+                data[offset + BORROWED_AMOUNT_SF_OFFSET..offset + BORROWED_AMOUNT_SF_OFFSET + 16]
+                    .try_into()
+                    .unwrap(),
+            ),
+        })
+        .filter(|borrow| borrow.reserve != Pubkey::default())
+        .collect();
+
+    Ok(ObligationPosition { deposits, borrows })
+}
+
+// The reserve's collateral and debt farm states, `Pubkey::default()` where it has none.
+pub fn get_reserve_farms<C: AccountSource>(
+    client: &C,
+    reserve: &Pubkey,
+) -> Result<(Pubkey, Pubkey)> {
+    let data = client.get_account(reserve)?.data;
+    let read_pubkey = |offset: usize| -> Result<Pubkey> {
+        data.get(offset..offset + 32)
+            .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+            .ok_or_else(|| eyre!("Invalid reserve account {}", reserve))
+    };
+
+    Ok((
+        read_pubkey(RESERVE_FARM_COLLATERAL_OFFSET)?,
+        read_pubkey(RESERVE_FARM_DEBT_OFFSET)?,
+    ))
+}
+
 impl Builder {
     pub fn get_lend_digest(
         &self,
@@ -13,19 +192,20 @@ impl Builder {
         sub_account: u8,
         tag: u8,
         id: u8,
+        reserve: Option<&str>,
     ) -> Result<(Pubkey, String)> {
+        let (reserve, market) = self.kamino_config.lend_reserve(reserve)?;
+        let (collateral_mint, collateral_supply) = reserve.collateral()?;
         let lend = KaminoDeposit::new(
             vault_id,
             sub_account,
-            self.kamino_config.lending_market,
-            self.kamino_config.lend.reserve,
-            self.kamino_config.lend.reserve_liquidity_mint,
-            self.kamino_config.lend.reserve_liquidity_supply,
-            self.kamino_config.lend.reserve_collateral_mint,
-            self.kamino_config
-                .lend
-                .reserve_destination_deposit_collateral,
-            self.kamino_config.lend.reserve_farm_state,
+            market.lending_market,
+            reserve.reserve,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            collateral_mint,
+            collateral_supply,
+            reserve.farm_collateral,
             tag,
             id,
             0,
@@ -40,17 +220,17 @@ impl Builder {
         sub_account: u8,
         tag: u8,
         id: u8,
+        reserve: Option<&str>,
     ) -> Result<(Pubkey, String)> {
+        let (reserve, market) = self.kamino_config.borrow_reserve(reserve)?;
         let borrow = KaminoBorrow::new(
             vault_id,
             sub_account,
-            self.kamino_config.lending_market,
-            self.kamino_config.borrow.reserve,
-            self.kamino_config.borrow.reserve_source_liquidity_mint,
-            self.kamino_config.borrow.reserve_source_liquidity,
-            self.kamino_config
-                .borrow
-                .reserve_source_liquidity_fee_receiver,
+            market.lending_market,
+            reserve.reserve,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            reserve.fee_receiver()?,
             tag,
             id,
             0,
@@ -59,3 +239,26 @@ impl Builder {
         Ok(borrow.get_digest())
     }
 }
+
+impl<C: AccountSource> Builder<C> {
+    // The position on `market`, or on the default lend reserve's market.
+    pub fn get_kamino_position(
+        &self,
+        vault_id: u64,
+        sub_account: u8,
+        tag: u8,
+        id: u8,
+        market: Option<&str>,
+    ) -> Result<ObligationPosition> {
+        let market = self.kamino_config.lending_market(market)?;
+
+        get_obligation_position(
+            &self.client,
+            vault_id,
+            sub_account,
+            &market.lending_market,
+            tag,
+            id,
+        )
+    }
+}
@@ -1,5 +1,9 @@
 pub mod boring_vault;
+pub mod cpi_digest;
 pub mod jito;
 pub mod kamino;
 
 pub use boring_vault::*;
+pub use cpi_digest::*;
+pub use jito::*;
+pub use kamino::*;
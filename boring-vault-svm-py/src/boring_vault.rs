@@ -1,23 +1,36 @@
-use boring_vault_svm_core::KeypairOrPublickey;
-use pyo3::{pymethods, PyErr, PyResult};
+use boring_vault_svm_core::{
+    config::StrategyManifest, manage_instructions::parse_operators, view::format_operators,
+    KeypairOrPublickey,
+};
+use pyo3::{
+    pymethods,
+    types::{PyDict, PyDictMethods},
+    Bound, PyErr, PyResult, Python,
+};
+use solana_instruction::{AccountMeta, Instruction};
 
 use crate::{
-    utils::{to_keypair_from_bytes, to_pubkey_from_string},
+    utils::{to_keypair, to_pubkey_from_string, SignerArg},
     Builder,
 };
 
+// (label, cpi digest pda, digest, already exists)
+type CpiDigestProvision = (String, String, [u8; 32], bool);
+// (cpi digest pda, label or None when unknown, operators)
+type CpiDigestInventoryEntry = (String, Option<String>, String);
+
 #[pymethods]
 impl Builder {
     fn initialize(
         &mut self,
         authority: String,
-        signer_bytes: &[u8],
-        program_signer_bytes: &[u8],
+        signer_bytes: SignerArg,
+        program_signer_bytes: SignerArg,
     ) -> PyResult<()> {
         let authority = to_pubkey_from_string(authority)?;
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let program_signer: KeypairOrPublickey =
-            KeypairOrPublickey::Keypair(to_keypair_from_bytes(program_signer_bytes)?);
+            KeypairOrPublickey::Keypair(to_keypair(program_signer_bytes)?);
 
         self.inner
             .initialize(authority, signer, program_signer)
@@ -26,10 +39,12 @@ impl Builder {
         Ok(())
     }
 
-    fn deploy(
+    // Returns the id the vault is deployed as with its addresses, the vault being sub-account 0.
+    fn deploy<'py>(
         &mut self,
+        py: Python<'py>,
         authority: String,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         base_asset: String,
         name: String,
         symbol: String,
@@ -43,9 +58,10 @@ impl Builder {
         performance_fee_bps: Option<u16>,
         withdraw_authority: Option<String>,
         strategist: Option<String>,
-    ) -> PyResult<()> {
+        vault_id: Option<u64>,
+    ) -> PyResult<Bound<'py, PyDict>> {
         let authority = to_pubkey_from_string(authority)?;
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let base_asset = to_pubkey_from_string(base_asset)?;
 
         let exchange_rate_provider = match exchange_rate_provider {
@@ -68,10 +84,12 @@ impl Builder {
             None => None,
         };
 
-        self.inner
+        let vault = self
+            .inner
             .deploy(
                 authority,
                 signer,
+                vault_id,
                 base_asset,
                 name,
                 symbol,
@@ -88,12 +106,18 @@ impl Builder {
             )
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
-        Ok(())
+        let dict = PyDict::new(py);
+        dict.set_item("vault_id", vault.vault_id)?;
+        dict.set_item("vault_state", vault.vault_state.to_string())?;
+        dict.set_item("vault", vault.sub_account(0).to_string())?;
+        dict.set_item("share_mint", vault.share_mint.to_string())?;
+
+        Ok(dict)
     }
 
     fn update_asset_data(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         mint: String,
         allow_deposits: bool,
@@ -105,7 +129,7 @@ impl Builder {
         max_staleness: u64,
         min_samples: u32,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let mint = to_pubkey_from_string(mint)?;
         let price_feed = to_pubkey_from_string(price_feed)?;
 
@@ -128,8 +152,8 @@ impl Builder {
         Ok(())
     }
 
-    fn pause(&mut self, signer_bytes: &[u8], vault_id: u64) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+    fn pause(&mut self, signer_bytes: SignerArg, vault_id: u64) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .pause(signer, vault_id)
@@ -138,8 +162,8 @@ impl Builder {
         Ok(())
     }
 
-    fn unpause(&mut self, signer_bytes: &[u8], vault_id: u64) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+    fn unpause(&mut self, signer_bytes: SignerArg, vault_id: u64) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .unpause(signer, vault_id)
@@ -150,11 +174,11 @@ impl Builder {
 
     fn transfer_authority(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         pending_authority: String,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let pending_authority_pubkey = to_pubkey_from_string(pending_authority)?;
 
         self.inner
@@ -164,8 +188,8 @@ impl Builder {
         Ok(())
     }
 
-    fn accept_authority(&mut self, signer_bytes: &[u8], vault_id: u64) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+    fn accept_authority(&mut self, signer_bytes: SignerArg, vault_id: u64) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .accept_authority(signer, vault_id)
@@ -176,11 +200,11 @@ impl Builder {
 
     fn close_cpi_digest(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         digest: [u8; 32],
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .close_cpi_digest(signer, vault_id, digest)
@@ -189,13 +213,40 @@ impl Builder {
         Ok(())
     }
 
+    fn provision_cpi_digests(
+        &mut self,
+        authority_bytes: SignerArg,
+        manifest_path: String,
+    ) -> PyResult<Vec<CpiDigestProvision>> {
+        let authority = KeypairOrPublickey::Keypair(to_keypair(authority_bytes)?);
+        let manifest = StrategyManifest::new(&manifest_path)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
+        let provisions = self
+            .inner
+            .provision_cpi_digests(authority, &manifest)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(provisions
+            .into_iter()
+            .map(|provision| {
+                (
+                    provision.label,
+                    provision.cpi_digest_pda.to_string(),
+                    provision.digest,
+                    provision.exists,
+                )
+            })
+            .collect())
+    }
+
     fn update_exchange_rate_provider(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         new_provider: String,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let new_provider_pubkey = to_pubkey_from_string(new_provider)?;
 
         self.inner
@@ -207,11 +258,11 @@ impl Builder {
 
     fn set_withdraw_authority(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         new_authority: String,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let new_authority_pubkey = to_pubkey_from_string(new_authority)?;
 
         self.inner
@@ -223,11 +274,11 @@ impl Builder {
 
     fn set_payout(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         new_payout: String,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let new_payout_pubkey = to_pubkey_from_string(new_payout)?;
 
         self.inner
@@ -239,13 +290,13 @@ impl Builder {
 
     fn configure_exchange_rate_update_bounds(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         upper_bound: u16,
         lower_bound: u16,
         minimum_update_delay: u32,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .configure_exchange_rate_update_bounds(
@@ -262,12 +313,12 @@ impl Builder {
 
     fn set_fees(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         platform_fee_bps: u16,
         performance_fee_bps: u16,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .set_fees(signer, vault_id, platform_fee_bps, performance_fee_bps)
@@ -278,11 +329,11 @@ impl Builder {
 
     fn set_strategist(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         new_strategist: String,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let new_strategist_pubkey = to_pubkey_from_string(new_strategist)?;
 
         self.inner
@@ -294,11 +345,11 @@ impl Builder {
 
     fn claim_fees_in_base(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         sub_account: u8,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .claim_fees_in_base(signer, vault_id, sub_account)
@@ -309,12 +360,12 @@ impl Builder {
 
     fn deposit_sol(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         deposit_amount: u64,
         min_mint_amount: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .deposit_sol(signer, vault_id, deposit_amount, min_mint_amount)
@@ -325,13 +376,13 @@ impl Builder {
 
     fn deposit(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         deposit_mint: String,
         deposit_amount: u64,
         min_mint_amount: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let deposit_mint_pubkey = to_pubkey_from_string(deposit_mint)?;
 
         self.inner
@@ -349,13 +400,13 @@ impl Builder {
 
     fn withdraw(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         withdraw_mint: String,
         share_amount: u64,
         min_asset_amount: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
         let withdraw_mint_pubkey = to_pubkey_from_string(withdraw_mint)?;
 
         self.inner
@@ -373,11 +424,11 @@ impl Builder {
 
     fn update_exchange_rate(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         new_exchange_rate: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .update_exchange_rate(signer, vault_id, new_exchange_rate)
@@ -388,17 +439,17 @@ impl Builder {
 
     fn manage_transfer_sol_between_sub_accounts(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         to_sub_account: u8,
         amount: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
@@ -416,13 +467,65 @@ impl Builder {
         Ok(())
     }
 
+    // Accounts are (pubkey, is_signer, is_writable) and operators are written like
+    // "IngestInstruction(0, 8), IngestAccount(1)".
+    fn manage_raw_instruction(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        program_id: String,
+        accounts: Vec<(String, bool, bool)>,
+        data: Vec<u8>,
+        operators: String,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let accounts = accounts
+            .into_iter()
+            .map(|(pubkey, is_signer, is_writable)| {
+                Ok(AccountMeta {
+                    pubkey: to_pubkey_from_string(pubkey)?,
+                    is_signer,
+                    is_writable,
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let instruction = Instruction {
+            program_id: to_pubkey_from_string(program_id)?,
+            accounts,
+            data,
+        };
+        let operators = parse_operators(&operators)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
+        self.inner
+            .manage_raw_instruction(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                instruction,
+                operators,
+            )
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
     fn set_deposit_sub_account(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         new_sub_account: u8,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .set_deposit_sub_account(signer, vault_id, new_sub_account)
@@ -433,11 +536,11 @@ impl Builder {
 
     fn set_withdraw_sub_account(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         new_sub_account: u8,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
             .set_withdraw_sub_account(signer, vault_id, new_sub_account)
@@ -445,4 +548,51 @@ impl Builder {
 
         Ok(())
     }
+
+    // !--- READ FUNCTIONS ---!
+
+    #[pyo3(signature = (vault_id, manifest_path=None))]
+    fn get_cpi_digest_inventory(
+        &self,
+        vault_id: u64,
+        manifest_path: Option<String>,
+    ) -> PyResult<Vec<CpiDigestInventoryEntry>> {
+        let manifest = manifest_path
+            .map(|path| StrategyManifest::new(&path))
+            .transpose()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
+        let inventory = self
+            .inner
+            .get_cpi_digest_inventory(vault_id, manifest.as_ref())
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(inventory
+            .known
+            .iter()
+            .chain(&inventory.unknown)
+            .map(|entry| {
+                (
+                    entry.address.to_string(),
+                    entry.label.clone(),
+                    format_operators(&entry.operators),
+                )
+            })
+            .collect())
+    }
+
+    // (label, local digest matches the program's)
+    fn self_test_cpi_digests(&self, payer: String, vault_id: u64) -> PyResult<Vec<(String, bool)>> {
+        let payer = to_pubkey_from_string(payer)?;
+
+        let checks = self
+            .inner
+            .self_test_cpi_digests(&payer, vault_id)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(checks
+            .iter()
+            .map(|check| (check.label.clone(), check.matches()))
+            .collect())
+    }
 }
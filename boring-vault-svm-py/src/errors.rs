@@ -0,0 +1,63 @@
+use boring_vault_svm_core::utils::{
+    DecodedError, ASSOCIATED_TOKEN_PROGRAM, BORING_QUEUE_PROGRAM, BORING_VAULT_PROGRAM,
+    KAMINO_LENDING_PROGRAM, SPL_STAKE_POOL_PROGRAM, SPL_TOKEN_2022_PROGRAM, SPL_TOKEN_PROGRAM,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyRuntimeError},
+    prelude::*,
+};
+
+// Raised when a program rejects a transaction, with the decoded `instruction_index`,
+// `program_id`, `program`, `code`, `name` and `message` as attributes.
+create_exception!(boring_vault_svm, ProgramError, PyException);
+create_exception!(boring_vault_svm, BoringVaultError, ProgramError);
+create_exception!(boring_vault_svm, BoringQueueError, ProgramError);
+create_exception!(boring_vault_svm, KaminoError, ProgramError);
+create_exception!(boring_vault_svm, StakePoolError, ProgramError);
+create_exception!(boring_vault_svm, TokenError, ProgramError);
+
+// Program errors map to their exception class, anything else is a RuntimeError.
+pub fn to_py_err(e: eyre::Report) -> PyErr {
+    match e.downcast_ref::<DecodedError>() {
+        Some(decoded) => decoded_to_py_err(decoded, e.to_string()),
+        None => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+pub fn decoded_to_py_err(decoded: &DecodedError, message: String) -> PyErr {
+    let err = match decoded.program_name.as_deref() {
+        Some(BORING_VAULT_PROGRAM) => BoringVaultError::new_err(message),
+        Some(BORING_QUEUE_PROGRAM) => BoringQueueError::new_err(message),
+        Some(KAMINO_LENDING_PROGRAM) => KaminoError::new_err(message),
+        Some(SPL_STAKE_POOL_PROGRAM) => StakePoolError::new_err(message),
+        Some(SPL_TOKEN_PROGRAM | SPL_TOKEN_2022_PROGRAM | ASSOCIATED_TOKEN_PROGRAM) => {
+            TokenError::new_err(message)
+        }
+        _ => ProgramError::new_err(message),
+    };
+
+    Python::with_gil(|py| {
+        let value = err.value(py);
+        let _ = value.setattr("instruction_index", decoded.instruction_index);
+        let _ = value.setattr("program_id", decoded.program_id.map(|id| id.to_string()));
+        let _ = value.setattr("program", &decoded.program_name);
+        let _ = value.setattr("code", decoded.code);
+        let _ = value.setattr("name", &decoded.name);
+        let _ = value.setattr("message", &decoded.message);
+    });
+
+    err
+}
+
+pub fn add_exceptions(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("ProgramError", py.get_type::<ProgramError>())?;
+    m.add("BoringVaultError", py.get_type::<BoringVaultError>())?;
+    m.add("BoringQueueError", py.get_type::<BoringQueueError>())?;
+    m.add("KaminoError", py.get_type::<KaminoError>())?;
+    m.add("StakePoolError", py.get_type::<StakePoolError>())?;
+    m.add("TokenError", py.get_type::<TokenError>())?;
+
+    Ok(())
+}
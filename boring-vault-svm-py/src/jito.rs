@@ -1,22 +1,29 @@
-use boring_vault_svm_core::KeypairOrPublickey;
+use boring_vault_svm_core::{view::StakeAccountStatus, KeypairOrPublickey};
 use pyo3::{pymethods, PyErr, PyResult};
 
-use crate::{utils::to_keypair_from_bytes, Builder};
+use crate::{
+    utils::{to_keypair, to_pubkey_from_string, SignerArg},
+    Builder,
+};
+
+// (stake account, lamports, "uninitialized" | "active" | "deactivating" | "withdrawable",
+// stake still cooling down)
+type PendingStakeAccount = (String, u64, &'static str, Option<u64>);
 
 #[pymethods]
 impl Builder {
     fn manage_mint_jito_sol(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         amount: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
@@ -27,6 +34,126 @@ impl Builder {
         Ok(())
     }
 
+    fn manage_withdraw_jito_sol(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        self.inner
+            .withdraw_jito_sol(signer, authority, vault_id, sub_account, amount)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Returns the stake account the JitoSOL is unstaked into.
+    fn manage_unstake_jito_sol(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        amount: u64,
+    ) -> PyResult<String> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let stake_account = self
+            .inner
+            .unstake_jito_sol(signer, authority, vault_id, sub_account, amount)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(stake_account.to_string())
+    }
+
+    fn manage_deactivate_stake(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_account: String,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let stake_account = to_pubkey_from_string(stake_account)?;
+
+        self.inner
+            .deactivate_stake(signer, authority, vault_id, sub_account, stake_account)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn manage_withdraw_stake(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_account: String,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let stake_account = to_pubkey_from_string(stake_account)?;
+
+        self.inner
+            .withdraw_stake(signer, authority, vault_id, sub_account, stake_account)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Returns the stake accounts withdrawn from.
+    fn manage_withdraw_unstaked_sol(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> PyResult<Vec<String>> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let stake_accounts = self
+            .inner
+            .withdraw_unstaked_sol(signer, authority, vault_id, sub_account)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(stake_accounts
+            .iter()
+            .map(|stake_account| stake_account.to_string())
+            .collect())
+    }
+
     // !--- READ FUNCTIONS ---!
 
     fn get_jito_digest(&mut self, vault_id: u64, sub_account: u8) -> PyResult<()> {
@@ -36,4 +163,35 @@ impl Builder {
 
         Ok(())
     }
+
+    fn get_pending_stake_accounts(
+        &self,
+        vault_id: u64,
+        sub_account: u8,
+    ) -> PyResult<Vec<PendingStakeAccount>> {
+        let stake_accounts = self
+            .inner
+            .get_pending_stake_accounts(vault_id, sub_account)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(stake_accounts
+            .iter()
+            .map(|stake_account| {
+                let (status, deactivating) = match stake_account.status {
+                    StakeAccountStatus::Uninitialized => ("uninitialized", None),
+                    StakeAccountStatus::Active => ("active", None),
+                    StakeAccountStatus::Deactivating { deactivating } => {
+                        ("deactivating", Some(deactivating))
+                    }
+                    StakeAccountStatus::Withdrawable => ("withdrawable", None),
+                };
+                (
+                    stake_account.address.to_string(),
+                    stake_account.lamports,
+                    status,
+                    deactivating,
+                )
+            })
+            .collect())
+    }
 }
@@ -0,0 +1,36 @@
+use boring_vault_svm_core::{config::JupiterSwapInstructions, KeypairOrPublickey};
+use pyo3::{pymethods, PyErr, PyResult};
+
+use crate::{
+    utils::{to_keypair, SignerArg},
+    Builder,
+};
+
+#[pymethods]
+impl Builder {
+    // Takes the path of a saved Jupiter `/swap-instructions` response.
+    fn swap(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        swap_instructions_path: String,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let swap = JupiterSwapInstructions::new(&swap_instructions_path)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+
+        self.inner
+            .swap(signer, authority, vault_id, sub_account, &swap)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+}
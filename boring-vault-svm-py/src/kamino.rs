@@ -1,21 +1,24 @@
 use boring_vault_svm_core::KeypairOrPublickey;
 use pyo3::{pymethods, PyErr, PyResult};
 
-use crate::{utils::to_keypair_from_bytes, Builder};
+use crate::{
+    utils::{to_keypair, SignerArg},
+    Builder,
+};
 
 #[pymethods]
 impl Builder {
     fn manage_kamino_init_user_metadata(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
@@ -26,43 +29,55 @@ impl Builder {
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, tag, id, market=None))]
     fn manage_kamino_init_obligation(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         tag: u8,
         id: u8,
+        market: Option<String>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
         self.inner
-            .init_obligation(signer, authority, vault_id, sub_account, tag, id)
+            .init_obligation(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                tag,
+                id,
+                market.as_deref(),
+            )
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, tag, id, mode, reserve=None))]
     fn manage_kamino_init_obligation_farms_for_reserve(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         tag: Option<u8>,
         id: Option<u8>,
         mode: u8,
+        reserve: Option<String>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
@@ -75,70 +90,89 @@ impl Builder {
                 tag,
                 id,
                 mode,
+                reserve.as_deref(),
             )
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, reserves=None))]
     fn manage_kamino_refresh_reserve(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
+        reserves: Option<Vec<String>>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
+        let keys = reserves
+            .as_ref()
+            .map(|reserves| reserves.iter().map(String::as_str).collect::<Vec<_>>());
+
         self.inner
-            .refresh_reserves(signer, authority, vault_id, sub_account)
+            .refresh_reserves(signer, authority, vault_id, sub_account, keys.as_deref())
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, tag, id, market=None))]
     fn manage_kamino_refresh_obligation(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         tag: u8,
         id: u8,
+        market: Option<String>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
         self.inner
-            .refresh_obligation(signer, authority, vault_id, sub_account, tag, id)
+            .refresh_obligation(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                tag,
+                id,
+                market.as_deref(),
+            )
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, tag, id, mode, reserve=None))]
     fn manage_kamino_refresh_obligation_farms_for_reserve(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         tag: u8,
         id: u8,
         mode: u8,
+        reserve: Option<String>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
@@ -151,40 +185,45 @@ impl Builder {
                 tag,
                 id,
                 mode,
+                reserve.as_deref(),
             )
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, vault_id, sub_account, market=None))]
     fn manage_kamino_refresh_price_list(
         &mut self,
-        signer_bytes: &[u8],
+        signer_bytes: SignerArg,
         vault_id: u64,
         sub_account: u8,
+        market: Option<String>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         self.inner
-            .refresh_price_list(signer, vault_id, sub_account)
+            .refresh_price_list(signer, vault_id, sub_account, market.as_deref())
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, amount, tag, id, reserve=None))]
     fn lend(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: &[u8],
+        signer_bytes: SignerArg,
+        authority_bytes: SignerArg,
         vault_id: u64,
         sub_account: u8,
         amount: u64,
         tag: u8,
         id: u8,
+        reserve: Option<String>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
-        let authority = KeypairOrPublickey::Keypair(to_keypair_from_bytes(authority_bytes)?);
+        let authority = KeypairOrPublickey::Keypair(to_keypair(authority_bytes)?);
 
         self.inner
             .lend(
@@ -195,25 +234,28 @@ impl Builder {
                 amount,
                 tag,
                 id,
+                reserve.as_deref(),
             )
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok(())
     }
 
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, amount, tag, id, reserve=None))]
     fn borrow(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: &[u8],
+        signer_bytes: SignerArg,
+        authority_bytes: SignerArg,
         vault_id: u64,
         sub_account: u8,
         amount: u64,
         tag: u8,
         id: u8,
+        reserve: Option<String>,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
-        let authority = KeypairOrPublickey::Keypair(to_keypair_from_bytes(authority_bytes)?);
+        let authority = KeypairOrPublickey::Keypair(to_keypair(authority_bytes)?);
 
         self.inner
             .borrow(
@@ -224,6 +266,73 @@ impl Builder {
                 amount,
                 tag,
                 id,
+                reserve.as_deref(),
+            )
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Repays the whole debt when `amount` is None.
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, amount, tag, id, reserve=None))]
+    fn repay(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: SignerArg,
+        vault_id: u64,
+        sub_account: u8,
+        amount: Option<u64>,
+        tag: u8,
+        id: u8,
+        reserve: Option<String>,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = KeypairOrPublickey::Keypair(to_keypair(authority_bytes)?);
+
+        self.inner
+            .repay(
+                signer,
+                Some(authority),
+                vault_id,
+                sub_account,
+                amount,
+                tag,
+                id,
+                reserve.as_deref(),
+            )
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
+    // Withdraws all the collateral when `amount` is None.
+    #[pyo3(signature = (signer_bytes, authority_bytes, vault_id, sub_account, amount, tag, id, reserve=None))]
+    fn withdraw_lend(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: SignerArg,
+        vault_id: u64,
+        sub_account: u8,
+        amount: Option<u64>,
+        tag: u8,
+        id: u8,
+        reserve: Option<String>,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = KeypairOrPublickey::Keypair(to_keypair(authority_bytes)?);
+
+        self.inner
+            .withdraw_lend(
+                signer,
+                Some(authority),
+                vault_id,
+                sub_account,
+                amount,
+                tag,
+                id,
+                reserve.as_deref(),
             )
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
@@ -232,31 +341,35 @@ impl Builder {
 
     // !--- READ FUNCTIONS ---!
 
+    #[pyo3(signature = (vault_id, sub_account, tag, id, reserve=None))]
     fn get_lend_digest(
         &mut self,
         vault_id: u64,
         sub_account: u8,
         tag: u8,
         id: u8,
+        reserve: Option<String>,
     ) -> PyResult<(String, String)> {
         let (address, digest) = self
             .inner
-            .get_lend_digest(vault_id, sub_account, tag, id)
+            .get_lend_digest(vault_id, sub_account, tag, id, reserve.as_deref())
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok((address.to_string(), digest))
     }
 
+    #[pyo3(signature = (vault_id, sub_account, tag, id, reserve=None))]
     fn get_borrow_digest(
         &mut self,
         vault_id: u64,
         sub_account: u8,
         tag: u8,
         id: u8,
+        reserve: Option<String>,
     ) -> PyResult<(String, String)> {
         let (address, digest) = self
             .inner
-            .get_borrow_digest(vault_id, sub_account, tag, id)
+            .get_borrow_digest(vault_id, sub_account, tag, id, reserve.as_deref())
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
         Ok((address.to_string(), digest))
@@ -1,12 +1,25 @@
 #![allow(clippy::too_many_arguments)]
-use boring_vault_svm_core::config::BuilderConfig;
+use std::str::FromStr;
+
+use boring_vault_svm_core::builder::SendOutcome;
+use boring_vault_svm_core::config::{BuilderConfig, ComputeBudgetConfig, PriorityFeePolicy};
+use boring_vault_svm_core::utils::KeypairSource;
+use boring_vault_svm_core::KeypairOrPublickey;
+use errors::{decoded_to_py_err, to_py_err};
+use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
 use solana_keypair::Keypair;
+use utils::{to_keypair, to_pubkey_from_string, SignerArg};
 
 mod boring_vault;
+mod errors;
 mod jito;
+mod jupiter;
 mod kamino;
+mod partial;
+mod simulation;
 mod solend;
+mod stake_pool;
 mod system;
 mod utils;
 
@@ -23,9 +36,11 @@ impl Builder {
         data_path: Option<String>,
         kamino_file: Option<String>,
         vault_file: Option<String>,
-        lend_mint: Option<String>,
-        borrow_mint: Option<String>,
-    ) -> Self {
+        default_lend_reserve: Option<String>,
+        default_borrow_reserve: Option<String>,
+        lookup_tables: Option<Vec<String>>,
+        payer: Option<String>,
+    ) -> PyResult<Self> {
         let mut config = BuilderConfig::default();
 
         if let Some(url) = rpc_url {
@@ -44,34 +59,246 @@ impl Builder {
             config = config.with_vault_file(file);
         }
 
-        if let Some(profile) = lend_mint {
-            config = config.with_lend_mint(profile);
+        if let Some(reserve) = default_lend_reserve {
+            config = config.with_default_lend_reserve(reserve);
+        }
+
+        if let Some(reserve) = default_borrow_reserve {
+            config = config.with_default_borrow_reserve(reserve);
+        }
+
+        if let Some(addresses) = lookup_tables {
+            let lookup_tables = addresses
+                .into_iter()
+                .map(to_pubkey_from_string)
+                .collect::<PyResult<Vec<_>>>()?;
+            config = config.with_lookup_tables(lookup_tables);
         }
 
-        if let Some(profile) = borrow_mint {
-            config = config.with_borrow_mint(profile);
+        // Signer spec, e.g. "file:~/.config/solana/id.json", used when sends get no payer.
+        if let Some(spec) = payer {
+            let payer = KeypairSource::from_str(&spec)
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+            config = config.with_payer(payer);
         }
 
-        Self {
+        Ok(Self {
             inner: config.build(),
+        })
+    }
+
+    fn set_compute_budget(
+        &mut self,
+        compute_unit_margin_bps: u16,
+        priority_fee: Option<u64>,
+        priority_fee_percentile: Option<u8>,
+        max_priority_fee: Option<u64>,
+    ) -> PyResult<()> {
+        let priority_fee = match (priority_fee, priority_fee_percentile) {
+            (Some(_), Some(_)) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "Only one of priority_fee and priority_fee_percentile can be set",
+                ))
+            }
+            (Some(price), None) => PriorityFeePolicy::Fixed(price),
+            (None, Some(percentile)) => PriorityFeePolicy::Percentile(percentile),
+            (None, None) => PriorityFeePolicy::None,
+        };
+
+        self.inner.compute_budget = Some(
+            ComputeBudgetConfig::default()
+                .with_compute_unit_margin_bps(compute_unit_margin_bps)
+                .with_priority_fee(priority_fee)
+                .with_max_priority_fee(max_priority_fee),
+        );
+
+        Ok(())
+    }
+
+    fn disable_compute_budget(&mut self) {
+        self.inner.compute_budget = None;
+    }
+
+    fn set_cpi_digest_check(&mut self, enabled: bool) {
+        self.inner.cpi_digest_check = enabled;
+    }
+
+    fn set_bundle_config(
+        &mut self,
+        block_engine_url: Option<String>,
+        tip_account: Option<String>,
+        tip_lamports: Option<u64>,
+    ) -> PyResult<()> {
+        let mut config = self.inner.bundle_config.clone();
+
+        if let Some(url) = block_engine_url {
+            config = config.with_block_engine_url(url);
+        }
+
+        if let Some(address) = tip_account {
+            config = config.with_tip_account(to_pubkey_from_string(address)?);
+        }
+
+        if let Some(lamports) = tip_lamports {
+            config = config.with_tip_lamports(lamports);
         }
+
+        self.inner.bundle_config = config;
+
+        Ok(())
     }
 
-    fn try_bundle_all(&mut self, payer_bytes: &[u8]) -> PyResult<String> {
-        let payer = Keypair::from_bytes(payer_bytes)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+    // The nonce authority is given as keypair bytes, or as a pubkey when signing happens elsewhere.
+    fn use_durable_nonce(
+        &mut self,
+        nonce_account: String,
+        authority_bytes: Option<SignerArg>,
+        authority: Option<String>,
+    ) -> PyResult<()> {
+        let nonce_account = to_pubkey_from_string(nonce_account)?;
+        let authority = match (authority_bytes, authority) {
+            (Some(bytes), None) => KeypairOrPublickey::Keypair(to_keypair(bytes)?),
+            (None, Some(pubkey)) => KeypairOrPublickey::Publickey(to_pubkey_from_string(pubkey)?),
+            _ => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "Exactly one of authority_bytes and authority must be set",
+                ))
+            }
+        };
 
-        let tx_hash = self
+        self.inner.use_durable_nonce(nonce_account, authority);
+
+        Ok(())
+    }
+
+    fn clear_durable_nonce(&mut self) {
+        self.inner.clear_durable_nonce();
+    }
+
+    fn invalidate_account_cache(&self) {
+        self.inner.invalidate_account_cache();
+    }
+
+    // Returns the nonce authority and current nonce value.
+    fn get_nonce(&self, nonce_account: String) -> PyResult<(String, String)> {
+        let nonce_account = to_pubkey_from_string(nonce_account)?;
+
+        let data = self
             .inner
-            .try_bundle_all(payer)
+            .get_nonce_data(&nonce_account)
             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
 
+        Ok((data.authority.to_string(), data.blockhash().to_string()))
+    }
+
+    fn add_lookup_table(&mut self, address: String) -> PyResult<()> {
+        let address = to_pubkey_from_string(address)?;
+        self.inner.add_lookup_table(address);
+
+        Ok(())
+    }
+
+    fn load_vault_lookup_table(&mut self, vault_id: u64, sub_account: u8) -> PyResult<String> {
+        let address = self
+            .inner
+            .load_vault_lookup_table(vault_id, sub_account)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(address.to_string())
+    }
+
+    fn try_bundle_all(&mut self, payer_bytes: Option<SignerArg>) -> PyResult<String> {
+        let payer = self.payer(payer_bytes)?;
+
+        let tx_hash = self.inner.try_bundle_all(payer).map_err(to_py_err)?;
+
         Ok(tx_hash)
     }
+
+    // Returns the outcome ("landed", "failed" or "expired"), the signature and, when it failed,
+    // the exception `try_send_all` would raise for it.
+    fn try_send(
+        &mut self,
+        py: Python<'_>,
+        payer_bytes: Option<SignerArg>,
+    ) -> PyResult<(String, String, Option<PyObject>)> {
+        let payer = self.payer(payer_bytes)?;
+
+        let outcome = self.inner.try_send(payer).map_err(to_py_err)?;
+
+        Ok(match outcome {
+            SendOutcome::Landed { signature } => {
+                ("landed".to_string(), signature.to_string(), None)
+            }
+            SendOutcome::Failed {
+                signature,
+                err,
+                decoded,
+            } => {
+                let err = match decoded {
+                    Some(decoded) => decoded_to_py_err(&decoded, decoded.to_string()),
+                    None => PyRuntimeError::new_err(err.to_string()),
+                };
+                (
+                    "failed".to_string(),
+                    signature.to_string(),
+                    Some(err.into_value(py).into_any()),
+                )
+            }
+            SendOutcome::Expired { signature } => {
+                ("expired".to_string(), signature.to_string(), None)
+            }
+        })
+    }
+
+    fn try_send_all(&mut self, payer_bytes: Option<SignerArg>) -> PyResult<Vec<String>> {
+        let payer = self.payer(payer_bytes)?;
+
+        let tx_hashes = self.inner.try_send_all(payer).map_err(to_py_err)?;
+
+        Ok(tx_hashes)
+    }
+
+    fn compile_bundle_b64(&self, payer: String) -> PyResult<Vec<String>> {
+        let payer = to_pubkey_from_string(payer)?;
+
+        let txs = self
+            .inner
+            .compile_bundle_b64(payer)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(txs)
+    }
+
+    fn compile_plan_b64(&self, payer: String) -> PyResult<Vec<String>> {
+        let payer = to_pubkey_from_string(payer)?;
+
+        let txs = self
+            .inner
+            .compile_plan_to_versioned_transactions_b64(payer)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(txs)
+    }
+}
+
+impl Builder {
+    // Falls back to the payer the builder was created with.
+    fn payer(&self, payer_bytes: Option<SignerArg>) -> PyResult<Keypair> {
+        match payer_bytes {
+            Some(payer) => to_keypair(payer),
+            None => self
+                .inner
+                .payer()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string())),
+        }
+    }
 }
 
 #[pymodule]
 fn boring_vault_svm(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Builder>()?;
+    errors::add_exceptions(m)?;
+    m.add_class::<partial::PartiallySignedTransaction>()?;
     Ok(())
 }
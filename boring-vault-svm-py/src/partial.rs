@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use pyo3::{exceptions::PyValueError, pyclass, pymethods, PyErr, PyResult};
+use solana_signature::Signature;
+
+use crate::{
+    utils::{to_keypair, to_pubkey_from_string, SignerArg},
+    Builder,
+};
+
+#[pyclass]
+#[derive(Clone)]
+pub struct PartiallySignedTransaction {
+    inner: boring_vault_svm_core::builder::PartiallySignedTransaction,
+}
+
+#[pymethods]
+impl PartiallySignedTransaction {
+    #[staticmethod]
+    fn from_b64(b64_tx: String) -> PyResult<Self> {
+        let inner = boring_vault_svm_core::builder::PartiallySignedTransaction::from_b64(&b64_tx)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        Ok(Self { inner })
+    }
+
+    fn to_b64(&self) -> PyResult<String> {
+        self.inner
+            .to_b64()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
+    }
+
+    fn message_b64(&self) -> String {
+        self.inner.message_b64()
+    }
+
+    fn required_signers(&self) -> Vec<String> {
+        self.inner
+            .required_signers()
+            .iter()
+            .map(|pubkey| pubkey.to_string())
+            .collect()
+    }
+
+    fn missing_signers(&self) -> Vec<String> {
+        self.inner
+            .missing_signers()
+            .iter()
+            .map(|pubkey| pubkey.to_string())
+            .collect()
+    }
+
+    fn is_fully_signed(&self) -> bool {
+        self.inner.is_fully_signed()
+    }
+
+    fn add_signature(&mut self, pubkey: String, signature: String) -> PyResult<()> {
+        let pubkey = to_pubkey_from_string(pubkey)?;
+        let signature =
+            Signature::from_str(&signature).map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        self.inner
+            .add_signature(&pubkey, signature)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn sign(&mut self, signer_bytes: SignerArg) -> PyResult<()> {
+        let signer = to_keypair(signer_bytes)?;
+
+        self.inner
+            .sign(&signer)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    // Returns the fully signed transaction as base64.
+    fn finalize(&self) -> PyResult<String> {
+        self.inner
+            .clone()
+            .finalize()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        self.to_b64()
+    }
+}
+
+#[pymethods]
+impl Builder {
+    fn export_partially_signed(&self, payer: String) -> PyResult<PartiallySignedTransaction> {
+        let payer = to_pubkey_from_string(payer)?;
+
+        let inner = self
+            .inner
+            .export_partially_signed(payer)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(PartiallySignedTransaction { inner })
+    }
+
+    fn export_plan_partially_signed(
+        &self,
+        payer: String,
+    ) -> PyResult<Vec<PartiallySignedTransaction>> {
+        let payer = to_pubkey_from_string(payer)?;
+
+        let txs = self
+            .inner
+            .export_plan_partially_signed(payer)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(txs
+            .into_iter()
+            .map(|inner| PartiallySignedTransaction { inner })
+            .collect())
+    }
+
+    fn send_partially_signed(&self, tx: PartiallySignedTransaction) -> PyResult<String> {
+        self.inner
+            .send_partially_signed(tx.inner)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
+    }
+}
@@ -0,0 +1,98 @@
+use boring_vault_svm_core::builder::{BalanceKind, SimulationResult};
+use pyo3::{
+    pymethods,
+    types::{PyBytes, PyDict, PyDictMethods, PyList, PyListMethods},
+    Bound, PyErr, PyResult, Python,
+};
+
+use crate::{utils::to_pubkey_from_string, Builder};
+
+#[pymethods]
+impl Builder {
+    fn simulate<'py>(&self, py: Python<'py>, payer: String) -> PyResult<Bound<'py, PyDict>> {
+        let payer = to_pubkey_from_string(payer)?;
+
+        let result = self
+            .inner
+            .simulate(&payer)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        to_simulation_dict(py, &result)
+    }
+
+    fn simulate_plan<'py>(&self, py: Python<'py>, payer: String) -> PyResult<Bound<'py, PyList>> {
+        let payer = to_pubkey_from_string(payer)?;
+
+        let results = self
+            .inner
+            .simulate_plan(&payer)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        let list = PyList::empty(py);
+        for result in &results {
+            list.append(to_simulation_dict(py, result)?)?;
+        }
+
+        Ok(list)
+    }
+}
+
+fn to_simulation_dict<'py>(
+    py: Python<'py>,
+    result: &SimulationResult,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("success", result.is_success())?;
+    dict.set_item("err", result.err.as_ref().map(|err| err.to_string()))?;
+    dict.set_item("logs", &result.logs)?;
+    dict.set_item("units_consumed", result.units_consumed)?;
+
+    match &result.decoded_error {
+        Some(decoded) => {
+            let error = PyDict::new(py);
+            error.set_item("instruction_index", decoded.instruction_index)?;
+            error.set_item("program_id", decoded.program_id.map(|id| id.to_string()))?;
+            error.set_item("program", &decoded.program_name)?;
+            error.set_item("code", decoded.code)?;
+            error.set_item("name", &decoded.name)?;
+            error.set_item("message", &decoded.message)?;
+            dict.set_item("decoded_error", error)?;
+        }
+        None => dict.set_item("decoded_error", py.None())?,
+    }
+
+    match &result.return_data {
+        Some((program_id, data)) => dict.set_item(
+            "return_data",
+            (program_id.to_string(), PyBytes::new(py, data)),
+        )?,
+        None => dict.set_item("return_data", py.None())?,
+    }
+
+    let balances = PyList::empty(py);
+    for change in &result.balances {
+        let balance = PyDict::new(py);
+        balance.set_item("address", change.address.to_string())?;
+        match change.kind {
+            BalanceKind::SubAccount {
+                vault_id,
+                sub_account,
+            } => {
+                balance.set_item("kind", "sub_account")?;
+                balance.set_item("vault_id", vault_id)?;
+                balance.set_item("sub_account", sub_account)?;
+            }
+            BalanceKind::TokenAccount { mint, owner } => {
+                balance.set_item("kind", "token_account")?;
+                balance.set_item("mint", mint.to_string())?;
+                balance.set_item("owner", owner.to_string())?;
+            }
+        }
+        balance.set_item("pre", change.pre)?;
+        balance.set_item("post", change.post)?;
+        balances.append(balance)?;
+    }
+    dict.set_item("balances", balances)?;
+
+    Ok(dict)
+}
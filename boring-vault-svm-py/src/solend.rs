@@ -2,7 +2,7 @@ use boring_vault_svm_core::KeypairOrPublickey;
 use pyo3::{pymethods, PyErr, PyResult};
 
 use crate::{
-    utils::{to_keypair_from_bytes, to_pubkey_from_string},
+    utils::{to_keypair, to_pubkey_from_string, SignerArg},
     Builder,
 };
 
@@ -10,8 +10,8 @@ use crate::{
 impl Builder {
     fn manage_deposit_solend(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         deposit_mint: String,
@@ -25,10 +25,10 @@ impl Builder {
         switchboard_price_oracle: String,
         amount: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
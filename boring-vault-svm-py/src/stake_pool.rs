@@ -0,0 +1,93 @@
+use boring_vault_svm_core::KeypairOrPublickey;
+use pyo3::{pymethods, PyErr, PyResult};
+
+use crate::{
+    utils::{to_keypair, to_pubkey_from_string, SignerArg},
+    Builder,
+};
+
+#[pymethods]
+impl Builder {
+    fn manage_stake_pool_deposit_sol(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: String,
+        amount: u64,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let stake_pool = to_pubkey_from_string(stake_pool)?;
+
+        self.inner
+            .stake_pool_deposit_sol(signer, authority, vault_id, sub_account, stake_pool, amount)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn manage_stake_pool_withdraw_sol(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: String,
+        amount: u64,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let stake_pool = to_pubkey_from_string(stake_pool)?;
+
+        self.inner
+            .stake_pool_withdraw_sol(signer, authority, vault_id, sub_account, stake_pool, amount)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn manage_stake_pool_deposit_stake(
+        &mut self,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
+        vault_id: u64,
+        sub_account: u8,
+        stake_pool: String,
+        stake_account: String,
+    ) -> PyResult<()> {
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
+
+        let authority = match authority_bytes {
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
+            None => None,
+        };
+
+        let stake_pool = to_pubkey_from_string(stake_pool)?;
+        let stake_account = to_pubkey_from_string(stake_account)?;
+
+        self.inner
+            .stake_pool_deposit_stake(
+                signer,
+                authority,
+                vault_id,
+                sub_account,
+                stake_pool,
+                stake_account,
+            )
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
+}
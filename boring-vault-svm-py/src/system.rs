@@ -1,22 +1,25 @@
 use boring_vault_svm_core::KeypairOrPublickey;
 use pyo3::{pymethods, PyErr, PyResult};
 
-use crate::{utils::to_keypair_from_bytes, Builder};
+use crate::{
+    utils::{to_keypair, to_pubkey_from_string, SignerArg},
+    Builder,
+};
 
 #[pymethods]
 impl Builder {
     fn manage_wrap_sol(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
         amount: u64,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
@@ -29,15 +32,15 @@ impl Builder {
 
     fn manage_unwrap_sol(
         &mut self,
-        signer_bytes: &[u8],
-        authority_bytes: Option<&[u8]>,
+        signer_bytes: SignerArg,
+        authority_bytes: Option<SignerArg>,
         vault_id: u64,
         sub_account: u8,
     ) -> PyResult<()> {
-        let signer = KeypairOrPublickey::Keypair(to_keypair_from_bytes(signer_bytes)?);
+        let signer = KeypairOrPublickey::Keypair(to_keypair(signer_bytes)?);
 
         let authority = match authority_bytes {
-            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair_from_bytes(bytes)?)),
+            Some(bytes) => Some(KeypairOrPublickey::Keypair(to_keypair(bytes)?)),
             None => None,
         };
 
@@ -47,4 +50,21 @@ impl Builder {
 
         Ok(())
     }
+
+    fn create_nonce_account(
+        &mut self,
+        payer_bytes: SignerArg,
+        nonce_account_bytes: SignerArg,
+        authority: String,
+    ) -> PyResult<()> {
+        let payer = KeypairOrPublickey::Keypair(to_keypair(payer_bytes)?);
+        let nonce_account = KeypairOrPublickey::Keypair(to_keypair(nonce_account_bytes)?);
+        let authority = to_pubkey_from_string(authority)?;
+
+        self.inner
+            .create_nonce_account(payer, nonce_account, authority)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+
+        Ok(())
+    }
 }
@@ -1,13 +1,29 @@
 use std::str::FromStr;
 
-use pyo3::{exceptions::PyValueError, PyResult};
+use boring_vault_svm_core::utils::read_keypair;
+use pyo3::{exceptions::PyValueError, FromPyObject, PyResult};
 use solana_keypair::Keypair;
 use solana_pubkey::Pubkey;
 
+// A keypair given either as its secret bytes or as a signer spec string, e.g.
+// "file:~/.config/solana/id.json", "env:VAULT_SIGNER" or "mnemonic:<words>?key=0/0".
+#[derive(FromPyObject)]
+pub enum SignerArg {
+    Spec(String),
+    Bytes(Vec<u8>),
+}
+
 pub fn to_pubkey_from_string(authority: String) -> PyResult<Pubkey> {
     Pubkey::from_str(&authority).map_err(|e| PyValueError::new_err(e.to_string()))
 }
 
-pub fn to_keypair_from_bytes(signer_bytes: &[u8]) -> PyResult<Keypair> {
-    Keypair::from_bytes(signer_bytes).map_err(|e| PyValueError::new_err(e.to_string()))
+pub fn to_keypair(signer: SignerArg) -> PyResult<Keypair> {
+    match signer {
+        SignerArg::Spec(spec) => {
+            read_keypair(&spec).map_err(|e| PyValueError::new_err(e.to_string()))
+        }
+        SignerArg::Bytes(bytes) => {
+            Keypair::try_from(bytes.as_slice()).map_err(|e| PyValueError::new_err(e.to_string()))
+        }
+    }
 }
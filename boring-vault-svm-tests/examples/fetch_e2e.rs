@@ -0,0 +1,227 @@
+// Fetches what the ignored e2e tests need from a cluster: the program binaries into the programs
+// dir and the `kamino`, `jito` and `solend` fixtures.
+//
+//     cargo run -p boring-vault-svm-tests --example fetch_e2e -- [rpc url]
+use std::{collections::BTreeSet, fs, path::Path};
+
+use boring_vault_svm_core::{
+    instructions::get_stake_pool_accounts,
+    manage_instructions::JITO_STAKE_POOL,
+    state::ValidatorList,
+    utils::{
+        get_account_data, get_multiple_accounts_chunked, get_vault_pda, AccountSource,
+        RecordingAccountSource,
+    },
+    view::get_reserve_farms,
+};
+use boring_vault_svm_tests::{
+    fixture_path, programs_dir, SolendReserve, PROGRAMS, PROGRAMS_DIR_ENV, SOLEND_PROGRAM_ID,
+};
+use eyre::{eyre, Result};
+use serde_json::Value;
+use solana_client::rpc_client::RpcClient;
+use solana_pubkey::{pubkey, Pubkey};
+
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+const BPF_LOADER_UPGRADEABLE_ID: Pubkey = pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+// A program account is its `UpgradeableLoaderState::Program` tag and programdata address, and
+// the ELF follows the `ProgramData` tag, slot and upgrade authority.
+const PROGRAMDATA_ADDRESS_OFFSET: usize = 4;
+const PROGRAMDATA_METADATA_LEN: usize = 45;
+
+// Where a farm state's global config is, after the discriminator and farm admin.
+const FARM_GLOBAL_CONFIG_OFFSET: usize = 40;
+
+const SOLEND_MAIN_MARKET: Pubkey = pubkey!("4UpD2fh7xH3VP9QQaXtsS1YY3bxzWhtfpks7FatyKvdY");
+const SOLEND_SOL_RESERVE: Pubkey = pubkey!("8PbodeaosQP19SjYFx855UMqWxH2HynZLdBXmsrbac36");
+
+fn main() -> Result<()> {
+    let url = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+
+    dump_programs(&RpcClient::new(url.clone()))?;
+    fs::create_dir_all(fixture_path("").parent().unwrap())?;
+    record_fixture(&url, "kamino", record_kamino)?;
+    record_fixture(&url, "jito", record_jito)?;
+    record_fixture(&url, "solend", record_solend)?;
+
+    Ok(())
+}
+
+// Writes each program's ELF to `<programs dir>/<name>.so`, keeping binaries already there so a
+// locally built boring vault program isn't replaced.
+fn dump_programs(client: &RpcClient) -> Result<()> {
+    let dir = programs_dir();
+    fs::create_dir_all(&dir)?;
+
+    for (name, program_id) in PROGRAMS {
+        let path = dir.join(format!("{}.so", name));
+        if path.exists() {
+            println!("{} already in {}", name, dir.display());
+            continue;
+        }
+
+        let account = AccountSource::get_account(client, program_id)?;
+        let elf = if account.owner == BPF_LOADER_UPGRADEABLE_ID {
+            let programdata_address = account
+                .data
+                .get(PROGRAMDATA_ADDRESS_OFFSET..PROGRAMDATA_ADDRESS_OFFSET + 32)
+                .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+                .ok_or_else(|| eyre!("Invalid program account {}", program_id))?;
+            let programdata = AccountSource::get_account(client, &programdata_address)?;
+
+            programdata
+                .data
+                .get(PROGRAMDATA_METADATA_LEN..)
+                .ok_or_else(|| eyre!("Invalid programdata account {}", programdata_address))?
+                .to_vec()
+        } else {
+            account.data
+        };
+
+        fs::write(&path, elf)?;
+        println!("Dumped {} to {}", name, path.display());
+    }
+    println!("Set {} to load them from elsewhere", PROGRAMS_DIR_ENV);
+
+    Ok(())
+}
+
+fn record_fixture(
+    url: &str,
+    name: &str,
+    record: fn(&RecordingAccountSource<RpcClient>) -> Result<()>,
+) -> Result<()> {
+    let source = RecordingAccountSource::new(RpcClient::new(url.to_string()));
+    source.get_slot()?;
+    source.get_latest_blockhash()?;
+    record(&source)?;
+
+    let path = fixture_path(name);
+    source.save(&path.to_string_lossy())?;
+    println!(
+        "Recorded {} accounts to {}",
+        source.fixture().accounts.len(),
+        path.display()
+    );
+
+    Ok(())
+}
+
+// Every address in `data/kamino.json`, and the farm global configs of its reserves.
+fn record_kamino(source: &RecordingAccountSource<RpcClient>) -> Result<()> {
+    let data_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/kamino.json");
+    let data: Value = serde_json::from_str(&fs::read_to_string(data_path)?)?;
+    let mut addresses = BTreeSet::new();
+    collect_addresses(&data, &mut addresses);
+    get_multiple_accounts_chunked(source, &addresses.iter().copied().collect::<Vec<_>>())?;
+
+    let mut farms = vec![];
+    for reserve in data["reserves"].as_object().into_iter().flatten() {
+        let address: Pubkey = reserve.1["reserve"]
+            .as_str()
+            .ok_or_else(|| eyre!("Reserve {} has no address", reserve.0))?
+            .parse()?;
+        let (farm_collateral, farm_debt) = get_reserve_farms(source, &address)?;
+        farms.extend(
+            [farm_collateral, farm_debt]
+                .into_iter()
+                .filter(|farm| *farm != Pubkey::default()),
+        );
+    }
+    let global_configs = get_multiple_accounts_chunked(source, &farms)?
+        .into_iter()
+        .flatten()
+        .filter_map(|farm| {
+            farm.data
+                .get(FARM_GLOBAL_CONFIG_OFFSET..FARM_GLOBAL_CONFIG_OFFSET + 32)
+                .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+        })
+        .collect::<BTreeSet<_>>();
+    get_multiple_accounts_chunked(source, &global_configs.into_iter().collect::<Vec<_>>())?;
+
+    Ok(())
+}
+
+fn collect_addresses(value: &Value, addresses: &mut BTreeSet<Pubkey>) {
+    match value {
+        Value::String(s) => {
+            if let Ok(address) = s.parse() {
+                addresses.insert(address);
+            }
+        }
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| collect_addresses(value, addresses)),
+        Value::Object(values) => values
+            .values()
+            .for_each(|value| collect_addresses(value, addresses)),
+        _ => {}
+    }
+}
+
+// The Jito stake pool with its reserve, fee account, mint, validator list and validator stake
+// accounts.
+fn record_jito(source: &RecordingAccountSource<RpcClient>) -> Result<()> {
+    let stake_pool = get_stake_pool_accounts(source, &JITO_STAKE_POOL)?;
+    get_multiple_accounts_chunked(
+        source,
+        &[
+            stake_pool.reserve_stake,
+            stake_pool.manager_fee_account,
+            stake_pool.pool_mint,
+        ],
+    )?;
+
+    let validator_list: ValidatorList = get_account_data(source, &stake_pool.validator_list)?;
+    let stake_accounts = validator_list
+        .validators
+        .iter()
+        .map(|info| {
+            stake_pool
+                .validator_stake_account(&info.vote_account_address, info.validator_seed_suffix)
+        })
+        .collect::<Vec<_>>();
+    get_multiple_accounts_chunked(source, &stake_accounts)?;
+
+    Ok(())
+}
+
+// The main Solend market, its SOL reserve with its oracles and token accounts, and the obligation
+// of sub-account 0 of the first vault when it exists.
+fn record_solend(source: &RecordingAccountSource<RpcClient>) -> Result<()> {
+    let reserve = SolendReserve::unpack(
+        SOLEND_SOL_RESERVE,
+        &source.get_account(&SOLEND_SOL_RESERVE)?.data,
+    )?;
+    if reserve.lending_market != SOLEND_MAIN_MARKET {
+        return Err(eyre!(
+            "Reserve {} is not in market {}",
+            SOLEND_SOL_RESERVE,
+            SOLEND_MAIN_MARKET
+        ));
+    }
+    let obligation = Pubkey::create_with_seed(
+        &get_vault_pda(0, 0),
+        &SOLEND_MAIN_MARKET.to_string()[..32],
+        &SOLEND_PROGRAM_ID,
+    )?;
+
+    get_multiple_accounts_chunked(
+        source,
+        &[
+            reserve.lending_market,
+            reserve.liquidity_mint,
+            reserve.liquidity_supply,
+            reserve.pyth_oracle,
+            reserve.switchboard_oracle,
+            reserve.collateral_mint,
+            reserve.collateral_supply,
+            obligation,
+        ],
+    )?;
+
+    Ok(())
+}
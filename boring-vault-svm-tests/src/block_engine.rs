@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+
+use eyre::{eyre, Result};
+use serde_json::{json, Value};
+use solana_sdk::transaction::TransactionError;
+
+use crate::serve_json_rpc;
+
+pub const MOCK_BUNDLE_ID: &str = "mock-bundle";
+
+// How the mock block engine treats submitted bundles.
+#[derive(Debug, Clone)]
+pub enum MockBundleResult {
+    Landed,
+    // Accepted, then reported as failed with this error.
+    Failed(TransactionError),
+    // `sendBundle` answers with a JSON-RPC error carrying this message.
+    Rejected(String),
+}
+
+// Local stand-in for a Jito block engine serving `sendBundle` and `getBundleStatuses`. Keeps
+// every request it got so tests can check the payloads.
+pub struct MockBlockEngine {
+    url: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl MockBlockEngine {
+    pub fn start(result: MockBundleResult) -> Result<Self> {
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let received = requests.clone();
+        let url = serve_json_rpc("block engine", move |request| {
+            received.lock().unwrap().push(request.clone());
+            rpc_result(&result, request)
+        })?;
+
+        Ok(Self { url, requests })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    // Params of every `sendBundle` request so far.
+    pub fn sent_bundles(&self) -> Vec<Value> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request["method"] == "sendBundle")
+            .map(|request| request["params"].clone())
+            .collect()
+    }
+}
+
+fn rpc_result(result: &MockBundleResult, request: &Value) -> Result<Value> {
+    match request["method"].as_str() {
+        Some("sendBundle") => match result {
+            MockBundleResult::Rejected(message) => Err(eyre!("{}", message)),
+            _ => Ok(json!(MOCK_BUNDLE_ID)),
+        },
+        Some("getBundleStatuses") => {
+            let err = match result {
+                MockBundleResult::Failed(err) => json!({ "Err": err }),
+                _ => json!({ "Ok": null }),
+            };
+
+            Ok(json!({
+                "context": { "slot": 1 },
+                "value": [{
+                    "bundle_id": MOCK_BUNDLE_ID,
+                    "transactions": [],
+                    "slot": 1,
+                    "confirmation_status": "confirmed",
+                    "err": err,
+                }],
+            }))
+        }
+        method => Err(eyre!("Unknown method {:?}", method)),
+    }
+}
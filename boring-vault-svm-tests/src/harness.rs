@@ -0,0 +1,440 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anchor_lang::AccountSerialize;
+use boring_vault_svm_core::{
+    builder::{Builder, SendOutcome},
+    config::{BuilderConfig, SendConfig},
+    manage_instructions::{KAMINO_PROGRAM_ID, KAMINO_SCOPE_PROGRAM_ID},
+    nonblocking::AsyncBuilder,
+    utils::{
+        boring_vault_svm::{
+            self,
+            accounts::{CpiDigest, ProgramConfig},
+            types::Operators,
+        },
+        get_program_config_pda, AccountFixture, AccountSource,
+    },
+    KeypairOrPublickey,
+};
+use eyre::{eyre, Result};
+use litesvm::LiteSVM;
+use solana_account::Account;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_program_option::COption;
+use solana_program_pack::Pack;
+use solana_pubkey::{pubkey, Pubkey};
+use solana_sdk::{
+    clock::Clock, epoch_schedule::EpochSchedule, native_token::LAMPORTS_PER_SOL,
+    signature::Signature, slot_hashes::SlotHashes,
+};
+use solana_signer::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+use crate::{serve_lite_svm_rpc, svm::LiteSvmSource};
+
+// Directory holding the program binaries, overridable for CI.
+pub const PROGRAMS_DIR_ENV: &str = "BORING_VAULT_PROGRAMS_DIR";
+
+pub const BORING_VAULT_PROGRAM: &str = "boring_vault_svm";
+pub const BORING_ONCHAIN_QUEUE_PROGRAM: &str = "boring_onchain_queue";
+pub const BORING_ONCHAIN_QUEUE_ID: Pubkey = pubkey!("4yfE2VJQmxmcnUhrb8vdz7H8w313EZ3eJh5DbANBgtmd");
+
+pub const KAMINO_LENDING_PROGRAM: &str = "kamino_lending";
+pub const KAMINO_FARMS_PROGRAM: &str = "kamino_farms";
+pub const KAMINO_FARMS_PROGRAM_ID: Pubkey = pubkey!("FarmsPZpWu9i7Kky8tPN37rs2TpmMrAZrC7S7vJa91Hr");
+pub const SCOPE_PROGRAM: &str = "scope";
+pub const SPL_STAKE_POOL_PROGRAM: &str = "spl_stake_pool";
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey =
+    pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+pub const SOLEND_PROGRAM: &str = "solend";
+pub const SOLEND_PROGRAM_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+
+// Programs loaded when their binary is in the programs dir.
+pub const PROGRAMS: &[(&str, Pubkey)] = &[
+    (BORING_VAULT_PROGRAM, boring_vault_svm::ID),
+    (BORING_ONCHAIN_QUEUE_PROGRAM, BORING_ONCHAIN_QUEUE_ID),
+    (KAMINO_LENDING_PROGRAM, KAMINO_PROGRAM_ID),
+    (KAMINO_FARMS_PROGRAM, KAMINO_FARMS_PROGRAM_ID),
+    (SCOPE_PROGRAM, KAMINO_SCOPE_PROGRAM_ID),
+    (SPL_STAKE_POOL_PROGRAM, SPL_STAKE_POOL_PROGRAM_ID),
+    (SOLEND_PROGRAM, SOLEND_PROGRAM_ID),
+];
+
+const PAYER_LAMPORTS: u64 = 1_000 * LAMPORTS_PER_SOL;
+
+// A LiteSVM with the boring vault program loaded and a `Builder` pointed at it.
+pub struct TestHarness {
+    pub builder: Builder<LiteSvmSource>,
+    pub payer: Keypair,
+    pub authority: Keypair,
+    programs_dir: PathBuf,
+}
+
+impl TestHarness {
+    // Loads the boring vault program and any external program whose binary is present.
+    pub fn new() -> Result<Self> {
+        let programs_dir = programs_dir();
+
+        // The builder simulates unsigned transactions to size the compute budget, and LiteSVM
+        // can only skip signature verification for the whole instance.
+        let mut svm = LiteSVM::new().with_sigverify(false);
+        for (name, program_id) in PROGRAMS {
+            let path = program_path(&programs_dir, name);
+            if path.exists() {
+                svm.add_program_from_file(*program_id, &path)?;
+            }
+        }
+
+        let builder = builder_config().build_with_client(LiteSvmSource::new(svm));
+
+        let harness = Self {
+            builder,
+            payer: Keypair::new(),
+            authority: Keypair::new(),
+            programs_dir,
+        };
+        harness.airdrop(&harness.payer.pubkey(), PAYER_LAMPORTS)?;
+        harness.airdrop(&harness.authority.pubkey(), PAYER_LAMPORTS)?;
+
+        Ok(harness)
+    }
+
+    pub fn has_program(&self, name: &str) -> bool {
+        program_path(&self.programs_dir, name).exists()
+    }
+
+    // Program binaries are build artifacts, so tests needing one are `#[ignore]`d and run with
+    // `--ignored`, where a missing binary fails them instead of passing silently.
+    pub fn require_program(&self, name: &str) -> Result<()> {
+        let path = program_path(&self.programs_dir, name);
+        if !path.exists() {
+            return Err(eyre!(
+                "{} not found, build the program or set {}",
+                path.display(),
+                PROGRAMS_DIR_ENV
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn source(&self) -> &LiteSvmSource {
+        &self.builder.client
+    }
+
+    // An `AsyncBuilder` reading the same svm through a local RPC server. Its kamino and vault
+    // configs are copied from the blocking builder.
+    pub fn async_builder(&self) -> Result<AsyncBuilder> {
+        let url = serve_lite_svm_rpc(self.source().clone())?;
+        let mut builder = builder_config().build_async_with_client(RpcClient::new(url));
+        builder.kamino_config = self.builder.kamino_config.clone();
+        builder.vault_config = self.builder.vault_config.clone();
+
+        Ok(builder)
+    }
+
+    pub fn payer_signer(&self) -> KeypairOrPublickey {
+        KeypairOrPublickey::Keypair(self.payer.insecure_clone())
+    }
+
+    pub fn authority_signer(&self) -> KeypairOrPublickey {
+        KeypairOrPublickey::Keypair(self.authority.insecure_clone())
+    }
+
+    // Sends the builder's instructions and errors with the program logs unless they landed.
+    // The builder is cleared either way so the next test step starts empty.
+    pub fn send(&mut self) -> Result<Signature> {
+        let outcome = self.try_send()?;
+
+        match outcome {
+            SendOutcome::Landed { signature } => Ok(signature),
+            outcome => Err(eyre!(
+                "{}\n{}",
+                outcome,
+                self.source().logs(outcome.signature()).join("\n")
+            )),
+        }
+    }
+
+    pub fn try_send(&mut self) -> Result<SendOutcome> {
+        let outcome = self.builder.try_send(self.payer.insecure_clone());
+        self.builder.clear()?;
+
+        // A new blockhash per transaction, so sending the same instructions twice isn't
+        // rejected as already processed.
+        self.source().svm_mut().expire_blockhash();
+
+        outcome
+    }
+
+    pub fn airdrop(&self, address: &Pubkey, lamports: u64) -> Result<()> {
+        self.source()
+            .svm_mut()
+            .airdrop(address, lamports)
+            .map_err(|failed| eyre!("Airdrop to {} failed: {}", address, failed.err))?;
+        self.builder.invalidate_account_cache();
+
+        Ok(())
+    }
+
+    // Moves the clock to `slot` and its mainnet epoch, which programs like the stake pool check
+    // their state against. The slot is added to the slot hashes so lookup tables can be created
+    // from it.
+    pub fn warp_to_slot(&self, slot: u64) {
+        let mut svm = self.source().svm_mut();
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.slot = slot;
+        clock.epoch = EpochSchedule::without_warmup().get_epoch(slot);
+        svm.set_sysvar(&clock);
+
+        let blockhash = svm.latest_blockhash();
+        svm.set_sysvar(&SlotHashes::new(&[(slot, blockhash)]));
+    }
+
+    pub fn set_account(&self, address: Pubkey, account: Account) -> Result<()> {
+        self.source().svm_mut().set_account(address, account)?;
+        // Accounts written behind the builder's back aren't in its plan cache yet.
+        self.builder.invalidate_account_cache();
+
+        Ok(())
+    }
+
+    // Loads accounts recorded from a cluster with `RecordingAccountSource` and warps to the slot
+    // they were recorded at. Errors when the fixture hasn't been recorded.
+    pub fn load_fixture(&self, name: &str) -> Result<()> {
+        let path = fixture_path(name);
+        if !path.exists() {
+            return Err(eyre!("Fixture {} not found", path.display()));
+        }
+
+        let fixture = AccountFixture::load(&path.to_string_lossy())?;
+        for (address, account) in &fixture.accounts {
+            self.set_account(address.parse()?, account.to_account()?)?;
+        }
+        self.warp_to_slot(fixture.slot);
+
+        Ok(())
+    }
+
+    // Initializes the program config with the program keypair when it sits next to the binary,
+    // otherwise writes the config account directly.
+    pub fn initialize_program(&mut self) -> Result<()> {
+        let keypair_path = self
+            .programs_dir
+            .join(format!("{}-keypair.json", BORING_VAULT_PROGRAM));
+
+        match read_keypair_file(&keypair_path) {
+            Ok(program_keypair) => {
+                self.builder.initialize(
+                    self.authority.pubkey(),
+                    self.payer_signer(),
+                    KeypairOrPublickey::Keypair(program_keypair),
+                )?;
+                self.send()?;
+            }
+            Err(_) => self.set_program_config(self.authority.pubkey(), 0)?,
+        }
+
+        Ok(())
+    }
+
+    pub fn set_program_config(&self, authority: Pubkey, vault_count: u64) -> Result<()> {
+        let mut data = vec![];
+        ProgramConfig {
+            authority,
+            vault_count,
+        }
+        .try_serialize(&mut data)?;
+
+        self.set_program_account(get_program_config_pda(), data)
+    }
+
+    // Writes a CPI digest account as `initialize_cpi_digest` would, without the program.
+    pub fn set_cpi_digest(&self, cpi_digest_pda: Pubkey, operators: Operators) -> Result<()> {
+        let mut data = vec![];
+        CpiDigest { operators }.try_serialize(&mut data)?;
+
+        self.set_program_account(cpi_digest_pda, data)
+    }
+
+    // Initializes the program and deploys a vault for a new 9 decimal mint that accepts its base
+    // asset, returning the vault id and the mint.
+    pub fn setup_vault(&mut self) -> Result<(u64, Pubkey)> {
+        self.initialize_program()?;
+
+        let base_mint = self.create_mint(9)?;
+        let vault_id = self.deploy_vault(base_mint)?;
+        self.allow_pegged_asset(vault_id, base_mint)?;
+
+        Ok((vault_id, base_mint))
+    }
+
+    // Deploys a vault for `base_asset` with the authority as exchange rate provider, payout
+    // address and strategist.
+    pub fn deploy_vault(&mut self, base_asset: Pubkey) -> Result<u64> {
+        let vault_id = self.builder.next_vault_id()?;
+
+        self.builder.deploy(
+            self.authority.pubkey(),
+            self.authority_signer(),
+            Some(vault_id),
+            base_asset,
+            format!("Test Vault {}", vault_id),
+            format!("TV{}", vault_id),
+            Some(self.authority.pubkey()),
+            LAMPORTS_PER_SOL,
+            Some(self.authority.pubkey()),
+            10_500,
+            9_500,
+            0,
+            None,
+            None,
+            None,
+            Some(self.authority.pubkey()),
+        )?;
+        self.send()?;
+
+        Ok(vault_id)
+    }
+
+    // Lets deposits and withdrawals of `mint` at the base asset price, so no oracle is needed.
+    pub fn allow_pegged_asset(&mut self, vault_id: u64, mint: Pubkey) -> Result<()> {
+        self.builder.update_asset_data(
+            self.authority_signer(),
+            vault_id,
+            mint,
+            true,
+            true,
+            0,
+            true,
+            Pubkey::default(),
+            false,
+            0,
+            0,
+        )?;
+        self.send()?;
+
+        Ok(())
+    }
+
+    pub fn create_mint(&self, decimals: u8) -> Result<Pubkey> {
+        let mint = Pubkey::new_unique();
+        self.set_mint(mint, decimals)?;
+
+        Ok(mint)
+    }
+
+    pub fn set_mint(&self, address: Pubkey, decimals: u8) -> Result<()> {
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                mint_authority: COption::Some(self.payer.pubkey()),
+                supply: u64::MAX / 2,
+                decimals,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &mut data,
+        )?;
+
+        self.set_token_program_account(address, data)
+    }
+
+    // Writes an spl token ATA holding `amount`, without going through the token program.
+    pub fn set_token_balance(&self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Result<Pubkey> {
+        let ata = get_associated_token_address_with_program_id(owner, mint, &spl_token::ID);
+
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint: *mint,
+                owner: *owner,
+                amount,
+                delegate: COption::None,
+                state: AccountState::Initialized,
+                is_native: COption::None,
+                delegated_amount: 0,
+                close_authority: COption::None,
+            },
+            &mut data,
+        )?;
+        self.set_token_program_account(ata, data)?;
+
+        Ok(ata)
+    }
+
+    // Works for spl token and token 2022 accounts, whose amount sits at the same offset.
+    pub fn token_balance(&self, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> u64 {
+        let ata = get_associated_token_address_with_program_id(owner, mint, token_program);
+
+        self.source()
+            .get_account(&ata)
+            .ok()
+            .and_then(|account| account.data.get(64..72).map(|bytes| bytes.to_vec()))
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .unwrap_or(0)
+    }
+
+    pub fn lamports(&self, address: &Pubkey) -> u64 {
+        self.source()
+            .get_account(address)
+            .map(|account| account.lamports)
+            .unwrap_or(0)
+    }
+
+    fn set_program_account(&self, address: Pubkey, data: Vec<u8>) -> Result<()> {
+        self.set_owned_account(address, boring_vault_svm::ID, data)
+    }
+
+    fn set_token_program_account(&self, address: Pubkey, data: Vec<u8>) -> Result<()> {
+        self.set_owned_account(address, spl_token::ID, data)
+    }
+
+    fn set_owned_account(&self, address: Pubkey, owner: Pubkey, data: Vec<u8>) -> Result<()> {
+        let lamports = self
+            .source()
+            .get_minimum_balance_for_rent_exemption(data.len())?;
+
+        self.set_account(
+            address,
+            Account {
+                lamports,
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+    }
+}
+
+pub fn programs_dir() -> PathBuf {
+    env::var(PROGRAMS_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| Path::new(env!("CARGO_MANIFEST_DIR")).join("programs"))
+}
+
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(format!("{}.json", name))
+}
+
+// Config of the harness builders: the repo's protocol data and no wait between status polls.
+pub fn builder_config() -> BuilderConfig {
+    let data_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data");
+
+    BuilderConfig::default()
+        .with_data_path(data_path.to_string_lossy())
+        .with_send(SendConfig::default().with_poll_interval(Duration::ZERO))
+}
+
+fn program_path(programs_dir: &Path, name: &str) -> PathBuf {
+    programs_dir.join(format!("{}.so", name))
+}
@@ -0,0 +1,73 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+use eyre::Result;
+use serde_json::{json, Value};
+
+// Serves JSON-RPC on a random localhost port, one request per connection, and returns its url.
+// Errors from `handler` are returned as JSON-RPC errors.
+pub fn serve_json_rpc<F>(name: &'static str, handler: F) -> Result<String>
+where
+    F: Fn(&Value) -> Result<Value> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}", listener.local_addr()?);
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = handle_request(&handler, stream) {
+                eprintln!("{}: {}", name, e);
+            }
+        }
+    });
+
+    Ok(url)
+}
+
+fn handle_request<F>(handler: &F, mut stream: TcpStream) -> Result<()>
+where
+    F: Fn(&Value) -> Result<Value>,
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let request: Value = serde_json::from_slice(&body)?;
+
+    let response = match handler(&request) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32602, "message": e.to_string() },
+        }),
+    }
+    .to_string();
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.len(),
+        response
+    )?;
+
+    Ok(())
+}
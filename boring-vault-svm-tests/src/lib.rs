@@ -0,0 +1,19 @@
+pub mod block_engine;
+pub mod harness;
+pub mod json_rpc;
+pub mod rpc_server;
+pub mod signing_service;
+pub mod solend;
+pub mod stake_pool;
+pub mod status_source;
+pub mod svm;
+
+pub use block_engine::*;
+pub use harness::*;
+pub use json_rpc::*;
+pub use rpc_server::*;
+pub use signing_service::*;
+pub use solend::*;
+pub use stake_pool::*;
+pub use status_source::*;
+pub use svm::*;
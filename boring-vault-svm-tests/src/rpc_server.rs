@@ -0,0 +1,111 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring_vault_svm_core::utils::AccountSource;
+use eyre::{eyre, Result};
+use serde_json::{json, Value};
+use solana_account::Account;
+use solana_client::{
+    rpc_config::{RpcProgramAccountsConfig, RpcSimulateTransactionConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::transaction::VersionedTransaction;
+
+use crate::{serve_json_rpc, svm::encode_ui_account, LiteSvmSource};
+
+// Serves the account reads, blockhashes and simulations of the async builder over JSON-RPC from
+// `source`, so it runs against the same svm as the blocking one. Returns the url.
+pub fn serve_lite_svm_rpc(source: LiteSvmSource) -> Result<String> {
+    serve_json_rpc("svm rpc", move |request| {
+        rpc_result(&source, request["method"].as_str(), &request["params"])
+    })
+}
+
+fn rpc_result(source: &LiteSvmSource, method: Option<&str>, params: &Value) -> Result<Value> {
+    let context = json!({ "slot": source.get_slot()? });
+
+    match method {
+        Some("getAccountInfo") => {
+            let address = parse_pubkey(&params[0])?;
+            let account = source.get_multiple_accounts(&[address])?.pop().flatten();
+
+            Ok(json!({ "context": context, "value": account.as_ref().map(encode_ui_account) }))
+        }
+        Some("getMultipleAccounts") => {
+            let addresses = params[0]
+                .as_array()
+                .ok_or_else(|| eyre!("Expected a list of addresses"))?
+                .iter()
+                .map(parse_pubkey)
+                .collect::<Result<Vec<_>>>()?;
+            let accounts = source
+                .get_multiple_accounts(&addresses)?
+                .iter()
+                .map(|account| account.as_ref().map(encode_ui_account))
+                .collect::<Vec<_>>();
+
+            Ok(json!({ "context": context, "value": accounts }))
+        }
+        Some("getProgramAccounts") => {
+            let program_id = parse_pubkey(&params[0])?;
+            let config: RpcProgramAccountsConfig = serde_json::from_value(params[1].clone())?;
+            let filters = config.filters.unwrap_or_default();
+
+            let accounts = source
+                .get_program_accounts(&program_id, &[])?
+                .into_iter()
+                .filter(|(_, account)| filters.iter().all(|filter| matches(filter, account)))
+                .map(|(address, account)| {
+                    json!({ "pubkey": address.to_string(), "account": encode_ui_account(&account) })
+                })
+                .collect::<Vec<_>>();
+
+            Ok(json!(accounts))
+        }
+        Some("getMinimumBalanceForRentExemption") => {
+            let data_len = params[0]
+                .as_u64()
+                .ok_or_else(|| eyre!("Expected a data length"))?;
+
+            Ok(json!(source.get_minimum_balance_for_rent_exemption(
+                data_len as usize
+            )?))
+        }
+        Some("getSlot") => Ok(json!(source.get_slot()?)),
+        Some("getLatestBlockhash") => Ok(json!({
+            "context": context,
+            "value": {
+                "blockhash": source.get_latest_blockhash()?.to_string(),
+                "lastValidBlockHeight": source.get_slot()? + 150,
+            },
+        })),
+        Some("simulateTransaction") => {
+            let encoded = params[0]
+                .as_str()
+                .ok_or_else(|| eyre!("Expected an encoded transaction"))?;
+            let transaction: VersionedTransaction =
+                bincode::deserialize(&STANDARD.decode(encoded)?)?;
+            let config: RpcSimulateTransactionConfig = serde_json::from_value(params[1].clone())?;
+
+            Ok(json!({
+                "context": context,
+                "value": source.simulate_transaction(&transaction, config)?,
+            }))
+        }
+        method => Err(eyre!("Unsupported method {:?}", method)),
+    }
+}
+
+fn parse_pubkey(value: &Value) -> Result<Pubkey> {
+    Ok(value
+        .as_str()
+        .ok_or_else(|| eyre!("Expected an address"))?
+        .parse()?)
+}
+
+fn matches(filter: &RpcFilterType, account: &Account) -> bool {
+    match filter {
+        RpcFilterType::DataSize(size) => account.data.len() as u64 == *size,
+        RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&account.data),
+        RpcFilterType::TokenAccountState => false,
+    }
+}
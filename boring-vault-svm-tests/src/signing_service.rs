@@ -0,0 +1,81 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring_vault_svm_core::utils::RemoteSigner;
+use eyre::{eyre, Result};
+use serde_json::{json, Value};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+use crate::serve_json_rpc;
+
+// Local stand-in for a remote signing service. Serves `RemoteSigner`'s JSON-RPC protocol for one
+// keypair on a random localhost port, one request per connection.
+pub struct SigningService {
+    url: String,
+    pubkey: Pubkey,
+    signed: Arc<AtomicUsize>,
+}
+
+impl SigningService {
+    pub fn start(keypair: Keypair) -> Result<Self> {
+        let pubkey = keypair.pubkey();
+        let signed = Arc::new(AtomicUsize::new(0));
+
+        let counter = signed.clone();
+        let url = serve_json_rpc("signing service", move |request| {
+            let result = rpc_result(&keypair, request)?;
+            if request["method"] == "signMessage" {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+
+            Ok(result)
+        })?;
+
+        Ok(Self {
+            url,
+            pubkey,
+            signed,
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    pub fn signer(&self) -> RemoteSigner {
+        RemoteSigner::new(self.url.clone(), self.pubkey)
+    }
+
+    // Number of messages signed so far.
+    pub fn signed(&self) -> usize {
+        self.signed.load(Ordering::SeqCst)
+    }
+}
+
+fn rpc_result(keypair: &Keypair, request: &Value) -> Result<Value> {
+    match request["method"].as_str() {
+        Some("getPubkey") => Ok(json!(keypair.pubkey().to_string())),
+        Some("signMessage") => {
+            let pubkey = request["params"][0].as_str().unwrap_or_default();
+            if pubkey != keypair.pubkey().to_string() {
+                return Err(eyre!("Unknown signer {}", pubkey));
+            }
+            let message = request["params"][1]
+                .as_str()
+                .ok_or_else(|| eyre!("Missing message"))?;
+            let signature = keypair.sign_message(&STANDARD.decode(message)?);
+
+            Ok(json!(signature.to_string()))
+        }
+        method => Err(eyre!("Unknown method {:?}", method)),
+    }
+}
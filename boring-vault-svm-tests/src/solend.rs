@@ -0,0 +1,39 @@
+use eyre::{eyre, Result};
+use solana_pubkey::Pubkey;
+
+pub const SOLEND_RESERVE_LEN: usize = 619;
+
+// Addresses read from a packed Solend reserve.
+pub struct SolendReserve {
+    pub address: Pubkey,
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub pyth_oracle: Pubkey,
+    pub switchboard_oracle: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_supply: Pubkey,
+}
+
+impl SolendReserve {
+    pub fn unpack(address: Pubkey, data: &[u8]) -> Result<Self> {
+        let pubkey_at = |offset: usize| -> Result<Pubkey> {
+            let bytes: [u8; 32] = data
+                .get(offset..offset + 32)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| eyre!("Reserve {} is too short", address))?;
+            Ok(Pubkey::new_from_array(bytes))
+        };
+
+        Ok(Self {
+            address,
+            lending_market: pubkey_at(10)?,
+            liquidity_mint: pubkey_at(42)?,
+            liquidity_supply: pubkey_at(75)?,
+            pyth_oracle: pubkey_at(107)?,
+            switchboard_oracle: pubkey_at(139)?,
+            collateral_mint: pubkey_at(227)?,
+            collateral_supply: pubkey_at(267)?,
+        })
+    }
+}
@@ -0,0 +1,150 @@
+use boring_vault_svm_core::manage_instructions::{
+    get_stake_pool_deposit_authority, STAKE_ACCOUNT_SIZE, STAKE_PROGRAM_ID,
+};
+use solana_program::stake::{
+    stake_flags::StakeFlags,
+    state::{Delegation, Meta, Stake, StakeStateV2},
+};
+use solana_program_pack::Pack;
+use solana_pubkey::Pubkey;
+use solana_sdk::{account::Account, native_token::LAMPORTS_PER_SOL};
+use spl_stake_pool::state::{AccountType, StakePool, ValidatorList, ValidatorStakeInfo};
+
+const MAX_VALIDATORS: u32 = 10;
+
+pub struct MockStakePool {
+    pub address: Pubkey,
+    pub program_id: Pubkey,
+    pub validator_list: Pubkey,
+    pub reserve_stake: Pubkey,
+    pub pool_mint: Pubkey,
+    pub manager_fee_account: Pubkey,
+}
+
+impl Default for MockStakePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockStakePool {
+    // A pool on a fork of the stake pool program, like Sanctum's.
+    pub fn new() -> Self {
+        Self {
+            address: Pubkey::new_unique(),
+            program_id: Pubkey::new_unique(),
+            validator_list: Pubkey::new_unique(),
+            reserve_stake: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            manager_fee_account: Pubkey::new_unique(),
+        }
+    }
+
+    // The pool's state, taking deposits from anyone.
+    pub fn state(&self) -> StakePool {
+        StakePool {
+            account_type: AccountType::StakePool,
+            manager: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            stake_deposit_authority: get_stake_pool_deposit_authority(
+                &self.address,
+                &self.program_id,
+            ),
+            stake_withdraw_bump_seed: 255,
+            validator_list: self.validator_list,
+            reserve_stake: self.reserve_stake,
+            pool_mint: self.pool_mint,
+            manager_fee_account: self.manager_fee_account,
+            token_program_id: spl_token::ID,
+            ..StakePool::default()
+        }
+    }
+
+    pub fn account(&self, sol_deposit_authority: Option<Pubkey>) -> Account {
+        self.state_account(&StakePool {
+            sol_deposit_authority,
+            ..self.state()
+        })
+    }
+
+    pub fn state_account(&self, state: &StakePool) -> Account {
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: borsh::to_vec(state).unwrap(),
+            owner: self.program_id,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    // A `ValidatorList` with one active validator, plus preallocated space.
+    pub fn validator_list_account(&self, vote_account: &Pubkey, seed_suffix: u32) -> Account {
+        self.validators_account(&[validator(vote_account, seed_suffix, LAMPORTS_PER_SOL)])
+    }
+
+    pub fn validators_account(&self, validators: &[ValidatorStakeInfo]) -> Account {
+        let mut validator_list = ValidatorList::new(MAX_VALIDATORS);
+        validator_list.validators = validators.to_vec();
+        let mut data = borsh::to_vec(&validator_list).unwrap();
+        data.resize(9 + MAX_VALIDATORS as usize * ValidatorStakeInfo::LEN, 0);
+
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: self.program_id,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+}
+
+// An active validator of the pool.
+pub fn validator(
+    vote_account: &Pubkey,
+    seed_suffix: u32,
+    active_stake_lamports: u64,
+) -> ValidatorStakeInfo {
+    ValidatorStakeInfo {
+        active_stake_lamports: active_stake_lamports.into(),
+        validator_seed_suffix: seed_suffix.into(),
+        vote_account_address: *vote_account,
+        ..ValidatorStakeInfo::default()
+    }
+}
+
+pub fn stake_state_account(state: &StakeStateV2) -> Account {
+    let mut data = bincode::serialize(state).unwrap();
+    data.resize(STAKE_ACCOUNT_SIZE as usize, 0);
+
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner: STAKE_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// 1 SOL delegated to `vote_account` since epoch 0, deactivated in `deactivation_epoch`.
+pub fn stake_account(vote_account: &Pubkey, deactivation_epoch: u64) -> Account {
+    let delegation = Delegation {
+        voter_pubkey: *vote_account,
+        stake: LAMPORTS_PER_SOL,
+        activation_epoch: 0,
+        deactivation_epoch,
+        ..Delegation::default()
+    };
+
+    stake_state_account(&StakeStateV2::Stake(
+        Meta::default(),
+        Stake {
+            delegation,
+            credits_observed: 0,
+        },
+        StakeFlags::empty(),
+    ))
+}
+
+pub fn delegated_stake_account(vote_account: &Pubkey) -> Account {
+    stake_account(vote_account, u64::MAX)
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring_vault_svm_core::utils::AccountSource;
+use eyre::{eyre, Result};
+use serde_json::{json, Value};
+use solana_account::Account;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    hash::Hash,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use solana_transaction_status_client_types::{TransactionConfirmationStatus, TransactionStatus};
+
+use crate::serve_json_rpc;
+
+// Stand-in for a cluster that answers signature statuses from a script, to drive the send loop.
+// Each status poll takes the next status, the last one repeating. The block height is the number
+// of polls so far, so a blockhash expires `expires_after` polls after it was fetched.
+#[derive(Clone)]
+pub struct MockStatusSource {
+    state: Arc<Mutex<MockStatusState>>,
+}
+
+struct MockStatusState {
+    statuses: VecDeque<Option<TransactionStatus>>,
+    expires_after: u64,
+    polls: u64,
+    sent_transactions: Vec<VersionedTransaction>,
+    rebroadcasts: usize,
+}
+
+impl MockStatusSource {
+    pub fn new(statuses: Vec<Option<TransactionStatus>>, expires_after: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockStatusState {
+                statuses: statuses.into(),
+                expires_after,
+                polls: 0,
+                sent_transactions: vec![],
+                rebroadcasts: 0,
+            })),
+        }
+    }
+
+    // Transactions sent with preflight, one per signed attempt.
+    pub fn sent_transactions(&self) -> Vec<VersionedTransaction> {
+        self.state.lock().unwrap().sent_transactions.clone()
+    }
+
+    pub fn rebroadcasts(&self) -> usize {
+        self.state.lock().unwrap().rebroadcasts
+    }
+
+    // Serves the same script over JSON-RPC for the async builder. Returns the url.
+    pub fn serve_rpc(&self) -> Result<String> {
+        let source = self.clone();
+        serve_json_rpc("status rpc", move |request| {
+            source.rpc_result(request["method"].as_str(), &request["params"])
+        })
+    }
+
+    fn rpc_result(&self, method: Option<&str>, params: &Value) -> Result<Value> {
+        let context = json!({ "slot": self.get_slot()? });
+
+        match method {
+            Some("getVersion") => Ok(json!({ "solana-core": "2.2.0" })),
+            Some("getLatestBlockhash") => {
+                let (blockhash, last_valid_block_height) =
+                    self.get_latest_blockhash_with_expiry()?;
+
+                Ok(json!({
+                    "context": context,
+                    "value": {
+                        "blockhash": blockhash.to_string(),
+                        "lastValidBlockHeight": last_valid_block_height,
+                    },
+                }))
+            }
+            Some("getBlockHeight") => Ok(json!(self.get_block_height()?)),
+            Some("sendTransaction") => {
+                let encoded = params[0]
+                    .as_str()
+                    .ok_or_else(|| eyre!("Expected an encoded transaction"))?;
+                let transaction: VersionedTransaction =
+                    bincode::deserialize(&STANDARD.decode(encoded)?)?;
+                let config: RpcSendTransactionConfig = serde_json::from_value(params[1].clone())?;
+
+                Ok(json!(self
+                    .send_transaction_with_config(&transaction, config)?
+                    .to_string()))
+            }
+            Some("getSignatureStatuses") => {
+                let signatures = params[0]
+                    .as_array()
+                    .ok_or_else(|| eyre!("Expected a list of signatures"))?
+                    .iter()
+                    .map(|signature| Ok(signature.as_str().unwrap_or_default().parse()?))
+                    .collect::<Result<Vec<Signature>>>()?;
+
+                Ok(json!({
+                    "context": context,
+                    "value": self.get_signature_statuses(&signatures)?,
+                }))
+            }
+            method => Err(eyre!("Unsupported method {:?}", method)),
+        }
+    }
+}
+
+impl AccountSource for MockStatusSource {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        Err(eyre!("Account {} not found", address))
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(vec![None; addresses.len()])
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        self.get_block_height()
+    }
+
+    // A new blockhash per call, so every attempt is signed differently.
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(Hash::new_unique())
+    }
+
+    fn get_block_height(&self) -> Result<u64> {
+        Ok(self.state.lock().unwrap().polls)
+    }
+
+    fn get_latest_blockhash_with_expiry(&self) -> Result<(Hash, u64)> {
+        let state = self.state.lock().unwrap();
+
+        Ok((Hash::new_unique(), state.polls + state.expires_after))
+    }
+
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        self.state
+            .lock()
+            .unwrap()
+            .sent_transactions
+            .push(transaction.clone());
+
+        Ok(transaction.signatures[0])
+    }
+
+    // Sends skipping preflight are the send loop's rebroadcasts.
+    fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        if !config.skip_preflight {
+            return self.send_transaction(transaction);
+        }
+        self.state.lock().unwrap().rebroadcasts += 1;
+
+        Ok(transaction.signatures[0])
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        let mut state = self.state.lock().unwrap();
+        state.polls += 1;
+        let status = match state.statuses.len() {
+            0 => None,
+            1 => state.statuses[0].clone(),
+            _ => state.statuses.pop_front().flatten(),
+        };
+
+        Ok(vec![status; signatures.len()])
+    }
+}
+
+// A status reached at `confirmation_status`, failed with `err` if given.
+pub fn transaction_status(
+    confirmation_status: TransactionConfirmationStatus,
+    err: Option<TransactionError>,
+) -> TransactionStatus {
+    // Finalized statuses are the ones without a confirmation count.
+    let confirmations = match confirmation_status {
+        TransactionConfirmationStatus::Processed => Some(0),
+        TransactionConfirmationStatus::Confirmed => Some(1),
+        TransactionConfirmationStatus::Finalized => None,
+    };
+
+    TransactionStatus {
+        slot: 1,
+        confirmations,
+        status: err.clone().map_or(Ok(()), Err),
+        err,
+        confirmation_status: Some(confirmation_status),
+    }
+}
@@ -0,0 +1,225 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring_vault_svm_core::utils::AccountSource;
+use eyre::{eyre, Result};
+use litesvm::LiteSVM;
+use solana_account::{Account, ReadableAccount};
+use solana_account_decoder_client_types::{UiAccount, UiAccountData, UiAccountEncoding};
+use solana_client::{
+    client_error::ClientError, rpc_config::RpcSimulateTransactionConfig,
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    clock::Clock, hash::Hash, signature::Signature, transaction::VersionedTransaction,
+};
+use solana_transaction_status_client_types::{
+    TransactionConfirmationStatus, TransactionStatus, UiReturnDataEncoding, UiTransactionReturnData,
+};
+
+// Runs the builders against an in-process LiteSVM instead of a validator. Transactions execute
+// as soon as they are sent, so every landed signature is reported as finalized. Clones share the
+// same svm.
+#[derive(Clone)]
+pub struct LiteSvmSource {
+    svm: Arc<Mutex<LiteSVM>>,
+    account_reads: Arc<AtomicUsize>,
+}
+
+impl LiteSvmSource {
+    pub fn new(svm: LiteSVM) -> Self {
+        Self {
+            svm: Arc::new(Mutex::new(svm)),
+            account_reads: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    // `get_account` and `get_multiple_accounts` calls so far, what an RPC would count as round
+    // trips.
+    pub fn account_reads(&self) -> usize {
+        self.account_reads.load(Ordering::SeqCst)
+    }
+
+    pub fn svm(&self) -> MutexGuard<'_, LiteSVM> {
+        self.svm.lock().unwrap()
+    }
+
+    pub fn svm_mut(&self) -> MutexGuard<'_, LiteSVM> {
+        self.svm()
+    }
+
+    // Logs of a sent transaction, including ones that failed after being charged fees.
+    pub fn logs(&self, signature: &Signature) -> Vec<String> {
+        match self.svm().get_transaction(signature) {
+            Some(Ok(meta)) => meta.logs.clone(),
+            Some(Err(failed)) => failed.meta.logs.clone(),
+            None => vec![],
+        }
+    }
+}
+
+impl AccountSource for LiteSvmSource {
+    fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        self.account_reads.fetch_add(1, Ordering::SeqCst);
+        self.svm()
+            .get_account(address)
+            .filter(|account| account.lamports > 0)
+            .ok_or_else(|| eyre!("Account {} not found", address))
+    }
+
+    fn get_multiple_accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.account_reads.fetch_add(1, Ordering::SeqCst);
+        let svm = self.svm();
+
+        Ok(addresses
+            .iter()
+            .map(|address| {
+                svm.get_account(address)
+                    .filter(|account| account.lamports > 0)
+            })
+            .collect())
+    }
+
+    fn get_slot(&self) -> Result<u64> {
+        Ok(self.svm().get_sysvar::<Clock>().slot)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.svm().latest_blockhash())
+    }
+
+    // Failures are returned as a client error so the builder reports them like a failed preflight.
+    fn send_transaction(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        match self.svm_mut().send_transaction(transaction.clone()) {
+            Ok(meta) => Ok(meta.signature),
+            Err(failed) => {
+                let logs = failed.meta.logs.join("\n");
+                Err(eyre::Report::new(ClientError::from(failed.err)).wrap_err(logs))
+            }
+        }
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(self.svm().minimum_balance_for_rent_exemption(data_len))
+    }
+
+    fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_prefix: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .svm()
+            .accounts_db()
+            .inner
+            .iter()
+            .filter(|(_, account)| {
+                account.lamports() > 0
+                    && account.owner() == program_id
+                    && account.data().starts_with(data_prefix)
+            })
+            .map(|(address, account)| (*address, Account::from(account.clone())))
+            .collect())
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        let svm = self.svm();
+        let slot = svm.get_sysvar::<Clock>().slot;
+
+        Ok(signatures
+            .iter()
+            .map(|signature| {
+                svm.get_transaction(signature).map(|result| {
+                    let err = result.as_ref().err().map(|failed| failed.err.clone());
+                    TransactionStatus {
+                        slot,
+                        confirmations: None,
+                        status: err.clone().map_or(Ok(()), Err),
+                        err,
+                        confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+                    }
+                })
+            })
+            .collect())
+    }
+
+    // Expects the svm to be built without signature verification, since the builder simulates
+    // unsigned transactions.
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> Result<RpcSimulateTransactionResult> {
+        let svm = self.svm();
+
+        let mut transaction = transaction.clone();
+        if config.replace_recent_blockhash {
+            transaction
+                .message
+                .set_recent_blockhash(svm.latest_blockhash());
+        }
+
+        let (err, meta, post_accounts) = match svm.simulate_transaction(transaction) {
+            Ok(info) => (None, info.meta, info.post_accounts),
+            Err(failed) => (Some(failed.err), failed.meta, vec![]),
+        };
+
+        let accounts = match config.accounts {
+            Some(accounts_config) if err.is_none() => Some(
+                accounts_config
+                    .addresses
+                    .iter()
+                    .map(|address| {
+                        let address: Pubkey = address.parse()?;
+                        let account = post_accounts
+                            .iter()
+                            .find(|(key, _)| *key == address)
+                            .map(|(_, account)| Account::from(account.clone()))
+                            .or_else(|| svm.get_account(&address));
+
+                        Ok(account.map(|account| encode_ui_account(&account)))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            _ => None,
+        };
+
+        // An empty return data is what the RPC reports as none.
+        let return_data = (!meta.return_data.data.is_empty()).then(|| UiTransactionReturnData {
+            program_id: meta.return_data.program_id.to_string(),
+            data: (
+                STANDARD.encode(&meta.return_data.data),
+                UiReturnDataEncoding::Base64,
+            ),
+        });
+
+        Ok(RpcSimulateTransactionResult {
+            err,
+            logs: Some(meta.logs),
+            accounts,
+            units_consumed: Some(meta.compute_units_consumed),
+            loaded_accounts_data_size: None,
+            return_data,
+            inner_instructions: None,
+            replacement_blockhash: None,
+        })
+    }
+}
+
+pub(crate) fn encode_ui_account(account: &Account) -> UiAccount {
+    UiAccount {
+        lamports: account.lamports,
+        data: UiAccountData::Binary(STANDARD.encode(&account.data), UiAccountEncoding::Base64),
+        owner: account.owner.to_string(),
+        executable: account.executable,
+        rent_epoch: account.rent_epoch,
+        space: Some(account.data.len() as u64),
+    }
+}
@@ -0,0 +1,119 @@
+use anchor_lang::AccountSerialize;
+use boring_vault_svm_core::{
+    builder::SendOutcome,
+    manage_instructions::KAMINO_PROGRAM_ID,
+    utils::{
+        boring_vault_svm::{
+            self,
+            accounts::{AssetData, BoringVault},
+        },
+        get_asset_data_pda, get_vault_state_pda,
+    },
+};
+use boring_vault_svm_tests::TestHarness;
+use eyre::Result;
+use solana_account::Account;
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+const VAULT_ID: u64 = 1;
+
+// Writes a vault state and asset data for `mint` as `deploy` and `update_asset_data` would,
+// without the program.
+fn write_vault(harness: &TestHarness, mint: Pubkey) -> Result<()> {
+    let vault_state_pda = get_vault_state_pda(VAULT_ID);
+
+    let mut vault_state = vec![];
+    BoringVault::default().try_serialize(&mut vault_state)?;
+    let mut asset_data = vec![];
+    AssetData::default().try_serialize(&mut asset_data)?;
+
+    for (address, data) in [
+        (vault_state_pda, vault_state),
+        (get_asset_data_pda(vault_state_pda, mint), asset_data),
+    ] {
+        harness.set_account(
+            address,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: boring_vault_svm::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn deposit_and_withdraw_read_accounts_once() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let mint = harness.create_mint(9)?;
+    write_vault(&harness, mint)?;
+
+    let reads = harness.source().account_reads();
+    harness
+        .builder
+        .deposit(harness.payer_signer(), VAULT_ID, mint, 1_000, 0)?;
+    assert_eq!(harness.source().account_reads() - reads, 1);
+    // The user, vault and user share ATAs are all missing.
+    assert_eq!(harness.builder.instructions.len(), 4);
+
+    harness.builder.clear()?;
+    let reads = harness.source().account_reads();
+    harness
+        .builder
+        .withdraw(harness.payer_signer(), VAULT_ID, mint, 1_000, 0)?;
+    assert_eq!(harness.source().account_reads() - reads, 1);
+
+    Ok(())
+}
+
+#[test]
+fn lend_reads_accounts_once() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+
+    let reads = harness.source().account_reads();
+    harness.builder.lend(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        LAMPORTS_PER_SOL,
+        0,
+        0,
+        None,
+    )?;
+    assert_eq!(harness.source().account_reads() - reads, 1);
+
+    let lend_ix = harness.builder.instructions.last().unwrap();
+    assert_eq!(lend_ix.program_id, boring_vault_svm::ID);
+    assert!(lend_ix
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == KAMINO_PROGRAM_ID));
+
+    Ok(())
+}
+
+#[test]
+fn failed_send_clears_account_cache() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let mint = harness.create_mint(9)?;
+    write_vault(&harness, mint)?;
+
+    harness
+        .builder
+        .deposit(harness.payer_signer(), VAULT_ID, mint, 1_000, 0)?;
+    assert!(!harness.builder.account_cache.is_empty());
+
+    // The vault state is a placeholder, so the deposit can't land.
+    let outcome = harness.builder.try_send(harness.payer.insecure_clone());
+    assert!(!matches!(outcome, Ok(SendOutcome::Landed { .. })));
+    assert!(!harness.builder.instructions.is_empty());
+    assert!(harness.builder.account_cache.is_empty());
+
+    Ok(())
+}
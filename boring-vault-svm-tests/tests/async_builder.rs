@@ -0,0 +1,640 @@
+use anchor_lang::AccountSerialize;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring_vault_svm_core::{
+    config::{JupiterSwapInstructions, StrategyManifest},
+    instructions::{get_external_instruction_cpi_digest, get_unstake_account, WSOL_MINT},
+    manage_instructions::{
+        parse_operators, ExternalInstruction, MintJitoSol, JUPITER_PROGRAM_ID, KAMINO_PROGRAM_ID,
+    },
+    nonblocking::AsyncBuilder,
+    utils::{
+        boring_vault_svm::{
+            self,
+            accounts::{AssetData, BoringVault},
+        },
+        get_asset_data_pda, get_obligation, get_vault_pda, get_vault_state_pda,
+    },
+    KeypairOrPublickey,
+};
+use boring_vault_svm_tests::{delegated_stake_account, stake_account, MockStakePool, TestHarness};
+use eyre::Result;
+use serde_json::{json, Value};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_sdk::{account::Account, native_token::LAMPORTS_PER_SOL};
+use solana_signer::Signer;
+use solana_system_interface::{instruction as system_instruction, program as system_program};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, ID as ASSOCIATED_TOKEN_PROGRAM_ID,
+};
+
+const VAULT_ID: u64 = 1;
+const TAG: u8 = 0;
+const ID: u8 = 0;
+
+// Both builders planned the same instructions, groups, lookup tables and signers.
+fn assert_same_plan(harness: &TestHarness, builder: &AsyncBuilder) {
+    assert_eq!(builder.instructions, harness.builder.instructions);
+    assert_eq!(builder.groups, harness.builder.groups);
+    assert_eq!(builder.lookup_tables, harness.builder.lookup_tables);
+
+    let mut signers = builder.signers.keys().collect::<Vec<_>>();
+    let mut expected = harness.builder.signers.keys().collect::<Vec<_>>();
+    signers.sort();
+    expected.sort();
+    assert_eq!(signers, expected);
+}
+
+fn owned_account(owner: Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn kamino_repay_and_withdraw() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let (lend, market) = harness.builder.kamino_config.lend_reserve(None)?;
+    let (borrow, _) = harness.builder.kamino_config.borrow_reserve(None)?;
+
+    // A `Reserve` with a debt farm, and an obligation with one deposit and one borrow.
+    let mut reserve = vec![0; 8624];
+    reserve[96..128].copy_from_slice(Pubkey::new_unique().as_ref());
+    harness.set_account(borrow.reserve, owned_account(KAMINO_PROGRAM_ID, reserve))?;
+    let obligation = get_obligation(
+        TAG,
+        ID,
+        &get_vault_pda(VAULT_ID, 0),
+        &market.lending_market,
+        &system_program::ID,
+        &system_program::ID,
+        &KAMINO_PROGRAM_ID,
+    );
+    let mut data = vec![0; 3344];
+    data[96..128].copy_from_slice(lend.reserve.as_ref());
+    data[128..136].copy_from_slice(&(5 * LAMPORTS_PER_SOL).to_le_bytes());
+    data[1208..1240].copy_from_slice(borrow.reserve.as_ref());
+    data[1296..1312].copy_from_slice(&(1u128 << 60).to_le_bytes());
+    harness.set_account(obligation, owned_account(KAMINO_PROGRAM_ID, data))?;
+
+    let mut builder = harness.async_builder()?;
+    let position = builder
+        .get_kamino_position(VAULT_ID, 0, TAG, ID, None)
+        .await?;
+    assert_eq!(position.reserves(), vec![lend.reserve, borrow.reserve]);
+
+    harness.builder.repay(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        None,
+        TAG,
+        ID,
+        None,
+    )?;
+    harness.builder.withdraw_lend(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        Some(LAMPORTS_PER_SOL),
+        TAG,
+        ID,
+        None,
+    )?;
+    builder
+        .repay(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            None,
+            TAG,
+            ID,
+            None,
+        )
+        .await?;
+    builder
+        .withdraw_lend(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            Some(LAMPORTS_PER_SOL),
+            TAG,
+            ID,
+            None,
+        )
+        .await?;
+    assert_same_plan(&harness, &builder);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn jupiter_swap() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let user = Pubkey::new_unique();
+    let output_mint = Pubkey::new_unique();
+    let ata = |owner: &Pubkey, mint: &Pubkey| {
+        get_associated_token_address_with_program_id(owner, mint, &spl_token::ID)
+    };
+    let account = |pubkey: &Pubkey, is_signer: bool, is_writable: bool| -> Value {
+        json!({ "pubkey": pubkey.to_string(), "isSigner": is_signer, "isWritable": is_writable })
+    };
+
+    // A `/swap-instructions` response selling wSOL, with an ATA setup and the wSOL cleanup.
+    let swap = JupiterSwapInstructions::from_json(
+        &json!({
+            "computeBudgetInstructions": [],
+            "setupInstructions": [{
+                "programId": ASSOCIATED_TOKEN_PROGRAM_ID.to_string(),
+                "accounts": [
+                    account(&user, true, true),
+                    account(&ata(&user, &output_mint), false, true),
+                    account(&user, false, false),
+                    account(&output_mint, false, false),
+                    account(&Pubkey::default(), false, false),
+                    account(&spl_token::ID, false, false),
+                ],
+                "data": STANDARD.encode([1]),
+            }],
+            "swapInstruction": {
+                "programId": JUPITER_PROGRAM_ID.to_string(),
+                "accounts": [
+                    account(&spl_token::ID, false, false),
+                    account(&Pubkey::new_unique(), false, false),
+                    account(&user, true, false),
+                    account(&ata(&user, WSOL_MINT), false, true),
+                    account(&Pubkey::new_unique(), false, true),
+                    account(&Pubkey::new_unique(), false, true),
+                    account(&ata(&user, &output_mint), false, true),
+                    account(WSOL_MINT, false, false),
+                    account(&output_mint, false, false),
+                    account(&JUPITER_PROGRAM_ID, false, false),
+                    account(&JUPITER_PROGRAM_ID, false, false),
+                    account(&Pubkey::new_unique(), false, false),
+                    account(&JUPITER_PROGRAM_ID, false, false),
+                    account(&Pubkey::new_unique(), false, true),
+                ],
+                "data": STANDARD.encode([193, 32, 155, 51, 65, 214, 156, 129, 1, 0, 0, 0, 0, 0, 0, 0]),
+            },
+            "cleanupInstruction": {
+                "programId": spl_token::ID.to_string(),
+                "accounts": [
+                    account(&ata(&user, WSOL_MINT), false, true),
+                    account(&user, false, true),
+                    account(&user, true, false),
+                ],
+                "data": STANDARD.encode([9]),
+            },
+            "otherInstructions": [],
+            "addressLookupTableAddresses": [Pubkey::new_unique().to_string()],
+            "prioritizationFeeLamports": 0,
+        })
+        .to_string(),
+    )?;
+
+    let mut builder = harness.async_builder()?;
+    harness.builder.swap(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        &swap,
+    )?;
+    builder
+        .swap(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            &swap,
+        )
+        .await?;
+    assert_same_plan(&harness, &builder);
+    assert_eq!(builder.lookup_tables, swap.address_lookup_table_addresses);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn stake_pool_deposits_and_withdrawals() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let pool = MockStakePool::new();
+    let vote_account = Pubkey::new_unique();
+    let stake_account = Pubkey::new_unique();
+    harness.set_account(pool.address, pool.account(None))?;
+    harness.set_account(
+        pool.validator_list,
+        pool.validator_list_account(&vote_account, 0),
+    )?;
+    harness.set_account(stake_account, delegated_stake_account(&vote_account))?;
+
+    let mut builder = harness.async_builder()?;
+    harness.builder.stake_pool_deposit_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        pool.address,
+        LAMPORTS_PER_SOL,
+    )?;
+    harness.builder.stake_pool_withdraw_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        pool.address,
+        LAMPORTS_PER_SOL,
+    )?;
+    harness.builder.stake_pool_deposit_stake(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        pool.address,
+        stake_account,
+    )?;
+    builder
+        .stake_pool_deposit_sol(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            pool.address,
+            LAMPORTS_PER_SOL,
+        )
+        .await?;
+    builder
+        .stake_pool_withdraw_sol(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            pool.address,
+            LAMPORTS_PER_SOL,
+        )
+        .await?;
+    builder
+        .stake_pool_deposit_stake(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            pool.address,
+            stake_account,
+        )
+        .await?;
+    assert_same_plan(&harness, &builder);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn jito_stake_accounts() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    // Epoch 10, with one unstake account still active and one deactivated long ago.
+    harness.warp_to_slot(4_320_000);
+    for (index, deactivation_epoch) in [u64::MAX, 1].into_iter().enumerate() {
+        let (address, _) = get_unstake_account(VAULT_ID, 0, index as u8)?;
+        harness.set_account(
+            address,
+            stake_account(&Pubkey::new_unique(), deactivation_epoch),
+        )?;
+    }
+    let (active, _) = get_unstake_account(VAULT_ID, 0, 0)?;
+
+    let mut builder = harness.async_builder()?;
+    let pending = builder.get_pending_stake_accounts(VAULT_ID, 0).await?;
+    let expected = harness.builder.get_pending_stake_accounts(VAULT_ID, 0)?;
+    assert_eq!(pending.len(), 2);
+    assert_eq!(
+        pending
+            .iter()
+            .map(|pending| pending.status)
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|pending| pending.status)
+            .collect::<Vec<_>>()
+    );
+
+    let withdrawn = harness.builder.withdraw_unstaked_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+    )?;
+    harness.builder.deactivate_stake(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        active,
+    )?;
+    harness.builder.withdraw_stake(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        active,
+    )?;
+    let withdrawn_async = builder
+        .withdraw_unstaked_sol(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+        )
+        .await?;
+    builder
+        .deactivate_stake(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            active,
+        )
+        .await?;
+    builder
+        .withdraw_stake(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            active,
+        )
+        .await?;
+    assert_eq!(withdrawn_async, withdrawn);
+    assert_same_plan(&harness, &builder);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn raw_instruction_and_solend() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let transfer =
+        system_instruction::transfer(&get_vault_pda(VAULT_ID, 0), &Pubkey::new_unique(), 1_000);
+    let operators =
+        parse_operators("IngestInstruction(0, 4), IngestAccount(1), IngestInstructionDataSize")?;
+    let solend = (0..9).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+    let mut builder = harness.async_builder()?;
+    harness.builder.manage_raw_instruction(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        transfer.clone(),
+        operators.clone(),
+    )?;
+    harness.builder.deposit_solend(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        solend[0],
+        solend[1],
+        solend[2],
+        solend[3],
+        solend[4],
+        solend[5],
+        solend[6],
+        solend[7],
+        solend[8],
+        LAMPORTS_PER_SOL,
+    )?;
+    builder
+        .manage_raw_instruction(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            transfer,
+            operators,
+        )
+        .await?;
+    builder
+        .deposit_solend(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            solend[0],
+            solend[1],
+            solend[2],
+            solend[3],
+            solend[4],
+            solend[5],
+            solend[6],
+            solend[7],
+            solend[8],
+            LAMPORTS_PER_SOL,
+        )
+        .await?;
+    assert_same_plan(&harness, &builder);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn provisions_cpi_digests() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let manifest = StrategyManifest::from_json(&format!(
+        r#"{{
+            "vault_id": {VAULT_ID},
+            "instructions": [
+                {{ "kind": "mint_jito_sol", "sub_account": 0 }},
+                {{ "kind": "transfer_sol_between_sub_accounts", "sub_account": 0, "to_sub_account": 1 }}
+            ]
+        }}"#
+    ))?;
+    // The MintJitoSol digest is already on chain.
+    let mint = MintJitoSol::new(VAULT_ID, 0, 0);
+    let (mint_digest_pda, _) = get_external_instruction_cpi_digest(&mint)?;
+    harness.set_cpi_digest(mint_digest_pda, mint.ix_operators())?;
+
+    let mut builder = harness.async_builder()?;
+    let provisions = builder
+        .provision_cpi_digests(harness.authority_signer(), &manifest)
+        .await?;
+    let expected = harness
+        .builder
+        .provision_cpi_digests(harness.authority_signer(), &manifest)?;
+    assert_eq!(
+        provisions
+            .iter()
+            .map(|provision| (provision.cpi_digest_pda, provision.exists))
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|provision| (provision.cpi_digest_pda, provision.exists))
+            .collect::<Vec<_>>()
+    );
+    assert!(provisions[0].exists && !provisions[1].exists);
+    assert_same_plan(&harness, &builder);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn vault_operations() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.set_program_config(harness.authority.pubkey(), VAULT_ID)?;
+    let mint = harness.create_mint(9)?;
+    let vault_state_pda = get_vault_state_pda(VAULT_ID);
+    let mut vault_state = vec![];
+    BoringVault::default().try_serialize(&mut vault_state)?;
+    let mut asset_data = vec![];
+    AssetData::default().try_serialize(&mut asset_data)?;
+    harness.set_account(
+        vault_state_pda,
+        owned_account(boring_vault_svm::ID, vault_state),
+    )?;
+    harness.set_account(
+        get_asset_data_pda(vault_state_pda, mint),
+        owned_account(boring_vault_svm::ID, asset_data),
+    )?;
+    let nonce_account = Keypair::new();
+
+    let mut builder = harness.async_builder()?;
+    for _ in 0..2 {
+        let vault = builder
+            .deploy(
+                harness.authority.pubkey(),
+                harness.authority_signer(),
+                None,
+                mint,
+                "Test Vault".to_string(),
+                "TV".to_string(),
+                None,
+                LAMPORTS_PER_SOL,
+                None,
+                10_500,
+                9_500,
+                0,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+        let expected = harness.builder.deploy(
+            harness.authority.pubkey(),
+            harness.authority_signer(),
+            None,
+            mint,
+            "Test Vault".to_string(),
+            "TV".to_string(),
+            None,
+            LAMPORTS_PER_SOL,
+            None,
+            10_500,
+            9_500,
+            0,
+            None,
+            None,
+            None,
+            None,
+        )?;
+        assert_eq!(vault.vault_state, expected.vault_state);
+    }
+    assert_eq!(builder.pending_vault_ids, vec![VAULT_ID, VAULT_ID + 1]);
+    assert_eq!(builder.next_vault_id().await?, VAULT_ID + 2);
+
+    builder
+        .deposit(harness.payer_signer(), VAULT_ID, mint, 1_000, 0)
+        .await?;
+    harness
+        .builder
+        .deposit(harness.payer_signer(), VAULT_ID, mint, 1_000, 0)?;
+    builder
+        .withdraw(harness.payer_signer(), VAULT_ID, mint, 1_000, 0)
+        .await?;
+    harness
+        .builder
+        .withdraw(harness.payer_signer(), VAULT_ID, mint, 1_000, 0)?;
+    builder
+        .wrap_sol(
+            harness.payer_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            1_000,
+        )
+        .await?;
+    harness.builder.wrap_sol(
+        harness.payer_signer(),
+        Some(harness.authority_signer()),
+        VAULT_ID,
+        0,
+        1_000,
+    )?;
+    builder
+        .create_nonce_account(
+            harness.payer_signer(),
+            KeypairOrPublickey::Keypair(nonce_account.insecure_clone()),
+            harness.authority.pubkey(),
+        )
+        .await?;
+    harness.builder.create_nonce_account(
+        harness.payer_signer(),
+        KeypairOrPublickey::Keypair(nonce_account.insecure_clone()),
+        harness.authority.pubkey(),
+    )?;
+    assert_same_plan(&harness, &builder);
+
+    // The accounts read for the plan stay cached until it's cleared.
+    assert!(!builder.account_cache.is_empty());
+    builder.clear()?;
+    assert!(builder.account_cache.is_empty());
+    assert!(builder.pending_vault_ids.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn checks_cpi_digests_when_configured() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.builder.cpi_digest_check = true;
+    let mut builder = harness.async_builder()?;
+    builder.cpi_digest_check = true;
+
+    // Without the program loaded, the `view_cpi_digest` simulation fails in both builders.
+    let err = builder
+        .transfer_sol_between_sub_accounts(
+            harness.payer_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            1,
+            1_000,
+        )
+        .await
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("view_cpi_digest simulation failed"));
+    let expected = harness
+        .builder
+        .transfer_sol_between_sub_accounts(
+            harness.payer_signer(),
+            Some(harness.authority_signer()),
+            VAULT_ID,
+            0,
+            1,
+            1_000,
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), expected.to_string());
+    assert!(builder.instructions.is_empty());
+
+    Ok(())
+}
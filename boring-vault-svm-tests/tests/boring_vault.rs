@@ -0,0 +1,399 @@
+use boring_vault_svm_core::{
+    builder::SendOutcome,
+    instructions::get_external_instruction_cpi_digest,
+    manage_instructions::TransferSolBetweenSubAccounts,
+    utils::{get_vault_pda, get_vault_share_mint, get_vault_state_pda, AccountSource},
+    view::{get_asset_data, get_vault_state},
+    KeypairOrPublickey,
+};
+use boring_vault_svm_tests::{TestHarness, BORING_VAULT_PROGRAM};
+use eyre::Result;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_signer::Signer;
+
+const DEPOSIT_AMOUNT: u64 = 5 * LAMPORTS_PER_SOL;
+
+fn vault_harness() -> Result<(TestHarness, u64, Pubkey)> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+
+    let (vault_id, base_mint) = harness.setup_vault()?;
+
+    Ok((harness, vault_id, base_mint))
+}
+
+fn user_shares(harness: &TestHarness, user: &Pubkey, vault_id: u64) -> u64 {
+    let share_mint = get_vault_share_mint(get_vault_state_pda(vault_id));
+
+    harness.token_balance(user, &share_mint, &spl_token_2022::ID)
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn deposit_manage_withdraw() -> Result<()> {
+    let (mut harness, vault_id, base_mint) = vault_harness()?;
+    let user = harness.payer.pubkey();
+    let vault_pda = get_vault_pda(vault_id, 0);
+
+    harness.set_token_balance(&user, &base_mint, DEPOSIT_AMOUNT)?;
+    harness.builder.deposit(
+        harness.payer_signer(),
+        vault_id,
+        base_mint,
+        DEPOSIT_AMOUNT,
+        0,
+    )?;
+    harness.send()?;
+
+    assert_eq!(harness.token_balance(&user, &base_mint, &spl_token::ID), 0);
+    assert_eq!(
+        harness.token_balance(&vault_pda, &base_mint, &spl_token::ID),
+        DEPOSIT_AMOUNT
+    );
+    // The exchange rate is one base asset per share.
+    assert_eq!(user_shares(&harness, &user, vault_id), DEPOSIT_AMOUNT);
+
+    // The CPI digest doesn't exist yet, so the authority initializes it in the same transaction.
+    harness.airdrop(&vault_pda, LAMPORTS_PER_SOL)?;
+    harness.builder.transfer_sol_between_sub_accounts(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        1,
+        LAMPORTS_PER_SOL,
+    )?;
+    harness.send()?;
+    assert!(harness.lamports(&get_vault_pda(vault_id, 1)) >= LAMPORTS_PER_SOL);
+
+    harness.builder.withdraw(
+        harness.payer_signer(),
+        vault_id,
+        base_mint,
+        DEPOSIT_AMOUNT,
+        0,
+    )?;
+    harness.send()?;
+
+    assert_eq!(user_shares(&harness, &user, vault_id), 0);
+    assert_eq!(
+        harness.token_balance(&user, &base_mint, &spl_token::ID),
+        DEPOSIT_AMOUNT
+    );
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn initialize_and_deploy() -> Result<()> {
+    let (harness, vault_id, base_mint) = vault_harness()?;
+
+    let (_, vault) = get_vault_state(harness.source(), vault_id)?;
+    assert_eq!(vault.config.vault_id, vault_id);
+    assert_eq!(vault.config.authority, harness.authority.pubkey());
+    assert_eq!(vault.teller.base_asset, base_mint);
+    assert_eq!(vault.teller.exchange_rate, LAMPORTS_PER_SOL);
+    assert_eq!(vault.manager.strategist, harness.authority.pubkey());
+
+    let (_, asset_data) = get_asset_data(harness.source(), vault_id, base_mint)?;
+    assert!(asset_data.allow_deposits);
+    assert!(asset_data.is_pegged_to_base_asset);
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn deploy_vaults_in_one_plan() -> Result<()> {
+    let (mut harness, vault_id, base_mint) = vault_harness()?;
+    let authority = harness.authority.pubkey();
+
+    // Each deploy takes the next id and is configured before any of them land.
+    let mut vaults = vec![];
+    for _ in 0..2 {
+        let vault = harness.builder.deploy(
+            authority,
+            harness.authority_signer(),
+            None,
+            base_mint,
+            "Plan Vault".to_string(),
+            "PV".to_string(),
+            None,
+            LAMPORTS_PER_SOL,
+            None,
+            10_500,
+            9_500,
+            0,
+            None,
+            None,
+            None,
+            None,
+        )?;
+        harness.builder.update_asset_data(
+            harness.authority_signer(),
+            vault.vault_id,
+            base_mint,
+            true,
+            true,
+            0,
+            true,
+            Pubkey::default(),
+            false,
+            0,
+            0,
+        )?;
+        vaults.push(vault);
+    }
+    assert_eq!(vaults[0].vault_id, vault_id + 1);
+    assert_eq!(vaults[1].vault_id, vault_id + 2);
+    harness.send()?;
+
+    for vault in vaults {
+        let (vault_state_pda, vault_state) = get_vault_state(harness.source(), vault.vault_id)?;
+        assert_eq!(vault_state_pda, vault.vault_state);
+        assert_eq!(vault_state.config.vault_id, vault.vault_id);
+        assert!(harness.source().get_account(&vault.share_mint).is_ok());
+
+        let (asset_data_pda, asset_data) =
+            get_asset_data(harness.source(), vault.vault_id, base_mint)?;
+        assert_eq!(asset_data_pda, vault.asset_data(base_mint));
+        assert!(asset_data.allow_deposits);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn deposit_sol() -> Result<()> {
+    let (mut harness, vault_id, _) = vault_harness()?;
+    let user = harness.payer.pubkey();
+    let vault_pda = get_vault_pda(vault_id, 0);
+
+    // SOL deposits are keyed by the default pubkey.
+    harness.allow_pegged_asset(vault_id, Pubkey::default())?;
+    let vault_lamports = harness.lamports(&vault_pda);
+
+    harness
+        .builder
+        .deposit_sol(harness.payer_signer(), vault_id, DEPOSIT_AMOUNT, 0)?;
+    harness.send()?;
+
+    assert_eq!(
+        harness.lamports(&vault_pda) - vault_lamports,
+        DEPOSIT_AMOUNT
+    );
+    assert_eq!(user_shares(&harness, &user, vault_id), DEPOSIT_AMOUNT);
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn pause_and_unpause() -> Result<()> {
+    let (mut harness, vault_id, base_mint) = vault_harness()?;
+
+    harness
+        .builder
+        .pause(harness.authority_signer(), vault_id)?;
+    harness.send()?;
+    assert!(get_vault_state(harness.source(), vault_id)?.1.config.paused);
+
+    // Deposits are rejected while paused.
+    harness.set_token_balance(&harness.payer.pubkey(), &base_mint, DEPOSIT_AMOUNT)?;
+    harness.builder.deposit(
+        harness.payer_signer(),
+        vault_id,
+        base_mint,
+        DEPOSIT_AMOUNT,
+        0,
+    )?;
+    assert!(matches!(harness.try_send()?, SendOutcome::Failed { .. }));
+
+    harness
+        .builder
+        .unpause(harness.authority_signer(), vault_id)?;
+    harness.send()?;
+    assert!(!get_vault_state(harness.source(), vault_id)?.1.config.paused);
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn transfer_and_accept_authority() -> Result<()> {
+    let (mut harness, vault_id, _) = vault_harness()?;
+    let new_authority = Keypair::new();
+    harness.airdrop(&new_authority.pubkey(), LAMPORTS_PER_SOL)?;
+
+    harness.builder.transfer_authority(
+        harness.authority_signer(),
+        vault_id,
+        new_authority.pubkey(),
+    )?;
+    harness.send()?;
+    let (_, vault) = get_vault_state(harness.source(), vault_id)?;
+    assert_eq!(vault.config.pending_authority, new_authority.pubkey());
+
+    harness.builder.accept_authority(
+        KeypairOrPublickey::Keypair(new_authority.insecure_clone()),
+        vault_id,
+    )?;
+    harness.send()?;
+    let (_, vault) = get_vault_state(harness.source(), vault_id)?;
+    assert_eq!(vault.config.authority, new_authority.pubkey());
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn close_cpi_digest() -> Result<()> {
+    let (mut harness, vault_id, _) = vault_harness()?;
+    harness.airdrop(&get_vault_pda(vault_id, 0), LAMPORTS_PER_SOL)?;
+
+    harness.builder.transfer_sol_between_sub_accounts(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        1,
+        1,
+    )?;
+    harness.send()?;
+
+    let (cpi_digest_pda, digest) = get_external_instruction_cpi_digest(
+        &TransferSolBetweenSubAccounts::new(vault_id, 0, 1, 1),
+    )?;
+    assert!(harness.source().get_account(&cpi_digest_pda).is_ok());
+
+    harness
+        .builder
+        .close_cpi_digest(harness.authority_signer(), vault_id, digest)?;
+    harness.send()?;
+    assert!(harness.source().get_account(&cpi_digest_pda).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn update_exchange_rate() -> Result<()> {
+    let (mut harness, vault_id, _) = vault_harness()?;
+    let provider = Keypair::new();
+    harness.airdrop(&provider.pubkey(), LAMPORTS_PER_SOL)?;
+
+    harness.builder.update_exchange_rate_provider(
+        harness.authority_signer(),
+        vault_id,
+        provider.pubkey(),
+    )?;
+    harness.send()?;
+
+    harness.builder.configure_exchange_rate_update_bounds(
+        harness.authority_signer(),
+        vault_id,
+        11_000,
+        9_000,
+        0,
+    )?;
+    harness.send()?;
+
+    let new_exchange_rate = LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 20;
+    harness.builder.update_exchange_rate(
+        KeypairOrPublickey::Keypair(provider.insecure_clone()),
+        vault_id,
+        new_exchange_rate,
+    )?;
+    harness.send()?;
+
+    let (_, vault) = get_vault_state(harness.source(), vault_id)?;
+    assert_eq!(vault.teller.exchange_rate_provider, provider.pubkey());
+    assert_eq!(
+        vault.teller.allowed_exchange_rate_change_upper_bound,
+        11_000
+    );
+    assert_eq!(vault.teller.allowed_exchange_rate_change_lower_bound, 9_000);
+    assert_eq!(vault.teller.exchange_rate, new_exchange_rate);
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn set_vault_roles() -> Result<()> {
+    let (mut harness, vault_id, _) = vault_harness()?;
+    let withdraw_authority = Pubkey::new_unique();
+    let payout = Pubkey::new_unique();
+    let strategist = Pubkey::new_unique();
+
+    harness.builder.set_withdraw_authority(
+        harness.authority_signer(),
+        vault_id,
+        withdraw_authority,
+    )?;
+    harness
+        .builder
+        .set_payout(harness.authority_signer(), vault_id, payout)?;
+    harness
+        .builder
+        .set_strategist(harness.authority_signer(), vault_id, strategist)?;
+    harness.send()?;
+
+    let (_, vault) = get_vault_state(harness.source(), vault_id)?;
+    assert_eq!(vault.teller.withdraw_authority, withdraw_authority);
+    assert_eq!(vault.teller.payout_address, payout);
+    assert_eq!(vault.manager.strategist, strategist);
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn set_fees_and_claim() -> Result<()> {
+    let (mut harness, vault_id, base_mint) = vault_harness()?;
+
+    harness
+        .builder
+        .set_fees(harness.authority_signer(), vault_id, 100, 1_000)?;
+    harness.send()?;
+
+    let (_, vault) = get_vault_state(harness.source(), vault_id)?;
+    assert_eq!(vault.teller.platform_fee_bps, 100);
+    assert_eq!(vault.teller.performance_fee_bps, 1_000);
+
+    // Nothing is owed yet, so this only checks the claim accounts line up.
+    harness
+        .builder
+        .claim_fees_in_base(harness.authority_signer(), vault_id, 0)?;
+    harness.send()?;
+    assert_eq!(
+        harness.token_balance(&harness.authority.pubkey(), &base_mint, &spl_token::ID),
+        vault.teller.fees_owed_in_base_asset
+    );
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn set_deposit_and_withdraw_sub_accounts() -> Result<()> {
+    let (mut harness, vault_id, _) = vault_harness()?;
+
+    harness
+        .builder
+        .set_deposit_sub_account(harness.authority_signer(), vault_id, 1)?;
+    harness
+        .builder
+        .set_withdraw_sub_account(harness.authority_signer(), vault_id, 2)?;
+    harness.send()?;
+
+    let (_, vault) = get_vault_state(harness.source(), vault_id)?;
+    assert_eq!(vault.config.deposit_sub_account, 1);
+    assert_eq!(vault.config.withdraw_sub_account, 2);
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring_vault_svm_core::{
+    config::BundleConfig,
+    utils::{boring_vault_svm, DecodedError},
+};
+use boring_vault_svm_tests::{
+    builder_config, MockBlockEngine, MockBundleResult, TestHarness, MOCK_BUNDLE_ID,
+};
+use eyre::Result;
+use serde_json::json;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    transaction::{TransactionError, VersionedTransaction},
+};
+use solana_signer::Signer;
+use solana_system_interface::program as system_program;
+
+const TIP_LAMPORTS: u64 = 5_000;
+
+// Points the builder at `block_engine` and adds `groups` instructions of `program_id`, each too
+// large to share a transaction with another. The mock never executes them, so they aren't
+// simulated for a compute budget either.
+fn bundle_harness(
+    block_engine: &MockBlockEngine,
+    program_id: Pubkey,
+    groups: usize,
+) -> Result<TestHarness> {
+    let mut harness = TestHarness::new()?;
+    harness.builder.bundle_config = BundleConfig::default()
+        .with_block_engine_url(block_engine.url())
+        .with_tip_account(Pubkey::new_unique())
+        .with_tip_lamports(TIP_LAMPORTS)
+        .with_poll_interval(Duration::ZERO);
+    harness.builder.compute_budget = None;
+
+    for _ in 0..groups {
+        harness.builder.start_group();
+        harness
+            .builder
+            .instructions
+            .push(Instruction::new_with_bytes(program_id, &[1; 700], vec![]));
+    }
+
+    Ok(harness)
+}
+
+fn decode_transaction(b64: &serde_json::Value) -> Result<VersionedTransaction> {
+    let bytes = STANDARD.decode(b64.as_str().unwrap_or_default())?;
+
+    Ok(bincode::deserialize(&bytes)?)
+}
+
+#[test]
+fn sends_tipped_bundle() -> Result<()> {
+    let block_engine = MockBlockEngine::start(MockBundleResult::Landed)?;
+    let mut harness = bundle_harness(&block_engine, Pubkey::new_unique(), 2)?;
+    let tip_account = harness.builder.bundle_config.tip_account;
+
+    let bundle_id = harness
+        .builder
+        .try_bundle_all(harness.payer.insecure_clone())?;
+    assert_eq!(bundle_id, MOCK_BUNDLE_ID);
+    assert!(harness.builder.instructions.is_empty());
+
+    let bundles = block_engine.sent_bundles();
+    assert_eq!(bundles.len(), 1);
+    assert_eq!(bundles[0][1], json!({ "encoding": "base64" }));
+    let transactions = bundles[0][0]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(decode_transaction)
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(transactions.len(), 2);
+
+    for transaction in &transactions {
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+        assert_eq!(
+            transaction.message.static_account_keys()[0],
+            harness.payer.pubkey()
+        );
+    }
+
+    // Only the last transaction tips, with the transfer as its last instruction.
+    assert!(!transactions[0]
+        .message
+        .static_account_keys()
+        .contains(&tip_account));
+    let message = &transactions[1].message;
+    let tip = message.instructions().last().unwrap();
+    let keys = message.static_account_keys();
+    assert_eq!(keys[tip.program_id_index as usize], system_program::ID);
+    assert_eq!(keys[tip.accounts[1] as usize], tip_account);
+    assert_eq!(tip.data[4..], TIP_LAMPORTS.to_le_bytes());
+
+    // The status was polled for the id `sendBundle` returned.
+    let statuses = block_engine
+        .requests()
+        .into_iter()
+        .find(|request| request["method"] == "getBundleStatuses")
+        .unwrap();
+    assert_eq!(statuses["params"], json!([[MOCK_BUNDLE_ID]]));
+
+    Ok(())
+}
+
+#[test]
+fn maps_rejected_bundles() -> Result<()> {
+    let block_engine = MockBlockEngine::start(MockBundleResult::Rejected(
+        "bundle contains an already processed transaction".to_string(),
+    ))?;
+    let mut harness = bundle_harness(&block_engine, Pubkey::new_unique(), 1)?;
+
+    let err = harness
+        .builder
+        .try_bundle_all(harness.payer.insecure_clone())
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("bundle contains an already processed transaction"));
+
+    // Nothing landed, so the instructions are kept for a retry.
+    assert_eq!(harness.builder.instructions.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn decodes_failed_bundles() -> Result<()> {
+    let block_engine = MockBlockEngine::start(MockBundleResult::Failed(
+        TransactionError::InstructionError(0, InstructionError::Custom(6000)),
+    ))?;
+    let mut harness = bundle_harness(&block_engine, boring_vault_svm::ID, 1)?;
+
+    let err = harness
+        .builder
+        .try_bundle_all(harness.payer.insecure_clone())
+        .unwrap_err();
+    assert!(err.to_string().contains(MOCK_BUNDLE_ID));
+
+    let decoded = err.downcast_ref::<DecodedError>().unwrap();
+    assert_eq!(decoded.program_id, Some(boring_vault_svm::ID));
+    assert_eq!(decoded.name.as_deref(), Some("SlippageExceeded"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn async_builder_sends_tipped_bundle() -> Result<()> {
+    let block_engine = MockBlockEngine::start(MockBundleResult::Landed)?;
+    let harness = bundle_harness(&block_engine, Pubkey::new_unique(), 2)?;
+    let mut builder = harness.async_builder()?;
+    builder.bundle_config = harness.builder.bundle_config.clone();
+    builder.compute_budget = None;
+    builder.instructions = harness.builder.instructions.clone();
+    builder.groups = harness.builder.groups.clone();
+
+    let bundle_id = builder
+        .try_bundle_all(harness.payer.insecure_clone())
+        .await?;
+    assert_eq!(bundle_id, MOCK_BUNDLE_ID);
+    assert!(builder.instructions.is_empty());
+
+    // Planned like the blocking builder's bundle: two transactions, the last one tipping.
+    let bundles = block_engine.sent_bundles();
+    assert_eq!(bundles.len(), 1);
+    let transactions = bundles[0][0]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(decode_transaction)
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(transactions.len(), 2);
+    assert!(transactions[1]
+        .message
+        .static_account_keys()
+        .contains(&harness.builder.bundle_config.tip_account));
+
+    Ok(())
+}
+
+#[test]
+fn block_engine_follows_rpc_cluster() -> Result<()> {
+    let builder = builder_config()
+        .with_rpc_url("https://api.mainnet-beta.solana.com")
+        .build();
+    assert_eq!(
+        builder.bundle_config.block_engine_url()?,
+        "https://mainnet.block-engine.jito.wtf/api/v1/bundles"
+    );
+
+    let builder = builder_config()
+        .with_rpc_url("https://api.testnet.solana.com")
+        .build();
+    assert!(builder
+        .bundle_config
+        .block_engine_url()?
+        .contains("testnet"));
+
+    // An explicit url wins over the cluster's.
+    let builder = builder_config()
+        .with_rpc_url("https://api.mainnet-beta.solana.com")
+        .with_bundle(BundleConfig::default().with_block_engine_url("http://localhost:1234"))
+        .build();
+    assert_eq!(
+        builder.bundle_config.block_engine_url()?,
+        "http://localhost:1234"
+    );
+
+    // Jito has no devnet block engine, so devnet transactions are never bundled to mainnet.
+    let mut builder = builder_config()
+        .with_rpc_url("https://api.devnet.solana.com")
+        .build();
+    builder.instructions.push(Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &[1],
+        vec![],
+    ));
+    let err = builder.try_bundle_all(Keypair::new()).unwrap_err();
+    assert!(err.to_string().contains("No block engine url"));
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use boring_vault_svm_core::{
+    builder::{
+        compute_budget_instructions, get_compute_unit_limit,
+        placeholder_compute_budget_instructions, select_compute_unit_price, MAX_COMPUTE_UNIT_LIMIT,
+    },
+    config::{ComputeBudgetConfig, PriorityFeePolicy},
+    utils::AccountSource,
+};
+use boring_vault_svm_tests::TestHarness;
+use eyre::Result;
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, hash::Hash, native_token::LAMPORTS_PER_SOL,
+};
+use solana_signer::Signer;
+use solana_system_interface::{instruction as system_instruction, program as system_program};
+
+#[test]
+fn compute_budget_is_on_by_default() -> Result<()> {
+    let harness = TestHarness::new()?;
+    assert!(harness.builder.compute_budget.is_some());
+    assert_eq!(
+        harness.builder.transaction_prefix_instructions(),
+        placeholder_compute_budget_instructions(Some(&ComputeBudgetConfig::default()))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn missing_dependencies_get_max_limit() -> Result<()> {
+    let harness = TestHarness::new()?;
+    let blockhash = harness.source().get_latest_blockhash()?;
+    let mint = harness.create_mint(6)?;
+    let destination = harness.set_token_balance(&Pubkey::new_unique(), &mint, 0)?;
+
+    // The source account an earlier transaction of the plan would create doesn't exist yet.
+    let instruction = spl_token::instruction::transfer(
+        &spl_token::ID,
+        &Pubkey::new_unique(),
+        &destination,
+        &harness.payer.pubkey(),
+        &[],
+        1,
+    )?;
+    let budgeted = harness.builder.apply_compute_budget(
+        std::slice::from_ref(&instruction),
+        &harness.payer.pubkey(),
+        blockhash,
+        &[],
+    )?;
+    assert_eq!(
+        budgeted[0],
+        ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT)
+    );
+
+    // Other simulation failures aren't hidden behind the max limit.
+    let transfer = system_instruction::transfer(
+        &harness.payer.pubkey(),
+        &Pubkey::new_unique(),
+        harness.lamports(&harness.payer.pubkey()) + 1,
+    );
+    let err = harness
+        .builder
+        .apply_compute_budget(&[transfer], &harness.payer.pubkey(), blockhash, &[])
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Compute budget simulation failed"));
+
+    Ok(())
+}
+
+#[test]
+fn compute_unit_limit_adds_margin() {
+    let config = ComputeBudgetConfig::default().with_compute_unit_margin_bps(1_000);
+    assert_eq!(get_compute_unit_limit(100_000, &config), 110_000);
+    // The margin rounds down.
+    assert_eq!(get_compute_unit_limit(12_345, &config), 13_579);
+    assert_eq!(get_compute_unit_limit(0, &config), 0);
+
+    // The limit never goes above what the runtime allows.
+    assert_eq!(
+        get_compute_unit_limit(1_300_000, &config),
+        MAX_COMPUTE_UNIT_LIMIT
+    );
+
+    let no_margin = config.with_compute_unit_margin_bps(0);
+    assert_eq!(get_compute_unit_limit(12_345, &no_margin), 12_345);
+}
+
+#[test]
+fn compute_unit_price_follows_policy() {
+    let mut fees = vec![50, 10, 40, 20, 30];
+
+    let none = ComputeBudgetConfig::default().with_priority_fee(PriorityFeePolicy::None);
+    assert_eq!(select_compute_unit_price(&none, &mut fees), None);
+
+    let fixed = ComputeBudgetConfig::default()
+        .with_priority_fee(PriorityFeePolicy::Fixed(7))
+        .with_max_priority_fee(None);
+    assert_eq!(select_compute_unit_price(&fixed, &mut fees), Some(7));
+
+    let median = ComputeBudgetConfig::default()
+        .with_priority_fee(PriorityFeePolicy::Percentile(50))
+        .with_max_priority_fee(None);
+    assert_eq!(select_compute_unit_price(&median, &mut fees), Some(30));
+    assert_eq!(select_compute_unit_price(&median, &mut []), Some(0));
+
+    let capped = median.with_max_priority_fee(Some(25));
+    assert_eq!(select_compute_unit_price(&capped, &mut fees), Some(25));
+}
+
+#[test]
+fn limit_comes_before_price() {
+    let instructions = compute_budget_instructions(200_000, Some(5));
+    assert_eq!(
+        instructions,
+        vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(5),
+        ]
+    );
+
+    assert_eq!(compute_budget_instructions(200_000, None).len(), 1);
+}
+
+#[test]
+fn budget_is_prepended_after_nonce_advance() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.builder.compute_budget = Some(
+        ComputeBudgetConfig::default()
+            .with_priority_fee(PriorityFeePolicy::Fixed(5))
+            .with_max_priority_fee(None),
+    );
+    let transfer = system_instruction::transfer(
+        &harness.payer.pubkey(),
+        &Pubkey::new_unique(),
+        LAMPORTS_PER_SOL,
+    );
+    let blockhash = harness.source().get_latest_blockhash()?;
+
+    // The limit is sized from a simulation of the transfer.
+    let budgeted = harness.builder.apply_compute_budget(
+        std::slice::from_ref(&transfer),
+        &harness.payer.pubkey(),
+        blockhash,
+        &[],
+    )?;
+    let compute_budget_program = budgeted[0].program_id;
+    assert_eq!(budgeted.len(), 3);
+    assert_ne!(
+        budgeted[0],
+        ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT)
+    );
+    assert_eq!(
+        budgeted[1],
+        ComputeBudgetInstruction::set_compute_unit_price(5)
+    );
+    assert_eq!(budgeted[2], transfer);
+
+    // The nonce advance has to stay the first instruction.
+    harness
+        .builder
+        .use_durable_nonce(Pubkey::new_unique(), harness.payer_signer());
+    let message = harness.builder.compile_transaction_message(
+        &[transfer],
+        &harness.payer.pubkey(),
+        Hash::default(),
+        &[],
+    )?;
+    let keys = message.static_account_keys();
+    let program_ids = message
+        .instructions()
+        .iter()
+        .map(|ix| keys[ix.program_id_index as usize])
+        .collect::<Vec<_>>();
+    assert_eq!(
+        program_ids,
+        vec![
+            system_program::ID,
+            compute_budget_program,
+            compute_budget_program,
+            system_program::ID
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use boring_vault_svm_core::{
+    config::StrategyManifest,
+    instructions::{get_external_instruction_cpi_digest, view_cpi_digest},
+    manage_instructions::{
+        ExternalInstruction, MintJitoSol, TransferSol, TransferSolBetweenSubAccounts,
+    },
+    utils::get_cpi_digest_pda,
+    view::format_operators,
+};
+use boring_vault_svm_tests::{TestHarness, BORING_VAULT_PROGRAM};
+use eyre::Result;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+
+fn set_digest<T: ExternalInstruction>(harness: &TestHarness, eix: &T) -> Result<Pubkey> {
+    let (cpi_digest_pda, _) = get_external_instruction_cpi_digest(eix)?;
+    harness.set_cpi_digest(cpi_digest_pda, eix.ix_operators())?;
+
+    Ok(cpi_digest_pda)
+}
+
+// The digest accounts are written directly, so this runs without the program binary.
+#[test]
+fn labels_known_digests() -> Result<()> {
+    let harness = TestHarness::new()?;
+    harness.set_program_config(harness.authority.pubkey(), 2)?;
+    let vault_id = 1;
+    let to = Pubkey::new_unique();
+
+    let mint = set_digest(&harness, &MintJitoSol::new(vault_id, 0, 0))?;
+    let between = TransferSolBetweenSubAccounts::new(vault_id, 2, 1, 0);
+    let between_pda = set_digest(&harness, &between)?;
+    let transfer = set_digest(&harness, &TransferSol::new(vault_id, 0, to, 0))?;
+    // Another vault's digest is left out, one nothing derives is unknown.
+    set_digest(&harness, &MintJitoSol::new(0, 0, 0))?;
+    let unknown = get_cpi_digest_pda(vault_id, [7; 32]);
+    harness.set_cpi_digest(unknown, between.ix_operators())?;
+
+    let manifest = StrategyManifest::from_json(&format!(
+        r#"{{ "vault_id": {vault_id}, "instructions": [{{ "kind": "transfer_sol", "sub_account": 0, "to": "{to}" }}] }}"#
+    ))?;
+    let inventory = harness
+        .builder
+        .get_cpi_digest_inventory(vault_id, Some(&manifest))?;
+
+    assert_eq!(inventory.known.len(), 3);
+    let label = |address: Pubkey| {
+        inventory
+            .known
+            .iter()
+            .find(|entry| entry.address == address)
+            .and_then(|entry| entry.label.clone())
+    };
+    assert!(label(mint).unwrap().starts_with("MintJitoSol"));
+    // Only the destination is part of the digest, so it allows any source sub-account.
+    assert_eq!(
+        label(between_pda).as_deref(),
+        Some("TransferSolBetweenSubAccounts to sub-account 1, any sub-account")
+    );
+    assert_eq!(
+        label(transfer),
+        Some(format!(
+            "TransferSol to {}…, any sub-account",
+            &to.to_string()[..4]
+        ))
+    );
+
+    let between_entry = inventory
+        .known
+        .iter()
+        .find(|entry| entry.address == between_pda)
+        .unwrap();
+    assert_eq!(
+        between_entry.digest,
+        Some(get_external_instruction_cpi_digest(&between)?.1)
+    );
+    assert_eq!(
+        format_operators(&between_entry.operators),
+        "IngestInstruction(0, 4), IngestAccount(1), IngestInstructionDataSize"
+    );
+
+    assert_eq!(inventory.unknown.len(), 1);
+    assert_eq!(inventory.unknown[0].address, unknown);
+    assert!(inventory.to_string().contains("Unknown CPI digests"));
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn program_digests_match_local() -> Result<()> {
+    let harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+
+    let checks = harness
+        .builder
+        .self_test_cpi_digests(&harness.authority.pubkey(), 1)?;
+    assert!(checks.len() >= 16);
+    for label in [
+        "RawExternalInstruction",
+        "JupiterRoute route",
+        "JupiterRoute shared_accounts_route",
+    ] {
+        assert!(checks.iter().any(|check| check.label == label));
+    }
+    for check in &checks {
+        assert!(check.matches(), "{} digest differs", check.label);
+    }
+
+    let eix = MintJitoSol::new(1, 0, 0);
+    assert_eq!(
+        view_cpi_digest(harness.source(), &harness.authority.pubkey(), &eix)?,
+        get_external_instruction_cpi_digest(&eix)?.1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn checks_digest_before_initializing() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+
+    // Off by default, so nothing is simulated.
+    harness.builder.transfer_sol_between_sub_accounts(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        1,
+        0,
+        1,
+        0,
+    )?;
+    assert_eq!(harness.builder.instructions.len(), 2);
+
+    harness.builder.cpi_digest_check = true;
+    let checked = harness.builder.transfer_sol_between_sub_accounts(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        1,
+        0,
+        2,
+        0,
+    );
+    if harness.has_program(BORING_VAULT_PROGRAM) {
+        checked?;
+        assert_eq!(harness.builder.instructions.len(), 4);
+    } else {
+        // Without the program there is no digest to compare against.
+        assert!(checked.is_err());
+    }
+
+    // Existing digests aren't checked again.
+    let existing = TransferSolBetweenSubAccounts::new(1, 0, 3, 0);
+    set_digest(&harness, &existing)?;
+    harness.builder.transfer_sol_between_sub_accounts(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        1,
+        0,
+        3,
+        0,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use boring_vault_svm_core::{
+    builder::SendOutcome,
+    manage_instructions::{KAMINO_PROGRAM_ID, SPL_STAKE_POOL_PROGRAM_ID},
+    utils::{
+        boring_vault_svm, decode_transaction_error, BundleStatus, DecodedError,
+        BORING_QUEUE_PROGRAM, BORING_VAULT_PROGRAM, KAMINO_LENDING_PROGRAM, SPL_STAKE_POOL_PROGRAM,
+        SPL_TOKEN_PROGRAM,
+    },
+};
+use boring_vault_svm_tests::{TestHarness, BORING_ONCHAIN_QUEUE_ID};
+use eyre::Result;
+use serde_json::json;
+use solana_pubkey::Pubkey;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use solana_signer::Signer;
+
+fn custom(instruction_index: u8, code: u32) -> TransactionError {
+    TransactionError::InstructionError(instruction_index, InstructionError::Custom(code))
+}
+
+#[test]
+fn decodes_idl_errors() {
+    let program_ids = [
+        Pubkey::new_unique(),
+        boring_vault_svm::ID,
+        BORING_ONCHAIN_QUEUE_ID,
+    ];
+
+    let decoded = decode_transaction_error(&custom(1, 6000), &[], &program_ids).unwrap();
+    assert_eq!(decoded.program_id, Some(boring_vault_svm::ID));
+    assert_eq!(decoded.program_name.as_deref(), Some(BORING_VAULT_PROGRAM));
+    assert_eq!(decoded.name.as_deref(), Some("SlippageExceeded"));
+    assert_eq!(
+        decoded.message.as_deref(),
+        Some("Slippage tolerance exceeded")
+    );
+
+    let decoded = decode_transaction_error(&custom(2, 6001), &[], &program_ids).unwrap();
+    assert_eq!(decoded.program_name.as_deref(), Some(BORING_QUEUE_PROGRAM));
+    assert_eq!(decoded.name.as_deref(), Some("QueuePaused"));
+
+    // Unknown programs keep the bare code.
+    let decoded = decode_transaction_error(&custom(0, 6000), &[], &program_ids).unwrap();
+    assert_eq!(decoded.program_name, None);
+    assert_eq!(decoded.name, None);
+    assert_eq!(decoded.code, Some(6000));
+
+    let decoded = decode_transaction_error(&custom(0, 6050), &[], &[KAMINO_PROGRAM_ID]).unwrap();
+    assert_eq!(
+        decoded.program_name.as_deref(),
+        Some(KAMINO_LENDING_PROGRAM)
+    );
+    assert_eq!(decoded.name.as_deref(), Some("NoFarmForReserve"));
+
+    // Codes past the bundled Kamino table keep the program and code.
+    let decoded = decode_transaction_error(&custom(0, 6200), &[], &[KAMINO_PROGRAM_ID]).unwrap();
+    assert_eq!(
+        decoded.program_name.as_deref(),
+        Some(KAMINO_LENDING_PROGRAM)
+    );
+    assert_eq!(decoded.name, None);
+    assert_eq!(decoded.code, Some(6200));
+    assert_eq!(
+        decoded.message.as_deref(),
+        Some("custom program error: 0x1838")
+    );
+}
+
+#[test]
+fn decodes_cpi_errors_from_logs() {
+    let logs = [
+        format!("Program {} invoke [1]", boring_vault_svm::ID),
+        format!("Program {} invoke [2]", SPL_STAKE_POOL_PROGRAM_ID),
+        format!(
+            "Program {} failed: custom program error: 0x1c",
+            SPL_STAKE_POOL_PROGRAM_ID
+        ),
+        format!(
+            "Program {} failed: custom program error: 0x1c",
+            boring_vault_svm::ID
+        ),
+    ];
+
+    let decoded = decode_transaction_error(&custom(0, 28), &logs, &[boring_vault_svm::ID]).unwrap();
+    assert_eq!(decoded.program_id, Some(SPL_STAKE_POOL_PROGRAM_ID));
+    assert_eq!(
+        decoded.program_name.as_deref(),
+        Some(SPL_STAKE_POOL_PROGRAM)
+    );
+    assert_eq!(decoded.name.as_deref(), Some("WithdrawalTooSmall"));
+
+    // The Anchor error log names errors the registry doesn't know about.
+    let logs = [
+        "Program log: AnchorError caused by account: vault_state. Error Code: AccountNotInitialized. Error Number: 3012. Error Message: The program expected this account to be already initialized.".to_string(),
+    ];
+    let decoded =
+        decode_transaction_error(&custom(0, 3012), &logs, &[boring_vault_svm::ID]).unwrap();
+    assert_eq!(decoded.name.as_deref(), Some("AccountNotInitialized"));
+    assert_eq!(decoded.code, Some(3012));
+}
+
+#[test]
+fn decodes_bundle_status_errors() -> Result<()> {
+    let status: BundleStatus = serde_json::from_value(json!({
+        "bundle_id": "bundle",
+        "transactions": [],
+        "slot": 1,
+        "confirmation_status": null,
+        "err": { "Err": { "InstructionError": [3, { "Custom": 6002 }] } },
+    }))?;
+
+    assert!(!status.is_ok());
+    assert_eq!(status.transaction_error(), Some(custom(3, 6002)));
+
+    Ok(())
+}
+
+// An SPL Token transfer of more than the balance fails in simulation with a named error.
+#[test]
+fn simulation_decodes_token_errors() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let owner = harness.payer.pubkey();
+    let mint = harness.create_mint(6)?;
+    let source = harness.set_token_balance(&owner, &mint, 100)?;
+    let destination = harness.set_token_balance(&Pubkey::new_unique(), &mint, 0)?;
+
+    harness
+        .builder
+        .instructions
+        .push(spl_token::instruction::transfer(
+            &spl_token::ID,
+            &source,
+            &destination,
+            &owner,
+            &[],
+            101,
+        )?);
+    let result = harness.builder.simulate(&owner)?;
+
+    let decoded: DecodedError = result.decoded_error.unwrap();
+    assert_eq!(decoded.program_id, Some(spl_token::ID));
+    assert_eq!(decoded.program_name.as_deref(), Some(SPL_TOKEN_PROGRAM));
+    assert_eq!(decoded.name.as_deref(), Some("InsufficientFunds"));
+    assert_eq!(
+        decoded.to_string(),
+        format!(
+            "Instruction {} failed in spl_token: InsufficientFunds (1): Insufficient funds",
+            decoded.instruction_index
+        )
+    );
+
+    Ok(())
+}
+
+// Sends decode the error the same way, on the outcome of `try_send` and the error of
+// `try_send_all`. With a compute budget, the simulation sizing it fails first.
+#[test]
+fn sends_decode_token_errors() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let compute_budget = harness.builder.compute_budget.take();
+    let owner = harness.payer.pubkey();
+    let mint = harness.create_mint(6)?;
+    let source = harness.set_token_balance(&owner, &mint, 100)?;
+    let destination = harness.set_token_balance(&Pubkey::new_unique(), &mint, 0)?;
+    let transfer =
+        spl_token::instruction::transfer(&spl_token::ID, &source, &destination, &owner, &[], 101)?;
+
+    harness.builder.instructions.push(transfer.clone());
+    let SendOutcome::Failed {
+        decoded: Some(decoded),
+        ..
+    } = harness.try_send()?
+    else {
+        panic!("Expected a decoded failure");
+    };
+    assert_eq!(decoded.program_name.as_deref(), Some(SPL_TOKEN_PROGRAM));
+    assert_eq!(decoded.name.as_deref(), Some("InsufficientFunds"));
+
+    harness.builder.instructions.push(transfer.clone());
+    let err = harness
+        .builder
+        .try_send_all(harness.payer.insecure_clone())
+        .unwrap_err();
+    assert!(err.to_string().starts_with("Transaction 1 of 1 failed"));
+    let decoded = err.downcast_ref::<DecodedError>().unwrap();
+    assert_eq!(decoded.name.as_deref(), Some("InsufficientFunds"));
+    harness.builder.clear()?;
+
+    harness.builder.compute_budget = compute_budget;
+    harness.builder.instructions.push(transfer);
+    let err = harness
+        .builder
+        .try_send(harness.payer.insecure_clone())
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Compute budget simulation failed"));
+    let decoded = err.downcast_ref::<DecodedError>().unwrap();
+    assert_eq!(decoded.name.as_deref(), Some("InsufficientFunds"));
+
+    Ok(())
+}
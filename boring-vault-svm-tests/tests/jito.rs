@@ -0,0 +1,160 @@
+use boring_vault_svm_core::{
+    instructions::get_unstake_account, manage_instructions::JITO_MINT, utils::get_vault_pda,
+    view::StakeAccountStatus,
+};
+use boring_vault_svm_tests::{
+    stake_account, stake_state_account, TestHarness, BORING_VAULT_PROGRAM, SPL_STAKE_POOL_PROGRAM,
+};
+use eyre::Result;
+use solana_program::{
+    stake::state::{Meta, StakeStateV2},
+    stake_history::{StakeHistory, StakeHistoryEntry},
+};
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+// Needs the stake pool program and a `jito` fixture holding the Jito stake pool, its reserve,
+// fee account and the JitoSOL mint.
+#[test]
+#[ignore = "needs the stake pool program and the jito fixture"]
+fn mint_jito_sol() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+    harness.require_program(SPL_STAKE_POOL_PROGRAM)?;
+    harness.load_fixture("jito")?;
+    let (vault_id, _) = harness.setup_vault()?;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    harness.airdrop(&vault_pda, 2 * LAMPORTS_PER_SOL)?;
+
+    harness.builder.mint_jito_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        LAMPORTS_PER_SOL,
+    )?;
+    harness.send()?;
+
+    // JitoSOL trades above SOL, so fewer tokens than lamports are minted.
+    let jito_sol = harness.token_balance(&vault_pda, &JITO_MINT, &spl_token::ID);
+    assert!(jito_sol > 0 && jito_sol < LAMPORTS_PER_SOL);
+
+    Ok(())
+}
+
+#[test]
+fn pending_stake_accounts_follow_deactivation() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let vault_id = 1;
+    // Epoch 10.
+    harness.warp_to_slot(4_320_000);
+    // Half of the cluster's effective stake started cooling down in epoch 8, at 25% an epoch.
+    let mut stake_history = StakeHistory::default();
+    stake_history.add(
+        8,
+        StakeHistoryEntry {
+            effective: 4 * LAMPORTS_PER_SOL,
+            activating: 0,
+            deactivating: 2 * LAMPORTS_PER_SOL,
+        },
+    );
+    harness.source().svm_mut().set_sysvar(&stake_history);
+
+    let vote_account = Pubkey::new_unique();
+    let accounts = [
+        stake_account(&vote_account, u64::MAX),
+        stake_account(&vote_account, 10),
+        // Not in the stake history, so it has fully cooled down.
+        stake_account(&vote_account, 9),
+        stake_account(&vote_account, 8),
+        // Split from the reserve, never delegated.
+        stake_state_account(&StakeStateV2::Initialized(Meta::default())),
+        // Created, but not split into yet.
+        stake_state_account(&StakeStateV2::Uninitialized),
+    ];
+    for (index, account) in accounts.into_iter().enumerate() {
+        let (address, _) = get_unstake_account(vault_id, 0, index as u8)?;
+        harness.set_account(address, account)?;
+    }
+    // Another sub-account's unstake account isn't pending for this one.
+    let (other, _) = get_unstake_account(vault_id, 1, 4)?;
+    harness.set_account(
+        other,
+        stake_state_account(&StakeStateV2::Initialized(Meta::default())),
+    )?;
+
+    let pending = harness.builder.get_pending_stake_accounts(vault_id, 0)?;
+    assert_eq!(
+        pending
+            .iter()
+            .map(|stake_account| stake_account.status)
+            .collect::<Vec<_>>(),
+        vec![
+            StakeAccountStatus::Active,
+            StakeAccountStatus::Deactivating {
+                deactivating: LAMPORTS_PER_SOL
+            },
+            StakeAccountStatus::Withdrawable,
+            StakeAccountStatus::Deactivating {
+                deactivating: LAMPORTS_PER_SOL / 2
+            },
+            StakeAccountStatus::Withdrawable,
+            StakeAccountStatus::Uninitialized,
+        ]
+    );
+    assert_eq!(pending[4].seed, "unstake-04");
+
+    // Only the withdrawable ones are withdrawn, the shared digest is initialized once.
+    let withdrawn = harness.builder.withdraw_unstaked_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+    )?;
+    assert_eq!(withdrawn, vec![pending[2].address, pending[4].address]);
+    assert_eq!(harness.builder.instructions.len(), 3);
+
+    Ok(())
+}
+
+// Needs the same `jito` fixture, with the validator list and its stake accounts.
+#[test]
+#[ignore = "needs the stake pool program and the jito fixture"]
+fn unstake_jito_sol() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+    harness.require_program(SPL_STAKE_POOL_PROGRAM)?;
+    harness.load_fixture("jito")?;
+    let (vault_id, _) = harness.setup_vault()?;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    harness.airdrop(&vault_pda, 2 * LAMPORTS_PER_SOL)?;
+
+    harness.builder.mint_jito_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        LAMPORTS_PER_SOL,
+    )?;
+    harness.send()?;
+    let jito_sol = harness.token_balance(&vault_pda, &JITO_MINT, &spl_token::ID);
+
+    let stake_account = harness.builder.unstake_jito_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        jito_sol,
+    )?;
+    harness.send()?;
+
+    assert_eq!(
+        harness.token_balance(&vault_pda, &JITO_MINT, &spl_token::ID),
+        0
+    );
+    let pending = harness.builder.get_pending_stake_accounts(vault_id, 0)?;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].address, stake_account);
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use boring_vault_svm_core::{
+    config::JupiterSwapInstructions,
+    instructions::{get_external_instruction_cpi_digest, WSOL_MINT},
+    manage_instructions::{CloseAccount, ExternalInstruction, JupiterRoute, JUPITER_PROGRAM_ID},
+    utils::get_vault_pda,
+};
+use boring_vault_svm_tests::TestHarness;
+use eyre::Result;
+use serde_json::{json, Value};
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, ID as ASSOCIATED_TOKEN_PROGRAM_ID,
+};
+use spl_token::ID as TOKEN_PROGRAM_ID;
+
+const SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
+
+fn account(pubkey: &Pubkey, is_signer: bool, is_writable: bool) -> Value {
+    json!({ "pubkey": pubkey.to_string(), "isSigner": is_signer, "isWritable": is_writable })
+}
+
+fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &TOKEN_PROGRAM_ID)
+}
+
+// A `/swap-instructions` response selling wSOL for `output_mint`, requested for `user`.
+fn swap_instructions(user: &Pubkey, output_mint: &Pubkey, amount: u64, amm: &Pubkey) -> Value {
+    let mut data = SHARED_ACCOUNTS_ROUTE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    json!({
+        "computeBudgetInstructions": [],
+        "setupInstructions": [{
+            "programId": ASSOCIATED_TOKEN_PROGRAM_ID.to_string(),
+            "accounts": [
+                account(user, true, true),
+                account(&ata(user, output_mint), false, true),
+                account(user, false, false),
+                account(output_mint, false, false),
+                account(&Pubkey::default(), false, false),
+                account(&TOKEN_PROGRAM_ID, false, false),
+            ],
+            "data": STANDARD.encode([1]),
+        }],
+        "swapInstruction": {
+            "programId": JUPITER_PROGRAM_ID.to_string(),
+            "accounts": [
+                account(&TOKEN_PROGRAM_ID, false, false),
+                account(&Pubkey::new_unique(), false, false),
+                account(user, true, false),
+                account(&ata(user, WSOL_MINT), false, true),
+                account(&Pubkey::new_unique(), false, true),
+                account(&Pubkey::new_unique(), false, true),
+                account(&ata(user, output_mint), false, true),
+                account(WSOL_MINT, false, false),
+                account(output_mint, false, false),
+                account(&JUPITER_PROGRAM_ID, false, false),
+                account(&JUPITER_PROGRAM_ID, false, false),
+                account(&Pubkey::new_unique(), false, false),
+                account(&JUPITER_PROGRAM_ID, false, false),
+                account(amm, false, true),
+            ],
+            "data": STANDARD.encode(data),
+        },
+        "cleanupInstruction": {
+            "programId": TOKEN_PROGRAM_ID.to_string(),
+            "accounts": [
+                account(&ata(user, WSOL_MINT), false, true),
+                account(user, false, true),
+                account(user, true, false),
+            ],
+            "data": STANDARD.encode([9]),
+        },
+        "otherInstructions": [],
+        "addressLookupTableAddresses": [Pubkey::new_unique().to_string()],
+        "prioritizationFeeLamports": 0,
+    })
+}
+
+#[test]
+fn digest_is_stable_across_quotes() -> Result<()> {
+    let vault_id = 1;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    let output_mint = Pubkey::new_unique();
+
+    let swap = JupiterSwapInstructions::from_json(
+        &swap_instructions(&vault_pda, &output_mint, 1_000, &Pubkey::new_unique()).to_string(),
+    )?;
+    let route = JupiterRoute::new(vault_id, 0, &swap.swap_instruction)?;
+    assert_eq!(route.user(), vault_pda);
+    let accounts = route.ix_remaining_accounts();
+    assert!(accounts.iter().all(|account| !account.is_signer));
+
+    // Another wallet's quote, amount and AMMs describe the same swap once rewritten.
+    let user = Pubkey::new_unique();
+    let requote = JupiterSwapInstructions::from_json(
+        &swap_instructions(&user, &output_mint, 5_000, &Pubkey::new_unique()).to_string(),
+    )?;
+    let rerouted = JupiterRoute::new(vault_id, 0, &requote.swap_instruction)?;
+    assert_eq!(rerouted.ix_remaining_accounts()[2].pubkey, vault_pda);
+    assert_eq!(
+        rerouted.ix_remaining_accounts()[6].pubkey,
+        ata(&vault_pda, &output_mint)
+    );
+    assert_eq!(
+        get_external_instruction_cpi_digest(&route)?,
+        get_external_instruction_cpi_digest(&rerouted)?
+    );
+
+    // A different output mint is a different digest.
+    let other = JupiterSwapInstructions::from_json(
+        &swap_instructions(
+            &vault_pda,
+            &Pubkey::new_unique(),
+            1_000,
+            &Pubkey::new_unique(),
+        )
+        .to_string(),
+    )?;
+    assert_ne!(
+        get_external_instruction_cpi_digest(&route)?,
+        get_external_instruction_cpi_digest(&JupiterRoute::new(
+            vault_id,
+            0,
+            &other.swap_instruction
+        )?)?
+    );
+
+    assert!(JupiterRoute::new(vault_id, 0, &swap.setup_instructions[0]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn swap_adds_setup_cleanup_and_lookup_tables() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let vault_id = 1;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    let output_mint = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+
+    let swap = JupiterSwapInstructions::from_json(
+        &swap_instructions(&user, &output_mint, 1_000, &Pubkey::new_unique()).to_string(),
+    )?;
+    harness.builder.swap(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        &swap,
+    )?;
+
+    // ATA setup, then the route and wSOL cleanup, each with their CPI digest.
+    let instructions = &harness.builder.instructions;
+    assert_eq!(instructions.len(), 5);
+    assert_eq!(
+        instructions[0].accounts[0].pubkey,
+        harness.authority.pubkey()
+    );
+    assert!(instructions[0].accounts[0].is_signer);
+    assert_eq!(
+        instructions[0].accounts[1].pubkey,
+        ata(&vault_pda, &output_mint)
+    );
+    assert_eq!(instructions[0].accounts[2].pubkey, vault_pda);
+    assert!(instructions
+        .iter()
+        .flat_map(|instruction| &instruction.accounts)
+        .all(|account| account.pubkey != user));
+
+    let (close_digest_pda, _) = get_external_instruction_cpi_digest(&CloseAccount::new(
+        vault_id,
+        0,
+        ata(&vault_pda, WSOL_MINT),
+        TOKEN_PROGRAM_ID,
+    ))?;
+    assert!(instructions[3]
+        .accounts
+        .iter()
+        .any(|account| account.pubkey == close_digest_pda));
+
+    assert_eq!(
+        harness.builder.lookup_tables,
+        swap.address_lookup_table_addresses
+    );
+
+    // Wrapping SOL in setup would need the sub-account to sign outside the manage instruction.
+    let mut wrapping = swap.clone();
+    wrapping.setup_instructions[0].program_id = Pubkey::default();
+    assert!(harness
+        .builder
+        .swap(harness.authority_signer(), None, vault_id, 0, &wrapping)
+        .is_err());
+
+    Ok(())
+}
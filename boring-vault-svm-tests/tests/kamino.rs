@@ -0,0 +1,669 @@
+use boring_vault_svm_core::{
+    builder::SendOutcome,
+    config::{KaminoConfig, KaminoReserveConfig},
+    instructions::{create_manage_instruction, get_external_instruction_cpi_digest, WSOL_MINT},
+    manage_instructions::{
+        ExternalInstruction, KaminoRepay, KaminoWithdraw, JITO_MINT, KAMINO_PROGRAM_ID,
+    },
+    utils::{boring_vault_svm, get_obligation, get_vault_pda, AccountSource},
+};
+use boring_vault_svm_tests::{
+    TestHarness, BORING_VAULT_PROGRAM, KAMINO_FARMS_PROGRAM, KAMINO_LENDING_PROGRAM, SCOPE_PROGRAM,
+};
+use eyre::Result;
+use solana_pubkey::{pubkey, Pubkey};
+use solana_sdk::{account::Account, native_token::LAMPORTS_PER_SOL};
+use solana_system_interface::program as system_program;
+use std::{env, fs};
+
+const TAG: u8 = 0;
+const ID: u8 = 0;
+const FARM_MODE: u8 = 0;
+const USDC_MINT: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+
+// Needs the Kamino lending, farms and scope programs and a `kamino` fixture holding the market,
+// reserves, farms and oracle accounts listed in `data/kamino.json`. Returns the vault id.
+fn kamino_harness() -> Result<(TestHarness, u64)> {
+    let mut harness = TestHarness::new()?;
+    for program in [
+        BORING_VAULT_PROGRAM,
+        KAMINO_LENDING_PROGRAM,
+        KAMINO_FARMS_PROGRAM,
+        SCOPE_PROGRAM,
+    ] {
+        harness.require_program(program)?;
+    }
+    harness.load_fixture("kamino")?;
+    let (vault_id, _) = harness.setup_vault()?;
+    harness.airdrop(&get_vault_pda(vault_id, 0), 10 * LAMPORTS_PER_SOL)?;
+
+    Ok((harness, vault_id))
+}
+
+// Runs every setup step the vault needs before it can lend.
+fn init_obligation(harness: &mut TestHarness, vault_id: u64) -> Result<()> {
+    harness.builder.init_user_metadata(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+    )?;
+    harness.send()?;
+
+    harness.builder.init_obligation(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        TAG,
+        ID,
+        None,
+    )?;
+    harness.send()?;
+
+    harness.builder.init_obligation_farms_for_reserve(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        Some(TAG),
+        Some(ID),
+        FARM_MODE,
+        None,
+    )?;
+    harness.send()?;
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the Kamino programs and the kamino fixture"]
+fn init_obligation_accounts() -> Result<()> {
+    let (mut harness, vault_id) = kamino_harness()?;
+
+    init_obligation(&mut harness, vault_id)?;
+
+    let lookup_table = harness.builder.load_vault_lookup_table(vault_id, 0)?;
+    assert!(harness.source().get_account(&lookup_table).is_ok());
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the Kamino programs and the kamino fixture"]
+fn refresh_accounts() -> Result<()> {
+    let (mut harness, vault_id) = kamino_harness()?;
+    init_obligation(&mut harness, vault_id)?;
+    let (lend_reserve, _) = harness.builder.kamino_config.lend_reserve(None)?;
+
+    harness
+        .builder
+        .refresh_price_list(harness.authority_signer(), vault_id, 0, None)?;
+    harness.send()?;
+
+    harness
+        .builder
+        .refresh_reserve(vault_id, 0, lend_reserve.reserve)?;
+    harness.send()?;
+
+    harness.builder.refresh_reserves(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        None,
+    )?;
+    harness.builder.refresh_obligation(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        TAG,
+        ID,
+        None,
+    )?;
+    harness.send()?;
+
+    harness.builder.refresh_reserves(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        None,
+    )?;
+    harness.builder.refresh_obligation_farms_for_reserve(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        TAG,
+        ID,
+        FARM_MODE,
+        None,
+    )?;
+    harness.send()?;
+
+    harness
+        .builder
+        .refresh_kamino_accounts(vault_id, 0, TAG, ID, None)?;
+    harness.send()?;
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the Kamino programs and the kamino fixture"]
+fn lend_and_borrow() -> Result<()> {
+    let (mut harness, vault_id) = kamino_harness()?;
+    init_obligation(&mut harness, vault_id)?;
+    let vault_pda = get_vault_pda(vault_id, 0);
+
+    harness.set_token_balance(&vault_pda, &JITO_MINT, 5 * LAMPORTS_PER_SOL)?;
+    harness.builder.lend(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        5 * LAMPORTS_PER_SOL,
+        TAG,
+        ID,
+        None,
+    )?;
+    harness.send()?;
+    assert_eq!(
+        harness.token_balance(&vault_pda, &JITO_MINT, &spl_token::ID),
+        0
+    );
+
+    harness.builder.borrow(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        LAMPORTS_PER_SOL,
+        TAG,
+        ID,
+        None,
+    )?;
+    harness.send()?;
+    assert_eq!(
+        harness.token_balance(&vault_pda, WSOL_MINT, &spl_token::ID),
+        LAMPORTS_PER_SOL
+    );
+
+    Ok(())
+}
+
+fn kamino_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner: KAMINO_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// An `Obligation` on the default market with one deposit and one borrow, at the offsets Kamino
+// lays them out at.
+fn write_obligation(
+    harness: &TestHarness,
+    vault_id: u64,
+    deposit: (Pubkey, u64),
+    borrow: (Pubkey, u128),
+) -> Result<()> {
+    let market = harness.builder.kamino_config.lending_market(None)?;
+    let obligation = get_obligation(
+        TAG,
+        ID,
+        &get_vault_pda(vault_id, 0),
+        &market.lending_market,
+        &system_program::ID,
+        &system_program::ID,
+        &KAMINO_PROGRAM_ID,
+    );
+
+    let mut data = vec![0; 3344];
+    data[96..128].copy_from_slice(deposit.0.as_ref());
+    data[128..136].copy_from_slice(&deposit.1.to_le_bytes());
+    data[1208..1240].copy_from_slice(borrow.0.as_ref());
+    data[1296..1312].copy_from_slice(&borrow.1.to_le_bytes());
+
+    harness.set_account(obligation, kamino_account(data))
+}
+
+// A `Reserve` with only its farms set.
+fn write_reserve(harness: &TestHarness, reserve: Pubkey, debt_farm: Pubkey) -> Result<()> {
+    let mut data = vec![0; 8624];
+    data[96..128].copy_from_slice(debt_farm.as_ref());
+
+    harness.set_account(reserve, kamino_account(data))
+}
+
+// Adds a SOL reserve to the JLP market, so two markets list SOL.
+fn add_jlp_sol_reserve(config: &mut KaminoConfig) -> KaminoReserveConfig {
+    let reserve = KaminoReserveConfig {
+        name: "jlp-sol".to_string(),
+        market: "jlp".to_string(),
+        reserve: Pubkey::new_unique(),
+        liquidity_mint: *WSOL_MINT,
+        liquidity_supply: Pubkey::new_unique(),
+        collateral_mint: Pubkey::new_unique(),
+        collateral_supply: Pubkey::new_unique(),
+        fee_receiver: Pubkey::new_unique(),
+        farm_collateral: Pubkey::default(),
+        farm_debt: Pubkey::default(),
+    };
+    config
+        .reserves
+        .insert(reserve.name.clone(), reserve.clone());
+
+    reserve
+}
+
+#[test]
+fn decodes_obligation_position() -> Result<()> {
+    let harness = TestHarness::new()?;
+    let vault_id = 1;
+    let (lend, _) = harness.builder.kamino_config.lend_reserve(None)?;
+    let (borrow, _) = harness.builder.kamino_config.borrow_reserve(None)?;
+
+    // No obligation yet is an empty position.
+    let position = harness
+        .builder
+        .get_kamino_position(vault_id, 0, TAG, ID, None)?;
+    assert!(position.deposits.is_empty() && position.borrows.is_empty());
+
+    // 1.5 lamports of debt rounds up.
+    write_obligation(
+        &harness,
+        vault_id,
+        (lend.reserve, 5 * LAMPORTS_PER_SOL),
+        (borrow.reserve, 3 << 59),
+    )?;
+
+    let position = harness
+        .builder
+        .get_kamino_position(vault_id, 0, TAG, ID, None)?;
+    assert_eq!(position.deposits.len(), 1);
+    assert_eq!(
+        position.deposit(&lend.reserve).unwrap().deposited_amount,
+        5 * LAMPORTS_PER_SOL
+    );
+    assert_eq!(position.borrows.len(), 1);
+    assert_eq!(
+        position.borrow(&borrow.reserve).unwrap().borrowed_amount(),
+        2
+    );
+    assert!(position.borrow(&lend.reserve).is_none());
+    assert_eq!(position.reserves(), vec![lend.reserve, borrow.reserve]);
+
+    Ok(())
+}
+
+#[test]
+fn repay_and_withdraw_all() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let vault_id = 1;
+    let (lend, market) = harness.builder.kamino_config.lend_reserve(None)?;
+    let (borrow, _) = harness.builder.kamino_config.borrow_reserve(None)?;
+    let debt_farm = Pubkey::new_unique();
+    write_reserve(&harness, borrow.reserve, debt_farm)?;
+
+    // Nothing to repay or withdraw yet.
+    write_obligation(
+        &harness,
+        vault_id,
+        (Pubkey::default(), 0),
+        (Pubkey::default(), 0),
+    )?;
+    assert!(harness
+        .builder
+        .repay(
+            harness.authority_signer(),
+            None,
+            vault_id,
+            0,
+            None,
+            TAG,
+            ID,
+            None
+        )
+        .is_err());
+    assert!(harness
+        .builder
+        .withdraw_lend(
+            harness.authority_signer(),
+            None,
+            vault_id,
+            0,
+            None,
+            TAG,
+            ID,
+            None
+        )
+        .is_err());
+
+    write_obligation(
+        &harness,
+        vault_id,
+        (lend.reserve, 5 * LAMPORTS_PER_SOL),
+        (borrow.reserve, 1 << 60),
+    )?;
+
+    // The refreshes, the CPI digest init, then repaying whatever the debt is once refreshed.
+    harness.builder.repay(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        None,
+        TAG,
+        ID,
+        None,
+    )?;
+    let repay = KaminoRepay::new(
+        vault_id,
+        0,
+        market.lending_market,
+        borrow.reserve,
+        borrow.liquidity_mint,
+        borrow.liquidity_supply,
+        debt_farm,
+        TAG,
+        ID,
+        u64::MAX,
+    );
+    let instructions = harness.builder.instructions.clone();
+    assert_eq!(instructions.len(), 5);
+    assert!(instructions[4].data.ends_with(&repay.ix_data()));
+    assert!(instructions[4]
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == debt_farm));
+
+    // Withdrawing all of the collateral also creates the vault's liquidity ATA.
+    harness.builder.instructions.clear();
+    harness.builder.withdraw_lend(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        None,
+        TAG,
+        ID,
+        None,
+    )?;
+    let withdraw = KaminoWithdraw::new(
+        vault_id,
+        0,
+        market.lending_market,
+        lend.reserve,
+        lend.liquidity_mint,
+        lend.liquidity_supply,
+        lend.collateral_mint,
+        lend.collateral_supply,
+        lend.farm_collateral,
+        TAG,
+        ID,
+        5 * LAMPORTS_PER_SOL,
+    );
+    let instructions = &harness.builder.instructions;
+    assert_eq!(instructions.len(), 6);
+    assert!(instructions[5].data.ends_with(&withdraw.ix_data()));
+
+    Ok(())
+}
+
+// The digest check runs before the CPI, so the Kamino programs aren't needed.
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn repay_rejects_foreign_obligation() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+    let (vault_id, _) = harness.setup_vault()?;
+    let (borrow, market) = harness.builder.kamino_config.borrow_reserve(None)?;
+    let repay = KaminoRepay::new(
+        vault_id,
+        0,
+        market.lending_market,
+        borrow.reserve,
+        borrow.liquidity_mint,
+        borrow.liquidity_supply,
+        Pubkey::default(),
+        TAG,
+        ID,
+        LAMPORTS_PER_SOL,
+    );
+    let (cpi_digest_pda, _) = get_external_instruction_cpi_digest(&repay)?;
+    harness.set_cpi_digest(cpi_digest_pda, repay.ix_operators())?;
+
+    let obligation_of = |vault_id| {
+        get_obligation(
+            TAG,
+            ID,
+            &get_vault_pda(vault_id, 0),
+            &market.lending_market,
+            &system_program::ID,
+            &system_program::ID,
+            &KAMINO_PROGRAM_ID,
+        )
+    };
+    let mut instructions = create_manage_instruction(
+        harness.source(),
+        &harness.authority_signer(),
+        None,
+        false,
+        repay,
+    )?;
+    assert_eq!(instructions.len(), 1);
+
+    // The same repay, against another vault's obligation.
+    for meta in &mut instructions[0].accounts {
+        if meta.pubkey == obligation_of(vault_id) {
+            meta.pubkey = obligation_of(vault_id + 1);
+        }
+    }
+    harness.builder.instructions.extend(instructions);
+    harness
+        .builder
+        .add_signer_if_keypair(harness.authority_signer());
+
+    match harness.try_send()? {
+        SendOutcome::Failed { decoded, .. } => assert_eq!(
+            decoded.and_then(|decoded| decoded.program_id),
+            Some(boring_vault_svm::ID)
+        ),
+        outcome => panic!("Repaying a foreign obligation wasn't rejected: {}", outcome),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn selects_reserves_per_call() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let vault_id = 1;
+    let jlp_sol = add_jlp_sol_reserve(&mut harness.builder.kamino_config);
+    let config = harness.builder.kamino_config.clone();
+
+    // Reserves are keyed by name, or by mint when only one market lists it.
+    assert_eq!(config.reserve(&JITO_MINT.to_string())?.name, "jito");
+    assert!(config.reserve(&WSOL_MINT.to_string()).is_err());
+    assert_eq!(config.reserve("jlp-sol")?.reserve, jlp_sol.reserve);
+
+    // Lending on the JLP market refreshes the reserve and the obligation there.
+    harness.builder.lend(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        LAMPORTS_PER_SOL,
+        TAG,
+        ID,
+        Some("jlp-sol"),
+    )?;
+    let jlp_market = config.market("jlp")?.lending_market;
+    let instructions = &harness.builder.instructions;
+    assert_eq!(instructions.len(), 4);
+    assert_eq!(instructions[0].accounts[0].pubkey, jlp_sol.reserve);
+    assert_eq!(instructions[1].accounts[0].pubkey, jlp_market);
+    for account in [jlp_market, jlp_sol.reserve, jlp_sol.collateral_mint] {
+        assert!(instructions[3]
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == account));
+    }
+
+    // One obligation is refreshed at a time, so the reserves must share its market.
+    assert!(harness
+        .builder
+        .refresh_kamino_accounts(vault_id, 0, TAG, ID, Some(&["jito", "jlp-sol"]))
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn ships_main_jlp_and_altcoin_markets() -> Result<()> {
+    let harness = TestHarness::new()?;
+    let config = &harness.builder.kamino_config;
+
+    for market in ["main", "jlp", "altcoins"] {
+        config.market(market)?;
+    }
+    let (usdc, market) = config.lend_reserve(Some(&USDC_MINT.to_string()))?;
+    assert_eq!(usdc.name, "usdc");
+    assert_eq!(market.lending_market, config.market("jlp")?.lending_market);
+
+    // Reserve vaults are the lending program's addresses for the market and mint.
+    for reserve in config.reserves.values() {
+        let market = config.market(&reserve.market)?.lending_market;
+        let vault = |seed: &str| {
+            Pubkey::find_program_address(
+                &[
+                    seed.as_bytes(),
+                    market.as_ref(),
+                    reserve.liquidity_mint.as_ref(),
+                ],
+                &KAMINO_PROGRAM_ID,
+            )
+            .0
+        };
+        assert_eq!(reserve.liquidity_supply, vault("reserve_liq_supply"));
+        for (address, seed) in [
+            (reserve.collateral_mint, "reserve_coll_mint"),
+            (reserve.collateral_supply, "reserve_coll_supply"),
+            (reserve.fee_receiver, "fee_receiver"),
+        ] {
+            if address != Pubkey::default() {
+                assert_eq!(address, vault(seed), "{} of {}", seed, reserve.name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn refreshes_obligation_reserves() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let vault_id = 1;
+    let (lend, market) = harness.builder.kamino_config.lend_reserve(None)?;
+    let (borrow, _) = harness.builder.kamino_config.borrow_reserve(None)?;
+    write_obligation(
+        &harness,
+        vault_id,
+        (lend.reserve, LAMPORTS_PER_SOL),
+        (borrow.reserve, 1 << 60),
+    )?;
+
+    // Only the lend reserve is picked, the borrow reserve is refreshed since the obligation has
+    // debt on it. The obligation takes its deposit then borrow reserves.
+    harness
+        .builder
+        .refresh_kamino_accounts(vault_id, 0, TAG, ID, Some(&["jito"]))?;
+    let instructions = &harness.builder.instructions;
+    assert_eq!(instructions.len(), 3);
+    assert_eq!(instructions[0].accounts[0].pubkey, lend.reserve);
+    assert_eq!(instructions[1].accounts[0].pubkey, borrow.reserve);
+    let obligation_accounts = instructions[2]
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect::<Vec<_>>();
+    assert_eq!(obligation_accounts[0], market.lending_market);
+    assert_eq!(obligation_accounts[2..], [lend.reserve, borrow.reserve]);
+
+    Ok(())
+}
+
+#[test]
+fn converts_legacy_profile_config() -> Result<()> {
+    let harness = TestHarness::new()?;
+    let config = &harness.builder.kamino_config;
+    let (jito, market) = config.lend_reserve(None)?;
+    let (sol, _) = config.borrow_reserve(None)?;
+
+    // The layout before markets and reserves, a lend and a borrow profile on one market.
+    let legacy = serde_json::json!({
+        "jito": {
+            "lend": {
+                "reserve": jito.reserve.to_string(),
+                "reserve_farm_state": jito.farm_collateral.to_string(),
+                "reserve_liquidity_mint": jito.liquidity_mint.to_string(),
+                "reserve_liquidity_supply": jito.liquidity_supply.to_string(),
+                "reserve_collateral_mint": jito.collateral_mint.to_string(),
+                "reserve_destination_deposit_collateral": jito.collateral_supply.to_string(),
+                "lending_market": market.lending_market.to_string(),
+                "oracle_prices": market.oracle_prices.to_string(),
+                "oracle_mapping": market.oracle_mapping.to_string(),
+                "oracle_twaps": market.oracle_twaps.to_string(),
+                "price_accounts": market
+                    .price_accounts
+                    .iter()
+                    .map(|account| account.to_string())
+                    .collect::<Vec<_>>(),
+                "tokens": market.tokens,
+            }
+        },
+        "sol": {
+            "borrow": {
+                "reserve": sol.reserve.to_string(),
+                "reserve_source_liquidity_mint": sol.liquidity_mint.to_string(),
+                "reserve_source_liquidity": sol.liquidity_supply.to_string(),
+                "reserve_source_liquidity_fee_receiver": sol.fee_receiver.to_string(),
+            }
+        }
+    });
+    let path = env::temp_dir().join(format!("kamino-{}.json", Pubkey::new_unique()));
+    fs::write(&path, legacy.to_string())?;
+    let converted = KaminoConfig::new(path.to_str().unwrap(), "jito", "sol");
+    fs::remove_file(&path)?;
+    let converted = converted?;
+
+    let (lend, lend_market) = converted.lend_reserve(None)?;
+    let (borrow, borrow_market) = converted.borrow_reserve(None)?;
+    assert_eq!(lend.market, borrow.market);
+    assert_eq!(lend_market.lending_market, market.lending_market);
+    assert_eq!(lend_market.price_accounts, market.price_accounts);
+    assert_eq!(borrow_market.tokens, market.tokens);
+    assert_eq!(
+        (lend.reserve, lend.collateral_supply, lend.farm_collateral),
+        (jito.reserve, jito.collateral_supply, jito.farm_collateral)
+    );
+    assert_eq!(
+        (borrow.reserve, borrow.liquidity_supply, borrow.fee_receiver),
+        (sol.reserve, sol.liquidity_supply, sol.fee_receiver)
+    );
+
+    // A borrow profile with no lend market to sit on can't be converted, the error names the
+    // current layout.
+    let borrow_only = serde_json::json!({ "sol": legacy["sol"].clone() });
+    fs::write(&path, borrow_only.to_string())?;
+    let error = KaminoConfig::new(path.to_str().unwrap(), "sol", "sol").unwrap_err();
+    fs::remove_file(&path)?;
+    assert!(error.to_string().contains("\"markets\""));
+
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use std::{env, fs, path::PathBuf};
+
+use boring_vault_svm_core::{
+    builder::SendOutcome,
+    utils::{read_keypair, KeypairSource},
+    KeypairOrPublickey,
+};
+use boring_vault_svm_tests::TestHarness;
+use eyre::{eyre, Result};
+use solana_keypair::{keypair_from_seed_phrase_and_passphrase, write_keypair_file, Keypair};
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_signer::Signer;
+
+const PHRASE: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+#[test]
+fn file_specs() -> Result<()> {
+    let keypair = Keypair::new();
+    let path = env::temp_dir().join(format!("{}.json", keypair.pubkey()));
+    write_keypair_file(&keypair, &path).map_err(|e| eyre!("{}", e))?;
+
+    let from_file = read_keypair(&format!("file:{}", path.display()));
+    let from_path = read_keypair(&path.to_string_lossy());
+    fs::remove_file(&path)?;
+    assert_eq!(from_file?.pubkey(), keypair.pubkey());
+    assert_eq!(from_path?.pubkey(), keypair.pubkey());
+
+    let home = env::var("HOME")?;
+    assert_eq!(
+        "file:~/.config/solana/id.json".parse::<KeypairSource>()?,
+        KeypairSource::File(PathBuf::from(format!("{}/.config/solana/id.json", home)))
+    );
+    assert!(read_keypair("file:/does/not/exist.json").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn env_specs() -> Result<()> {
+    let keypair = Keypair::new();
+    let json_var = format!("KEYPAIR_SOURCE_JSON_{}", keypair.pubkey());
+    let base58_var = format!("KEYPAIR_SOURCE_BASE58_{}", keypair.pubkey());
+    env::set_var(
+        &json_var,
+        serde_json::to_string(&keypair.to_bytes().to_vec())?,
+    );
+    env::set_var(&base58_var, keypair.to_base58_string());
+
+    assert_eq!(
+        read_keypair(&format!("env:{}", json_var))?.pubkey(),
+        keypair.pubkey()
+    );
+    assert_eq!(
+        read_keypair(&format!("env:{}", base58_var))?.pubkey(),
+        keypair.pubkey()
+    );
+    assert!(read_keypair("env:KEYPAIR_SOURCE_UNSET").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn mnemonic_specs() -> Result<()> {
+    // Without a derivation path the phrase resolves like `solana-keygen recover`.
+    let base = read_keypair(&format!("mnemonic:{}", PHRASE))?;
+    let expected =
+        keypair_from_seed_phrase_and_passphrase(PHRASE, "").map_err(|e| eyre!("{}", e))?;
+    assert_eq!(base.pubkey(), expected.pubkey());
+
+    let derived = read_keypair(&format!("mnemonic:{}?key=0/0", PHRASE))?;
+    let full_path = read_keypair(&format!("mnemonic:{}?full-path=m/44'/501'/0'/0'", PHRASE))?;
+    assert_eq!(derived.pubkey(), full_path.pubkey());
+    assert_ne!(derived.pubkey(), base.pubkey());
+
+    let other_account = read_keypair(&format!("mnemonic:{}?key=1/0", PHRASE))?;
+    let with_passphrase = read_keypair(&format!("mnemonic:{}?key=0/0&passphrase=vault", PHRASE))?;
+    assert_ne!(other_account.pubkey(), derived.pubkey());
+    assert_ne!(with_passphrase.pubkey(), derived.pubkey());
+
+    // The phrase never shows up in debug output.
+    let source: KeypairSource = format!("mnemonic:{}", PHRASE).parse()?;
+    assert!(!format!("{:?}", source).contains("abandon"));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_specs() {
+    for spec in [
+        "",
+        "ledger://usb",
+        "env:",
+        "mnemonic:",
+        "mnemonic:abandon?key=x/y",
+        "mnemonic:abandon?key=0/0&full-path=m/44'/501'",
+        "prompt:?account=0",
+    ] {
+        assert!(spec.parse::<KeypairSource>().is_err(), "{}", spec);
+    }
+}
+
+// Sends with the payer the builder was configured with.
+#[test]
+fn builder_payer() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    assert!(harness.builder.payer().is_err());
+
+    let payer = Keypair::new();
+    let var = format!("KEYPAIR_SOURCE_PAYER_{}", payer.pubkey());
+    env::set_var(&var, payer.to_base58_string());
+    harness.builder.payer = Some(format!("env:{}", var).parse()?);
+    harness.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL)?;
+
+    let nonce_account = Keypair::new();
+    harness.builder.create_nonce_account(
+        KeypairOrPublickey::Keypair(harness.builder.payer()?),
+        KeypairOrPublickey::Keypair(nonce_account.insecure_clone()),
+        payer.pubkey(),
+    )?;
+    let payer = harness.builder.payer()?;
+    let outcome = harness.builder.try_send(payer)?;
+    assert!(matches!(outcome, SendOutcome::Landed { .. }), "{}", outcome);
+
+    let nonce_data = harness.builder.get_nonce_data(&nonce_account.pubkey())?;
+    assert_eq!(nonce_data.authority, harness.builder.payer()?.pubkey());
+
+    Ok(())
+}
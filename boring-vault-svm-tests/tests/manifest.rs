@@ -0,0 +1,183 @@
+use std::{env, fs};
+
+use boring_vault_svm_core::{
+    config::{ManifestInstruction, StrategyManifest},
+    instructions::get_external_instruction_cpi_digest,
+    manage_instructions::{
+        AuthorizeStake, MintJitoSol, StakeAuthorize, TransferSolBetweenSubAccounts, JITO_MINT,
+        SPL_STAKE_POOL_PROGRAM_ID,
+    },
+    utils::AccountSource,
+};
+use boring_vault_svm_tests::{TestHarness, BORING_VAULT_PROGRAM};
+use eyre::Result;
+use solana_pubkey::Pubkey;
+
+const LENDING_MARKET: &str = "7u3HeHxYDLhnCoErrtycNokbQYbWGzLs6JSDqGAv5PfF";
+
+fn manifest_json(vault_id: u64) -> String {
+    format!(
+        r#"{{
+            "vault_id": {vault_id},
+            "instructions": [
+                {{ "kind": "mint_jito_sol", "sub_account": 0, "amount": 1000 }},
+                {{ "kind": "mint_jito_sol", "sub_account": 0 }},
+                {{ "kind": "transfer_sol_between_sub_accounts", "sub_account": 0, "to_sub_account": 1 }},
+                {{ "kind": "kamino_init_obligation", "sub_account": 1, "lending_market": "{LENDING_MARKET}", "tag": 0, "id": 0 }}
+            ]
+        }}"#
+    )
+}
+
+#[test]
+fn parses_json_and_toml() -> Result<()> {
+    let from_json = StrategyManifest::from_json(&manifest_json(7))?;
+    let from_toml = StrategyManifest::from_toml(&format!(
+        r#"
+        vault_id = 7
+
+        [[instructions]]
+        kind = "mint_jito_sol"
+        sub_account = 0
+        amount = 1000
+
+        [[instructions]]
+        kind = "mint_jito_sol"
+        sub_account = 0
+
+        [[instructions]]
+        kind = "transfer_sol_between_sub_accounts"
+        sub_account = 0
+        to_sub_account = 1
+
+        [[instructions]]
+        kind = "kamino_init_obligation"
+        sub_account = 1
+        lending_market = "{LENDING_MARKET}"
+        tag = 0
+        id = 0
+        "#
+    ))?;
+
+    assert_eq!(from_json.vault_id, 7);
+    assert_eq!(from_json.instructions.len(), 4);
+    assert!(matches!(
+        from_toml.instructions[3],
+        ManifestInstruction::KaminoInitObligation { sub_account: 1, .. }
+    ));
+
+    // Both formats describe the same digests, and they match the ones `create_manage_instruction`
+    // would initialize.
+    let json_digests = from_json
+        .external_instructions()
+        .iter()
+        .map(|eix| get_external_instruction_cpi_digest(eix.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    let toml_digests = from_toml
+        .external_instructions()
+        .iter()
+        .map(|eix| get_external_instruction_cpi_digest(eix.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(json_digests, toml_digests);
+    assert_eq!(
+        json_digests[0],
+        get_external_instruction_cpi_digest(&MintJitoSol::new(7, 0, 0))?
+    );
+    assert_eq!(
+        json_digests[2],
+        get_external_instruction_cpi_digest(&TransferSolBetweenSubAccounts::new(7, 0, 1, 0))?
+    );
+    // Amounts aren't part of the digest.
+    assert_eq!(json_digests[0], json_digests[1]);
+
+    // The file extension picks the format.
+    let path = env::temp_dir().join(format!("manifest-{}.toml", Pubkey::new_unique()));
+    fs::write(&path, "vault_id = 3\ninstructions = []\n")?;
+    let from_file = StrategyManifest::new(&path.to_string_lossy());
+    fs::remove_file(&path)?;
+    assert_eq!(from_file?.vault_id, 3);
+
+    assert!(StrategyManifest::from_json(
+        r#"{ "vault_id": 1, "instructions": [{ "kind": "swap" }] }"#
+    )
+    .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn describes_stake_pool_instructions() -> Result<()> {
+    let new_authority = Pubkey::new_unique();
+    let manifest = StrategyManifest::from_json(&format!(
+        r#"{{
+            "vault_id": 7,
+            "instructions": [
+                {{ "kind": "deposit_sol", "sub_account": 0, "stake_pool_program": "{}", "pool_mint": "{}", "token_program": "{}" }},
+                {{ "kind": "authorize_stake", "sub_account": 0, "new_authority": "{}", "stake_authorize": "withdrawer" }}
+            ]
+        }}"#,
+        SPL_STAKE_POOL_PROGRAM_ID,
+        JITO_MINT,
+        spl_token::ID,
+        new_authority
+    ))?;
+    assert_eq!(manifest.instructions[0].kind(), "DepositSol");
+
+    let digests = manifest
+        .external_instructions()
+        .iter()
+        .map(|eix| get_external_instruction_cpi_digest(eix.as_ref()))
+        .collect::<Result<Vec<_>>>()?;
+    // A SOL deposit into the Jito pool is what `MintJitoSol` does.
+    assert_eq!(
+        digests[0],
+        get_external_instruction_cpi_digest(&MintJitoSol::new(7, 0, 0))?
+    );
+    assert_eq!(
+        digests[1],
+        get_external_instruction_cpi_digest(&AuthorizeStake::new(
+            7,
+            0,
+            Pubkey::new_unique(),
+            new_authority,
+            StakeAuthorize::Withdrawer,
+        ))?
+    );
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn provisions_missing_digests() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+    let (vault_id, _) = harness.setup_vault()?;
+    let manifest = StrategyManifest::from_json(&manifest_json(vault_id))?;
+
+    let provisions = harness
+        .builder
+        .provision_cpi_digests(harness.authority_signer(), &manifest)?;
+    // The two MintJitoSol entries share a digest.
+    assert_eq!(provisions.len(), 3);
+    assert!(provisions.iter().all(|provision| !provision.exists));
+    assert_eq!(provisions[0].label, "MintJitoSol (sub-account 0)");
+    assert_eq!(harness.builder.instructions.len(), 3);
+    harness.send()?;
+
+    for provision in &provisions {
+        assert!(harness
+            .source()
+            .get_account(&provision.cpi_digest_pda)
+            .is_ok());
+    }
+
+    // Nothing left to initialize the second time around.
+    let provisions = harness
+        .builder
+        .provision_cpi_digests(harness.authority_signer(), &manifest)?;
+    assert!(provisions.iter().all(|provision| provision.exists));
+    assert!(harness.builder.instructions.is_empty());
+
+    Ok(())
+}
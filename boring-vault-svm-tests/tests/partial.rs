@@ -0,0 +1,103 @@
+use boring_vault_svm_core::builder::PartiallySignedTransaction;
+use boring_vault_svm_tests::TestHarness;
+use eyre::Result;
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_signer::Signer;
+use solana_system_interface::instruction as system_instruction;
+
+// Transfers from the payer and from `cosigner`, with only the payer's keypair in the builder.
+fn cosigned_transfers(harness: &mut TestHarness, cosigner: &Keypair, to: &Pubkey) -> Result<()> {
+    harness.airdrop(&cosigner.pubkey(), LAMPORTS_PER_SOL)?;
+    harness.builder.add_signer(harness.payer.insecure_clone());
+    harness.builder.instructions.extend([
+        system_instruction::transfer(&harness.payer.pubkey(), to, LAMPORTS_PER_SOL),
+        system_instruction::transfer(&cosigner.pubkey(), to, LAMPORTS_PER_SOL / 2),
+    ]);
+
+    Ok(())
+}
+
+#[test]
+fn completes_signatures_after_export() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let cosigner = Keypair::new();
+    let to = Pubkey::new_unique();
+    cosigned_transfers(&mut harness, &cosigner, &to)?;
+
+    let partial = harness
+        .builder
+        .export_partially_signed(harness.payer.pubkey())?;
+    assert_eq!(
+        partial.required_signers(),
+        &[harness.payer.pubkey(), cosigner.pubkey()]
+    );
+    assert_eq!(partial.missing_signers(), vec![cosigner.pubkey()]);
+    assert!(!partial.is_fully_signed());
+
+    // The export round trips with the payer's signature kept.
+    let b64 = partial.to_b64()?;
+    let mut imported = PartiallySignedTransaction::from_b64(&b64)?;
+    assert_eq!(imported, partial);
+    assert!(imported.clone().finalize().is_err());
+
+    // The cosigner signs the message bytes somewhere else.
+    let signature = cosigner.sign_message(&imported.message_data());
+    imported.add_signature(&cosigner.pubkey(), signature)?;
+    assert!(imported.is_fully_signed());
+
+    harness.builder.send_partially_signed(imported)?;
+    assert_eq!(harness.lamports(&to), LAMPORTS_PER_SOL * 3 / 2);
+
+    Ok(())
+}
+
+#[test]
+fn signs_with_any_subset() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let cosigner = Keypair::new();
+    let to = Pubkey::new_unique();
+    cosigned_transfers(&mut harness, &cosigner, &to)?;
+
+    // Without the payer's keypair either, nothing is signed yet.
+    harness.builder.signers.clear();
+    let mut partial = harness
+        .builder
+        .export_partially_signed(harness.payer.pubkey())?;
+    assert_eq!(
+        partial.missing_signers(),
+        vec![harness.payer.pubkey(), cosigner.pubkey()]
+    );
+
+    partial.sign(&cosigner)?;
+    assert_eq!(partial.missing_signers(), vec![harness.payer.pubkey()]);
+    partial.sign(&harness.payer)?;
+
+    harness.builder.send_partially_signed(partial)?;
+    assert_eq!(harness.lamports(&to), LAMPORTS_PER_SOL * 3 / 2);
+
+    Ok(())
+}
+
+#[test]
+fn rejects_signers_not_in_the_message() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let cosigner = Keypair::new();
+    cosigned_transfers(&mut harness, &cosigner, &Pubkey::new_unique())?;
+    let mut partial = harness
+        .builder
+        .export_partially_signed(harness.payer.pubkey())?;
+
+    let stranger = Keypair::new();
+    assert!(partial.sign(&stranger).is_err());
+
+    // A signature by someone else doesn't pass for the cosigner's.
+    let signature = stranger.sign_message(&partial.message_data());
+    assert!(partial
+        .add_signature(&cosigner.pubkey(), signature)
+        .is_err());
+    assert_eq!(partial.missing_signers(), vec![cosigner.pubkey()]);
+
+    Ok(())
+}
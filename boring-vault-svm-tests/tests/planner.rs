@@ -0,0 +1,109 @@
+use boring_vault_svm_core::builder::{
+    fits_in_transaction, plan_instruction_groups, split_instruction_groups,
+    MAX_TRANSACTION_ACCOUNTS,
+};
+use eyre::Result;
+use solana_pubkey::Pubkey;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::AddressLookupTableAccount,
+};
+
+// An instruction taking `data_len` bytes of data and `accounts` new writable accounts.
+fn instruction(data_len: usize, accounts: usize) -> Instruction {
+    Instruction::new_with_bytes(
+        Pubkey::new_unique(),
+        &vec![1; data_len],
+        (0..accounts)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+            .collect(),
+    )
+}
+
+// A lookup table holding every account of `instruction`.
+fn lookup_table(instruction: &Instruction) -> AddressLookupTableAccount {
+    AddressLookupTableAccount {
+        key: Pubkey::new_unique(),
+        addresses: instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect(),
+    }
+}
+
+#[test]
+fn splits_at_group_starts() {
+    let instructions = (0..5).map(|_| instruction(1, 0)).collect::<Vec<_>>();
+
+    let groups = split_instruction_groups(&instructions, &[0, 2, 2, 4, 9]);
+    let lengths = groups.iter().map(|group| group.len()).collect::<Vec<_>>();
+    assert_eq!(lengths, vec![2, 2, 1]);
+
+    // Instructions before the first recorded group are a group of their own.
+    let groups = split_instruction_groups(&instructions, &[3]);
+    let lengths = groups.iter().map(|group| group.len()).collect::<Vec<_>>();
+    assert_eq!(lengths, vec![3, 2]);
+}
+
+#[test]
+fn packs_groups_without_splitting_them() -> Result<()> {
+    let payer = Pubkey::new_unique();
+    // Two of these fit in a transaction, three don't.
+    let instructions = (0..5).map(|_| instruction(400, 0)).collect::<Vec<_>>();
+    let groups = instructions.chunks(1).collect::<Vec<_>>();
+
+    let plan = plan_instruction_groups(&groups, &[], &payer, &[])?;
+    let lengths = plan.iter().map(|tx| tx.len()).collect::<Vec<_>>();
+    assert_eq!(lengths, vec![2, 2, 1]);
+    assert_eq!(plan.concat(), instructions);
+
+    // A group of two starting mid transaction moves to the next one whole.
+    let groups = vec![&instructions[..1], &instructions[1..3], &instructions[3..4]];
+    let plan = plan_instruction_groups(&groups, &[], &payer, &[])?;
+    let lengths = plan.iter().map(|tx| tx.len()).collect::<Vec<_>>();
+    assert_eq!(lengths, vec![1, 2, 1]);
+
+    Ok(())
+}
+
+#[test]
+fn reserves_room_for_the_prefix() -> Result<()> {
+    let payer = Pubkey::new_unique();
+    let instructions = (0..2).map(|_| instruction(500, 0)).collect::<Vec<_>>();
+    let groups = instructions.chunks(1).collect::<Vec<_>>();
+
+    let plan = plan_instruction_groups(&groups, &[], &payer, &[])?;
+    assert_eq!(plan.len(), 1);
+
+    // The prefix isn't part of the plan, only its size is.
+    let prefix = [instruction(300, 0)];
+    let plan = plan_instruction_groups(&groups, &prefix, &payer, &[])?;
+    assert_eq!(plan.len(), 2);
+    assert_eq!(plan.concat(), instructions);
+
+    Ok(())
+}
+
+#[test]
+fn rejects_groups_too_large_for_a_transaction() {
+    let payer = Pubkey::new_unique();
+
+    let too_big = [instruction(1_300, 0)];
+    assert!(!fits_in_transaction(&too_big, &payer, &[]));
+    assert!(plan_instruction_groups(&[&too_big], &[], &payer, &[]).is_err());
+
+    // Small enough through a lookup table, but loading too many accounts.
+    let too_many_accounts = [instruction(0, MAX_TRANSACTION_ACCOUNTS)];
+    let lookup_tables = [lookup_table(&too_many_accounts[0])];
+    assert!(!fits_in_transaction(
+        &too_many_accounts,
+        &payer,
+        &lookup_tables
+    ));
+    assert!(plan_instruction_groups(&[&too_many_accounts], &[], &payer, &lookup_tables).is_err());
+
+    let fewer_accounts = [instruction(0, MAX_TRANSACTION_ACCOUNTS - 8)];
+    let lookup_tables = [lookup_table(&fewer_accounts[0])];
+    assert!(fits_in_transaction(&fewer_accounts, &payer, &lookup_tables));
+}
@@ -0,0 +1,86 @@
+use boring_vault_svm_core::{
+    instructions::get_external_instruction_cpi_digest,
+    manage_instructions::{
+        parse_operators, ExternalInstruction, RawExternalInstruction, TransferSol,
+    },
+    utils::{get_vault_pda, AccountSource},
+    view::format_operators,
+};
+use boring_vault_svm_tests::{TestHarness, BORING_VAULT_PROGRAM};
+use eyre::Result;
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_system_interface::instruction as system_instruction;
+
+const TRANSFER_OPERATORS: &str =
+    "IngestInstruction(0, 4), IngestAccount(1), IngestInstructionDataSize";
+
+#[test]
+fn wraps_sdk_instructions() -> Result<()> {
+    let vault_id = 1;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    let to = Pubkey::new_unique();
+
+    // The system program SDK marks the source as a signer.
+    let transfer = system_instruction::transfer(&vault_pda, &to, 1_000);
+    assert!(transfer.accounts[0].is_signer);
+    let raw =
+        RawExternalInstruction::new(vault_id, 0, transfer, parse_operators(TRANSFER_OPERATORS)?);
+
+    let accounts = raw.ix_remaining_accounts();
+    assert_eq!(accounts[0].pubkey, vault_pda);
+    assert!(!accounts[0].is_signer && accounts[0].is_writable);
+
+    // Same digest as the hand-written instruction.
+    assert_eq!(
+        get_external_instruction_cpi_digest(&raw)?,
+        get_external_instruction_cpi_digest(&TransferSol::new(vault_id, 0, to, 1_000))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parses_operators() -> Result<()> {
+    let operators = parse_operators(TRANSFER_OPERATORS)?;
+    assert_eq!(format_operators(&operators), TRANSFER_OPERATORS);
+    assert_eq!(
+        format_operators(&parse_operators("Noop,IngestAccount(3)")?),
+        "Noop, IngestAccount(3)"
+    );
+    assert!(parse_operators("")?.operators.is_empty());
+
+    assert!(parse_operators("IngestAccount(256)").is_err());
+    assert!(parse_operators("IngestInstruction(0)").is_err());
+    assert!(parse_operators("Ingest").is_err());
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn manages_raw_instruction() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+    let (vault_id, _) = harness.setup_vault()?;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    let to = Pubkey::new_unique();
+    harness.airdrop(&vault_pda, LAMPORTS_PER_SOL)?;
+
+    harness.builder.manage_raw_instruction(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        system_instruction::transfer(&vault_pda, &to, LAMPORTS_PER_SOL / 2),
+        parse_operators(TRANSFER_OPERATORS)?,
+    )?;
+    harness.send()?;
+
+    assert_eq!(
+        harness.source().get_account(&to)?.lamports,
+        LAMPORTS_PER_SOL / 2
+    );
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use boring_vault_svm_core::{
+    builder::{Builder, SendOutcome},
+    config::SendConfig,
+    utils::boring_vault_svm,
+};
+use boring_vault_svm_tests::{builder_config, transaction_status, MockStatusSource};
+use eyre::Result;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_keypair::Keypair;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    transaction::TransactionError,
+};
+use solana_signer::Signer;
+use solana_transaction_status_client_types::TransactionConfirmationStatus::{
+    Confirmed, Finalized, Processed,
+};
+
+// Rebroadcasts on every poll and gives up after two blockhashes.
+fn send_config() -> SendConfig {
+    SendConfig::default()
+        .with_poll_interval(Duration::ZERO)
+        .with_rebroadcast_interval(Duration::ZERO)
+        .with_max_attempts(2)
+}
+
+// A builder planning one boring vault instruction against `source`.
+fn status_builder(source: &MockStatusSource) -> Builder<MockStatusSource> {
+    let mut builder = builder_config()
+        .with_send(send_config())
+        .build_with_client(source.clone());
+    builder.compute_budget = None;
+    builder.instructions.push(Instruction::new_with_bytes(
+        boring_vault_svm::ID,
+        &[1],
+        vec![],
+    ));
+
+    builder
+}
+
+#[test]
+fn lands_once_finalized() -> Result<()> {
+    let source = MockStatusSource::new(vec![None, Some(transaction_status(Finalized, None))], 10);
+    let mut builder = status_builder(&source);
+
+    let outcome = builder.try_send(Keypair::new())?;
+    assert_eq!(
+        outcome,
+        SendOutcome::Landed {
+            signature: source.sent_transactions()[0].signatures[0]
+        }
+    );
+    assert_eq!(source.sent_transactions().len(), 1);
+    assert!(builder.instructions.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn decodes_failed_transactions() -> Result<()> {
+    let err = TransactionError::InstructionError(0, InstructionError::Custom(6005));
+    let source = MockStatusSource::new(
+        vec![Some(transaction_status(Finalized, Some(err.clone())))],
+        10,
+    );
+    let mut builder = status_builder(&source);
+
+    let outcome = builder.try_send(Keypair::new())?;
+    let SendOutcome::Failed {
+        err: failed,
+        decoded,
+        ..
+    } = outcome
+    else {
+        panic!("expected a failed outcome, got {}", outcome);
+    };
+    assert_eq!(failed, err);
+    assert_eq!(decoded.unwrap().program_id, Some(boring_vault_svm::ID));
+    // Nothing landed, so the instructions are kept for a retry.
+    assert_eq!(builder.instructions.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn expires_after_every_attempt() -> Result<()> {
+    let source = MockStatusSource::new(vec![None], 2);
+    let mut builder = status_builder(&source);
+
+    let outcome = builder.try_send(Keypair::new())?;
+    assert!(matches!(outcome, SendOutcome::Expired { .. }));
+
+    // Signed again with a new blockhash once the first one expired.
+    let sent = source.sent_transactions();
+    assert_eq!(sent.len(), 2);
+    assert_ne!(sent[0].signatures[0], sent[1].signatures[0]);
+    assert_eq!(outcome.signature(), &sent[1].signatures[0]);
+
+    Ok(())
+}
+
+#[test]
+fn rebroadcasts_until_confirmed() -> Result<()> {
+    let source = MockStatusSource::new(
+        vec![None, None, None, Some(transaction_status(Finalized, None))],
+        10,
+    );
+    let mut builder = status_builder(&source);
+
+    assert!(builder.try_send(Keypair::new())?.is_landed());
+    assert_eq!(source.sent_transactions().len(), 1);
+    assert_eq!(source.rebroadcasts(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn waits_on_processed_transactions_past_expiry() -> Result<()> {
+    // Processed before the blockhash expired, finalized only after.
+    let source = MockStatusSource::new(
+        vec![
+            Some(transaction_status(Processed, None)),
+            Some(transaction_status(Confirmed, None)),
+            Some(transaction_status(Confirmed, None)),
+            Some(transaction_status(Finalized, None)),
+        ],
+        0,
+    );
+    let mut builder = status_builder(&source);
+
+    assert!(builder.try_send(Keypair::new())?.is_landed());
+    assert_eq!(source.sent_transactions().len(), 1);
+
+    // Processed between the status check and the expiry check.
+    let source = MockStatusSource::new(
+        vec![
+            None,
+            Some(transaction_status(Processed, None)),
+            Some(transaction_status(Finalized, None)),
+        ],
+        0,
+    );
+    let mut builder = status_builder(&source);
+
+    assert!(builder.try_send(Keypair::new())?.is_landed());
+    assert_eq!(source.sent_transactions().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn async_builder_waits_on_processed_transactions_past_expiry() -> Result<()> {
+    let source = MockStatusSource::new(
+        vec![
+            Some(transaction_status(Processed, None)),
+            Some(transaction_status(Confirmed, None)),
+            Some(transaction_status(Finalized, None)),
+        ],
+        0,
+    );
+    let mut builder = builder_config()
+        .with_send(send_config())
+        .build_async_with_client(RpcClient::new(source.serve_rpc()?));
+    builder.compute_budget = None;
+    builder.instructions.push(Instruction::new_with_bytes(
+        boring_vault_svm::ID,
+        &[1],
+        vec![],
+    ));
+    let payer = Keypair::new();
+    let payer_pubkey = payer.pubkey();
+
+    let outcome = builder.try_send(payer).await?;
+    assert!(outcome.is_landed());
+    let sent = source.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].message.static_account_keys()[0], payer_pubkey);
+
+    Ok(())
+}
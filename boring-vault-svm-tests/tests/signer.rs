@@ -0,0 +1,78 @@
+use std::{env, fs};
+
+use boring_vault_svm_core::{utils::RemoteSigner, KeypairOrPublickey};
+use boring_vault_svm_tests::{SigningService, TestHarness};
+use eyre::{eyre, Result};
+use solana_keypair::{write_keypair_file, Keypair};
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_signer::Signer;
+
+// The nonce account is funded by a key only the signing service holds.
+#[test]
+fn remote_signer_signs_transactions() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let service = SigningService::start(Keypair::new())?;
+    harness.airdrop(&service.pubkey(), LAMPORTS_PER_SOL)?;
+    let nonce_account = Keypair::new();
+
+    harness.builder.create_nonce_account(
+        KeypairOrPublickey::from_signer(service.signer()),
+        KeypairOrPublickey::Keypair(nonce_account.insecure_clone()),
+        harness.payer.pubkey(),
+    )?;
+    harness.send()?;
+
+    let nonce_data = harness.builder.get_nonce_data(&nonce_account.pubkey())?;
+    assert_eq!(nonce_data.authority, harness.payer.pubkey());
+    assert!(service.signed() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn remote_signer_connect() -> Result<()> {
+    let keypair = Keypair::new();
+    let service = SigningService::start(keypair.insecure_clone())?;
+
+    let signer = RemoteSigner::connect(service.url())?;
+    assert_eq!(signer.pubkey(), keypair.pubkey());
+    assert_eq!(
+        signer.try_sign_message(b"message")?,
+        keypair.sign_message(b"message")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn remote_signer_rejects_unknown_key() -> Result<()> {
+    let service = SigningService::start(Keypair::new())?;
+    let signer = RemoteSigner::new(service.url(), Pubkey::new_unique());
+
+    assert!(signer.try_sign_message(b"message").is_err());
+    assert_eq!(service.signed(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn keypair_file_and_presigned_signers() -> Result<()> {
+    let keypair = Keypair::new();
+    let path = env::temp_dir().join(format!("{}.json", keypair.pubkey()));
+    write_keypair_file(&keypair, &path).map_err(|e| eyre!("{}", e))?;
+
+    let signer = KeypairOrPublickey::from_keypair_file(&path.to_string_lossy());
+    fs::remove_file(&path)?;
+    assert_eq!(signer?.pubkey(), keypair.pubkey());
+
+    // A presigned signature only signs the message it was made for.
+    let presigned =
+        KeypairOrPublickey::presigned(keypair.pubkey(), keypair.sign_message(b"message"))
+            .into_signer()
+            .ok_or_else(|| eyre!("Presigned signer can't sign"))?;
+    assert!(presigned.try_sign_message(b"message").is_ok());
+    assert!(presigned.try_sign_message(b"other message").is_err());
+
+    Ok(())
+}
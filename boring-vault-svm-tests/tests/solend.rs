@@ -0,0 +1,74 @@
+use boring_vault_svm_core::{
+    instructions::WSOL_MINT,
+    utils::{get_vault_pda, AccountFixture},
+};
+use boring_vault_svm_tests::{
+    fixture_path, SolendReserve, TestHarness, BORING_VAULT_PROGRAM, SOLEND_PROGRAM,
+    SOLEND_PROGRAM_ID, SOLEND_RESERVE_LEN,
+};
+use eyre::{eyre, Result};
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+// The SOL reserve in the `solend` fixture.
+fn find_sol_reserve() -> Result<SolendReserve> {
+    let fixture = AccountFixture::load(&fixture_path("solend").to_string_lossy())?;
+
+    for (address, account) in &fixture.accounts {
+        let account = account.to_account()?;
+        if account.owner != SOLEND_PROGRAM_ID || account.data.len() != SOLEND_RESERVE_LEN {
+            continue;
+        }
+
+        let reserve = SolendReserve::unpack(address.parse()?, &account.data)?;
+        if reserve.liquidity_mint == *WSOL_MINT {
+            return Ok(reserve);
+        }
+    }
+
+    Err(eyre!("No SOL reserve in the solend fixture"))
+}
+
+// Needs the Solend program and a `solend` fixture holding the market, its SOL reserve with its
+// oracles, and the obligation of sub-account 0 of the first vault.
+#[test]
+#[ignore = "needs the Solend program and the solend fixture"]
+fn deposit_solend() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+    harness.require_program(SOLEND_PROGRAM)?;
+    harness.load_fixture("solend")?;
+    let (vault_id, _) = harness.setup_vault()?;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    let reserve = find_sol_reserve()?;
+    let (lending_market_authority, _) =
+        Pubkey::find_program_address(&[reserve.lending_market.as_ref()], &SOLEND_PROGRAM_ID);
+
+    harness.set_mint(*WSOL_MINT, 9)?;
+    harness.set_token_balance(&vault_pda, WSOL_MINT, LAMPORTS_PER_SOL)?;
+
+    harness.builder.deposit_solend(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        *WSOL_MINT,
+        reserve.collateral_mint,
+        reserve.lending_market,
+        reserve.address,
+        reserve.liquidity_supply,
+        lending_market_authority,
+        reserve.collateral_supply,
+        reserve.pyth_oracle,
+        reserve.switchboard_oracle,
+        LAMPORTS_PER_SOL,
+    )?;
+    harness.send()?;
+
+    assert_eq!(
+        harness.token_balance(&vault_pda, WSOL_MINT, &spl_token::ID),
+        0
+    );
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use boring_vault_svm_core::{
+    instructions::{get_stake_pool_accounts, get_stake_to_split},
+    manage_instructions::{
+        get_stake_pool_withdraw_authority, jito_stake_pool, DepositSol, ExternalInstruction,
+        MintJitoSol, JITO_STAKE_POOL, SPL_STAKE_POOL_PROGRAM_ID, STAKE_ACCOUNT_SIZE,
+    },
+    utils::{get_vault_pda, AccountSource},
+};
+use boring_vault_svm_tests::{delegated_stake_account, validator, MockStakePool, TestHarness};
+use eyre::Result;
+use solana_pubkey::Pubkey;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_stake_pool::state::StakePool;
+
+#[test]
+fn jito_preset_matches_pool() -> Result<()> {
+    let jito = jito_stake_pool();
+    assert_eq!(
+        jito.withdraw_authority,
+        get_stake_pool_withdraw_authority(&JITO_STAKE_POOL, &SPL_STAKE_POOL_PROGRAM_ID)
+    );
+
+    // Existing `MintJitoSol` digests keep working.
+    let preset = MintJitoSol::new(1, 0, LAMPORTS_PER_SOL);
+    let deposit = DepositSol::new(1, 0, jito, LAMPORTS_PER_SOL);
+    assert_eq!(preset.get_digest(), deposit.get_digest());
+    assert_eq!(preset.to_instruction(), deposit.to_instruction());
+
+    Ok(())
+}
+
+#[test]
+fn decodes_any_stake_pool() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let pool = MockStakePool::new();
+    harness.set_account(pool.address, pool.account(None))?;
+
+    let accounts = get_stake_pool_accounts(harness.source(), &pool.address)?;
+    assert_eq!(accounts.program_id, pool.program_id);
+    assert_eq!(
+        accounts.withdraw_authority,
+        get_stake_pool_withdraw_authority(&pool.address, &pool.program_id)
+    );
+    assert_eq!(accounts.validator_list, pool.validator_list);
+    assert_eq!(accounts.reserve_stake, pool.reserve_stake);
+    assert_eq!(accounts.pool_mint, pool.pool_mint);
+    assert_eq!(accounts.manager_fee_account, pool.manager_fee_account);
+    assert_eq!(accounts.sol_deposit_authority, None);
+
+    let vault_id = 1;
+    let vault_pda = get_vault_pda(vault_id, 0);
+    harness.builder.stake_pool_deposit_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        pool.address,
+        LAMPORTS_PER_SOL,
+    )?;
+
+    // The CPI digest init, then the deposit into the fork's pool.
+    let instructions = &harness.builder.instructions;
+    assert_eq!(instructions.len(), 2);
+    let pool_token_account =
+        get_associated_token_address_with_program_id(&vault_pda, &pool.pool_mint, &spl_token::ID);
+    for account in [pool.program_id, pool.address, pool_token_account] {
+        assert!(instructions[1]
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == account));
+    }
+
+    // Pools gating SOL deposits behind their own authority can't be deposited into.
+    harness.set_account(pool.address, pool.account(Some(Pubkey::new_unique())))?;
+    assert!(harness
+        .builder
+        .stake_pool_deposit_sol(
+            harness.authority_signer(),
+            None,
+            vault_id,
+            0,
+            pool.address,
+            LAMPORTS_PER_SOL,
+        )
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn deposit_stake_into_validator() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let pool = MockStakePool::new();
+    let vote_account = Pubkey::new_unique();
+    let stake_account = Pubkey::new_unique();
+    harness.set_account(pool.address, pool.account(None))?;
+    harness.set_account(
+        pool.validator_list,
+        pool.validator_list_account(&vote_account, 3),
+    )?;
+    harness.set_account(stake_account, delegated_stake_account(&vote_account))?;
+
+    let vault_id = 1;
+    harness.builder.stake_pool_deposit_stake(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        pool.address,
+        stake_account,
+    )?;
+
+    // Authorizing the staker and the withdrawer, then the deposit, each with their CPI digest.
+    let instructions = &harness.builder.instructions;
+    assert_eq!(instructions.len(), 6);
+    let (validator_stake_account, _) = Pubkey::find_program_address(
+        &[
+            vote_account.as_ref(),
+            pool.address.as_ref(),
+            &3u32.to_le_bytes(),
+        ],
+        &pool.program_id,
+    );
+    assert!(instructions[5]
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == validator_stake_account));
+
+    // Stake delegated to a validator outside the pool is rejected.
+    let other = Pubkey::new_unique();
+    harness.set_account(other, delegated_stake_account(&Pubkey::new_unique()))?;
+    assert!(harness
+        .builder
+        .stake_pool_deposit_stake(
+            harness.authority_signer(),
+            None,
+            vault_id,
+            0,
+            pool.address,
+            other,
+        )
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn follows_preferred_validators() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let pool = MockStakePool::new();
+    let largest = Pubkey::new_unique();
+    let preferred = Pubkey::new_unique();
+    harness.set_account(
+        pool.address,
+        pool.state_account(&StakePool {
+            preferred_deposit_validator_vote_address: Some(preferred),
+            preferred_withdraw_validator_vote_address: Some(preferred),
+            ..pool.state()
+        }),
+    )?;
+    harness.set_account(
+        pool.validator_list,
+        pool.validators_account(&[
+            validator(&largest, 0, 10 * LAMPORTS_PER_SOL),
+            validator(&preferred, 0, 2 * LAMPORTS_PER_SOL),
+        ]),
+    )?;
+
+    // Stake is withdrawn from the preferred validator while it has some above the minimum.
+    let accounts = get_stake_pool_accounts(harness.source(), &pool.address)?;
+    assert_eq!(
+        get_stake_to_split(harness.source(), &accounts)?,
+        (accounts.validator_stake_account(&preferred, 0), true)
+    );
+
+    let minimum_lamports = harness
+        .source()
+        .get_minimum_balance_for_rent_exemption(STAKE_ACCOUNT_SIZE as usize)?
+        + 1_000_000;
+    harness.set_account(
+        pool.validator_list,
+        pool.validators_account(&[
+            validator(&largest, 0, 10 * LAMPORTS_PER_SOL),
+            validator(&preferred, 0, minimum_lamports),
+        ]),
+    )?;
+    assert_eq!(
+        get_stake_to_split(harness.source(), &accounts)?,
+        (accounts.validator_stake_account(&largest, 0), true)
+    );
+
+    // Stake deposits have to be delegated to the preferred validator.
+    let vault_id = 1;
+    for (vote_account, accepted) in [(largest, false), (preferred, true)] {
+        let stake_account = Pubkey::new_unique();
+        harness.set_account(stake_account, delegated_stake_account(&vote_account))?;
+        let result = harness.builder.stake_pool_deposit_stake(
+            harness.authority_signer(),
+            Some(harness.authority_signer()),
+            vault_id,
+            0,
+            pool.address,
+            stake_account,
+        );
+        assert_eq!(result.is_ok(), accepted);
+    }
+
+    Ok(())
+}
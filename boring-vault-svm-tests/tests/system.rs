@@ -0,0 +1,95 @@
+use boring_vault_svm_core::{
+    instructions::WSOL_MINT,
+    utils::{get_vault_pda, AccountSource},
+    KeypairOrPublickey,
+};
+use boring_vault_svm_tests::{TestHarness, BORING_VAULT_PROGRAM};
+use eyre::Result;
+use solana_keypair::Keypair;
+use solana_program_pack::Pack;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_signer::Signer;
+use spl_token::state::Account as TokenAccount;
+
+// Only needs the system program, so it runs without any program binary.
+#[test]
+fn create_and_use_nonce_account() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    let nonce_account = Keypair::new();
+
+    harness.builder.create_nonce_account(
+        harness.payer_signer(),
+        KeypairOrPublickey::Keypair(nonce_account.insecure_clone()),
+        harness.payer.pubkey(),
+    )?;
+    harness.send()?;
+
+    let nonce_data = harness.builder.get_nonce_data(&nonce_account.pubkey())?;
+    assert_eq!(nonce_data.authority, harness.payer.pubkey());
+
+    // Sending with the durable nonce advances it.
+    harness
+        .builder
+        .use_durable_nonce(nonce_account.pubkey(), harness.payer_signer());
+    harness.builder.create_nonce_account(
+        harness.payer_signer(),
+        KeypairOrPublickey::Keypair(Keypair::new()),
+        harness.payer.pubkey(),
+    )?;
+    harness.send()?;
+
+    let advanced = harness.builder.get_nonce_data(&nonce_account.pubkey())?;
+    assert_ne!(advanced.blockhash(), nonce_data.blockhash());
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "needs the boring_vault_svm program"]
+fn wrap_and_unwrap_sol() -> Result<()> {
+    let mut harness = TestHarness::new()?;
+    harness.require_program(BORING_VAULT_PROGRAM)?;
+    let (vault_id, _) = harness.setup_vault()?;
+    let vault_pda = get_vault_pda(vault_id, 0);
+
+    // The native mint isn't part of LiteSVM's genesis.
+    harness.set_mint(*WSOL_MINT, 9)?;
+    harness.airdrop(&vault_pda, 2 * LAMPORTS_PER_SOL)?;
+
+    harness.builder.wrap_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+        LAMPORTS_PER_SOL,
+    )?;
+    harness.send()?;
+
+    // The ATA is created over the transferred lamports, so its rent comes out of the wrapped SOL.
+    let rent = harness
+        .source()
+        .get_minimum_balance_for_rent_exemption(TokenAccount::LEN)?;
+    assert_eq!(
+        harness.token_balance(&vault_pda, WSOL_MINT, &spl_token::ID),
+        LAMPORTS_PER_SOL - rent
+    );
+
+    let vault_lamports = harness.lamports(&vault_pda);
+    harness.builder.unwrap_sol(
+        harness.authority_signer(),
+        Some(harness.authority_signer()),
+        vault_id,
+        0,
+    )?;
+    harness.send()?;
+    assert_eq!(
+        harness.token_balance(&vault_pda, WSOL_MINT, &spl_token::ID),
+        0
+    );
+    assert_eq!(
+        harness.lamports(&vault_pda),
+        vault_lamports + LAMPORTS_PER_SOL
+    );
+
+    Ok(())
+}
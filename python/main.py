@@ -1,25 +1,12 @@
 import boring_vault_svm
 import asyncio
-import json
-from pathlib import Path
 import os
-import json
-
-# Default funded keypair
-def get_default_keypair_secret():
-    home = str(Path.home())
-    with open(f"{home}/.config/solana/id.json", "r") as f:
-        secret_key_data = json.load(f)
-        return bytes(secret_key_data)
-
-def get_program_keypair_secret():
-    with open("../../program_keypairs/boring_vault_svm-keypair.json", "r") as f:
-        secret_key_data = json.load(f)
-        return bytes(secret_key_data)
 
 async def main():
-    signer_bytes = get_default_keypair_secret()
-    # program_signer_bytes = get_program_keypair_secret()
+    # Signers can be raw keypair bytes or a spec string: "file:<path>", "env:<VAR>",
+    # "prompt:" or "mnemonic:<words>?key=0/0".
+    signer_bytes = "file:~/.config/solana/id.json"
+    # program_signer_bytes = "file:../../program_keypairs/boring_vault_svm-keypair.json"
 
     try:
         # Create builder
@@ -30,8 +17,8 @@ async def main():
             data_path = None,
             kamino_file = None,
             vault_file = None,
-            lend_mint = None,
-            borrow_mint = None
+            default_lend_reserve = None,
+            default_borrow_reserve = None
         )
         authority_pubkey_str = "CSsqdfpwwBK8iueo9CuTLHc1M2uubj88UwXKCgZap7H2"
 